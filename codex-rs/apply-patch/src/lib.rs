@@ -1,6 +1,8 @@
+mod merge;
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod unified_diff;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -31,6 +33,9 @@ pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_too
 
 const APPLY_PATCH_COMMANDS: [&str; 2] = ["apply_patch", "applypatch"];
 
+/// Below this, the closest region is unlikely to be what the patch meant.
+const MIN_CLOSEST_MATCH_SIMILARITY: f64 = 0.5;
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ApplyPatchError {
     #[error(transparent)]
@@ -214,6 +219,19 @@ impl ApplyPatchAction {
 /// cwd must be an absolute path so that we can resolve relative paths in the
 /// patch.
 pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+    maybe_parse_apply_patch_verified_with_bases(argv, cwd, &|_| None)
+}
+
+/// Like [`maybe_parse_apply_patch_verified`], but when the chunks for a file
+/// no longer apply because the file changed after the patch was written,
+/// `base_contents` may supply the contents the patch was written against.
+/// The patch's changes are then three-way merged with the file's, and
+/// [`ApplyPatchAction::patch`] is rewritten to make the merged changes.
+pub fn maybe_parse_apply_patch_verified_with_bases(
+    argv: &[String],
+    cwd: &Path,
+    base_contents: &dyn Fn(&Path) -> Option<String>,
+) -> MaybeApplyPatchVerified {
     // Detect a raw patch body passed directly as the command or as the body of a bash -lc
     // script. In these cases, report an explicit error rather than applying the patch.
     match argv {
@@ -252,11 +270,17 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                 })
                 .unwrap_or_else(|| cwd.to_path_buf());
             let mut changes = HashMap::new();
-            for hunk in hunks {
+            let mut merged_chunks = HashMap::new();
+            for (index, hunk) in hunks.iter().enumerate() {
                 let path = hunk.resolve_path(&effective_cwd);
                 match hunk {
                     Hunk::AddFile { contents, .. } => {
-                        changes.insert(path, ApplyPatchFileChange::Add { content: contents });
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Add {
+                                content: contents.clone(),
+                            },
+                        );
                     }
                     Hunk::DeleteFile { .. } => {
                         let content = match std::fs::read_to_string(&path) {
@@ -278,8 +302,17 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
+                        } = match unified_diff_from_chunks(&path, chunks) {
                             Ok(diff) => diff,
+                            Err(e @ ApplyPatchError::ComputeReplacements(_)) => {
+                                match merge_with_base(&path, chunks, base_contents) {
+                                    Some((update, chunks)) => {
+                                        merged_chunks.insert(index, chunks);
+                                        update
+                                    }
+                                    None => return MaybeApplyPatchVerified::CorrectnessError(e),
+                                }
+                            }
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
                             }
@@ -288,13 +321,35 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                             path,
                             ApplyPatchFileChange::Update {
                                 unified_diff,
-                                move_path: move_path.map(|p| cwd.join(p)),
+                                move_path: move_path.as_ref().map(|p| cwd.join(p)),
                                 new_content: contents,
                             },
                         );
                     }
                 }
             }
+            let patch = if merged_chunks.is_empty() {
+                patch
+            } else {
+                let hunks: Vec<Hunk> = hunks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, hunk)| match (hunk, merged_chunks.remove(&index)) {
+                        (
+                            Hunk::UpdateFile {
+                                path, move_path, ..
+                            },
+                            Some(chunks),
+                        ) => Hunk::UpdateFile {
+                            path,
+                            move_path,
+                            chunks,
+                        },
+                        (hunk, _) => hunk,
+                    })
+                    .collect();
+                parser::format_patch(&hunks)
+            };
             MaybeApplyPatchVerified::Body(ApplyPatchAction {
                 changes,
                 patch,
@@ -647,26 +702,42 @@ fn derive_new_contents_from_chunks(
             }));
         }
     };
+    let new_contents = apply_chunks_to_contents(&original_contents, path, chunks)?;
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+    })
+}
+
+/// Applies the chunks to `contents`, the contents of the file at `path`.
+fn apply_chunks_to_contents(
+    contents: &str,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<String, ApplyPatchError> {
+    let original_lines = split_lines(contents);
+    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let new_lines = apply_replacements(original_lines, &replacements);
+    let final_newline = !chunks.iter().any(|chunk| chunk.no_newline_at_end_of_file);
+    Ok(join_lines(new_lines, final_newline))
+}
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
+fn split_lines(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = contents.split('\n').map(String::from).collect();
 
     // Drop the trailing empty element that results from the final newline so
     // that line counts match the behaviour of standard `diff`.
-    if original_lines.last().is_some_and(String::is_empty) {
-        original_lines.pop();
+    if lines.last().is_some_and(String::is_empty) {
+        lines.pop();
     }
+    lines
+}
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
-    let new_lines = apply_replacements(original_lines, &replacements);
-    let mut new_lines = new_lines;
-    if !new_lines.last().is_some_and(String::is_empty) {
-        new_lines.push(String::new());
+fn join_lines(mut lines: Vec<String>, final_newline: bool) -> String {
+    if final_newline && !lines.last().is_some_and(String::is_empty) {
+        lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    lines.join("\n")
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -693,17 +764,21 @@ fn compute_replacements(
                 line_index = idx + 1;
             } else {
                 return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
+                    "Failed to find context '{}' in {}{}",
                     ctx_line,
-                    path.display()
+                    path.display(),
+                    closest_match_excerpt(original_lines, std::slice::from_ref(ctx_line)),
                 )));
             }
         }
 
         if chunk.old_lines.is_empty() {
-            // Pure addition (no old lines). We'll add them at the end or just
-            // before the final empty line if one exists.
-            let insertion_idx = if original_lines.last().is_some_and(String::is_empty) {
+            // Pure addition (no old lines). We'll add them where the patch
+            // says, or else at the end or just before the final empty line if
+            // one exists.
+            let insertion_idx = if let Some(start_index) = chunk.start_index {
+                start_index.clamp(line_index, original_lines.len())
+            } else if original_lines.last().is_some_and(String::is_empty) {
                 original_lines.len() - 1
             } else {
                 original_lines.len()
@@ -724,8 +799,7 @@ fn compute_replacements(
         // located reliably.
 
         let mut pattern: &[String] = &chunk.old_lines;
-        let mut found =
-            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
+        let mut found = seek_nearest(original_lines, pattern, line_index, chunk);
 
         let mut new_slice: &[String] = &chunk.new_lines;
 
@@ -737,12 +811,7 @@ fn compute_replacements(
                 new_slice = &new_slice[..new_slice.len() - 1];
             }
 
-            found = seek_sequence::seek_sequence(
-                original_lines,
-                pattern,
-                line_index,
-                chunk.is_end_of_file,
-            );
+            found = seek_nearest(original_lines, pattern, line_index, chunk);
         }
 
         if let Some(start_idx) = found {
@@ -750,9 +819,10 @@ fn compute_replacements(
             line_index = start_idx + pattern.len();
         } else {
             return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
+                "Failed to find expected lines in {}:\n{}{}",
                 path.display(),
                 chunk.old_lines.join("\n"),
+                closest_match_excerpt(original_lines, pattern),
             )));
         }
     }
@@ -762,6 +832,122 @@ fn compute_replacements(
     Ok(replacements)
 }
 
+/// Finds `pattern` at or after `start` like [`seek_sequence::seek_sequence`],
+/// preferring the occurrence nearest to the chunk's `start_index`, if any.
+fn seek_nearest(
+    original_lines: &[String],
+    pattern: &[String],
+    start: usize,
+    chunk: &UpdateFileChunk,
+) -> Option<usize> {
+    let mut found =
+        seek_sequence::seek_sequence(original_lines, pattern, start, chunk.is_end_of_file)?;
+    let Some(start_index) = chunk.start_index else {
+        return Some(found);
+    };
+    let mut nearest = found;
+    // Occurrences come in order, so none after the first one at or past
+    // `start_index` is nearer.
+    while found < start_index {
+        let Some(next) =
+            seek_sequence::seek_sequence(original_lines, pattern, found + 1, chunk.is_end_of_file)
+                .filter(|&next| next > found)
+        else {
+            break;
+        };
+        if next.abs_diff(start_index) < nearest.abs_diff(start_index) {
+            nearest = next;
+        }
+        found = next;
+    }
+    Some(nearest)
+}
+
+/// Three-way merges the changes `chunks` make to the base contents of `path`
+/// with the changes made to the file since. Returns the update the merge
+/// makes to the current file and chunks that make it, or `None` when there is
+/// no base, the chunks do not apply to it either, or the changes conflict.
+fn merge_with_base(
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+    base_contents: &dyn Fn(&Path) -> Option<String>,
+) -> Option<(ApplyPatchFileUpdate, Vec<UpdateFileChunk>)> {
+    let base = base_contents(path)?;
+    let current = std::fs::read_to_string(path).ok()?;
+    if base == current {
+        return None;
+    }
+    let ours = apply_chunks_to_contents(&base, path, chunks).ok()?;
+    let current_lines = split_lines(&current);
+    let merged_lines = merge::merge3(&split_lines(&base), &split_lines(&ours), &current_lines)?;
+    let merged_chunks = chunks_between(&current_lines, &merged_lines);
+    if merged_chunks.is_empty() {
+        return None;
+    }
+    let final_newline = !chunks.iter().any(|chunk| chunk.no_newline_at_end_of_file);
+    let merged = join_lines(merged_lines, final_newline);
+    let unified_diff = TextDiff::from_lines(&current, &merged)
+        .unified_diff()
+        .context_radius(1)
+        .to_string();
+    Some((
+        ApplyPatchFileUpdate {
+            unified_diff,
+            content: merged,
+        },
+        merged_chunks,
+    ))
+}
+
+/// Chunks, with three lines of context, that turn `old` into `new`.
+fn chunks_between(old: &[String], new: &[String]) -> Vec<UpdateFileChunk> {
+    let ops = similar::capture_diff_slices(similar::Algorithm::Myers, old, new);
+    similar::group_diff_ops(ops, 3)
+        .into_iter()
+        .map(|group| {
+            let mut chunk = UpdateFileChunk {
+                change_context: None,
+                old_lines: Vec::new(),
+                new_lines: Vec::new(),
+                is_end_of_file: false,
+                no_newline_at_end_of_file: false,
+                start_index: None,
+            };
+            for op in group {
+                let (_, old_range, new_range) = op.as_tag_tuple();
+                chunk.is_end_of_file = old_range.end == old.len();
+                chunk.old_lines.extend_from_slice(&old[old_range]);
+                chunk.new_lines.extend_from_slice(&new[new_range]);
+            }
+            chunk
+        })
+        .collect()
+}
+
+/// Describes the region of the file that most resembles `pattern`, with line
+/// numbers, so that the patch can be corrected without re-reading the file.
+/// Empty when nothing is similar enough to be worth showing.
+fn closest_match_excerpt(original_lines: &[String], pattern: &[String]) -> String {
+    let Some((start, similarity)) = seek_sequence::closest_sequence(original_lines, pattern) else {
+        return String::new();
+    };
+    if similarity < MIN_CLOSEST_MATCH_SIMILARITY {
+        return String::new();
+    }
+    let end = (start + pattern.len()).min(original_lines.len());
+    let width = end.to_string().len();
+    let mut excerpt = format!(
+        "\nClosest match is lines {}-{} ({:.0}% similar):",
+        start + 1,
+        end,
+        similarity * 100.0
+    );
+    for (number, line) in (start + 1..).zip(&original_lines[start..end]) {
+        excerpt.push_str(&format!("\n{number:>width$} | {line}"));
+    }
+    excerpt
+}
+
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
 /// returning the modified file contents as a vector of lines.
 fn apply_replacements(
@@ -1621,4 +1807,158 @@ g
         let result = apply_patch(&patch, &mut stdout, &mut stderr);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_git_diff_updates_and_renames_files() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("old.txt");
+        let new = dir.path().join("new.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&old, "one\ntwo\nthree\n").unwrap();
+        let patch = format!(
+            "diff --git a/{old} b/{new}\nsimilarity index 80%\nrename from {old}\nrename to {new}\n--- a/{old}\n+++ b/{new}\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\ndiff --git a/{added} b/{added}\nnew file mode 100644\n--- /dev/null\n+++ b/{added}\n@@ -0,0 +1 @@\n+hello\n\\ No newline at end of file\n",
+            old = old.display(),
+            new = new.display(),
+            added = added.display(),
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "one\n2\nthree\n");
+        assert_eq!(fs::read_to_string(&added).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_unified_diff_hunks_apply_near_their_start_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("repeated.txt");
+        fs::write(&path, "a\nx\nb\na\nx\nb\nend\n").unwrap();
+        let patch = format!(
+            "--- a/{path}\n+++ b/{path}\n@@ -4,3 +4,3 @@\n a\n-x\n+y\n b\n@@ -6,0 +7 @@\n+added\n",
+            path = path.display(),
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "a\nx\nb\na\ny\nb\nadded\nend\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_no_newline_markers_decide_the_final_newline() {
+        let dir = tempdir().unwrap();
+        let dropped = dir.path().join("dropped.txt");
+        let restored = dir.path().join("restored.txt");
+        fs::write(&dropped, "a\nb\n").unwrap();
+        fs::write(&restored, "a\nb").unwrap();
+        let patch = format!(
+            "--- a/{dropped}\n+++ b/{dropped}\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n\\ No newline at end of file\n--- a/{restored}\n+++ b/{restored}\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n",
+            dropped = dropped.display(),
+            restored = restored.display(),
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&dropped).unwrap(), "a\nc");
+        assert_eq!(fs::read_to_string(&restored).unwrap(), "a\nc\n");
+    }
+
+    #[test]
+    fn test_failed_chunk_reports_closest_match() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("greet.rs");
+        fs::write(
+            &path,
+            "use std::fmt;\n\nfn greet(name: &str) -> String {\n    format!(\"Hello, {name}!\")\n}\n",
+        )
+        .unwrap();
+        let chunks = vec![UpdateFileChunk {
+            change_context: None,
+            old_lines: strs_to_strings(&[
+                "fn greet(name: &str) -> String {",
+                "    format!(\"Hello, {}!\", name)",
+            ]),
+            new_lines: strs_to_strings(&[
+                "fn greet(name: &str) -> String {",
+                "    name.to_string()",
+            ]),
+            is_end_of_file: false,
+            no_newline_at_end_of_file: false,
+            start_index: None,
+        }];
+
+        let Err(ApplyPatchError::ComputeReplacements(message)) =
+            unified_diff_from_chunks(&path, &chunks)
+        else {
+            panic!("expected the chunk not to apply");
+        };
+        let excerpt = message
+            .split_once("\nClosest match")
+            .map(|(_, excerpt)| excerpt)
+            .unwrap();
+        assert!(excerpt.starts_with(" is lines 3-4 ("), "{message}");
+        assert!(
+            excerpt.ends_with(
+                "similar):\n3 | fn greet(name: &str) -> String {\n4 |     format!(\"Hello, {name}!\")"
+            ),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_update_merges_with_changes_made_since_base() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lib.py");
+        let base = "def a():\n    return 1\n\n\ndef b():\n    return 2\n";
+        // A formatter rewrote `a` after the patch was written against `base`.
+        fs::write(
+            &path,
+            "def a():\n    return (\n        1\n    )\n\n\ndef b():\n    return 2\n",
+        )
+        .unwrap();
+        let argv = vec![
+            "apply_patch".to_string(),
+            wrap_patch(&format!(
+                "*** Update File: {}\n@@\n     return 1\n \n \n def b():\n-    return 2\n+    return 3",
+                path.display()
+            )),
+        ];
+
+        assert!(matches!(
+            maybe_parse_apply_patch_verified(&argv, dir.path()),
+            MaybeApplyPatchVerified::CorrectnessError(ApplyPatchError::ComputeReplacements(_))
+        ));
+
+        let base_contents = |p: &Path| (p == path).then(|| base.to_string());
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified_with_bases(&argv, dir.path(), &base_contents)
+        else {
+            panic!("expected the patch to merge");
+        };
+        let expected = "def a():\n    return (\n        1\n    )\n\n\ndef b():\n    return 3\n";
+        assert_eq!(
+            action.changes().get(&path),
+            Some(&ApplyPatchFileChange::Update {
+                unified_diff: "@@ -7,2 +7,2 @@\n def b():\n-    return 2\n+    return 3\n"
+                    .to_string(),
+                move_path: None,
+                new_content: expected.to_string(),
+            })
+        );
+
+        // The rewritten patch makes the merged change to the current file.
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&action.patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+    }
 }
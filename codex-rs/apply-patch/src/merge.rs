@@ -0,0 +1,104 @@
+//! Line-based three-way merge, used to carry a patch written against an older
+//! copy of a file over to the file as it is now.
+
+use similar::Algorithm;
+use similar::DiffTag;
+use similar::capture_diff_slices;
+
+/// A replacement of the base lines `start..end` with `lines`.
+#[derive(Debug, PartialEq)]
+struct Edit<'a> {
+    start: usize,
+    end: usize,
+    lines: &'a [String],
+}
+
+/// Merges the changes `ours` and `theirs` each made to `base`. Returns `None`
+/// when they conflict, i.e. when both change the same base lines, or insert
+/// at the same place, differently.
+pub(crate) fn merge3(base: &[String], ours: &[String], theirs: &[String]) -> Option<Vec<String>> {
+    let mut edits = changed_regions(base, ours);
+    edits.extend(changed_regions(base, theirs));
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+    edits.dedup();
+
+    let mut merged = Vec::with_capacity(theirs.len());
+    let mut pos = 0;
+    let mut previous_start = None;
+    for edit in edits {
+        if edit.start < pos || previous_start == Some(edit.start) {
+            return None;
+        }
+        merged.extend_from_slice(&base[pos..edit.start]);
+        merged.extend_from_slice(edit.lines);
+        pos = edit.end;
+        previous_start = Some(edit.start);
+    }
+    merged.extend_from_slice(&base[pos..]);
+    Some(merged)
+}
+
+/// The regions of `base` that `changed` replaced. Adjacent changes, such as a
+/// deletion followed by an insertion, are joined into one edit.
+fn changed_regions<'a>(base: &[String], changed: &'a [String]) -> Vec<Edit<'a>> {
+    let mut ranges: Vec<(std::ops::Range<usize>, std::ops::Range<usize>)> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, changed) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some((old, new)) if old.end == old_range.start && new.end == new_range.start => {
+                old.end = old_range.end;
+                new.end = new_range.end;
+            }
+            _ => ranges.push((old_range, new_range)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(old, new)| Edit {
+            start: old.start,
+            end: old.end,
+            lines: &changed[new],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn merges_changes_to_different_regions() {
+        let base = lines("fn a() {\n    1\n}\n\nfn b() {\n    2\n}");
+        let ours = lines("fn a() {\n    1\n}\n\nfn b() {\n    3\n}");
+        let theirs = lines("fn a() {\n    one()\n}\n\nfn b() {\n    2\n}\n");
+        assert_eq!(
+            merge3(&base, &ours, &theirs),
+            Some(lines("fn a() {\n    one()\n}\n\nfn b() {\n    3\n}"))
+        );
+    }
+
+    #[test]
+    fn applies_identical_changes_once() {
+        let base = lines("a\nb\nc");
+        let changed = lines("a\nB\nc");
+        assert_eq!(merge3(&base, &changed, &changed), Some(changed.clone()));
+    }
+
+    #[test]
+    fn rejects_overlapping_changes() {
+        let base = lines("a\nb\nc");
+        assert_eq!(merge3(&base, &lines("a\nx\nc"), &lines("a\ny\nc")), None);
+        assert_eq!(
+            merge3(&base, &lines("a\nx\nb\nc"), &lines("a\ny\nb\nc")),
+            None
+        );
+    }
+}
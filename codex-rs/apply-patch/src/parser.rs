@@ -23,12 +23,13 @@
 //! The parser below is a little more lenient than the explicit spec and allows for
 //! leading/trailing whitespace around patch markers.
 use crate::ApplyPatchArgs;
+use crate::unified_diff;
 use std::path::Path;
 use std::path::PathBuf;
 
 use thiserror::Error;
 
-const BEGIN_PATCH_MARKER: &str = "*** Begin Patch";
+pub(crate) const BEGIN_PATCH_MARKER: &str = "*** Begin Patch";
 const END_PATCH_MARKER: &str = "*** End Patch";
 const ADD_FILE_MARKER: &str = "*** Add File: ";
 const DELETE_FILE_MARKER: &str = "*** Delete File: ";
@@ -101,6 +102,16 @@ pub struct UpdateFileChunk {
     /// If set to true, `old_lines` must occur at the end of the source file.
     /// (Tolerance around trailing newlines should be encouraged.)
     pub is_end_of_file: bool,
+
+    /// If set to true, `new_lines` end the file and the file has no trailing
+    /// newline, as a unified diff's `\ No newline at end of file` marker says.
+    pub no_newline_at_end_of_file: bool,
+
+    /// Index of the line at which `old_lines` start in the original file,
+    /// when the patch says (the `@@ -a,b` header of a unified diff). When
+    /// `old_lines` occur several times, the occurrence nearest to it is
+    /// replaced, and lines added without context are inserted there.
+    pub start_index: Option<usize>,
 }
 
 pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
@@ -112,6 +123,58 @@ pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
     parse_patch_text(patch, mode)
 }
 
+/// Renders `hunks` as patch text that [`parse_patch`] parses back into the
+/// same hunks, except that their `start_index` is lost.
+pub(crate) fn format_patch(hunks: &[Hunk]) -> String {
+    let mut lines = vec![BEGIN_PATCH_MARKER.to_string()];
+    for hunk in hunks {
+        match hunk {
+            AddFile { path, contents } => {
+                lines.push(format!("{ADD_FILE_MARKER}{}", path.display()));
+                lines.extend(contents.lines().map(|line| format!("+{line}")));
+            }
+            DeleteFile { path } => {
+                lines.push(format!("{DELETE_FILE_MARKER}{}", path.display()));
+            }
+            UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => {
+                lines.push(format!("{UPDATE_FILE_MARKER}{}", path.display()));
+                if let Some(move_path) = move_path {
+                    lines.push(format!("{MOVE_TO_MARKER}{}", move_path.display()));
+                }
+                for chunk in chunks {
+                    lines.push(match &chunk.change_context {
+                        Some(context) => format!("{CHANGE_CONTEXT_MARKER}{context}"),
+                        None => EMPTY_CHANGE_CONTEXT_MARKER.to_string(),
+                    });
+                    for op in similar::capture_diff_slices(
+                        similar::Algorithm::Myers,
+                        &chunk.old_lines,
+                        &chunk.new_lines,
+                    ) {
+                        for change in op.iter_changes(&chunk.old_lines, &chunk.new_lines) {
+                            let prefix = match change.tag() {
+                                similar::ChangeTag::Equal => ' ',
+                                similar::ChangeTag::Delete => '-',
+                                similar::ChangeTag::Insert => '+',
+                            };
+                            lines.push(format!("{prefix}{}", change.value()));
+                        }
+                    }
+                    if chunk.is_end_of_file {
+                        lines.push(EOF_MARKER.to_string());
+                    }
+                }
+            }
+        }
+    }
+    lines.push(END_PATCH_MARKER.to_string());
+    lines.join("\n")
+}
+
 enum ParseMode {
     /// Parse the patch text argument as is.
     Strict,
@@ -153,6 +216,16 @@ enum ParseMode {
 
 fn parse_patch_text(patch: &str, mode: ParseMode) -> Result<ApplyPatchArgs, ParseError> {
     let lines: Vec<&str> = patch.trim().lines().collect();
+    if unified_diff::is_unified_diff(&lines) {
+        // The diff itself is applied rather than the equivalent patch, which
+        // could not say where each hunk starts.
+        let hunks = unified_diff::parse_unified_diff(&lines)?;
+        return Ok(ApplyPatchArgs {
+            patch: lines.join("\n"),
+            hunks,
+            workdir: None,
+        });
+    }
     let lines: &[&str] = match check_patch_boundaries_strict(&lines) {
         Ok(()) => &lines,
         Err(e) => match mode {
@@ -312,7 +385,8 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             remaining_lines = &remaining_lines[chunk_lines..]
         }
 
-        if chunks.is_empty() {
+        // A rename needs no changes.
+        if chunks.is_empty() && move_path.is_none() {
             return Err(InvalidHunkError {
                 message: format!("Update file hunk for path '{path}' is empty"),
                 line_number,
@@ -377,6 +451,8 @@ fn parse_update_file_chunk(
         old_lines: Vec::new(),
         new_lines: Vec::new(),
         is_end_of_file: false,
+        no_newline_at_end_of_file: false,
+        start_index: None,
     };
     let mut parsed_lines = 0;
     for line in &lines[start_index..] {
@@ -498,7 +574,9 @@ fn test_parse_patch() {
                     change_context: Some("def f():".to_string()),
                     old_lines: vec!["    pass".to_string()],
                     new_lines: vec!["    return 123".to_string()],
-                    is_end_of_file: false
+                    is_end_of_file: false,
+                    no_newline_at_end_of_file: false,
+                    start_index: None
                 }]
             }
         ]
@@ -525,7 +603,9 @@ fn test_parse_patch() {
                     change_context: None,
                     old_lines: vec![],
                     new_lines: vec!["line".to_string()],
-                    is_end_of_file: false
+                    is_end_of_file: false,
+                    no_newline_at_end_of_file: false,
+                    start_index: None
                 }],
             },
             AddFile {
@@ -556,6 +636,8 @@ fn test_parse_patch() {
                 old_lines: vec!["import foo".to_string()],
                 new_lines: vec!["import foo".to_string(), "bar".to_string()],
                 is_end_of_file: false,
+                no_newline_at_end_of_file: false,
+                start_index: None,
             }],
         }]
     );
@@ -576,6 +658,8 @@ fn test_parse_patch_lenient() {
             old_lines: vec!["import foo".to_string()],
             new_lines: vec!["import foo".to_string(), "bar".to_string()],
             is_end_of_file: false,
+            no_newline_at_end_of_file: false,
+            start_index: None,
         }],
     }];
     let expected_error =
@@ -721,7 +805,9 @@ fn test_update_file_chunk() {
                     "add".to_string(),
                     "context2".to_string()
                 ],
-                is_end_of_file: false
+                is_end_of_file: false,
+                no_newline_at_end_of_file: false,
+                start_index: None
             }),
             6
         ))
@@ -733,9 +819,35 @@ fn test_update_file_chunk() {
                 change_context: None,
                 old_lines: vec![],
                 new_lines: vec!["line".to_string()],
-                is_end_of_file: true
+                is_end_of_file: true,
+                no_newline_at_end_of_file: false,
+                start_index: None
             }),
             3
         ))
     );
 }
+
+#[test]
+fn test_format_patch_round_trips() {
+    let patch = "*** Begin Patch
+*** Add File: new.txt
++hello
+*** Delete File: old.txt
+*** Update File: src.py
+*** Move to: dst.py
+@@ def f():
+-    pass
++    return 1
+ 
+ x = 2
+@@
+-y
++z
+*** End of File
+*** End Patch";
+    let hunks = parse_patch_text(patch, ParseMode::Strict)
+        .expect("patch should parse")
+        .hunks;
+    assert_eq!(format_patch(&hunks), patch);
+}
@@ -109,8 +109,80 @@ pub(crate) fn seek_sequence(
     None
 }
 
+/// Finds the region of `lines` that most resembles `pattern`, for reporting
+/// where a patch was probably meant to apply when [`seek_sequence`] fails.
+/// Returns the starting index of the best window of `pattern.len()` lines and
+/// its similarity between 0.0 and 1.0: the mean, over the pattern's lines, of
+/// the Dice coefficient of their character bigrams, ignoring leading and
+/// trailing whitespace.
+pub(crate) fn closest_sequence(lines: &[String], pattern: &[String]) -> Option<(usize, f64)> {
+    if lines.is_empty() || pattern.is_empty() {
+        return None;
+    }
+    let line_bigrams: Vec<Vec<(char, char)>> = lines.iter().map(|line| bigrams(line)).collect();
+    let pattern_bigrams: Vec<Vec<(char, char)>> =
+        pattern.iter().map(|line| bigrams(line)).collect();
+
+    let mut best: Option<(usize, f64)> = None;
+    for start in 0..=lines.len().saturating_sub(pattern.len()) {
+        let total: f64 = pattern_bigrams
+            .iter()
+            .enumerate()
+            .map(|(offset, pat)| match lines.get(start + offset) {
+                Some(line) => {
+                    line_similarity(line, &line_bigrams[start + offset], &pattern[offset], pat)
+                }
+                None => 0.0,
+            })
+            .sum();
+        let score = total / pattern.len() as f64;
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((start, score));
+        }
+    }
+    best
+}
+
+fn bigrams(line: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = line.trim().chars().collect();
+    let mut bigrams: Vec<(char, char)> = chars.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    bigrams.sort_unstable();
+    bigrams
+}
+
+fn line_similarity(
+    line: &str,
+    line_bigrams: &[(char, char)],
+    pattern: &str,
+    pattern_bigrams: &[(char, char)],
+) -> f64 {
+    if line_bigrams.is_empty() || pattern_bigrams.is_empty() {
+        // Lines too short to have bigrams only match themselves.
+        return if line.trim() == pattern.trim() {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    // Both lists are sorted, so their multiset intersection is a merge.
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < line_bigrams.len() && j < pattern_bigrams.len() {
+        match line_bigrams[i].cmp(&pattern_bigrams[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    (2 * common) as f64 / (line_bigrams.len() + pattern_bigrams.len()) as f64
+}
+
 #[cfg(test)]
 mod tests {
+    use super::closest_sequence;
     use super::seek_sequence;
     use std::string::ToString;
 
@@ -148,4 +220,23 @@ mod tests {
         // Should not panic – must return None when pattern cannot possibly fit.
         assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
     }
+
+    #[test]
+    fn test_closest_sequence_finds_reformatted_region() {
+        let lines = to_vec(&[
+            "use std::fmt;",
+            "",
+            "fn greet(name: &str) -> String {",
+            "    format!(\"Hello, {}!\", name)",
+            "}",
+        ]);
+        let pattern = to_vec(&[
+            "fn greet(name: &str) -> String {",
+            "    format!(\"Hello, {name}!\")",
+        ]);
+        let (start, score) = closest_sequence(&lines, &pattern).expect("a candidate");
+        assert_eq!(start, 2);
+        assert!(score > 0.8 && score < 1.0, "score: {score}");
+        assert_eq!(closest_sequence(&[], &pattern), None);
+    }
 }
@@ -0,0 +1,530 @@
+//! Conversion of standard unified diffs, including `git diff` output, into
+//! [`Hunk`]s, so that patches in either syntax are verified and applied the
+//! same way.
+//!
+//! Each `@@` hunk becomes an [`UpdateFileChunk`]. The line counts in its
+//! header say where it ends, and the start line only picks between places
+//! where its context occurs more than once: the context is what locates it,
+//! so hunks whose line numbers are off are still applied. New and deleted
+//! files, renames and `\ No newline at end of file` markers are understood:
+//! like every file `apply_patch` writes, the result ends with a newline
+//! unless such a marker follows the last line of the new file.
+//! Binary diffs are rejected, and mode-only changes are ignored.
+
+use std::path::PathBuf;
+
+use crate::parser::BEGIN_PATCH_MARKER;
+use crate::parser::Hunk;
+use crate::parser::ParseError;
+use crate::parser::ParseError::*;
+use crate::parser::UpdateFileChunk;
+
+const DEV_NULL: &str = "/dev/null";
+
+/// Whether `lines` are a unified diff rather than a `*** Begin Patch`
+/// envelope. Lines before the first file header, such as the commit message
+/// that `git show` and `git format-patch` print, are allowed.
+pub(crate) fn is_unified_diff(lines: &[&str]) -> bool {
+    !lines.iter().any(|line| line.trim() == BEGIN_PATCH_MARKER)
+        && first_file_header(lines).is_some()
+}
+
+/// The index of the first `diff ` line or `---`/`+++` pair.
+fn first_file_header(lines: &[&str]) -> Option<usize> {
+    (0..lines.len()).find(|&index| starts_file_header(&lines[index..]))
+}
+
+fn starts_file_header(lines: &[&str]) -> bool {
+    match lines {
+        [first, ..] if first.starts_with("diff ") => true,
+        [first, second, ..] => first.starts_with("--- ") && second.starts_with("+++ "),
+        _ => false,
+    }
+}
+
+/// The line ranges in a `@@ -a,b +c,d @@` hunk header.
+#[derive(Debug, PartialEq)]
+struct HunkHeader {
+    old_start: usize,
+    old_count: usize,
+    new_count: usize,
+}
+
+impl HunkHeader {
+    fn parse(line: &str) -> Option<Self> {
+        let ranges = line.strip_prefix("@@ -")?.split(" @@").next()?;
+        let (old, new) = ranges.split_once(" +")?;
+        let range = |range: &str| -> Option<(usize, usize)> {
+            match range.split_once(',') {
+                Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+                None => Some((range.parse().ok()?, 1)),
+            }
+        };
+        let (old_start, old_count) = range(old)?;
+        let (_, new_count) = range(new)?;
+        Some(Self {
+            old_start,
+            old_count,
+            new_count,
+        })
+    }
+
+    /// Index of the first line of the hunk in the old file. An empty range
+    /// starts after its line.
+    fn start_index(&self) -> usize {
+        if self.old_count == 0 {
+            self.old_start
+        } else {
+            self.old_start.saturating_sub(1)
+        }
+    }
+}
+
+/// One file's section of the diff.
+#[derive(Default)]
+struct FileDiff {
+    git: bool,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    rename_from: Option<String>,
+    rename_to: Option<String>,
+    new_file: bool,
+    deleted_file: bool,
+    chunks: Vec<UpdateFileChunk>,
+}
+
+pub(crate) fn parse_unified_diff(lines: &[&str]) -> Result<Vec<Hunk>, ParseError> {
+    let mut hunks = Vec::new();
+    let mut file: Option<FileDiff> = None;
+    let mut index = first_file_header(lines).unwrap_or(lines.len());
+    while index < lines.len() {
+        let line = lines[index];
+        let line_number = index + 1;
+
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            hunks.extend(finish_file(file.take(), line_number)?);
+            let (old_path, new_path) = split_git_header(header);
+            file = Some(FileDiff {
+                git: true,
+                old_path,
+                new_path,
+                ..Default::default()
+            });
+            index += 1;
+            continue;
+        }
+
+        if let Some(old) = line.strip_prefix("--- ")
+            && let Some(new) = lines.get(index + 1).and_then(|l| l.strip_prefix("+++ "))
+        {
+            // A `diff --git` header introduces the file; otherwise the
+            // `---` line does.
+            let current = match file.take() {
+                Some(current) if current.git && current.chunks.is_empty() => current,
+                previous => {
+                    hunks.extend(finish_file(previous, line_number)?);
+                    FileDiff::default()
+                }
+            };
+            file = Some(FileDiff {
+                old_path: Some(header_path(old)),
+                new_path: Some(header_path(new)),
+                ..current
+            });
+            index += 2;
+            continue;
+        }
+
+        if line.starts_with("@@ ") {
+            let Some(current) = file.as_mut() else {
+                return Err(InvalidHunkError {
+                    message: "Found a `@@` hunk before any `---`/`+++` file header".to_string(),
+                    line_number,
+                });
+            };
+            let (chunk, consumed) = parse_hunk(line, &lines[index + 1..]);
+            current.chunks.push(chunk);
+            index += 1 + consumed;
+            continue;
+        }
+
+        if let Some(current) = file.as_mut() {
+            if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                return Err(InvalidHunkError {
+                    message: "Binary diffs are not supported".to_string(),
+                    line_number,
+                });
+            } else if line.starts_with("new file mode ") {
+                current.new_file = true;
+            } else if line.starts_with("deleted file mode ") {
+                current.deleted_file = true;
+            } else if let Some(path) = line.strip_prefix("rename from ") {
+                current.rename_from = Some(unquote(path));
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                current.rename_to = Some(unquote(path));
+            }
+        }
+        // Anything else, such as `index` lines or the `-- ` signature that
+        // ends `git format-patch` output, carries nothing to apply.
+        index += 1;
+    }
+    hunks.extend(finish_file(file, lines.len())?);
+
+    if hunks.is_empty() {
+        return Err(InvalidPatchError(
+            "The diff does not change the contents or name of any file".to_string(),
+        ));
+    }
+    Ok(hunks)
+}
+
+/// Parses the lines of a hunk that follow its `header`, returning the chunk
+/// and the number of lines it spans.
+///
+/// Until the line counts in the header are used up, every line belongs to
+/// the hunk, even one that looks like a file header, such as the removal of
+/// a `-- x` line. Past them, which happens when the counts are too small,
+/// the hunk ends at the next header or at the first line that is not part of
+/// a hunk.
+fn parse_hunk(header: &str, lines: &[&str]) -> (UpdateFileChunk, usize) {
+    let header = HunkHeader::parse(header);
+    let mut chunk = UpdateFileChunk {
+        change_context: None,
+        old_lines: Vec::new(),
+        new_lines: Vec::new(),
+        is_end_of_file: false,
+        no_newline_at_end_of_file: false,
+        start_index: header.as_ref().map(HunkHeader::start_index),
+    };
+    let (mut old_left, mut new_left) = header
+        .as_ref()
+        .map_or((0, 0), |header| (header.old_count, header.new_count));
+    let mut consumed = 0;
+    // Blank lines are usually context lines whose leading space was lost, but
+    // past the counted lines they may just separate the hunk from the next
+    // file.
+    let mut trailing_blank_lines = 0;
+    // The first character of the previous line, which a `\ No newline at
+    // end of file` marker applies to.
+    let mut previous = None;
+    for (index, line) in lines.iter().enumerate() {
+        let counted = old_left > 0 || new_left > 0;
+        if !counted
+            && (line.starts_with("@@ ") || *line == "-- " || starts_file_header(&lines[index..]))
+        {
+            break;
+        }
+        match line.chars().next() {
+            None => {
+                chunk.old_lines.push(String::new());
+                chunk.new_lines.push(String::new());
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+                trailing_blank_lines = if counted { 0 } else { trailing_blank_lines + 1 };
+            }
+            Some(' ') => {
+                chunk.old_lines.push(line[1..].to_string());
+                chunk.new_lines.push(line[1..].to_string());
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+                trailing_blank_lines = 0;
+            }
+            Some('-') => {
+                chunk.old_lines.push(line[1..].to_string());
+                old_left = old_left.saturating_sub(1);
+                trailing_blank_lines = 0;
+            }
+            Some('+') => {
+                chunk.new_lines.push(line[1..].to_string());
+                new_left = new_left.saturating_sub(1);
+                trailing_blank_lines = 0;
+            }
+            // `\ No newline at end of file`
+            Some('\\') => {
+                if matches!(previous, Some(' ' | '-') | None) {
+                    chunk.is_end_of_file = true;
+                }
+                if matches!(previous, Some(' ' | '+') | None) {
+                    chunk.no_newline_at_end_of_file = true;
+                }
+            }
+            _ => break,
+        }
+        previous = line.chars().next();
+        consumed += 1;
+    }
+    chunk
+        .old_lines
+        .truncate(chunk.old_lines.len() - trailing_blank_lines);
+    chunk
+        .new_lines
+        .truncate(chunk.new_lines.len() - trailing_blank_lines);
+    (chunk, consumed)
+}
+
+fn finish_file(file: Option<FileDiff>, line_number: usize) -> Result<Option<Hunk>, ParseError> {
+    let Some(file) = file else {
+        return Ok(None);
+    };
+    let (old_path, new_path) = strip_git_prefixes(
+        file.git,
+        file.old_path.filter(|path| path != DEV_NULL),
+        file.new_path.filter(|path| path != DEV_NULL),
+    );
+    // Unlike the other paths, these are written without prefixes.
+    let old_path = file.rename_from.or(old_path);
+    let new_path = file.rename_to.or(new_path);
+
+    let missing_path = || InvalidHunkError {
+        message: "Could not determine which file the diff applies to".to_string(),
+        line_number,
+    };
+    if file.deleted_file || (new_path.is_none() && old_path.is_some()) {
+        let path = old_path.ok_or_else(missing_path)?;
+        return Ok(Some(Hunk::DeleteFile {
+            path: PathBuf::from(path),
+        }));
+    }
+    if file.new_file || old_path.is_none() {
+        let path = new_path.ok_or_else(missing_path)?;
+        let mut contents: String = file
+            .chunks
+            .iter()
+            .flat_map(|chunk| &chunk.new_lines)
+            .map(|line| format!("{line}\n"))
+            .collect();
+        if file
+            .chunks
+            .last()
+            .is_some_and(|chunk| chunk.no_newline_at_end_of_file)
+        {
+            contents.pop();
+        }
+        return Ok(Some(Hunk::AddFile {
+            path: PathBuf::from(path),
+            contents,
+        }));
+    }
+
+    let (Some(old_path), Some(new_path)) = (old_path, new_path) else {
+        return Err(missing_path());
+    };
+    // Outside of git, the old name is often a backup such as `file.orig`.
+    let (path, move_path) = if !file.git {
+        (new_path, None)
+    } else if old_path == new_path {
+        (old_path, None)
+    } else {
+        (old_path, Some(PathBuf::from(new_path)))
+    };
+    if file.chunks.is_empty() && move_path.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(Hunk::UpdateFile {
+        path: PathBuf::from(path),
+        move_path,
+        chunks: file.chunks,
+    }))
+}
+
+/// Removes the `a/` and `b/` prefixes that git puts on paths, which models
+/// also tend to write without a `diff --git` header. A plain diff counts as
+/// git-style when both of its paths carry them.
+fn strip_git_prefixes(
+    git: bool,
+    old_path: Option<String>,
+    new_path: Option<String>,
+) -> (Option<String>, Option<String>) {
+    let prefixed = |path: &Option<String>, prefix: &str| {
+        path.as_ref().is_none_or(|path| path.starts_with(prefix))
+    };
+    let git_style = git || (prefixed(&old_path, "a/") && prefixed(&new_path, "b/"));
+    if !git_style {
+        return (old_path, new_path);
+    }
+    let strip = |path: Option<String>, prefix: &str| {
+        path.map(|path| match path.strip_prefix(prefix) {
+            Some(stripped) => stripped.to_string(),
+            None => path,
+        })
+    };
+    (strip(old_path, "a/"), strip(new_path, "b/"))
+}
+
+/// Splits the `a/old b/new` of a `diff --git` line. Used for files without
+/// `---`/`+++` lines, such as renames without changes.
+fn split_git_header(header: &str) -> (Option<String>, Option<String>) {
+    match header.rsplit_once(" b/") {
+        Some((old, new)) if old.starts_with("a/") => {
+            (Some(old.to_string()), Some(format!("b/{new}")))
+        }
+        _ => (None, None),
+    }
+}
+
+/// The path in a `---` or `+++` line, without the timestamp that `diff -u`
+/// appends after a tab.
+fn header_path(header: &str) -> String {
+    let path = header.split('\t').next().unwrap_or(header);
+    unquote(path.trim_end())
+}
+
+/// Git quotes paths with unusual characters.
+fn unquote(path: &str) -> String {
+    match path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    {
+        Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse(diff: &str) -> Result<Vec<Hunk>, ParseError> {
+        let lines: Vec<&str> = diff.lines().collect();
+        assert!(is_unified_diff(&lines));
+        parse_unified_diff(&lines)
+    }
+
+    fn chunk(start_index: usize, old_lines: &[&str], new_lines: &[&str]) -> UpdateFileChunk {
+        UpdateFileChunk {
+            change_context: None,
+            old_lines: old_lines.iter().map(ToString::to_string).collect(),
+            new_lines: new_lines.iter().map(ToString::to_string).collect(),
+            is_end_of_file: false,
+            no_newline_at_end_of_file: false,
+            start_index: Some(start_index),
+        }
+    }
+
+    #[test]
+    fn parses_plain_unified_diff() {
+        let diff = "--- a/src/main.rs\t2024-01-01 00:00:00\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    println!(\"hi\");\n+    println!(\"hello\");\n }\n\n@@ -10,2 +10,3 @@\n a\n+b\n\\ No newline at end of file\n";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::UpdateFile {
+                path: PathBuf::from("src/main.rs"),
+                move_path: None,
+                chunks: vec![
+                    chunk(
+                        0,
+                        &["fn main() {", "    println!(\"hi\");", "}"],
+                        &["fn main() {", "    println!(\"hello\");", "}"],
+                    ),
+                    UpdateFileChunk {
+                        no_newline_at_end_of_file: true,
+                        ..chunk(9, &["a"], &["a", "b"])
+                    },
+                ],
+            }])
+        );
+    }
+
+    #[test]
+    fn hunk_counts_decide_where_git_show_hunks_end() {
+        let diff = "commit 0123456789abcdef
+Author: Dev <dev@example.com>
+
+    Reword the notes
+
+diff --git a/notes.md b/notes.md
+index 1..2 100644
+--- a/notes.md
++++ b/notes.md
+@@ -1,3 +1,3 @@
+ title
+--- x
++++ y
+ end
+--\x20
+2.40.0
+";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::UpdateFile {
+                path: PathBuf::from("notes.md"),
+                move_path: None,
+                chunks: vec![chunk(
+                    0,
+                    &["title", "-- x", "end"],
+                    &["title", "++ y", "end"]
+                )],
+            }])
+        );
+    }
+
+    #[test]
+    fn parses_git_diff_with_new_deleted_and_renamed_files() {
+        let diff = "diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3b18e51
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++hello
++world
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 3b18e51..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/before.rs b/after.rs
+similarity index 100%
+rename from before.rs
+rename to after.rs
+diff --git a/script.sh b/script.sh
+old mode 100644
+new mode 100755
+diff --git a/empty.txt b/empty.txt
+new file mode 100644
+index 0000000..e69de29
+";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![
+                Hunk::AddFile {
+                    path: PathBuf::from("new.txt"),
+                    contents: "hello\nworld\n".to_string(),
+                },
+                Hunk::DeleteFile {
+                    path: PathBuf::from("old.txt"),
+                },
+                Hunk::UpdateFile {
+                    path: PathBuf::from("before.rs"),
+                    move_path: Some(PathBuf::from("after.rs")),
+                    chunks: vec![],
+                },
+                Hunk::AddFile {
+                    path: PathBuf::from("empty.txt"),
+                    contents: String::new(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_binary_diffs_and_diffs_without_changes() {
+        assert_eq!(
+            parse(
+                "diff --git a/logo.png b/logo.png\nindex 1..2 100644\nBinary files a/logo.png and b/logo.png differ\n"
+            ),
+            Err(InvalidHunkError {
+                message: "Binary diffs are not supported".to_string(),
+                line_number: 3,
+            })
+        );
+        assert_eq!(
+            parse("diff --git a/run.sh b/run.sh\nold mode 100644\nnew mode 100755\n"),
+            Err(InvalidPatchError(
+                "The diff does not change the contents or name of any file".to_string()
+            ))
+        );
+    }
+}
@@ -79,7 +79,7 @@ where
     // Retain the TempDir so it exists for the lifetime of the invocation of
     // this executable. Admittedly, we could invoke `keep()` on it, but it
     // would be nice to avoid leaving temporary directories behind, if possible.
-    let _path_entry = match prepend_path_entry_for_apply_patch() {
+    let path_entry = match prepend_path_entry_for_apply_patch() {
         Ok(path_entry) => Some(path_entry),
         Err(err) => {
             // It is possible that Codex will proceed successfully even if
@@ -94,7 +94,13 @@ where
     let runtime = tokio::runtime::Runtime::new()?;
     runtime.block_on(async move {
         let codex_linux_sandbox_exe: Option<PathBuf> = if cfg!(target_os = "linux") {
-            std::env::current_exe().ok()
+            // Prefer the alias: its file name selects the sandbox even when a
+            // wrapper such as `systemd-run` does not pass on arg0.
+            path_entry
+                .as_ref()
+                .map(|dir| dir.path().join(LINUX_SANDBOX_ARG0))
+                .filter(|alias| alias.exists())
+                .or_else(|| std::env::current_exe().ok())
         } else {
             None
         };
@@ -137,6 +143,9 @@ where
 /// - WINDOWS: `apply_patch.bat` batch script to invoke the current executable
 ///   with the "secret" --codex-run-as-apply-patch flag.
 ///
+/// On Linux it also holds a `codex-linux-sandbox` symlink to the current
+/// executable.
+///
 /// This temporary directory is prepended to the PATH environment variable so
 /// that `apply_patch` can be on the PATH without requiring the user to
 /// install a separate `apply_patch` executable, simplifying the deployment of
@@ -172,6 +181,9 @@ fn prepend_path_entry_for_apply_patch() -> std::io::Result<TempDir> {
         }
     }
 
+    #[cfg(target_os = "linux")]
+    symlink(std::env::current_exe()?, path.join(LINUX_SANDBOX_ARG0))?;
+
     #[cfg(unix)]
     const PATH_SEPARATOR: &str = ":";
 
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use codex_common::CliConfigOverrides;
+use codex_core::bwrap::spawn_command_under_bwrap;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::exec_env::create_env;
@@ -9,6 +10,7 @@ use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
 use codex_protocol::config_types::SandboxMode;
 
+use crate::BwrapCommand;
 use crate::LandlockCommand;
 use crate::SeatbeltCommand;
 use crate::exit_status::handle_exit_status;
@@ -51,9 +53,29 @@ pub async fn run_command_under_landlock(
     .await
 }
 
+pub async fn run_command_under_bwrap(
+    command: BwrapCommand,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let BwrapCommand {
+        full_auto,
+        config_overrides,
+        command,
+    } = command;
+    run_command_under_sandbox(
+        full_auto,
+        command,
+        config_overrides,
+        codex_linux_sandbox_exe,
+        SandboxType::Bwrap,
+    )
+    .await
+}
+
 enum SandboxType {
     Seatbelt,
     Landlock,
+    Bwrap,
 }
 
 async fn run_command_under_sandbox(
@@ -93,6 +115,7 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                &config.resource_limits,
                 stdio_policy,
                 env,
             )
@@ -109,6 +132,20 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                None,
+                &config.resource_limits,
+                stdio_policy,
+                env,
+            )
+            .await?
+        }
+        SandboxType::Bwrap => {
+            spawn_command_under_bwrap(
+                command,
+                cwd,
+                &config.sandbox_policy,
+                sandbox_policy_cwd.as_path(),
+                &config.resource_limits,
                 stdio_policy,
                 env,
             )
@@ -5,6 +5,7 @@ use clap_complete::generate;
 use codex_arg0::arg0_dispatch_or_else;
 use codex_chatgpt::apply_command::ApplyCommand;
 use codex_chatgpt::apply_command::run_apply_command;
+use codex_cli::BwrapCommand;
 use codex_cli::LandlockCommand;
 use codex_cli::SeatbeltCommand;
 use codex_cli::login::run_login_status;
@@ -21,9 +22,15 @@ use std::path::PathBuf;
 use supports_color::Stream;
 
 mod mcp_cmd;
+mod review_cmd;
+mod sessions_cmd;
+mod usage_cmd;
 
 use crate::mcp_cmd::McpCli;
 use crate::proto::ProtoCli;
+use crate::review_cmd::ReviewCli;
+use crate::sessions_cmd::SessionsCli;
+use crate::usage_cmd::UsageCli;
 
 /// Codex CLI
 ///
@@ -56,6 +63,9 @@ enum Subcommand {
     #[clap(visible_alias = "e")]
     Exec(ExecCli),
 
+    /// Review code changes non-interactively and report findings as JSON, SARIF or GitHub annotations.
+    Review(ReviewCli),
+
     /// Manage login.
     Login(LoginCommand),
 
@@ -82,6 +92,12 @@ enum Subcommand {
     /// Resume a previous interactive session (picker by default; use --last to continue the most recent).
     Resume(ResumeCommand),
 
+    /// List, search, export and prune recorded sessions.
+    Sessions(SessionsCli),
+
+    /// Summarize token usage and cost across recorded sessions.
+    Usage(UsageCli),
+
     /// Internal: generate TypeScript protocol bindings.
     #[clap(hide = true)]
     GenerateTs(GenerateTsCommand),
@@ -122,6 +138,9 @@ enum DebugCommand {
 
     /// Run a command under Landlock+seccomp (Linux only).
     Landlock(LandlockCommand),
+
+    /// Run a command under bubblewrap (Linux only).
+    Bwrap(BwrapCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -245,6 +264,13 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Review(mut review_cli)) => {
+            prepend_config_flags(
+                &mut review_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            review_cli.run(codex_linux_sandbox_exe).await?;
+        }
         Some(Subcommand::Mcp(mut mcp_cli)) => {
             // Propagate any root-level config overrides (e.g. `-c key=value`).
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
@@ -264,6 +290,20 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Sessions(mut sessions_cli)) => {
+            prepend_config_flags(
+                &mut sessions_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            sessions_cli.run().await?;
+        }
+        Some(Subcommand::Usage(mut usage_cli)) => {
+            prepend_config_flags(
+                &mut usage_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            usage_cli.run()?;
+        }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(
                 &mut login_cli.config_overrides,
@@ -322,6 +362,17 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 )
                 .await?;
             }
+            DebugCommand::Bwrap(mut bwrap_cli) => {
+                prepend_config_flags(
+                    &mut bwrap_cli.config_overrides,
+                    root_config_overrides.clone(),
+                );
+                codex_cli::debug_sandbox::run_command_under_bwrap(
+                    bwrap_cli,
+                    codex_linux_sandbox_exe,
+                )
+                .await?;
+            }
         },
         Some(Subcommand::Apply(mut apply_cli)) => {
             prepend_config_flags(
@@ -13,6 +13,9 @@ use codex_core::config::load_global_mcp_servers;
 use codex_core::config::write_global_mcp_servers;
 use codex_core::config_types::McpServerConfig;
 use codex_core::config_types::McpServerTransportConfig;
+use codex_login::McpLoginOptions;
+use codex_login::run_mcp_login_server;
+use codex_rmcp_client::delete_oauth_tokens;
 
 /// [experimental] Launch Codex as an MCP server or manage configured MCP servers.
 ///
@@ -22,6 +25,8 @@ use codex_core::config_types::McpServerTransportConfig;
 /// - `get`    — show a single server (with `--json`)
 /// - `add`    — add a server launcher entry to `~/.codex/config.toml`
 /// - `remove` — delete a server entry
+/// - `login`  — authorize Codex with a streamable HTTP server using OAuth
+/// - `logout` — delete the OAuth tokens stored for a server
 #[derive(Debug, clap::Parser)]
 pub struct McpCli {
     #[clap(flatten)]
@@ -47,6 +52,12 @@ pub enum McpSubcommand {
 
     /// [experimental] Remove a global MCP server entry.
     Remove(RemoveArgs),
+
+    /// [experimental] Authorize Codex with a streamable HTTP MCP server using OAuth.
+    Login(LoginArgs),
+
+    /// [experimental] Delete the OAuth tokens stored for an MCP server.
+    Logout(LogoutArgs),
 }
 
 #[derive(Debug, clap::Parser)]
@@ -86,6 +97,18 @@ pub struct RemoveArgs {
     pub name: String,
 }
 
+#[derive(Debug, clap::Parser)]
+pub struct LoginArgs {
+    /// Name of the MCP server to authorize.
+    pub name: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LogoutArgs {
+    /// Name of the MCP server whose tokens should be deleted.
+    pub name: String,
+}
+
 impl McpCli {
     pub async fn run(self, codex_linux_sandbox_exe: Option<PathBuf>) -> Result<()> {
         let McpCli {
@@ -110,6 +133,12 @@ impl McpCli {
             McpSubcommand::Remove(args) => {
                 run_remove(&config_overrides, args)?;
             }
+            McpSubcommand::Login(args) => {
+                run_login(&config_overrides, args).await?;
+            }
+            McpSubcommand::Logout(args) => {
+                run_logout(&config_overrides, args)?;
+            }
         }
 
         Ok(())
@@ -152,6 +181,9 @@ fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Result<(
         },
         startup_timeout_sec: None,
         tool_timeout_sec: None,
+        approval: None,
+        allowed_tools: Vec::new(),
+        denied_tools: Vec::new(),
     };
 
     servers.insert(name.clone(), new_entry);
@@ -180,6 +212,8 @@ fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) ->
     if removed {
         write_global_mcp_servers(&codex_home, &servers)
             .with_context(|| format!("failed to write MCP servers to {}", codex_home.display()))?;
+        delete_oauth_tokens(&codex_home, &name)
+            .with_context(|| format!("failed to delete OAuth tokens for '{name}'"))?;
     }
 
     if removed {
@@ -191,6 +225,63 @@ fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) ->
     Ok(())
 }
 
+async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let LoginArgs { name } = login_args;
+
+    let Some(server) = config.mcp_servers.get(&name) else {
+        bail!("No MCP server named '{name}' found.");
+    };
+    let McpServerTransportConfig::StreamableHttp { url, bearer_token } = &server.transport else {
+        bail!(
+            "MCP server '{name}' uses stdio; OAuth login is only supported for streamable HTTP servers."
+        );
+    };
+    if bearer_token.is_some() {
+        eprintln!(
+            "Note: '{name}' has a bearer_token in config.toml, which is used instead of the OAuth tokens."
+        );
+    }
+
+    let opts = McpLoginOptions::new(config.codex_home.clone(), name.clone(), url.clone());
+    let login = run_mcp_login_server(opts)
+        .await
+        .with_context(|| format!("failed to start OAuth login for '{name}'"))?;
+    eprintln!(
+        "Starting local login server on http://127.0.0.1:{}.\nIf your browser did not open, navigate to this URL to authorize Codex:\n\n{}",
+        login.actual_port, login.auth_url,
+    );
+    login
+        .block_until_done()
+        .await
+        .with_context(|| format!("OAuth login for '{name}' failed"))?;
+
+    println!("Logged in to MCP server '{name}'.");
+
+    Ok(())
+}
+
+fn run_logout(config_overrides: &CliConfigOverrides, logout_args: LogoutArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let LogoutArgs { name } = logout_args;
+
+    let removed = delete_oauth_tokens(&config.codex_home, &name)
+        .with_context(|| format!("failed to delete OAuth tokens for '{name}'"))?;
+    if removed {
+        println!("Logged out of MCP server '{name}'.");
+    } else {
+        println!("No OAuth tokens stored for MCP server '{name}'.");
+    }
+
+    Ok(())
+}
+
 fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Result<()> {
     let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
     let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
@@ -427,6 +518,15 @@ fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<(
     if let Some(timeout) = server.tool_timeout_sec {
         println!("  tool_timeout_sec: {}", timeout.as_secs_f64());
     }
+    if let Some(approval) = server.approval {
+        println!("  approval: {}", approval.as_str());
+    }
+    if !server.allowed_tools.is_empty() {
+        println!("  allowed_tools: {}", server.allowed_tools.join(", "));
+    }
+    if !server.denied_tools.is_empty() {
+        println!("  denied_tools: {}", server.denied_tools.join(", "));
+    }
     println!("  remove: codex mcp remove {}", get_args.name);
 
     Ok(())
@@ -0,0 +1,465 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use clap::ValueEnum;
+use codex_common::CliConfigOverrides;
+use codex_core::AuthManager;
+use codex_core::ConversationManager;
+use codex_core::NewConversation;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::git_info::get_git_repo_root;
+use codex_core::git_info::merge_base_with_head;
+use codex_core::git_info::resolve_commit;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewFinding;
+use codex_core::protocol::ReviewOutputEvent;
+use codex_core::protocol::ReviewRequest;
+use codex_protocol::config_types::SandboxMode;
+use serde_json::json;
+
+/// Review code changes without opening the TUI and report the findings in a
+/// machine-readable format.
+///
+/// Exits with status 2 when a finding is at least as severe as `--fail-on`,
+/// so the command can gate CI pipelines. Errors exit with status 1.
+#[derive(Debug, clap::Parser)]
+pub struct ReviewCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[clap(flatten)]
+    pub target: ReviewTarget,
+
+    /// Format to write the findings in.
+    #[arg(long, value_enum, default_value_t = ReviewFormat::Json)]
+    pub format: ReviewFormat,
+
+    /// Write the findings to FILE instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Exit with status 2 if any finding has this priority or a more severe one.
+    #[arg(long = "fail-on", value_enum, default_value_t = FailOn::P1)]
+    pub fail_on: FailOn,
+
+    /// Model to review with (defaults to `review_model` from config.toml).
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    /// Configuration profile from config.toml to specify default options.
+    #[arg(long = "profile", short = 'p')]
+    pub config_profile: Option<String>,
+
+    /// Review the repository in DIR instead of the current directory.
+    #[arg(long = "cd", short = 'C', value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+}
+
+/// What to review; exactly one must be given.
+#[derive(Debug, clap::Args)]
+#[group(required = true, multiple = false)]
+pub struct ReviewTarget {
+    /// Review the changes on the current branch against BRANCH.
+    #[arg(long, value_name = "BRANCH")]
+    pub base: Option<String>,
+
+    /// Review the changes introduced by a commit.
+    #[arg(long, value_name = "SHA")]
+    pub commit: Option<String>,
+
+    /// Review staged, unstaged and untracked changes.
+    #[arg(long)]
+    pub uncommitted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReviewFormat {
+    /// The review output as produced by the reviewer.
+    Json,
+    /// SARIF 2.1.0, for code scanning dashboards.
+    Sarif,
+    /// GitHub Actions workflow commands, shown as annotations on the diff.
+    Github,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FailOn {
+    P0,
+    P1,
+    P2,
+    P3,
+    /// Never fail because of findings.
+    None,
+}
+
+impl FailOn {
+    fn is_exceeded_by(self, finding: &ReviewFinding) -> bool {
+        let threshold = match self {
+            FailOn::P0 => 0,
+            FailOn::P1 => 1,
+            FailOn::P2 => 2,
+            FailOn::P3 => 3,
+            FailOn::None => return false,
+        };
+        finding.priority <= threshold
+    }
+}
+
+impl ReviewCli {
+    pub async fn run(self, codex_linux_sandbox_exe: Option<PathBuf>) -> Result<()> {
+        let ReviewCli {
+            config_overrides,
+            target,
+            format,
+            output,
+            fail_on,
+            model,
+            config_profile,
+            cwd,
+        } = self;
+
+        let overrides = ConfigOverrides {
+            review_model: model,
+            config_profile,
+            // Nobody is around to approve anything, and a review has no
+            // business modifying the working tree.
+            approval_policy: Some(AskForApproval::Never),
+            sandbox_mode: Some(SandboxMode::ReadOnly),
+            cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        };
+        let cli_kv_overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+        let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)
+            .context("failed to load configuration")?;
+
+        let Some(repo_root) = get_git_repo_root(&config.cwd) else {
+            bail!("{} is not inside a git repository", config.cwd.display());
+        };
+        let review_request = review_request(&target, &config.cwd).await?;
+        eprintln!("Reviewing {}…", review_request.user_facing_hint);
+
+        let review = run_review(config, review_request).await?;
+
+        let rendered = match format {
+            ReviewFormat::Json => serde_json::to_string_pretty(&review)?,
+            ReviewFormat::Sarif => serde_json::to_string_pretty(&to_sarif(&review, &repo_root))?,
+            ReviewFormat::Github => to_github_annotations(&review, &repo_root),
+        };
+        match output {
+            Some(path) => std::fs::write(&path, format!("{rendered}\n"))
+                .with_context(|| format!("failed to write {}", path.display()))?,
+            None => println!("{rendered}"),
+        }
+
+        let failing = count_failing(&review, fail_on);
+        eprintln!(
+            "{} finding(s), {failing} at or above the --fail-on threshold.",
+            review.findings.len()
+        );
+        if let Some(code) = threshold_exit_code(failing) {
+            std::process::exit(code);
+        }
+        Ok(())
+    }
+}
+
+/// Exit status for a review whose findings reach the `--fail-on` threshold.
+/// Distinct from the status 1 that errors exit with, so CI can tell a failed
+/// run from a failed review.
+const FINDINGS_EXIT_CODE: i32 = 2;
+
+fn count_failing(review: &ReviewOutputEvent, fail_on: FailOn) -> usize {
+    review
+        .findings
+        .iter()
+        .filter(|finding| fail_on.is_exceeded_by(finding))
+        .count()
+}
+
+fn threshold_exit_code(failing: usize) -> Option<i32> {
+    (failing > 0).then_some(FINDINGS_EXIT_CODE)
+}
+
+/// Build the same review requests the TUI's `/review` presets submit.
+async fn review_request(target: &ReviewTarget, cwd: &Path) -> Result<ReviewRequest> {
+    if let Some(branch) = &target.base {
+        // Resolved here rather than by the model: CI checkouts often have no
+        // upstream configured for `branch`.
+        let Some(merge_base) = merge_base_with_head(cwd, branch).await else {
+            bail!("cannot find where the current branch forked from `{branch}`");
+        };
+        return Ok(ReviewRequest {
+            prompt: format!(
+                "Review the code changes against the base branch '{branch}'. The merge base of the current branch and {branch} is {merge_base}; run `git diff {merge_base}` to see what changes we would merge into the {branch} branch. Provide prioritized, actionable findings."
+            ),
+            user_facing_hint: format!("changes against '{branch}'"),
+        });
+    }
+    if let Some(rev) = &target.commit {
+        let Some(commit) = resolve_commit(cwd, rev).await else {
+            bail!("unknown commit `{rev}`");
+        };
+        let sha = commit.sha;
+        let subject = commit.subject;
+        let short = sha.chars().take(7).collect::<String>();
+        return Ok(ReviewRequest {
+            prompt: format!(
+                "Review the code changes introduced by commit {sha} (\"{subject}\"). Provide prioritized, actionable findings."
+            ),
+            user_facing_hint: format!("commit {short}"),
+        });
+    }
+    Ok(ReviewRequest {
+        prompt: "Review the current code changes (staged, unstaged, and untracked files) and provide prioritized findings.".to_string(),
+        user_facing_hint: "current changes".to_string(),
+    })
+}
+
+/// Run a review in a fresh conversation and wait for its output.
+async fn run_review(config: Config, review_request: ReviewRequest) -> Result<ReviewOutputEvent> {
+    let conversation_manager =
+        ConversationManager::new(AuthManager::shared(config.codex_home.clone()));
+    let NewConversation { conversation, .. } =
+        conversation_manager.new_conversation(config).await?;
+
+    conversation.submit(Op::Review { review_request }).await?;
+
+    let mut review_output = None;
+    let mut last_error = None;
+    loop {
+        match conversation.next_event().await?.msg {
+            EventMsg::ExitedReviewMode(event) => review_output = event.review_output,
+            EventMsg::Error(event) => last_error = Some(event.message),
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) => break,
+            _ => {}
+        }
+    }
+
+    conversation.submit(Op::Shutdown).await?;
+    while let Ok(event) = conversation.next_event().await {
+        if matches!(event.msg, EventMsg::ShutdownComplete) {
+            break;
+        }
+    }
+
+    match (review_output, last_error) {
+        (Some(review_output), _) => Ok(review_output),
+        (None, Some(message)) => bail!("review failed: {message}"),
+        (None, None) => bail!("review finished without producing any output"),
+    }
+}
+
+/// Path of `path` relative to `root` with `/` separators, as both SARIF and
+/// GitHub annotations expect.
+fn relative_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn sarif_level(priority: i32) -> &'static str {
+    match priority {
+        i32::MIN..=1 => "error",
+        2 => "warning",
+        _ => "note",
+    }
+}
+
+fn to_sarif(review: &ReviewOutputEvent, repo_root: &Path) -> serde_json::Value {
+    let results: Vec<serde_json::Value> = review
+        .findings
+        .iter()
+        .map(|finding| {
+            let range = &finding.code_location.line_range;
+            json!({
+                "ruleId": "codex-review",
+                "level": sarif_level(finding.priority),
+                "message": { "text": format!("{}\n\n{}", finding.title, finding.body) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": relative_path(&finding.code_location.absolute_file_path, repo_root),
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        "region": {
+                            "startLine": range.start.max(1),
+                            "endLine": range.end.max(range.start).max(1),
+                        },
+                    },
+                }],
+                "properties": {
+                    "priority": finding.priority,
+                    "confidence_score": finding.confidence_score,
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "codex",
+                    "informationUri": "https://github.com/openai/codex",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": "codex-review",
+                        "shortDescription": { "text": "Code review finding" },
+                    }],
+                },
+            },
+            "results": results,
+            "properties": {
+                "overall_correctness": review.overall_correctness,
+                "overall_explanation": review.overall_explanation,
+                "overall_confidence_score": review.overall_confidence_score,
+            },
+        }],
+    })
+}
+
+fn github_level(priority: i32) -> &'static str {
+    match priority {
+        i32::MIN..=1 => "error",
+        2 => "warning",
+        _ => "notice",
+    }
+}
+
+/// One `::error file=…::message` workflow command per finding.
+fn to_github_annotations(review: &ReviewOutputEvent, repo_root: &Path) -> String {
+    review
+        .findings
+        .iter()
+        .map(|finding| {
+            let range = &finding.code_location.line_range;
+            format!(
+                "::{} file={},line={},endLine={},title={}::{}",
+                github_level(finding.priority),
+                escape_github_property(&relative_path(
+                    &finding.code_location.absolute_file_path,
+                    repo_root
+                )),
+                range.start.max(1),
+                range.end.max(range.start).max(1),
+                escape_github_property(&finding.title),
+                escape_github_data(&finding.body),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn escape_github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::ReviewCodeLocation;
+    use codex_core::protocol::ReviewLineRange;
+    use pretty_assertions::assert_eq;
+
+    fn finding(priority: i32, path: &str, start: u32, end: u32) -> ReviewFinding {
+        ReviewFinding {
+            title: format!("[P{priority}] Check the result, too"),
+            body: "The error is dropped:\nhandle it.".to_string(),
+            confidence_score: 0.8,
+            priority,
+            code_location: ReviewCodeLocation {
+                absolute_file_path: PathBuf::from(path),
+                line_range: ReviewLineRange { start, end },
+            },
+        }
+    }
+
+    fn review(findings: Vec<ReviewFinding>) -> ReviewOutputEvent {
+        ReviewOutputEvent {
+            findings,
+            overall_correctness: "patch is incorrect".to_string(),
+            overall_explanation: "One error is ignored.".to_string(),
+            overall_confidence_score: 0.7,
+        }
+    }
+
+    #[test]
+    fn fail_on_counts_equal_and_more_severe_priorities() {
+        assert!(FailOn::P1.is_exceeded_by(&finding(0, "/repo/a.rs", 1, 1)));
+        assert!(FailOn::P1.is_exceeded_by(&finding(1, "/repo/a.rs", 1, 1)));
+        assert!(!FailOn::P1.is_exceeded_by(&finding(2, "/repo/a.rs", 1, 1)));
+        assert!(!FailOn::None.is_exceeded_by(&finding(0, "/repo/a.rs", 1, 1)));
+    }
+
+    #[test]
+    fn findings_at_the_threshold_exit_with_a_distinct_status() {
+        let review = review(vec![
+            finding(1, "/repo/a.rs", 1, 1),
+            finding(3, "/repo/b.rs", 2, 2),
+        ]);
+
+        let failing = count_failing(&review, FailOn::P1);
+        assert_eq!(failing, 1);
+        assert_eq!(threshold_exit_code(failing), Some(2));
+        assert_eq!(
+            threshold_exit_code(count_failing(&review, FailOn::P0)),
+            None
+        );
+        assert_eq!(
+            threshold_exit_code(count_failing(&review, FailOn::None)),
+            None
+        );
+    }
+
+    #[test]
+    fn github_annotations_escape_properties_and_data() {
+        let review = review(vec![
+            finding(1, "/repo/src/a,b.rs", 10, 12),
+            finding(3, "/elsewhere/c.rs", 4, 0),
+        ]);
+        assert_eq!(
+            to_github_annotations(&review, Path::new("/repo")),
+            "::error file=src/a%2Cb.rs,line=10,endLine=12,title=[P1] Check the result%2C too::The error is dropped:%0Ahandle it.\n\
+             ::notice file=/elsewhere/c.rs,line=4,endLine=4,title=[P3] Check the result%2C too::The error is dropped:%0Ahandle it."
+        );
+    }
+
+    #[test]
+    fn sarif_reports_relative_locations() {
+        let review = review(vec![finding(2, "/repo/src/lib.rs", 3, 5)]);
+        let sarif = to_sarif(&review, Path::new("/repo"));
+        let run = &sarif["runs"][0];
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["name"], "codex");
+        let result = &run["results"][0];
+        assert_eq!(result["level"], "warning");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "src/lib.rs", "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": 3, "endLine": 5 },
+            })
+        );
+        assert_eq!(result["properties"]["priority"], 2);
+    }
+}
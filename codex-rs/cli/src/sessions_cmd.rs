@@ -0,0 +1,610 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use clap::ValueEnum;
+use codex_common::CliConfigOverrides;
+use codex_core::RecordedSession;
+use codex_core::TranscriptMessage;
+use codex_core::TranscriptRole;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::find_session_path;
+use codex_core::list_sessions;
+use codex_core::prune_sessions;
+use codex_core::read_session;
+
+/// Browse, search, export and prune the sessions recorded in `~/.codex/sessions`.
+///
+/// Subcommands:
+/// - `list`   — list sessions, optionally filtered (with `--json`)
+/// - `search` — find sessions whose messages contain some text
+/// - `show`   — print the transcript of a session
+/// - `export` — write a session as Markdown, HTML or JSON
+/// - `prune`  — delete sessions older than a given age
+#[derive(Debug, clap::Parser)]
+pub struct SessionsCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub cmd: SessionsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum SessionsSubcommand {
+    /// List recorded sessions, newest first.
+    List(ListArgs),
+
+    /// Search user and assistant messages across sessions (case-insensitive).
+    Search(SearchArgs),
+
+    /// Print the transcript of a session.
+    Show(ShowArgs),
+
+    /// Export the transcript of a session.
+    Export(ExportArgs),
+
+    /// Delete sessions older than a given age.
+    Prune(PruneArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SessionFilters {
+    /// Only include sessions started in DIR or one of its subdirectories.
+    #[arg(long, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Only include sessions started on or after this UTC day (YYYY-MM-DD).
+    #[arg(long, value_name = "DAY")]
+    pub since: Option<String>,
+
+    /// Only include sessions started on or before this UTC day (YYYY-MM-DD).
+    #[arg(long, value_name = "DAY")]
+    pub until: Option<String>,
+
+    /// Only include sessions that used this model.
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    /// Only include sessions started on this git branch.
+    #[arg(long)]
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    #[clap(flatten)]
+    pub filters: SessionFilters,
+
+    /// Show at most this many sessions.
+    #[arg(long, short = 'n')]
+    pub limit: Option<usize>,
+
+    /// Output the sessions as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct SearchArgs {
+    /// Text to look for.
+    pub text: String,
+
+    #[clap(flatten)]
+    pub filters: SessionFilters,
+
+    /// Output the matches as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ShowArgs {
+    /// Session id (UUID).
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ExportArgs {
+    /// Session id (UUID).
+    #[arg(value_name = "SESSION_ID")]
+    pub session_id: String,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+    pub format: ExportFormat,
+
+    /// Write to FILE instead of stdout.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct PruneArgs {
+    /// Delete sessions last written to longer ago than this (e.g. `30d`, `2w`,
+    /// `12h`).
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    pub older_than: Duration,
+
+    /// Also delete old sessions from `~/.codex/archived_sessions`.
+    #[arg(long)]
+    pub include_archived: bool,
+
+    /// Print the sessions that would be deleted without deleting them.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl SessionsCli {
+    pub async fn run(self) -> Result<()> {
+        let SessionsCli {
+            config_overrides,
+            cmd,
+        } = self;
+
+        let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+        let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+            .context("failed to load configuration")?;
+        let codex_home = config.codex_home.as_path();
+
+        match cmd {
+            SessionsSubcommand::List(args) => run_list(codex_home, args).await,
+            SessionsSubcommand::Search(args) => run_search(codex_home, args).await,
+            SessionsSubcommand::Show(args) => run_show(codex_home, args).await,
+            SessionsSubcommand::Export(args) => run_export(codex_home, args).await,
+            SessionsSubcommand::Prune(args) => run_prune(codex_home, args).await,
+        }
+    }
+}
+
+impl SessionFilters {
+    /// Whether `session` started before `--since`. Sessions are listed newest
+    /// first, so every later one is older as well.
+    fn is_before_since(&self, session: &RecordedSession) -> bool {
+        self.since
+            .as_deref()
+            .is_some_and(|since| started_day(session) < since)
+    }
+
+    fn matches(&self, session: &RecordedSession) -> bool {
+        let day = started_day(session);
+        self.cwd
+            .as_ref()
+            .is_none_or(|cwd| session.cwd.starts_with(cwd))
+            && self.since.as_deref().is_none_or(|since| day >= since)
+            && self.until.as_deref().is_none_or(|until| day <= until)
+            && self
+                .model
+                .as_ref()
+                .is_none_or(|model| session.models.contains(model))
+            && self
+                .branch
+                .as_ref()
+                .is_none_or(|branch| session.git_branch.as_ref() == Some(branch))
+    }
+}
+
+/// Number of rollouts read per page while listing or searching.
+const PAGE_SIZE: usize = 50;
+
+/// Sessions matching `filters`, newest first. Pages through the rollouts so
+/// that at most `limit` matches are read, and stops once sessions are older
+/// than `--since`.
+async fn load_filtered(
+    codex_home: &Path,
+    mut filters: SessionFilters,
+    limit: Option<usize>,
+) -> Result<Vec<RecordedSession>> {
+    if let Some(cwd) = filters.cwd.take() {
+        let cwd = if cwd.is_relative() {
+            std::env::current_dir()?.join(cwd)
+        } else {
+            cwd
+        };
+        filters.cwd = Some(cwd);
+    }
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut matched = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = list_sessions(codex_home, PAGE_SIZE, cursor.as_ref())
+            .await
+            .with_context(|| format!("failed to read sessions from {}", codex_home.display()))?;
+        for session in page.sessions {
+            if filters.is_before_since(&session) {
+                return Ok(matched);
+            }
+            if filters.matches(&session) {
+                matched.push(session);
+                if matched.len() >= limit {
+                    return Ok(matched);
+                }
+            }
+        }
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return Ok(matched),
+        }
+    }
+}
+
+async fn run_list(codex_home: &Path, args: ListArgs) -> Result<()> {
+    let ListArgs {
+        filters,
+        limit,
+        json,
+    } = args;
+
+    let sessions = load_filtered(codex_home, filters, limit).await?;
+
+    if json {
+        let rows: Vec<serde_json::Value> = sessions
+            .iter()
+            .map(|session| {
+                serde_json::json!({
+                    "id": session.id,
+                    "timestamp": session.timestamp,
+                    "cwd": session.cwd,
+                    "git_branch": session.git_branch,
+                    "models": session.models,
+                    "path": session.path,
+                    "preview": preview(session),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if sessions.is_empty() {
+        println!("No sessions found.");
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 6]> = sessions
+        .iter()
+        .map(|session| {
+            [
+                session.id.clone(),
+                started(session).to_string(),
+                session
+                    .models
+                    .last()
+                    .cloned()
+                    .unwrap_or_else(|| "-".to_string()),
+                session
+                    .git_branch
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+                session.cwd.display().to_string(),
+                preview(session),
+            ]
+        })
+        .collect();
+    print_table(
+        ["ID", "Started", "Model", "Branch", "Directory", "Message"],
+        &rows,
+    );
+
+    Ok(())
+}
+
+async fn run_search(codex_home: &Path, args: SearchArgs) -> Result<()> {
+    let SearchArgs {
+        text,
+        filters,
+        json,
+    } = args;
+    if text.trim().is_empty() {
+        bail!("search text must not be empty");
+    }
+    let needle = text.to_lowercase();
+
+    let sessions = load_filtered(codex_home, filters, None).await?;
+    let mut matches: Vec<(&RecordedSession, &TranscriptMessage)> = Vec::new();
+    for session in &sessions {
+        for message in &session.messages {
+            if message.text.to_lowercase().contains(&needle) {
+                matches.push((session, message));
+            }
+        }
+    }
+
+    if json {
+        let rows: Vec<serde_json::Value> = matches
+            .iter()
+            .map(|(session, message)| {
+                serde_json::json!({
+                    "id": session.id,
+                    "cwd": session.cwd,
+                    "timestamp": message.timestamp,
+                    "role": message.role,
+                    "text": message.text,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if matches.is_empty() {
+        println!("No messages matching \"{text}\".");
+        return Ok(());
+    }
+
+    for (session, message) in matches {
+        let line = message
+            .text
+            .lines()
+            .find(|line| line.to_lowercase().contains(&needle))
+            .unwrap_or_default();
+        println!(
+            "{}  {}  {}: {}",
+            session.id,
+            started(session),
+            role_label(message.role),
+            truncate(line.trim(), 100)
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_show(codex_home: &Path, args: ShowArgs) -> Result<()> {
+    let session = load_session(codex_home, &args.session_id).await?;
+
+    println!("Session {}", session.id);
+    for (label, value) in session_details(&session) {
+        println!("{label}: {value}");
+    }
+    for message in &session.messages {
+        println!();
+        println!("{}:", role_label(message.role));
+        println!("{}", message.text.trim_end());
+    }
+
+    Ok(())
+}
+
+async fn run_export(codex_home: &Path, args: ExportArgs) -> Result<()> {
+    let ExportArgs {
+        session_id,
+        format,
+        output,
+    } = args;
+    let session = load_session(codex_home, &session_id).await?;
+
+    let rendered = match format {
+        ExportFormat::Markdown => render_markdown(&session),
+        ExportFormat::Html => render_html(&session),
+        ExportFormat::Json => serde_json::to_string_pretty(&session)? + "\n",
+    };
+
+    match output {
+        Some(path) => std::fs::write(&path, rendered)
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{rendered}"),
+    }
+
+    Ok(())
+}
+
+async fn run_prune(codex_home: &Path, args: PruneArgs) -> Result<()> {
+    let PruneArgs {
+        older_than,
+        include_archived,
+        dry_run,
+    } = args;
+
+    let pruned = prune_sessions(codex_home, older_than, include_archived, dry_run)
+        .await
+        .context("failed to prune sessions")?;
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for path in &pruned {
+        println!("{verb} {}", path.display());
+    }
+    let noun = if pruned.len() == 1 {
+        "session"
+    } else {
+        "sessions"
+    };
+    println!("{verb} {} {noun}.", pruned.len());
+
+    Ok(())
+}
+
+async fn load_session(codex_home: &Path, session_id: &str) -> Result<RecordedSession> {
+    let Some(path) = find_session_path(codex_home, session_id).await? else {
+        bail!("No session found with id {session_id}");
+    };
+    read_session(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Day a session was started (YYYY-MM-DD).
+fn started_day(session: &RecordedSession) -> &str {
+    session.timestamp.get(..10).unwrap_or(&session.timestamp)
+}
+
+/// Start time of a session, to the minute.
+fn started(session: &RecordedSession) -> &str {
+    session.timestamp.get(..16).unwrap_or(&session.timestamp)
+}
+
+/// First line of the first user message.
+fn preview(session: &RecordedSession) -> String {
+    session
+        .messages
+        .iter()
+        .find(|message| message.role == TranscriptRole::User)
+        .and_then(|message| message.text.lines().find(|line| !line.trim().is_empty()))
+        .map(|line| truncate(line.trim(), 60))
+        .unwrap_or_default()
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn role_label(role: TranscriptRole) -> &'static str {
+    match role {
+        TranscriptRole::User => "user",
+        TranscriptRole::Assistant => "assistant",
+    }
+}
+
+fn session_details(session: &RecordedSession) -> Vec<(&'static str, String)> {
+    let mut details = vec![
+        ("Started", session.timestamp.clone()),
+        ("Directory", session.cwd.display().to_string()),
+    ];
+    if let Some(branch) = &session.git_branch {
+        details.push(("Branch", branch.clone()));
+    }
+    if !session.models.is_empty() {
+        details.push(("Models", session.models.join(", ")));
+    }
+    details
+}
+
+fn render_markdown(session: &RecordedSession) -> String {
+    let mut out = format!("# Session {}\n\n", session.id);
+    for (label, value) in session_details(session) {
+        out.push_str(&format!("- **{label}:** {value}\n"));
+    }
+    for message in &session.messages {
+        let heading = match message.role {
+            TranscriptRole::User => "User",
+            TranscriptRole::Assistant => "Assistant",
+        };
+        out.push_str(&format!("\n## {heading}\n\n{}\n", message.text.trim_end()));
+    }
+    out
+}
+
+fn render_html(session: &RecordedSession) -> String {
+    let title = format!("Session {}", escape_html(&session.id));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n<dl>\n"
+    );
+    for (label, value) in session_details(session) {
+        out.push_str(&format!(
+            "<dt>{label}</dt><dd>{}</dd>\n",
+            escape_html(&value)
+        ));
+    }
+    out.push_str("</dl>\n");
+    for message in &session.messages {
+        let role = role_label(message.role);
+        out.push_str(&format!(
+            "<section class=\"{role}\">\n<h2>{role}</h2>\n<pre>{}</pre>\n</section>\n",
+            escape_html(message.text.trim_end())
+        ));
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn print_table<const N: usize>(headers: [&str; N], rows: &[[String; N]]) {
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_line = |cells: Vec<&str>| -> String {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_line(headers.to_vec()));
+    for row in rows {
+        println!("{}", format_line(row.iter().map(String::as_str).collect()));
+    }
+}
+
+/// Parses an age such as `30d`, `2w` or `12h`.
+fn parse_age(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in `{raw}` (expected h, d or w)"))?;
+    let (value, unit) = raw.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("invalid age `{raw}` (expected e.g. 30d)"))?;
+    let hours = match unit {
+        "h" => 1,
+        "d" => 24,
+        "w" => 24 * 7,
+        _ => return Err(format!("unknown unit `{unit}` (expected h, d or w)")),
+    };
+    Ok(Duration::from_secs(
+        value.saturating_mul(hours).saturating_mul(60 * 60),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_ages() {
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(parse_age("30d"), Ok(Duration::from_secs(30 * 24 * 60 * 60)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * 24 * 60 * 60)));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
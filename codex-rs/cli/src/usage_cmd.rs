@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use clap::ValueEnum;
+use codex_common::CliConfigOverrides;
+use codex_core::UsageRecord;
+use codex_core::collect_usage;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::protocol::TokenUsage;
+use codex_protocol::num_format::format_usd;
+use codex_protocol::num_format::format_with_separators;
+
+/// Summarize token usage and cost recorded in `~/.codex/sessions`.
+///
+/// Costs use the `[models.<slug>.pricing]` tables from `config.toml`; models
+/// without pricing are counted in tokens only.
+#[derive(Debug, clap::Parser)]
+pub struct UsageCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Columns to group usage by (comma-separated).
+    #[arg(long = "by", value_enum, value_delimiter = ',', default_value = "day")]
+    pub group_by: Vec<UsageDimension>,
+
+    /// Only include usage recorded on or after this UTC day (YYYY-MM-DD).
+    #[arg(long, value_name = "DAY")]
+    pub since: Option<String>,
+
+    /// Only include usage recorded on or before this UTC day (YYYY-MM-DD).
+    #[arg(long, value_name = "DAY")]
+    pub until: Option<String>,
+
+    /// Output the summary as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UsageDimension {
+    Day,
+    Month,
+    Model,
+    Provider,
+    Cwd,
+}
+
+impl UsageDimension {
+    fn header(self) -> &'static str {
+        match self {
+            UsageDimension::Day => "Day",
+            UsageDimension::Month => "Month",
+            UsageDimension::Model => "Model",
+            UsageDimension::Provider => "Provider",
+            UsageDimension::Cwd => "Directory",
+        }
+    }
+
+    fn json_key(self) -> &'static str {
+        match self {
+            UsageDimension::Day => "day",
+            UsageDimension::Month => "month",
+            UsageDimension::Model => "model",
+            UsageDimension::Provider => "provider",
+            UsageDimension::Cwd => "cwd",
+        }
+    }
+
+    fn value(self, record: &UsageRecord) -> String {
+        match self {
+            UsageDimension::Day => record.day.clone(),
+            UsageDimension::Month => record.day.get(..7).unwrap_or(&record.day).to_string(),
+            UsageDimension::Model => record.model.clone(),
+            UsageDimension::Provider => record.model_provider.clone(),
+            UsageDimension::Cwd => record.cwd.display().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct UsageTotals {
+    token_usage: TokenUsage,
+    /// Sum of the cost of priced models, `None` if none were priced.
+    cost_usd: Option<f64>,
+}
+
+impl UsageTotals {
+    fn add(&mut self, token_usage: &TokenUsage, cost_usd: Option<f64>) {
+        self.token_usage.add_assign(token_usage);
+        if let Some(cost_usd) = cost_usd {
+            self.cost_usd = Some(self.cost_usd.unwrap_or(0.0) + cost_usd);
+        }
+    }
+
+    fn cost_display(&self) -> String {
+        self.cost_usd
+            .map(format_usd)
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+impl UsageCli {
+    pub fn run(self) -> Result<()> {
+        let UsageCli {
+            config_overrides,
+            group_by,
+            since,
+            until,
+            json,
+        } = self;
+
+        let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+        let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+            .context("failed to load configuration")?;
+        let records =
+            collect_usage(&config.codex_home, &config.model_catalog).with_context(|| {
+                format!(
+                    "failed to read sessions from {}",
+                    config.codex_home.display()
+                )
+            })?;
+
+        let mut groups: BTreeMap<Vec<String>, UsageTotals> = BTreeMap::new();
+        let mut total = UsageTotals::default();
+        for record in records.iter().filter(|record| {
+            since.as_ref().is_none_or(|since| record.day >= *since)
+                && until.as_ref().is_none_or(|until| record.day <= *until)
+        }) {
+            let key = group_by.iter().map(|dim| dim.value(record)).collect();
+            groups
+                .entry(key)
+                .or_default()
+                .add(&record.token_usage, record.cost_usd);
+            total.add(&record.token_usage, record.cost_usd);
+        }
+
+        if json {
+            let rows = groups
+                .iter()
+                .map(|(key, totals)| -> serde_json::Result<serde_json::Value> {
+                    let mut row = serde_json::Map::new();
+                    for (dim, value) in group_by.iter().zip(key) {
+                        row.insert(dim.json_key().to_string(), value.clone().into());
+                    }
+                    row.insert(
+                        "token_usage".to_string(),
+                        serde_json::to_value(&totals.token_usage)?,
+                    );
+                    row.insert(
+                        "cost_usd".to_string(),
+                        serde_json::to_value(totals.cost_usd)?,
+                    );
+                    Ok(serde_json::Value::Object(row))
+                })
+                .collect::<serde_json::Result<Vec<_>>>()?;
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+            return Ok(());
+        }
+
+        if groups.is_empty() {
+            println!("No token usage recorded yet.");
+            return Ok(());
+        }
+
+        let mut headers: Vec<&str> = group_by.iter().map(|dim| dim.header()).collect();
+        headers.extend(["Input", "Cached", "Output", "Reasoning", "Cost"]);
+        let mut rows: Vec<Vec<String>> = groups
+            .iter()
+            .map(|(key, totals)| usage_row(key.clone(), totals))
+            .collect();
+        let mut total_key = vec![String::new(); group_by.len()];
+        if let Some(first) = total_key.first_mut() {
+            *first = "Total".to_string();
+        }
+        rows.push(usage_row(total_key, &total));
+
+        let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+        for row in &rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.len());
+            }
+        }
+
+        let key_columns = group_by.len();
+        let format_line = |cells: Vec<&str>| -> String {
+            cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i < key_columns {
+                        format!("{cell:<width$}", width = widths[i])
+                    } else {
+                        format!("{cell:>width$}", width = widths[i])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+        };
+
+        println!("{}", format_line(headers));
+        for row in &rows {
+            println!("{}", format_line(row.iter().map(String::as_str).collect()));
+        }
+
+        Ok(())
+    }
+}
+
+fn usage_row(mut cells: Vec<String>, totals: &UsageTotals) -> Vec<String> {
+    let usage = &totals.token_usage;
+    cells.extend([
+        format_with_separators(usage.non_cached_input()),
+        format_with_separators(usage.cached_input()),
+        format_with_separators(usage.output_tokens),
+        format_with_separators(usage.reasoning_output_tokens),
+        totals.cost_display(),
+    ]);
+    cells
+}
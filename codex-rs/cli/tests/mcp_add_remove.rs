@@ -3,6 +3,9 @@ use std::path::Path;
 use anyhow::Result;
 use codex_core::config::load_global_mcp_servers;
 use codex_core::config_types::McpServerTransportConfig;
+use codex_rmcp_client::StoredOAuthTokens;
+use codex_rmcp_client::load_oauth_tokens;
+use codex_rmcp_client::save_oauth_tokens;
 use predicates::str::contains;
 use pretty_assertions::assert_eq;
 use tempfile::TempDir;
@@ -93,3 +96,50 @@ fn add_with_env_preserves_key_order_and_values() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn login_requires_http_server_and_remove_deletes_tokens() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args(["mcp", "add", "docs", "--", "echo", "hello"])
+        .assert()
+        .success();
+
+    let mut login_cmd = codex_command(codex_home.path())?;
+    login_cmd
+        .args(["mcp", "login", "docs"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "OAuth login is only supported for streamable HTTP servers",
+        ));
+
+    let tokens = StoredOAuthTokens {
+        url: "https://docs.example.com/mcp".to_string(),
+        client_id: "client-1".to_string(),
+        client_secret: None,
+        token_endpoint: "https://docs.example.com/token".to_string(),
+        access_token: "access-1".to_string(),
+        refresh_token: None,
+        expires_at: None,
+    };
+    save_oauth_tokens(codex_home.path(), "docs", &tokens)?;
+
+    let mut remove_cmd = codex_command(codex_home.path())?;
+    remove_cmd
+        .args(["mcp", "remove", "docs"])
+        .assert()
+        .success();
+    assert_eq!(load_oauth_tokens(codex_home.path(), "docs")?, None);
+
+    let mut logout_cmd = codex_command(codex_home.path())?;
+    logout_cmd
+        .args(["mcp", "logout", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("No OAuth tokens stored for MCP server 'docs'."));
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use tempfile::TempDir;
+
+const SESSION_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn write_session(codex_home: &Path) -> Result<()> {
+    let timestamp = "2025-10-01T10:00:00.000Z";
+    let lines = [
+        json!({
+            "timestamp": timestamp,
+            "type": "session_meta",
+            "payload": {
+                "id": SESSION_ID,
+                "timestamp": timestamp,
+                "cwd": "/work/app",
+                "originator": "codex_cli_rs",
+                "cli_version": "0.0.0",
+                "instructions": null,
+                "git": { "branch": "feature/login" },
+            },
+        }),
+        json!({
+            "timestamp": timestamp,
+            "type": "turn_context",
+            "payload": {
+                "cwd": "/work/app",
+                "approval_policy": "on-request",
+                "sandbox_policy": { "mode": "read-only" },
+                "model": "grok-code-fast-1",
+                "summary": "auto",
+            },
+        }),
+        json!({
+            "timestamp": timestamp,
+            "type": "event_msg",
+            "payload": { "type": "user_message", "message": "Add a login <form>", "kind": "plain" },
+        }),
+        json!({
+            "timestamp": timestamp,
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": "Added the login form." },
+        }),
+    ];
+    let text = lines
+        .iter()
+        .map(JsonValue::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let dir = codex_home.join("sessions/2025/10/01");
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join(format!("rollout-2025-10-01T10-00-00-{SESSION_ID}.jsonl")),
+        text,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn lists_and_filters_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    write_session(codex_home.path())?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "list", "--branch", "feature/login", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let rows: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(rows[0]["id"], json!(SESSION_ID));
+    assert_eq!(rows[0]["models"], json!(["grok-code-fast-1"]));
+    assert_eq!(rows[0]["preview"], json!("Add a login <form>"));
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "list", "--model", "gpt-5"])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("No sessions found."));
+
+    Ok(())
+}
+
+#[test]
+fn searches_and_exports_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    write_session(codex_home.path())?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd.args(["sessions", "search", "LOGIN FORM"]).output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains(&format!(
+        "{SESSION_ID}  2025-10-01T10:00  assistant: Added the login form."
+    )));
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "export", SESSION_ID, "--format", "html"])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("<pre>Add a login &lt;form&gt;</pre>"));
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "show", "9b2c1c1e-4f5d-4b8a-9a43-2f0a5d6c7e81"])
+        .output()?;
+    assert!(!output.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn prunes_old_sessions() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    write_session(codex_home.path())?;
+    let rollout = codex_home.path().join(format!(
+        "sessions/2025/10/01/rollout-2025-10-01T10-00-00-{SESSION_ID}.jsonl"
+    ));
+
+    // The session started long ago but was written to just now, e.g. because
+    // it was resumed.
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "prune", "--older-than", "1d", "--dry-run"])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Would remove 0 sessions."));
+
+    let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+    std::fs::File::options()
+        .write(true)
+        .open(&rollout)?
+        .set_modified(two_days_ago)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "prune", "--older-than", "1d", "--dry-run"])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Would remove 1 session."));
+    assert!(rollout.exists());
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["sessions", "prune", "--older-than", "1d"])
+        .output()?;
+    assert!(output.status.success());
+    assert!(!rollout.exists());
+
+    Ok(())
+}
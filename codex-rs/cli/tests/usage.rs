@@ -0,0 +1,106 @@
+use std::path::Path;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn token_count(timestamp: &str, input: u64, output: u64) -> JsonValue {
+    let usage = json!({
+        "input_tokens": input,
+        "cached_input_tokens": 0,
+        "output_tokens": output,
+        "reasoning_output_tokens": 0,
+        "total_tokens": input + output,
+    });
+    json!({
+        "timestamp": timestamp,
+        "type": "event_msg",
+        "payload": {
+            "type": "token_count",
+            "info": {
+                "total_token_usage": usage,
+                "last_token_usage": usage,
+                "model_context_window": null,
+            },
+            "rate_limits": null,
+        },
+    })
+}
+
+#[test]
+fn usage_shows_empty_state() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd.arg("usage").output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("No token usage recorded yet."));
+
+    Ok(())
+}
+
+#[test]
+fn usage_prices_rollouts_by_model() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[models.grok-code-fast-1.pricing]
+input = 0.2
+output = 1.5
+"#,
+    )?;
+
+    let sessions = codex_home.path().join("sessions/2025/10/01");
+    std::fs::create_dir_all(&sessions)?;
+    let lines = [
+        json!({
+            "timestamp": "2025-10-01T10:00:00.000Z",
+            "type": "turn_context",
+            "payload": {
+                "cwd": "/work/app",
+                "approval_policy": "on-request",
+                "sandbox_policy": { "mode": "read-only" },
+                "model": "grok-code-fast-1",
+                "model_provider": "api_x",
+                "summary": "auto",
+            },
+        }),
+        token_count("2025-10-01T10:00:05.000Z", 1_000_000, 100_000),
+    ];
+    let text = lines
+        .iter()
+        .map(JsonValue::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(sessions.join("rollout-2025-10-01T10-00-00-x.jsonl"), text)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd
+        .args(["usage", "--by", "model,provider", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let rows: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(rows[0]["model"], json!("grok-code-fast-1"));
+    assert_eq!(rows[0]["provider"], json!("api_x"));
+    assert_eq!(rows[0]["token_usage"]["input_tokens"], json!(1_000_000));
+    let cost = rows[0]["cost_usd"].as_f64().expect("priced model");
+    assert!((cost - 0.35).abs() < 1e-9, "unexpected cost: {cost}");
+
+    let mut cmd = codex_command(codex_home.path())?;
+    let output = cmd.args(["usage", "--since", "2025-11-01"]).output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("No token usage recorded yet."));
+
+    Ok(())
+}
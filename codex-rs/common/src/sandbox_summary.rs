@@ -7,6 +7,8 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
+            allowed_domains,
+            deny_read,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
         } => {
@@ -27,8 +29,13 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             );
 
             summary.push_str(&format!(" [{}]", writable_entries.join(", ")));
+            if !deny_read.is_empty() {
+                summary.push_str(&format!(" (no read: {})", deny_read.join(", ")));
+            }
             if *network_access {
                 summary.push_str(" (network access enabled)");
+            } else if !allowed_domains.is_empty() {
+                summary.push_str(&format!(" (network: {})", allowed_domains.join(", ")));
             }
             summary
         }
@@ -0,0 +1,201 @@
+//! Sandboxing with bubblewrap (`bwrap`) for Linux systems where Landlock is
+//! not available, such as older kernels and many containers.
+//!
+//! The command runs in a private mount namespace: `/` is bound read-only,
+//! `/tmp` is a fresh tmpfs and each writable root is bound read-write with its
+//! read-only subpaths (such as `.git`) bound read-only again on top. Unless the
+//! policy allows network access, the command also gets an empty network
+//! namespace.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use tokio::process::Child;
+
+use crate::config_types::ResourceLimits;
+use crate::deny_read::resolve_deny_read_paths;
+use crate::protocol::SandboxPolicy;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+
+/// As with `sandbox-exec`, only the system copy of `bwrap` is trusted.
+pub(crate) const BWRAP_EXECUTABLE: &str = "/usr/bin/bwrap";
+
+pub async fn spawn_command_under_bwrap(
+    command: Vec<String>,
+    command_cwd: PathBuf,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    resource_limits: &ResourceLimits,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+) -> std::io::Result<Child> {
+    let args = create_bwrap_command_args(command, &command_cwd, sandbox_policy, sandbox_policy_cwd);
+    let arg0 = None;
+    spawn_child_async(
+        PathBuf::from(BWRAP_EXECUTABLE),
+        args,
+        arg0,
+        command_cwd,
+        sandbox_policy,
+        resource_limits,
+        stdio_policy,
+        env,
+    )
+    .await
+}
+
+pub(crate) fn create_bwrap_command_args(
+    command: Vec<String>,
+    command_cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+) -> Vec<String> {
+    let slash_tmp = Path::new("/tmp");
+    let mut args: Vec<String> = ["--die-with-parent", "--new-session", "--unshare-pid"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let mut writable_roots = Vec::new();
+    if sandbox_policy.has_full_disk_write_access() {
+        push_mount(&mut args, "--bind", Path::new("/"));
+        push_mount(&mut args, "--dev-bind", Path::new("/dev"));
+    } else {
+        push_mount(&mut args, "--ro-bind", Path::new("/"));
+        args.extend(["--dev", "/dev", "--tmpfs", "/tmp"].map(String::from));
+        for writable_root in sandbox_policy.get_writable_roots_with_cwd(sandbox_policy_cwd) {
+            let root = canonicalize(&writable_root.root);
+            // The fresh tmpfs is already writable.
+            if root != slash_tmp {
+                push_mount(&mut args, "--bind", &root);
+            }
+            for subpath in &writable_root.read_only_subpaths {
+                push_mount(&mut args, "--ro-bind", &canonicalize(subpath));
+            }
+            writable_roots.push(root);
+        }
+    }
+    args.extend(["--proc", "/proc"].map(String::from));
+
+    // Keep a cwd under `/tmp` reachable even when it is not writable.
+    let command_cwd = canonicalize(command_cwd);
+    if !sandbox_policy.has_full_disk_write_access()
+        && command_cwd.starts_with(slash_tmp)
+        && !writable_roots
+            .iter()
+            .any(|root| command_cwd.starts_with(root))
+    {
+        push_mount(&mut args, "--ro-bind", &command_cwd);
+    }
+
+    // Hide denied paths behind an empty directory or file.
+    for path in resolve_deny_read_paths(sandbox_policy.deny_read(), sandbox_policy_cwd) {
+        if path.is_dir() {
+            args.push("--tmpfs".to_string());
+        } else {
+            args.extend(["--ro-bind", "/dev/null"].map(String::from));
+        }
+        args.push(path.to_string_lossy().to_string());
+    }
+
+    if !sandbox_policy.has_full_network_access() {
+        args.push("--unshare-net".to_string());
+    }
+    args.push("--chdir".to_string());
+    args.push(command_cwd.to_string_lossy().to_string());
+    args.push("--".to_string());
+    args.extend(command);
+    args
+}
+
+/// Mounts `path` at the same location inside the sandbox.
+fn push_mount(args: &mut Vec<String>, option: &str, path: &Path) {
+    let path = path.to_string_lossy().to_string();
+    args.extend([option.to_string(), path.clone(), path]);
+}
+
+/// Canonicalizes `path` so that mounts land where symlinks point.
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Whether `bwrap` is installed and can create the namespaces it needs.
+#[cfg(target_os = "linux")]
+pub(crate) fn bwrap_available() -> bool {
+    std::process::Command::new(BWRAP_EXECUTABLE)
+        .args([
+            "--ro-bind",
+            "/",
+            "/",
+            "--unshare-pid",
+            "--unshare-net",
+            "--",
+            "true",
+        ])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn binds_writable_roots_over_a_read_only_root() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let repo = tmp
+            .path()
+            .canonicalize()
+            .expect("canonicalize")
+            .join("repo");
+        std::fs::create_dir_all(repo.join(".git")).expect("create .git");
+        std::fs::create_dir_all(repo.join("secrets")).expect("create secrets");
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec!["secrets".to_string()],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+
+        let args = create_bwrap_command_args(vec!["ls".to_string()], &repo, &policy, &repo);
+
+        let repo = repo.to_string_lossy().to_string();
+        let git = format!("{repo}/.git");
+        let secrets = format!("{repo}/secrets");
+        let expected: Vec<&str> = vec![
+            "--die-with-parent",
+            "--new-session",
+            "--unshare-pid",
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--tmpfs",
+            "/tmp",
+            "--bind",
+            &repo,
+            &repo,
+            "--ro-bind",
+            &git,
+            &git,
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            &secrets,
+            "--unshare-net",
+            "--chdir",
+            &repo,
+            "--",
+            "ls",
+        ];
+        assert_eq!(args, expected);
+    }
+}
@@ -16,6 +16,7 @@ use tracing::debug;
 use tracing::trace;
 
 use crate::ModelProviderInfo;
+use crate::client::parse_chat_rate_limit_snapshot;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
@@ -23,10 +24,18 @@ use crate::error::CodexErr;
 use crate::error::Result;
 use crate::model_family::ModelFamily;
 use crate::openai_tools::create_tools_json_for_chat_completions_api;
+use crate::protocol::TokenUsage;
 use crate::util::backoff;
+use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ReasoningItemContent;
+use codex_protocol::models::ReasoningItemReasoningSummary;
 use codex_protocol::models::ResponseItem;
+use serde::Deserialize;
+
+/// How long to keep reading after `finish_reason` for the trailing usage chunk
+/// before completing the turn without it.
+const USAGE_CHUNK_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Implementation for the classic Chat Completions API.
 pub(crate) async fn stream_chat_completions(
@@ -34,11 +43,13 @@ pub(crate) async fn stream_chat_completions(
     model_family: &ModelFamily,
     client: &reqwest::Client,
     provider: &ModelProviderInfo,
+    effort: Option<ReasoningEffortConfig>,
 ) -> Result<ResponseStream> {
-    if prompt.output_schema.is_some() {
-        return Err(CodexErr::UnsupportedOperation(
-            "output_schema is not supported for Chat Completions API".to_string(),
-        ));
+    if prompt.output_schema.is_some() && !provider.supports_response_format {
+        return Err(CodexErr::UnsupportedOperation(format!(
+            "output_schema is not supported by model provider {}; set `supports_response_format = true` if it accepts a json_schema response_format",
+            provider.name
+        )));
     }
 
     // Build messages array
@@ -274,12 +285,45 @@ pub(crate) async fn stream_chat_completions(
     }
 
     let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
         "tools": tools_json,
     });
+    if provider.supports_stream_usage
+        && let Some(obj) = payload.as_object_mut()
+    {
+        // Ask for a trailing chunk with token usage (including reasoning tokens).
+        obj.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
+    }
+    if let Some(schema) = &prompt.output_schema
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert(
+            "response_format".to_string(),
+            json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "codex_output_schema",
+                    "schema": schema,
+                    "strict": true,
+                },
+            }),
+        );
+    }
+    if model_family.supports_reasoning_effort
+        && let Some(effort) = effort
+        && let Some(obj) = payload.as_object_mut()
+    {
+        obj.insert(
+            "reasoning_effort".to_string(),
+            json!(chat_reasoning_effort(effort)),
+        );
+    }
 
     debug!(
         "POST to {}: {}",
@@ -303,11 +347,23 @@ pub(crate) async fn stream_chat_completions(
         match res {
             Ok(resp) if resp.status().is_success() => {
                 let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+
+                if let Some(snapshot) =
+                    parse_chat_rate_limit_snapshot(resp.headers(), &provider.rate_limit_headers())
+                    && tx_event
+                        .send(Ok(ResponseEvent::RateLimits(snapshot)))
+                        .await
+                        .is_err()
+                {
+                    debug!("receiver dropped rate limit snapshot event");
+                }
+
                 let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
                 tokio::spawn(process_chat_sse(
                     stream,
                     tx_event,
                     provider.stream_idle_timeout(),
+                    provider.reasoning_content_keys(),
                 ));
                 return Ok(ResponseStream { rx_event });
             }
@@ -344,13 +400,95 @@ pub(crate) async fn stream_chat_completions(
     }
 }
 
+/// The chat models that accept `reasoning_effort` (currently xAI's
+/// grok-3-mini) only understand `low` and `high`.
+fn chat_reasoning_effort(effort: ReasoningEffortConfig) -> &'static str {
+    match effort {
+        ReasoningEffortConfig::Minimal | ReasoningEffortConfig::Low => "low",
+        ReasoningEffortConfig::Medium | ReasoningEffortConfig::High => "high",
+    }
+}
+
+/// `usage` object sent in the final chunk when `stream_options.include_usage`
+/// is set.
+#[derive(Debug, Deserialize)]
+struct ChatCompletionUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    prompt_tokens_details: Option<ChatCompletionPromptTokensDetails>,
+    completion_tokens_details: Option<ChatCompletionCompletionTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionPromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionCompletionTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: u64,
+}
+
+impl From<ChatCompletionUsage> for TokenUsage {
+    fn from(val: ChatCompletionUsage) -> Self {
+        // OpenAI counts reasoning tokens as part of `completion_tokens`, while
+        // xAI reports them separately (but includes them in `total_tokens`).
+        // Normalize to the Responses API convention where `output_tokens`
+        // includes reasoning.
+        let output_tokens = val
+            .total_tokens
+            .saturating_sub(val.prompt_tokens)
+            .max(val.completion_tokens);
+        TokenUsage {
+            input_tokens: val.prompt_tokens,
+            cached_input_tokens: val
+                .prompt_tokens_details
+                .map(|d| d.cached_tokens)
+                .unwrap_or(0),
+            output_tokens,
+            reasoning_output_tokens: val
+                .completion_tokens_details
+                .map(|d| d.reasoning_tokens)
+                .unwrap_or(0),
+            total_tokens: val.prompt_tokens + output_tokens,
+        }
+    }
+}
+
+/// Extract reasoning text from a `reasoning`-style field. Some providers send
+/// a plain string while others nest the text under an object (e.g.
+/// `{ "reasoning": { "text": "…" } }`).
+fn reasoning_text_from_value(value: &serde_json::Value) -> Option<&str> {
+    value
+        .as_str()
+        .or_else(|| value.get("text").and_then(|t| t.as_str()))
+        .or_else(|| value.get("content").and_then(|t| t.as_str()))
+        .filter(|s| !s.is_empty())
+}
+
+fn reasoning_item(text: String) -> ResponseItem {
+    ResponseItem::Reasoning {
+        id: String::new(),
+        summary: Vec::new(),
+        content: Some(vec![ReasoningItemContent::ReasoningText { text }]),
+        encrypted_content: None,
+    }
+}
+
 /// Lightweight SSE processor for the Chat Completions streaming format. The
 /// output is mapped onto Codex's internal [`ResponseEvent`] so that the rest
 /// of the pipeline can stay agnostic of the underlying wire format.
+///
+/// `reasoning_keys` lists the fields of `delta`/`message` that carry the
+/// model's reasoning text (see [`ModelProviderInfo::reasoning_content_key`]).
 async fn process_chat_sse<S>(
     stream: S,
     tx_event: mpsc::Sender<Result<ResponseEvent>>,
     idle_timeout: Duration,
+    reasoning_keys: Vec<String>,
 ) where
     S: Stream<Item = Result<Bytes>> + Unpin,
 {
@@ -372,9 +510,28 @@ async fn process_chat_sse<S>(
     let mut fn_call_state = FunctionCallState::default();
     let mut assistant_text = String::new();
     let mut reasoning_text = String::new();
+    let mut token_usage: Option<TokenUsage> = None;
+    // Set once a `finish_reason` has been seen. The usage chunk requested via
+    // `stream_options` arrives after it, so keep reading briefly for it.
+    let mut finished = false;
 
     loop {
-        let sse = match timeout(idle_timeout, stream.next()).await {
+        if finished && token_usage.is_some() {
+            let _ = tx_event
+                .send(Ok(ResponseEvent::Completed {
+                    response_id: String::new(),
+                    token_usage,
+                }))
+                .await;
+            return;
+        }
+
+        let wait = if finished {
+            idle_timeout.min(USAGE_CHUNK_TIMEOUT)
+        } else {
+            idle_timeout
+        };
+        let sse = match timeout(wait, stream.next()).await {
             Ok(Some(Ok(ev))) => ev,
             Ok(Some(Err(e))) => {
                 let _ = tx_event
@@ -387,7 +544,18 @@ async fn process_chat_sse<S>(
                 let _ = tx_event
                     .send(Ok(ResponseEvent::Completed {
                         response_id: String::new(),
-                        token_usage: None,
+                        token_usage,
+                    }))
+                    .await;
+                return;
+            }
+            Err(_) if finished => {
+                // The turn is over; no usage chunk arrived and the provider
+                // did not close the stream.
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: String::new(),
+                        token_usage,
                     }))
                     .await;
                 return;
@@ -419,21 +587,14 @@ async fn process_chat_sse<S>(
             }
 
             if !reasoning_text.is_empty() {
-                let item = ResponseItem::Reasoning {
-                    id: String::new(),
-                    summary: Vec::new(),
-                    content: Some(vec![ReasoningItemContent::ReasoningText {
-                        text: std::mem::take(&mut reasoning_text),
-                    }]),
-                    encrypted_content: None,
-                };
+                let item = reasoning_item(std::mem::take(&mut reasoning_text));
                 let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
             }
 
             let _ = tx_event
                 .send(Ok(ResponseEvent::Completed {
                     response_id: String::new(),
-                    token_usage: None,
+                    token_usage,
                 }))
                 .await;
             return;
@@ -446,6 +607,17 @@ async fn process_chat_sse<S>(
         };
         trace!("chat_completions received SSE chunk: {chunk:?}");
 
+        if let Some(usage) = chunk.get("usage").filter(|u| !u.is_null()) {
+            match serde_json::from_value::<ChatCompletionUsage>(usage.clone()) {
+                Ok(usage) => token_usage = Some(usage.into()),
+                Err(e) => debug!("failed to parse chat completion usage: {e}"),
+            }
+        }
+
+        if finished {
+            continue;
+        }
+
         let choice_opt = chunk.get("choices").and_then(|c| c.get(0));
 
         if let Some(choice) = choice_opt {
@@ -462,62 +634,23 @@ async fn process_chat_sse<S>(
                     .await;
             }
 
-            // Forward any reasoning/thinking deltas if present.
-            // Some providers stream `reasoning` as a plain string while others
-            // nest the text under an object (e.g. `{ "reasoning": { "text": "…" } }`).
-            if let Some(reasoning_val) = choice.get("delta").and_then(|d| d.get("reasoning")) {
-                let mut maybe_text = reasoning_val
-                    .as_str()
-                    .map(str::to_string)
-                    .filter(|s| !s.is_empty());
-
-                if maybe_text.is_none() && reasoning_val.is_object() {
-                    if let Some(s) = reasoning_val
-                        .get("text")
-                        .and_then(|t| t.as_str())
-                        .filter(|s| !s.is_empty())
-                    {
-                        maybe_text = Some(s.to_string());
-                    } else if let Some(s) = reasoning_val
-                        .get("content")
-                        .and_then(|t| t.as_str())
-                        .filter(|s| !s.is_empty())
+            // Forward any reasoning/thinking deltas if present. Some providers
+            // only include reasoning on the final message object.
+            for container in ["delta", "message"] {
+                let Some(container) = choice.get(container) else {
+                    continue;
+                };
+                for key in &reasoning_keys {
+                    if let Some(reasoning) = container.get(key).and_then(reasoning_text_from_value)
                     {
-                        maybe_text = Some(s.to_string());
-                    }
-                }
-
-                if let Some(reasoning) = maybe_text {
-                    // Accumulate so we can emit a terminal Reasoning item at the end.
-                    reasoning_text.push_str(&reasoning);
-                    let _ = tx_event
-                        .send(Ok(ResponseEvent::ReasoningContentDelta(reasoning)))
-                        .await;
-                }
-            }
-
-            // Some providers only include reasoning on the final message object.
-            if let Some(message_reasoning) = choice.get("message").and_then(|m| m.get("reasoning"))
-            {
-                // Accept either a plain string or an object with { text | content }
-                if let Some(s) = message_reasoning.as_str() {
-                    if !s.is_empty() {
-                        reasoning_text.push_str(s);
+                        // Accumulate so we can emit a terminal Reasoning item at the end.
+                        reasoning_text.push_str(reasoning);
                         let _ = tx_event
-                            .send(Ok(ResponseEvent::ReasoningContentDelta(s.to_string())))
+                            .send(Ok(ResponseEvent::ReasoningContentDelta(
+                                reasoning.to_string(),
+                            )))
                             .await;
                     }
-                } else if let Some(obj) = message_reasoning.as_object()
-                    && let Some(s) = obj
-                        .get("text")
-                        .and_then(|v| v.as_str())
-                        .or_else(|| obj.get("content").and_then(|v| v.as_str()))
-                    && !s.is_empty()
-                {
-                    reasoning_text.push_str(s);
-                    let _ = tx_event
-                        .send(Ok(ResponseEvent::ReasoningContentDelta(s.to_string())))
-                        .await;
                 }
             }
 
@@ -549,21 +682,14 @@ async fn process_chat_sse<S>(
                 }
             }
 
-            // Emit end-of-turn when finish_reason signals completion.
+            // Flush the turn's items when finish_reason signals completion.
             if let Some(finish_reason) = choice.get("finish_reason").and_then(|v| v.as_str()) {
                 match finish_reason {
                     "tool_calls" if fn_call_state.active => {
                         // First, flush the terminal raw reasoning so UIs can finalize
                         // the reasoning stream before any exec/tool events begin.
                         if !reasoning_text.is_empty() {
-                            let item = ResponseItem::Reasoning {
-                                id: String::new(),
-                                summary: Vec::new(),
-                                content: Some(vec![ReasoningItemContent::ReasoningText {
-                                    text: std::mem::take(&mut reasoning_text),
-                                }]),
-                                encrypted_content: None,
-                            };
+                            let item = reasoning_item(std::mem::take(&mut reasoning_text));
                             let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
                         }
 
@@ -592,32 +718,16 @@ async fn process_chat_sse<S>(
                         }
                         // Also emit a terminal Reasoning item so UIs can finalize raw reasoning.
                         if !reasoning_text.is_empty() {
-                            let item = ResponseItem::Reasoning {
-                                id: String::new(),
-                                summary: Vec::new(),
-                                content: Some(vec![ReasoningItemContent::ReasoningText {
-                                    text: std::mem::take(&mut reasoning_text),
-                                }]),
-                                encrypted_content: None,
-                            };
+                            let item = reasoning_item(std::mem::take(&mut reasoning_text));
                             let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
                         }
                     }
                     _ => {}
                 }
 
-                // Emit Completed regardless of reason so the agent can advance.
-                let _ = tx_event
-                    .send(Ok(ResponseEvent::Completed {
-                        response_id: String::new(),
-                        token_usage: None,
-                    }))
-                    .await;
-
-                // Prepare for potential next turn (should not happen in same stream).
-                // fn_call_state = FunctionCallState::default();
-
-                return; // End processing for this SSE stream.
+                // Completed is emitted once the usage is known, the stream
+                // ends, or USAGE_CHUNK_TIMEOUT passes without either.
+                finished = true;
             }
         }
     }
@@ -627,8 +737,9 @@ async fn process_chat_sse<S>(
 ///
 /// Stream adapter that merges the incremental `OutputItemDone` chunks coming from
 /// [`process_chat_sse`] into a *running* assistant message, **suppressing the
-/// per-token deltas**.  The stream stays silent while the model is thinking
-/// and only emits two events per turn:
+/// per-token deltas** of the assistant message. Reasoning deltas are
+/// forwarded as `ResponseEvent::ReasoningSummaryDelta` so UIs can show the
+/// model thinking; apart from those only two events are emitted per turn:
 ///
 ///   1. `ResponseEvent::OutputItemDone` with the *complete* assistant message
 ///      (fully concatenated).
@@ -719,6 +830,33 @@ where
                         }
                     }
 
+                    // Raw reasoning is hidden in AggregatedOnly mode, so surface the
+                    // reasoning item as a summary instead. It supersedes whatever
+                    // reasoning was accumulated so far.
+                    if matches!(this.mode, AggregateMode::AggregatedOnly)
+                        && let codex_protocol::models::ResponseItem::Reasoning {
+                            id,
+                            summary,
+                            content: Some(content),
+                            encrypted_content,
+                        } = item
+                    {
+                        this.cumulative_reasoning.clear();
+                        let summary = if summary.is_empty() {
+                            summary_from_reasoning_content(&content)
+                        } else {
+                            summary
+                        };
+                        return Poll::Ready(Some(Ok(ResponseEvent::OutputItemDone(
+                            codex_protocol::models::ResponseItem::Reasoning {
+                                id,
+                                summary,
+                                content: Some(content),
+                                encrypted_content,
+                            },
+                        ))));
+                    }
+
                     // Not an assistant message – forward immediately.
                     return Poll::Ready(Some(Ok(ResponseEvent::OutputItemDone(item))));
                 }
@@ -735,15 +873,14 @@ where
                     if !this.cumulative_reasoning.is_empty()
                         && matches!(this.mode, AggregateMode::AggregatedOnly)
                     {
+                        let content = vec![ReasoningItemContent::ReasoningText {
+                            text: std::mem::take(&mut this.cumulative_reasoning),
+                        }];
                         let aggregated_reasoning =
                             codex_protocol::models::ResponseItem::Reasoning {
                                 id: String::new(),
-                                summary: Vec::new(),
-                                content: Some(vec![
-                                    codex_protocol::models::ReasoningItemContent::ReasoningText {
-                                        text: std::mem::take(&mut this.cumulative_reasoning),
-                                    },
-                                ]),
+                                summary: summary_from_reasoning_content(&content),
+                                content: Some(content),
                                 encrypted_content: None,
                             };
                         this.pending
@@ -805,12 +942,17 @@ where
                 Poll::Ready(Some(Ok(ResponseEvent::ReasoningContentDelta(delta)))) => {
                     // Always accumulate reasoning deltas so we can emit a final Reasoning item at Completed.
                     this.cumulative_reasoning.push_str(&delta);
-                    if matches!(this.mode, AggregateMode::Streaming) {
+                    return match this.mode {
                         // In streaming mode, also forward the delta immediately.
-                        return Poll::Ready(Some(Ok(ResponseEvent::ReasoningContentDelta(delta))));
-                    } else {
-                        continue;
-                    }
+                        AggregateMode::Streaming => {
+                            Poll::Ready(Some(Ok(ResponseEvent::ReasoningContentDelta(delta))))
+                        }
+                        // Otherwise raw reasoning is hidden downstream, so
+                        // forward it as a summary delta for UIs to display.
+                        AggregateMode::AggregatedOnly => {
+                            Poll::Ready(Some(Ok(ResponseEvent::ReasoningSummaryDelta(delta))))
+                        }
+                    };
                 }
                 Poll::Ready(Some(Ok(ResponseEvent::ReasoningSummaryDelta(_)))) => {
                     continue;
@@ -826,6 +968,26 @@ where
     }
 }
 
+/// Chat providers do not summarize reasoning, so the raw text doubles as the
+/// summary shown when raw reasoning is hidden.
+fn summary_from_reasoning_content(
+    content: &[ReasoningItemContent],
+) -> Vec<ReasoningItemReasoningSummary> {
+    let text: String = content
+        .iter()
+        .map(|c| match c {
+            ReasoningItemContent::ReasoningText { text } | ReasoningItemContent::Text { text } => {
+                text.as_str()
+            }
+        })
+        .collect();
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        vec![ReasoningItemReasoningSummary::SummaryText { text }]
+    }
+}
+
 /// Extension trait that activates aggregation on any stream of [`ResponseEvent`].
 pub(crate) trait AggregateStreamExt: Stream<Item = Result<ResponseEvent>> + Sized {
     /// Returns a new stream that emits **only** the final assistant message
@@ -32,15 +32,20 @@ use crate::client_common::ResponsesApiRequest;
 use crate::client_common::create_reasoning_param_for_request;
 use crate::client_common::create_text_param_for_request;
 use crate::config::Config;
+use crate::config_types::ModelPricing;
 use crate::default_client::create_client;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::UsageLimitReachedError;
 use crate::flags::CODEX_RS_SSE_FIXTURE;
 use crate::model_family::ModelFamily;
+use crate::model_family::derive_default_model_family;
+use crate::model_provider_info::FallbackProvider;
 use crate::model_provider_info::ModelProviderInfo;
+use crate::model_provider_info::RateLimitHeaders;
+use crate::model_provider_info::RateLimitWindowHeaders;
 use crate::model_provider_info::WireApi;
-use crate::openai_model_info::get_model_info;
+use crate::openai_model_info::ModelInfo;
 use crate::openai_tools::create_tools_json_for_responses_api;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::RateLimitWindow;
@@ -102,18 +107,61 @@ impl ModelClient {
         }
     }
 
+    fn model_info(&self) -> Option<ModelInfo> {
+        self.config
+            .model_catalog
+            .model_info(&self.config.model_family)
+    }
+
     pub fn get_model_context_window(&self) -> Option<u64> {
         self.config
             .model_context_window
-            .or_else(|| get_model_info(&self.config.model_family).map(|info| info.context_window))
+            .or_else(|| self.model_info().and_then(|info| info.context_window))
     }
 
     pub fn get_auto_compact_token_limit(&self) -> Option<i64> {
         self.config.model_auto_compact_token_limit.or_else(|| {
-            get_model_info(&self.config.model_family).and_then(|info| info.auto_compact_token_limit)
+            self.model_info()
+                .and_then(|info| info.auto_compact_token_limit)
         })
     }
 
+    /// Clients for each entry of `fallback_providers`, in order. The turn loop
+    /// switches to the next one when this client's provider is unavailable
+    /// (see [`is_provider_unavailable`]) or its stream keeps failing.
+    pub(crate) fn fallback_clients(&self) -> Vec<ModelClient> {
+        self.config
+            .fallback_providers
+            .iter()
+            .map(|fallback| self.fallback_client(fallback))
+            .collect()
+    }
+
+    /// Builds a client that talks to `fallback` instead of the configured
+    /// provider, switching models when the entry names one.
+    fn fallback_client(&self, fallback: &FallbackProvider) -> ModelClient {
+        let mut config = (*self.config).clone();
+        if let Some(model) = &fallback.model {
+            config.model_family = config
+                .model_catalog
+                .find_family(model)
+                .unwrap_or_else(|| derive_default_model_family(model));
+            config.model = model.clone();
+        }
+        config.model_provider_id = fallback.provider_id.clone();
+        config.model_provider = fallback.provider.clone();
+
+        ModelClient {
+            config: Arc::new(config),
+            auth_manager: self.auth_manager.clone(),
+            client: self.client.clone(),
+            provider: fallback.provider.clone(),
+            conversation_id: self.conversation_id,
+            effort: self.effort,
+            summary: self.summary,
+        }
+    }
+
     /// Dispatches to either the Responses or Chat implementation depending on
     /// the provider config.  Public callers always invoke `stream()` – the
     /// specialised helpers are private to avoid accidental misuse.
@@ -127,6 +175,7 @@ impl ModelClient {
                     &self.config.model_family,
                     &self.client,
                     &self.provider,
+                    self.effort,
                 )
                 .await?;
 
@@ -184,21 +233,20 @@ impl ModelClient {
 
         let input_with_instructions = prompt.get_formatted_input();
 
-        let verbosity = match &self.config.model_family.family {
-            family if family == "gpt-5" => self.config.model_verbosity,
-            _ => {
-                if self.config.model_verbosity.is_some() {
-                    warn!(
-                        "model_verbosity is set but ignored for non-gpt-5 model family: {}",
-                        self.config.model_family.family
-                    );
-                }
-
-                None
+        let verbosity = if self.config.model_family.supports_verbosity {
+            self.config.model_verbosity
+        } else {
+            if self.config.model_verbosity.is_some() {
+                warn!(
+                    "model_verbosity is set but ignored for model family without verbosity support: {}",
+                    self.config.model_family.family
+                );
             }
+
+            None
         };
 
-        // Only include `text.verbosity` for GPT-5 family models
+        // Only include `text.verbosity` for models that support it
         let text = create_text_param_for_request(verbosity, &prompt.output_schema);
 
         // In general, we want to explicitly send `store: false` when using the Responses API,
@@ -399,6 +447,19 @@ impl ModelClient {
         self.provider.clone()
     }
 
+    /// Returns the id of the configured provider in the `model_providers` map.
+    pub fn get_model_provider_id(&self) -> String {
+        self.config.model_provider_id.clone()
+    }
+
+    /// Returns the configured pricing for the current model, if any.
+    pub fn get_model_pricing(&self) -> Option<ModelPricing> {
+        self.config
+            .model_catalog
+            .pricing(&self.config.model)
+            .cloned()
+    }
+
     /// Returns the currently configured model slug.
     pub fn get_model(&self) -> String {
         self.config.model.clone()
@@ -433,6 +494,22 @@ enum StreamAttemptError {
     Fatal(CodexErr),
 }
 
+/// Whether `err` means the provider itself is unavailable (server errors,
+/// exhausted rate limits, unreachable endpoint), as opposed to a problem with
+/// the request that another provider would reject too.
+pub(crate) fn is_provider_unavailable(err: &CodexErr) -> bool {
+    match err {
+        CodexErr::InternalServerError
+        | CodexErr::RetryLimit(_)
+        | CodexErr::UsageLimitReached(_) => true,
+        CodexErr::UnexpectedStatus(status, _) => {
+            status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+        CodexErr::Reqwest(e) => e.is_connect() || e.is_timeout(),
+        _ => false,
+    }
+}
+
 impl StreamAttemptError {
     /// attempt is 0-based.
     fn delay(&self, attempt: u64) -> Duration {
@@ -562,6 +639,78 @@ fn parse_rate_limit_snapshot(headers: &HeaderMap) -> Option<RateLimitSnapshot> {
     Some(RateLimitSnapshot { primary, secondary })
 }
 
+/// Builds a snapshot from provider-specific limit/remaining headers, as sent
+/// by OpenAI-compatible Chat Completions APIs. Returns `None` when the
+/// response carries none of the configured headers.
+pub(crate) fn parse_chat_rate_limit_snapshot(
+    headers: &HeaderMap,
+    names: &RateLimitHeaders,
+) -> Option<RateLimitSnapshot> {
+    let primary = names
+        .primary
+        .as_ref()
+        .and_then(|names| parse_limit_remaining_window(headers, names));
+    let secondary = names
+        .secondary
+        .as_ref()
+        .and_then(|names| parse_limit_remaining_window(headers, names));
+
+    (primary.is_some() || secondary.is_some()).then_some(RateLimitSnapshot { primary, secondary })
+}
+
+fn parse_limit_remaining_window(
+    headers: &HeaderMap,
+    names: &RateLimitWindowHeaders,
+) -> Option<RateLimitWindow> {
+    let limit = parse_header_f64(headers, &names.limit).filter(|limit| *limit > 0.0)?;
+    let remaining = parse_header_f64(headers, &names.remaining)?;
+    let used_percent = ((limit - remaining) / limit * 100.0).clamp(0.0, 100.0);
+    let resets_in_seconds = names
+        .reset
+        .as_deref()
+        .and_then(|name| parse_header_str(headers, name))
+        .and_then(parse_reset_seconds);
+
+    Some(RateLimitWindow {
+        used_percent,
+        window_minutes: names.window_minutes,
+        resets_in_seconds,
+    })
+}
+
+/// Parses a reset header value, either plain seconds (`"12"`, `"0.5"`) or a
+/// Go-style duration (`"1m30s"`, `"250ms"`), rounding up to whole seconds.
+fn parse_reset_seconds(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let seconds = match value.parse::<f64>() {
+        Ok(seconds) => seconds,
+        Err(_) => {
+            let mut total = 0.0;
+            let mut rest = value;
+            while !rest.is_empty() {
+                let unit_start = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+                let (number, tail) = rest.split_at(unit_start);
+                let unit_end = tail
+                    .find(|c: char| c.is_ascii_digit() || c == '.')
+                    .unwrap_or(tail.len());
+                let (unit, tail) = tail.split_at(unit_end);
+                let scale = match unit {
+                    "h" => 3600.0,
+                    "m" => 60.0,
+                    "s" => 1.0,
+                    "ms" => 0.001,
+                    _ => return None,
+                };
+                total += number.parse::<f64>().ok()? * scale;
+                rest = tail;
+            }
+            total
+        }
+    };
+
+    (seconds.is_finite() && seconds >= 0.0).then(|| seconds.ceil() as u64)
+}
+
 fn parse_rate_limit_window(
     headers: &HeaderMap,
     used_percent_header: &str,
@@ -977,6 +1126,10 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let events = collect_events(
@@ -1037,6 +1190,10 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let events = collect_events(&[sse1.as_bytes()], provider).await;
@@ -1071,6 +1228,10 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let events = collect_events(&[sse1.as_bytes()], provider).await;
@@ -1176,6 +1337,10 @@ mod tests {
                 stream_max_retries: Some(0),
                 stream_idle_timeout_ms: Some(1000),
                 requires_openai_auth: false,
+                reasoning_content_key: None,
+                rate_limit_headers: None,
+                supports_response_format: false,
+                supports_stream_usage: false,
             };
 
             let out = run_sse(evs, provider).await;
@@ -1188,6 +1353,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_chat_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit-requests", "480".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-requests", "360".parse().unwrap());
+        headers.insert("x-ratelimit-reset-requests", "7.5s".parse().unwrap());
+        headers.insert("x-ratelimit-limit-tokens", "2000000".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "500000".parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "1m30s".parse().unwrap());
+
+        let snapshot = parse_chat_rate_limit_snapshot(&headers, &RateLimitHeaders::default())
+            .expect("snapshot");
+        let primary = snapshot.primary.expect("primary window");
+        assert_eq!(primary.used_percent, 25.0);
+        assert_eq!(primary.window_minutes, Some(1));
+        assert_eq!(primary.resets_in_seconds, Some(8));
+        let secondary = snapshot.secondary.expect("secondary window");
+        assert_eq!(secondary.used_percent, 75.0);
+        assert_eq!(secondary.resets_in_seconds, Some(90));
+
+        assert!(
+            parse_chat_rate_limit_snapshot(&HeaderMap::new(), &RateLimitHeaders::default())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn parses_reset_durations() {
+        assert_eq!(parse_reset_seconds("12"), Some(12));
+        assert_eq!(parse_reset_seconds("250ms"), Some(1));
+        assert_eq!(parse_reset_seconds("6m0s"), Some(360));
+        assert_eq!(parse_reset_seconds("1h2m3s"), Some(3723));
+        assert_eq!(parse_reset_seconds("soon"), None);
+        assert_eq!(parse_reset_seconds("-1"), None);
+    }
+
     #[test]
     fn test_try_parse_retry_after() {
         let err = Error {
@@ -16,8 +16,12 @@ use async_channel::Receiver;
 use async_channel::Sender;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::MaybeApplyPatchVerified;
-use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_apply_patch::maybe_parse_apply_patch_verified_with_bases;
+use codex_git_tooling::CreateGhostCommitOptions;
+use codex_git_tooling::GitToolingError;
+use codex_git_tooling::create_ghost_commit;
 use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::protocol::CheckpointItem;
 use codex_protocol::protocol::ConversationPathResponseEvent;
 use codex_protocol::protocol::ExitedReviewModeEvent;
 use codex_protocol::protocol::ReviewRequest;
@@ -40,17 +44,26 @@ use tracing::trace;
 use tracing::warn;
 
 use crate::ModelProviderInfo;
+use crate::WireApi;
 use crate::apply_patch;
 use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::client::ModelClient;
+use crate::client::is_provider_unavailable;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
+use crate::config_types::ResourceLimits;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
+use crate::conversation_manager::is_user_turn_message;
+use crate::delegate_task::DELEGATE_TASK_TOOL_NAME;
+use crate::delegate_task::DelegateTaskManager;
+use crate::delegate_task::handle_delegate_task;
+use crate::deny_read::find_denied_path_in_output;
+use crate::deny_read::resolve_deny_read_paths;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
@@ -59,23 +72,35 @@ use crate::error::get_error_message_ui;
 use crate::exec::ExecParams;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
+use crate::exec::SandboxedCommand;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
+use crate::exec::create_sandboxed_command;
 use crate::exec::process_exec_tool_call;
 use crate::exec_command::EXEC_COMMAND_TOOL_NAME;
 use crate::exec_command::ExecCommandParams;
 use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
+use crate::exec_command::has_unmodeled_control_chars;
 use crate::exec_env::create_env;
+use crate::exec_policy::ExecPolicy;
+use crate::hooks::HookEvent;
+use crate::hooks::HookOutcome;
+use crate::hooks::HookRunner;
+use crate::hooks::SESSION_START_HOOKS_TIMEOUT;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_input::resolve_mcp_input;
 use crate::mcp_tool_call::handle_mcp_tool_call;
-use crate::model_family::find_family_for_model;
-use crate::openai_model_info::get_model_info;
+use crate::memory_tool::FORGET_TOOL_NAME;
+use crate::memory_tool::REMEMBER_TOOL_NAME;
+use crate::memory_tool::handle_forget;
+use crate::memory_tool::handle_remember;
 use crate::openai_tools::ApplyPatchToolArgs;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
+use crate::output_schema::validate_final_output;
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
 use crate::project_doc::get_user_instructions;
@@ -95,6 +120,10 @@ use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::McpInvocation;
+use crate::protocol::McpListPromptsResponseEvent;
+use crate::protocol::McpListResourcesResponseEvent;
+use crate::protocol::McpToolCallApprovalRequestEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
@@ -113,7 +142,10 @@ use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
+use crate::safety::assess_mcp_tool_call_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::session_search_tool::SEARCH_PAST_SESSIONS_TOOL_NAME;
+use crate::session_search_tool::handle_search_past_sessions;
 use crate::shell;
 use crate::state::ActiveTurn;
 use crate::state::SessionServices;
@@ -268,7 +300,7 @@ pub(crate) struct Session {
 }
 
 /// The context needed for a single turn of the conversation.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct TurnContext {
     pub(crate) client: ModelClient,
     /// The session's current working directory. All relative paths provided by
@@ -280,6 +312,7 @@ pub(crate) struct TurnContext {
     pub(crate) approval_policy: AskForApproval,
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy,
+    pub(crate) resource_limits: ResourceLimits,
     pub(crate) tools_config: ToolsConfig,
     pub(crate) is_review_mode: bool,
     pub(crate) final_output_json_schema: Option<Value>,
@@ -380,6 +413,7 @@ impl Session {
         let mcp_fut = McpConnectionManager::new(
             config.mcp_servers.clone(),
             config.use_experimental_use_rmcp_client,
+            &config.codex_home,
         );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
@@ -393,6 +427,14 @@ impl Session {
             anyhow::anyhow!("failed to initialize rollout recorder: {e:#}")
         })?;
         let rollout_path = rollout_recorder.rollout_path.clone();
+
+        // A policy that fails to load must not be ignored: it may contain
+        // `forbidden` rules the user relies on.
+        let exec_policy = ExecPolicy::load(&config.exec_policy_files).map_err(|e| {
+            error!("failed to load exec policy: {e:#}");
+            e
+        })?;
+
         // Create the mutable state for the Session.
         let state = SessionState::new();
 
@@ -441,6 +483,9 @@ impl Session {
                 include_web_search_request: config.tools_web_search_request,
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                 include_view_image_tool: config.include_view_image_tool,
+                include_search_past_sessions: config.tools_search_past_sessions,
+                include_memory_tools: config.tools_memory,
+                include_delegate_task: config.tools_delegate_task,
                 experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
             }),
             user_instructions,
@@ -448,6 +493,7 @@ impl Session {
             approval_policy,
             sandbox_policy,
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.resource_limits,
             cwd,
             is_review_mode: false,
             final_output_json_schema: None,
@@ -457,10 +503,15 @@ impl Session {
             session_manager: ExecSessionManager::default(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
             notifier: notify,
+            hooks: HookRunner::new(config.hooks.clone()),
+            delegate_tasks: DelegateTaskManager::new(config.clone(), auth_manager.clone()),
             rollout: Mutex::new(Some(rollout_recorder)),
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+            codex_home: config.codex_home.clone(),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            checkpoints: config.checkpoints,
+            exec_policy,
         };
 
         let sess = Arc::new(Session {
@@ -475,9 +526,30 @@ impl Session {
         // Dispatch the SessionConfiguredEvent first and then report any errors.
         // If resuming, include converted initial messages in the payload so UIs can render them immediately.
         let initial_messages = initial_history.get_event_msgs();
+        let resumed = !matches!(initial_history, InitialHistory::New);
         sess.record_initial_history(&turn_context, initial_history)
             .await;
 
+        // Capped so a slow hook cannot hold up the session from starting; the
+        // hook's process is killed when the timeout drops it.
+        let outcome = tokio::time::timeout(
+            SESSION_START_HOOKS_TIMEOUT,
+            sess.run_hooks(
+                INITIAL_SUBMIT_ID,
+                &turn_context.cwd,
+                HookEvent::SessionStart { resumed },
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            warn!("session_start hooks timed out after {SESSION_START_HOOKS_TIMEOUT:?}");
+            HookOutcome::default()
+        });
+        if let Some(context) = outcome.additional_context {
+            sess.record_conversation_items(&[user_message(context)])
+                .await;
+        }
+
         let events = std::iter::once(Event {
             id: INITIAL_SUBMIT_ID.to_owned(),
             msg: EventMsg::SessionConfigured(SessionConfiguredEvent {
@@ -530,6 +602,12 @@ impl Session {
                 // If persisting, persist all rollout items as-is (recorder filters)
                 if persist && !rollout_items.is_empty() {
                     self.persist_rollout_items(&rollout_items).await;
+                } else {
+                    let mut state = self.state.lock().await;
+                    state.rollout_user_messages = rollout_items
+                        .iter()
+                        .filter(|item| is_user_turn_message(item))
+                        .count();
                 }
             }
         }
@@ -590,6 +668,26 @@ impl Session {
         action: &ApplyPatchAction,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+    ) -> oneshot::Receiver<ReviewDecision> {
+        self.request_file_changes_approval(
+            sub_id,
+            call_id,
+            convert_apply_patch_to_protocol(action),
+            reason,
+            grant_root,
+        )
+        .await
+    }
+
+    /// Like [`Session::request_patch_approval`] for changes that were already
+    /// converted to the protocol form, e.g. by a `delegate_task` worker.
+    pub(crate) async fn request_file_changes_approval(
+        &self,
+        sub_id: String,
+        call_id: String,
+        changes: HashMap<PathBuf, FileChange>,
+        reason: Option<String>,
+        grant_root: Option<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
         // Add the tx_approve callback to the map before sending the request.
         let (tx_approve, rx_approve) = oneshot::channel();
@@ -612,7 +710,7 @@ impl Session {
             id: event_id,
             msg: EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
                 call_id,
-                changes: convert_apply_patch_to_protocol(action),
+                changes,
                 reason,
                 grant_root,
             }),
@@ -642,11 +740,51 @@ impl Session {
         }
     }
 
+    pub async fn request_mcp_tool_call_approval(
+        &self,
+        sub_id: String,
+        call_id: String,
+        invocation: McpInvocation,
+        reason: Option<String>,
+    ) -> ReviewDecision {
+        let (tx_approve, rx_approve) = oneshot::channel();
+        let event_id = sub_id.clone();
+        let prev_entry = {
+            let mut active = self.active_turn.lock().await;
+            match active.as_mut() {
+                Some(at) => {
+                    let mut ts = at.turn_state.lock().await;
+                    ts.insert_pending_approval(sub_id, tx_approve)
+                }
+                None => None,
+            }
+        };
+        if prev_entry.is_some() {
+            warn!("Overwriting existing pending approval for sub_id: {event_id}");
+        }
+
+        let event = Event {
+            id: event_id,
+            msg: EventMsg::McpToolCallApprovalRequest(McpToolCallApprovalRequestEvent {
+                call_id,
+                invocation,
+                reason,
+            }),
+        };
+        self.send_event(event).await;
+        rx_approve.await.unwrap_or_default()
+    }
+
     pub async fn add_approved_command(&self, cmd: Vec<String>) {
         let mut state = self.state.lock().await;
         state.add_approved_command(cmd);
     }
 
+    pub async fn add_approved_mcp_tool(&self, server: String, tool: String) {
+        let mut state = self.state.lock().await;
+        state.add_approved_mcp_tool(server, tool);
+    }
+
     /// Records input items: always append to conversation history and
     /// persist these response items to rollout.
     async fn record_conversation_items(&self, items: &[ResponseItem]) {
@@ -716,6 +854,14 @@ impl Session {
     }
 
     async fn persist_rollout_items(&self, items: &[RolloutItem]) {
+        let user_messages = items
+            .iter()
+            .filter(|item| is_user_turn_message(item))
+            .count();
+        if user_messages > 0 {
+            let mut state = self.state.lock().await;
+            state.rollout_user_messages += user_messages;
+        }
         let recorder = {
             let guard = self.services.rollout.lock().await;
             guard.clone()
@@ -741,9 +887,14 @@ impl Session {
         {
             let mut state = self.state.lock().await;
             if let Some(token_usage) = token_usage {
+                let cost_usd = turn_context
+                    .client
+                    .get_model_pricing()
+                    .map(|pricing| pricing.cost_usd(token_usage));
                 state.update_token_info_from_usage(
                     token_usage,
                     turn_context.client.get_model_context_window(),
+                    cost_usd,
                 );
             }
         }
@@ -793,6 +944,50 @@ impl Session {
         }
     }
 
+    /// Snapshots the working tree with a ghost commit before the agent acts on
+    /// the user's message, records it in the rollout and notifies clients.
+    /// Outside a Git repository this does nothing.
+    async fn record_checkpoint(&self, sub_id: &str, cwd: &Path, prompt: String) {
+        if !self.services.checkpoints {
+            return;
+        }
+        let repo_path = cwd.to_path_buf();
+        let commit = match tokio::task::spawn_blocking(move || {
+            create_ghost_commit(&CreateGhostCommitOptions::new(&repo_path))
+        })
+        .await
+        {
+            Ok(Ok(commit)) => commit,
+            Ok(Err(GitToolingError::NotAGitRepository { .. })) => return,
+            Ok(Err(e)) => {
+                warn!("failed to create checkpoint: {e}");
+                return;
+            }
+            Err(e) => {
+                warn!("checkpoint task failed: {e}");
+                return;
+            }
+        };
+
+        // The user message of this turn has already been recorded.
+        let nth_user_message = {
+            let state = self.state.lock().await;
+            state.rollout_user_messages.saturating_sub(1)
+        };
+        let checkpoint = CheckpointItem {
+            commit_id: commit.id().to_string(),
+            prompt,
+            nth_user_message,
+        };
+        self.persist_rollout_items(&[RolloutItem::Checkpoint(checkpoint.clone())])
+            .await;
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::CheckpointCreated(checkpoint),
+        })
+        .await;
+    }
+
     async fn on_exec_command_begin(
         &self,
         turn_diff_tracker: &mut TurnDiffTracker,
@@ -990,22 +1185,53 @@ impl Session {
         match active.as_mut() {
             Some(at) => {
                 let mut ts = at.turn_state.lock().await;
-                ts.push_pending_input(input.into());
+                ts.push_pending_input(input);
                 Ok(())
             }
             None => Err(input),
         }
     }
 
-    pub async fn get_pending_input(&self) -> Vec<ResponseInputItem> {
-        let mut active = self.active_turn.lock().await;
-        match active.as_mut() {
-            Some(at) => {
-                let mut ts = at.turn_state.lock().await;
-                ts.take_pending_input()
+    /// Replaces the MCP resources and prompts referenced in `items` with their
+    /// contents. Failures are reported to the user as background events.
+    async fn resolve_mcp_input(&self, sub_id: &str, items: Vec<InputItem>) -> Vec<InputItem> {
+        let references_mcp = items.iter().any(|item| {
+            matches!(
+                item,
+                InputItem::McpResource { .. } | InputItem::McpPrompt { .. }
+            )
+        });
+        if !references_mcp {
+            return items;
+        }
+        let (items, errors) = resolve_mcp_input(&self.services.mcp_connection_manager, items).await;
+        for error in errors {
+            self.notify_background_event(sub_id, error).await;
+        }
+        items
+    }
+
+    /// Takes the input submitted while the current turn was running, with its
+    /// MCP references resolved.
+    pub async fn get_pending_input(&self, sub_id: &str) -> Vec<ResponseInputItem> {
+        let pending = {
+            let mut active = self.active_turn.lock().await;
+            match active.as_mut() {
+                Some(at) => {
+                    let mut ts = at.turn_state.lock().await;
+                    ts.take_pending_input()
+                }
+                None => return Vec::with_capacity(0),
+            }
+        };
+        let mut input = Vec::with_capacity(pending.len());
+        for items in pending {
+            let items = self.resolve_mcp_input(sub_id, items).await;
+            if !items.is_empty() {
+                input.push(ResponseInputItem::from(items));
             }
-            None => Vec::with_capacity(0),
         }
+        input
     }
 
     pub async fn call_tool(
@@ -1020,6 +1246,25 @@ impl Session {
             .await
     }
 
+    /// Applies the server's approval settings to a call of `server`/`tool`.
+    pub async fn assess_mcp_tool_call(
+        &self,
+        server: &str,
+        tool: &str,
+        approval_policy: AskForApproval,
+    ) -> SafetyCheck {
+        let manager = &self.services.mcp_connection_manager;
+        let state = self.state.lock().await;
+        assess_mcp_tool_call_safety(
+            server,
+            tool,
+            manager.tool_policy(server),
+            manager.is_read_only_tool(server, tool),
+            approval_policy,
+            state.approved_mcp_tools_ref(),
+        )
+    }
+
     pub async fn interrupt_task(self: &Arc<Self>) {
         info!("interrupt received: abort current task, if any");
         self.abort_all_tasks(TurnAbortReason::Interrupted).await;
@@ -1042,6 +1287,55 @@ impl Session {
         &self.services.notifier
     }
 
+    /// Runs the hooks for `event`, telling the user about any that failed.
+    async fn run_hooks(&self, sub_id: &str, cwd: &Path, event: HookEvent) -> HookOutcome {
+        let outcome = self
+            .services
+            .hooks
+            .run(&self.conversation_id.to_string(), cwd, event)
+            .await;
+        for failure in &outcome.failures {
+            self.notify_background_event(sub_id, failure.clone()).await;
+        }
+        outcome
+    }
+
+    /// Runs the `user_prompt_submit` hooks for input the user submitted,
+    /// whether it starts a task or is injected into the running one. Returns
+    /// `None` when a hook blocked the prompt, otherwise the input with any
+    /// context the hooks added.
+    async fn run_user_prompt_submit_hooks(
+        &self,
+        sub_id: &str,
+        cwd: &Path,
+        mut input: Vec<InputItem>,
+    ) -> Option<Vec<InputItem>> {
+        let outcome = self
+            .run_hooks(
+                sub_id,
+                cwd,
+                HookEvent::UserPromptSubmit {
+                    turn_id: sub_id.to_string(),
+                    prompt: prompt_text(&input),
+                },
+            )
+            .await;
+        if let Some(reason) = outcome.denied {
+            self.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: format!("Prompt blocked by hook: {reason}"),
+                }),
+            })
+            .await;
+            return None;
+        }
+        if let Some(context) = outcome.additional_context {
+            input.push(InputItem::Text { text: context });
+        }
+        Some(input)
+    }
+
     fn user_shell(&self) -> &shell::Shell {
         &self.services.user_shell
     }
@@ -1101,8 +1395,10 @@ async fn submission_loop(
 
                 // Effective model + family
                 let (effective_model, effective_family) = if let Some(ref m) = model {
-                    let fam =
-                        find_family_for_model(m).unwrap_or_else(|| config.model_family.clone());
+                    let fam = config
+                        .model_catalog
+                        .find_family(m)
+                        .unwrap_or_else(|| config.model_family.clone());
                     (m.clone(), fam)
                 } else {
                     (prev.client.get_model(), prev.client.get_model_family())
@@ -1118,8 +1414,12 @@ async fn submission_loop(
                 let mut updated_config = (*config).clone();
                 updated_config.model = effective_model.clone();
                 updated_config.model_family = effective_family.clone();
-                if let Some(model_info) = get_model_info(&effective_family) {
-                    updated_config.model_context_window = Some(model_info.context_window);
+                if let Some(context_window) = config
+                    .model_catalog
+                    .model_info(&effective_family)
+                    .and_then(|info| info.context_window)
+                {
+                    updated_config.model_context_window = Some(context_window);
                 }
 
                 let client = ModelClient::new(
@@ -1144,6 +1444,9 @@ async fn submission_loop(
                     include_web_search_request: config.tools_web_search_request,
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                     include_view_image_tool: config.include_view_image_tool,
+                    include_search_past_sessions: config.tools_search_past_sessions,
+                    include_memory_tools: config.tools_memory,
+                    include_delegate_task: config.tools_delegate_task,
                     experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
                 });
 
@@ -1155,6 +1458,7 @@ async fn submission_loop(
                     approval_policy: new_approval_policy,
                     sandbox_policy: new_sandbox_policy.clone(),
                     shell_environment_policy: prev.shell_environment_policy.clone(),
+                    resource_limits: prev.resource_limits,
                     cwd: new_cwd.clone(),
                     is_review_mode: false,
                     final_output_json_schema: None,
@@ -1176,6 +1480,12 @@ async fn submission_loop(
                 }
             }
             Op::UserInput { items } => {
+                let Some(items) = sess
+                    .run_user_prompt_submit_hooks(&sub.id, &turn_context.cwd, items)
+                    .await
+                else {
+                    continue;
+                };
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items).await {
                     // no current task, spawn a new one
@@ -1193,6 +1503,12 @@ async fn submission_loop(
                 summary,
                 final_output_json_schema,
             } => {
+                let Some(items) = sess
+                    .run_user_prompt_submit_hooks(&sub.id, &cwd, items)
+                    .await
+                else {
+                    continue;
+                };
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items).await {
                     // Derive a fresh TurnContext for this turn using the provided overrides.
@@ -1200,15 +1516,21 @@ async fn submission_loop(
                     let auth_manager = turn_context.client.get_auth_manager();
 
                     // Derive a model family for the requested model; fall back to the session's.
-                    let model_family = find_family_for_model(&model)
+                    let model_family = config
+                        .model_catalog
+                        .find_family(&model)
                         .unwrap_or_else(|| config.model_family.clone());
 
                     // Create a per‑turn Config clone with the requested model/family.
                     let mut per_turn_config = (*config).clone();
                     per_turn_config.model = model.clone();
                     per_turn_config.model_family = model_family.clone();
-                    if let Some(model_info) = get_model_info(&model_family) {
-                        per_turn_config.model_context_window = Some(model_info.context_window);
+                    if let Some(context_window) = config
+                        .model_catalog
+                        .model_info(&model_family)
+                        .and_then(|info| info.context_window)
+                    {
+                        per_turn_config.model_context_window = Some(context_window);
                     }
 
                     // Build a new client with per‑turn reasoning settings.
@@ -1232,6 +1554,9 @@ async fn submission_loop(
                             use_streamable_shell_tool: config
                                 .use_experimental_streamable_shell_tool,
                             include_view_image_tool: config.include_view_image_tool,
+                            include_search_past_sessions: config.tools_search_past_sessions,
+                            include_memory_tools: config.tools_memory,
+                            include_delegate_task: config.tools_delegate_task,
                             experimental_unified_exec_tool: config
                                 .use_experimental_unified_exec_tool,
                         }),
@@ -1240,6 +1565,7 @@ async fn submission_loop(
                         approval_policy,
                         sandbox_policy,
                         shell_environment_policy: turn_context.shell_environment_policy.clone(),
+                        resource_limits: turn_context.resource_limits,
                         cwd,
                         is_review_mode: false,
                         final_output_json_schema,
@@ -1273,6 +1599,12 @@ async fn submission_loop(
                 }
                 other => sess.notify_approval(&id, other).await,
             },
+            Op::McpToolCallApproval { id, decision } => match decision {
+                ReviewDecision::Abort => {
+                    sess.interrupt_task().await;
+                }
+                other => sess.notify_approval(&id, other).await,
+            },
             Op::AddToHistory { text } => {
                 let id = sess.conversation_id;
                 let config = config.clone();
@@ -1330,6 +1662,43 @@ async fn submission_loop(
                 };
                 sess.send_event(event).await;
             }
+            Op::ListMcpPrompts => {
+                let sub_id = sub.id.clone();
+                let sess_clone = sess.clone();
+                tokio::spawn(async move {
+                    let prompts = sess_clone
+                        .services
+                        .mcp_connection_manager
+                        .list_all_prompts()
+                        .await;
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListPromptsResponse(McpListPromptsResponseEvent {
+                            prompts,
+                        }),
+                    };
+                    sess_clone.send_event(event).await;
+                });
+            }
+            Op::ListMcpResources => {
+                let sub_id = sub.id.clone();
+                let sess_clone = sess.clone();
+                tokio::spawn(async move {
+                    let manager = &sess_clone.services.mcp_connection_manager;
+                    let (resources, resource_templates) = tokio::join!(
+                        manager.list_all_resources(),
+                        manager.list_all_resource_templates()
+                    );
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListResourcesResponse(McpListResourcesResponseEvent {
+                            resources,
+                            resource_templates,
+                        }),
+                    };
+                    sess_clone.send_event(event).await;
+                });
+            }
             Op::ListCustomPrompts => {
                 let sub_id = sub.id.clone();
 
@@ -1444,7 +1813,9 @@ async fn spawn_review_thread(
     review_request: ReviewRequest,
 ) {
     let model = config.review_model.clone();
-    let review_model_family = find_family_for_model(&model)
+    let review_model_family = config
+        .model_catalog
+        .find_family(&model)
         .unwrap_or_else(|| parent_turn_context.client.get_model_family());
     let tools_config = ToolsConfig::new(&ToolsConfigParams {
         model_family: &review_model_family,
@@ -1453,6 +1824,9 @@ async fn spawn_review_thread(
         include_web_search_request: false,
         use_streamable_shell_tool: false,
         include_view_image_tool: false,
+        include_search_past_sessions: false,
+        include_memory_tools: false,
+        include_delegate_task: false,
         experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
     });
 
@@ -1468,8 +1842,12 @@ async fn spawn_review_thread(
     per_turn_config.model_family = model_family.clone();
     per_turn_config.model_reasoning_effort = Some(ReasoningEffortConfig::Low);
     per_turn_config.model_reasoning_summary = ReasoningSummaryConfig::Detailed;
-    if let Some(model_info) = get_model_info(&model_family) {
-        per_turn_config.model_context_window = Some(model_info.context_window);
+    if let Some(context_window) = config
+        .model_catalog
+        .model_info(&model_family)
+        .and_then(|info| info.context_window)
+    {
+        per_turn_config.model_context_window = Some(context_window);
     }
 
     let per_turn_config = Arc::new(per_turn_config);
@@ -1490,6 +1868,7 @@ async fn spawn_review_thread(
         approval_policy: parent_turn_context.approval_policy,
         sandbox_policy: parent_turn_context.sandbox_policy.clone(),
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
+        resource_limits: parent_turn_context.resource_limits,
         cwd: parent_turn_context.cwd.clone(),
         is_review_mode: true,
         final_output_json_schema: None,
@@ -1547,6 +1926,13 @@ pub(crate) async fn run_task(
     };
     sess.send_event(event).await;
 
+    // Resolved here rather than when the input is submitted so that a slow
+    // MCP server does not hold up other operations such as an interrupt.
+    let input = sess.resolve_mcp_input(&sub_id, input).await;
+    if input.is_empty() {
+        return None;
+    }
+    let prompt = prompt_text(&input);
     let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
     // For review threads, keep an isolated in-memory history so the
     // model sees a fresh conversation without the parent session's history.
@@ -1560,6 +1946,8 @@ pub(crate) async fn run_task(
     } else {
         sess.record_input_and_rollout_usermsg(&initial_input_for_turn)
             .await;
+        sess.record_checkpoint(&sub_id, &turn_context.cwd, prompt)
+            .await;
     }
 
     let mut last_agent_message: Option<String> = None;
@@ -1567,13 +1955,20 @@ pub(crate) async fn run_task(
     // many turns, from the perspective of the user, it is a single turn.
     let mut turn_diff_tracker = TurnDiffTracker::new();
     let mut auto_compact_recently_attempted = false;
+    // Chat providers may not enforce `response_format`, so the final message
+    // is validated locally and the model gets one chance to correct it.
+    let validate_output_schema = turn_context.final_output_json_schema.is_some()
+        && turn_context.client.get_provider().wire_api == WireApi::Chat;
+    let mut output_schema_retry_used = false;
+    // How often `turn_complete` hooks have asked the agent to keep going.
+    let mut hook_follow_ups = 0;
 
     loop {
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
         let pending_input = sess
-            .get_pending_input()
+            .get_pending_input(&sub_id)
             .await
             .into_iter()
             .map(ResponseItem::from)
@@ -1768,6 +2163,71 @@ pub(crate) async fn run_task(
                     last_agent_message = get_last_assistant_message_from_turn(
                         &items_to_record_in_conversation_history,
                     );
+                    if validate_output_schema
+                        && let Some(schema) = turn_context.final_output_json_schema.as_ref()
+                        && let Err(error) =
+                            validate_final_output(schema, last_agent_message.as_deref())
+                    {
+                        if !output_schema_retry_used {
+                            output_schema_retry_used = true;
+                            warn!("final message does not match the output schema: {error}");
+                            let correction = ResponseItem::Message {
+                                id: None,
+                                role: "user".to_string(),
+                                content: vec![ContentItem::InputText {
+                                    text: format!(
+                                        "Your final message does not match the required output schema: {error}. Reply again with only a JSON document that matches the schema."
+                                    ),
+                                }],
+                            };
+                            sess.record_conversation_items(&[correction]).await;
+                            continue;
+                        }
+                        sess.send_event(Event {
+                            id: sub_id.clone(),
+                            msg: EventMsg::Error(ErrorEvent {
+                                message: format!(
+                                    "Final message does not match the output schema: {error}"
+                                ),
+                            }),
+                        })
+                        .await;
+                    }
+                    if !is_review_mode {
+                        let outcome = sess
+                            .run_hooks(
+                                &sub_id,
+                                &turn_context.cwd,
+                                HookEvent::TurnComplete {
+                                    turn_id: sub_id.clone(),
+                                    input_messages: turn_input_messages.clone(),
+                                    last_assistant_message: last_agent_message.clone(),
+                                    follow_up: hook_follow_ups > 0,
+                                },
+                            )
+                            .await;
+                        let max_follow_ups = sess.services.hooks.max_follow_ups();
+                        if let Some(message) = outcome.follow_up_message {
+                            if hook_follow_ups < max_follow_ups {
+                                hook_follow_ups += 1;
+                                sess.notify_background_event(
+                                    &sub_id,
+                                    format!("turn_complete hook follow-up: {message}"),
+                                )
+                                .await;
+                                sess.record_conversation_items(&[user_message(message)])
+                                    .await;
+                                continue;
+                            }
+                            sess.notify_background_event(
+                                &sub_id,
+                                format!(
+                                    "ignoring turn_complete hook follow-up: the limit of {max_follow_ups} per task was reached"
+                                ),
+                            )
+                            .await;
+                        }
+                    }
                     sess.notifier()
                         .notify(&UserNotification::AgentTurnComplete {
                             turn_id: sub_id.clone(),
@@ -1812,6 +2272,26 @@ pub(crate) async fn run_task(
     last_agent_message
 }
 
+/// Text of the prompt in `input`, as passed to hooks and checkpoints.
+fn prompt_text(input: &[InputItem]) -> String {
+    input
+        .iter()
+        .filter_map(|item| match item {
+            InputItem::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn user_message(text: String) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText { text }],
+    }
+}
+
 /// Parse the review output; when not valid JSON, build a structured
 /// fallback that carries the plain text as the overall explanation.
 ///
@@ -1856,23 +2336,50 @@ async fn run_turn(
         output_schema: turn_context.final_output_json_schema.clone(),
     };
 
+    // When the provider is unavailable, or its stream keeps failing, the turn
+    // moves on to the next of `fallback_providers`. The turn is then run with
+    // the fallback's client so that the rollout and cost record the model
+    // that actually served it.
+    let mut fallbacks = turn_context.client.fallback_clients().into_iter();
+    let mut fallback_context: Option<TurnContext> = None;
     let mut retries = 0;
     loop {
-        match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await {
+        let turn_context = fallback_context.as_ref().unwrap_or(turn_context);
+        let err = match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await
+        {
             Ok(output) => return Ok(output),
-            Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
-            Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
-            Err(CodexErr::UsageLimitReached(e)) => {
+            Err(e) => e,
+        };
+
+        let max_retries = turn_context.client.get_provider().stream_max_retries();
+        if let Some(next) = fall_back_to_next_provider(
+            sess,
+            &sub_id,
+            turn_context,
+            &err,
+            retries >= max_retries,
+            &mut fallbacks,
+        )
+        .await
+        {
+            fallback_context = Some(next);
+            retries = 0;
+            continue;
+        }
+
+        match err {
+            CodexErr::Interrupted => return Err(CodexErr::Interrupted),
+            CodexErr::EnvVar(var) => return Err(CodexErr::EnvVar(var)),
+            CodexErr::UsageLimitReached(e) => {
                 let rate_limits = e.rate_limits.clone();
                 if let Some(rate_limits) = rate_limits {
                     sess.update_rate_limits(&sub_id, rate_limits).await;
                 }
                 return Err(CodexErr::UsageLimitReached(e));
             }
-            Err(CodexErr::UsageNotIncluded) => return Err(CodexErr::UsageNotIncluded),
-            Err(e) => {
+            CodexErr::UsageNotIncluded => return Err(CodexErr::UsageNotIncluded),
+            e => {
                 // Use the configured provider-specific stream retry budget.
-                let max_retries = turn_context.client.get_provider().stream_max_retries();
                 if retries < max_retries {
                     retries += 1;
                     let delay = match e {
@@ -1903,6 +2410,41 @@ async fn run_turn(
     }
 }
 
+/// Returns a copy of `turn_context` that uses the next of `fallbacks` when
+/// `err` means the provider is unavailable, or when its stream failed and the
+/// retries are `exhausted`. Notifies the user of the switch.
+async fn fall_back_to_next_provider(
+    sess: &Session,
+    sub_id: &str,
+    turn_context: &TurnContext,
+    err: &CodexErr,
+    exhausted: bool,
+    fallbacks: &mut impl Iterator<Item = ModelClient>,
+) -> Option<TurnContext> {
+    let stream_failed = exhausted && matches!(err, CodexErr::Stream(..));
+    if !is_provider_unavailable(err) && !stream_failed {
+        return None;
+    }
+    let client = fallbacks.next()?;
+    warn!(
+        "model provider unavailable ({err}); falling back to `{}`",
+        client.get_model_provider_id()
+    );
+    sess.notify_background_event(
+        sub_id,
+        format!(
+            "Model provider unavailable; continuing with {} ({}).",
+            client.get_provider().name,
+            client.get_model()
+        ),
+    )
+    .await;
+    Some(TurnContext {
+        client,
+        ..turn_context.clone()
+    })
+}
+
 /// When the model is prompted, it returns a stream of events. Some of these
 /// events map to a `ResponseItem`. A `ResponseItem` may need to be
 /// "handled" such that it produces a `ResponseInputItem` that needs to be
@@ -1986,6 +2528,7 @@ async fn try_run_turn(
         approval_policy: turn_context.approval_policy,
         sandbox_policy: turn_context.sandbox_policy.clone(),
         model: turn_context.client.get_model(),
+        model_provider: Some(turn_context.client.get_model_provider_id()),
         effort: turn_context.client.get_reasoning_effort(),
         summary: turn_context.client.get_reasoning_summary(),
     });
@@ -2129,10 +2672,12 @@ async fn handle_response_item(
             if let Some((server, tool_name)) =
                 sess.services.mcp_connection_manager.parse_tool_name(&name)
             {
-                let resp = handle_mcp_tool_call(
+                let resp = handle_mcp_tool_call_with_hooks(
                     sess,
+                    turn_context,
                     sub_id,
                     call_id.clone(),
+                    name,
                     server,
                     tool_name,
                     arguments,
@@ -2280,52 +2825,307 @@ async fn handle_response_item(
     Ok(output)
 }
 
-async fn handle_unified_exec_tool_call(
+/// Decides whether a PTY session may start `command` and the sandbox it runs
+/// under, the same way as for shell commands.
+async fn approve_pty_command(
     sess: &Session,
-    session_id: Option<String>,
-    arguments: Vec<String>,
-    timeout_ms: Option<u64>,
-) -> Result<String, FunctionCallError> {
-    let parsed_session_id = if let Some(session_id) = session_id {
-        match session_id.parse::<i32>() {
-            Ok(parsed) => Some(parsed),
-            Err(output) => {
-                return Err(FunctionCallError::RespondToModel(format!(
-                    "invalid session_id: {session_id} due to error {output:?}"
-                )));
-            }
-        }
-    } else {
-        None
-    };
-
-    let request = crate::unified_exec::UnifiedExecRequest {
-        session_id: parsed_session_id,
-        input_chunks: &arguments,
-        timeout_ms,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    command: &[String],
+) -> Result<SandboxType, FunctionCallError> {
+    let safety = {
+        let state = sess.state.lock().await;
+        assess_command_safety(
+            command,
+            turn_context.approval_policy,
+            &turn_context.sandbox_policy,
+            state.approved_commands_ref(),
+            &sess.services.exec_policy,
+            &turn_context.cwd,
+            false,
+        )
     };
+    sandbox_type_for_safety_check(
+        sess,
+        safety,
+        sub_id,
+        call_id,
+        command,
+        &turn_context.cwd,
+        None,
+    )
+    .await
+}
 
-    let value = sess
-        .services
-        .unified_exec_manager
-        .handle_request(request)
-        .await
-        .map_err(|err| {
-            FunctionCallError::RespondToModel(format!("unified exec failed: {err:?}"))
-        })?;
-
-    #[derive(Serialize)]
-    struct SerializedUnifiedExecResult {
-        session_id: Option<String>,
-        output: String,
-    }
-
-    serde_json::to_string(&SerializedUnifiedExecResult {
-        session_id: value.session_id.map(|id| id.to_string()),
-        output: value.output,
-    })
-    .map_err(|err| {
-        FunctionCallError::RespondToModel(format!(
+/// Checks the command lines that input to a running PTY session submits. The
+/// session keeps the sandbox it was started with, so a line that would only be
+/// auto-approved inside a sandbox needs approval when the session has none.
+/// Input with control characters or escape sequences the lines do not account
+/// for is shown to the user as written instead, unless the session is
+/// sandboxed or the sandbox is disabled altogether.
+async fn approve_pty_input(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    session_sandbox_type: SandboxType,
+    input: &str,
+    lines: &[String],
+) -> Result<(), FunctionCallError> {
+    if session_sandbox_type == SandboxType::None
+        && !matches!(turn_context.sandbox_policy, SandboxPolicy::DangerFullAccess)
+        && has_unmodeled_control_chars(input)
+    {
+        let safety = if turn_context.approval_policy == AskForApproval::Never {
+            SafetyCheck::Reject {
+                reason:
+                    "the input contains control characters and the session runs outside the sandbox"
+                        .to_string(),
+            }
+        } else {
+            SafetyCheck::AskUser
+        };
+        let command = vec![input.escape_debug().to_string()];
+        sandbox_type_for_safety_check(
+            sess,
+            safety,
+            sub_id,
+            call_id,
+            &command,
+            &turn_context.cwd,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+    for line in lines {
+        let command = vec!["bash".to_string(), "-lc".to_string(), line.clone()];
+        let safety = {
+            let state = sess.state.lock().await;
+            assess_command_safety(
+                &command,
+                turn_context.approval_policy,
+                &turn_context.sandbox_policy,
+                state.approved_commands_ref(),
+                &sess.services.exec_policy,
+                &turn_context.cwd,
+                false,
+            )
+        };
+        let safety = match safety {
+            SafetyCheck::AutoApprove { sandbox_type }
+                if sandbox_type != SandboxType::None
+                    && session_sandbox_type == SandboxType::None =>
+            {
+                if turn_context.approval_policy == AskForApproval::Never {
+                    SafetyCheck::Reject {
+                        reason: "the session runs outside the sandbox".to_string(),
+                    }
+                } else {
+                    SafetyCheck::AskUser
+                }
+            }
+            safety => safety,
+        };
+        sandbox_type_for_safety_check(
+            sess,
+            safety,
+            sub_id,
+            call_id,
+            &command,
+            &turn_context.cwd,
+            None,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Wraps `command` for a new PTY session in the turn's cwd and environment,
+/// under the turn's resource limits. Network requests of the session are
+/// reported as events of the call that started it.
+fn create_pty_command(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    command: Vec<String>,
+    sandbox_type: SandboxType,
+) -> Result<SandboxedCommand, FunctionCallError> {
+    create_sandboxed_command(
+        command,
+        turn_context.cwd.clone(),
+        create_env(&turn_context.shell_environment_policy),
+        sandbox_type,
+        &turn_context.sandbox_policy,
+        &turn_context.cwd,
+        &sess.services.codex_linux_sandbox_exe,
+        turn_context.resource_limits,
+        Some(StdoutStream {
+            sub_id: sub_id.to_string(),
+            call_id: call_id.to_string(),
+            tx_event: sess.tx_event.clone(),
+        }),
+    )
+    .map_err(|e| FunctionCallError::RespondToModel(format!("execution error: {e:?}")))
+}
+
+/// How lines submitted to a PTY session are shown in exec events; `None`
+/// when the input submits no command line.
+fn submitted_lines_for_display(lines: Vec<String>) -> Option<Vec<String>> {
+    if lines.is_empty() {
+        return None;
+    }
+    Some(vec![
+        "bash".to_string(),
+        "-lc".to_string(),
+        lines.join("\n"),
+    ])
+}
+
+/// Runs a PTY tool call that starts a session or submits command lines to
+/// one, emitting the begin and end events for it even on error. A session
+/// that is still running when the call returns ends with exit code 0.
+async fn run_pty_with_events<T, E: std::fmt::Display>(
+    sess: &Session,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    begin_ctx: ExecCommandContext,
+    call: impl std::future::Future<Output = Result<T, E>>,
+    to_output: impl FnOnce(&T, Duration) -> ExecToolCallOutput,
+) -> Result<T, E> {
+    let sub_id = begin_ctx.sub_id.clone();
+    let call_id = begin_ctx.call_id.clone();
+    sess.on_exec_command_begin(turn_diff_tracker, begin_ctx)
+        .await;
+
+    let start = std::time::Instant::now();
+    let result = call.await;
+    let output = match &result {
+        Ok(value) => to_output(value, start.elapsed()),
+        Err(e) => ExecToolCallOutput {
+            exit_code: -1,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(e.to_string()),
+            aggregated_output: StreamOutput::new(e.to_string()),
+            duration: start.elapsed(),
+            timed_out: false,
+        },
+    };
+    sess.on_exec_command_end(turn_diff_tracker, &sub_id, &call_id, &output, false)
+        .await;
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_unified_exec_tool_call(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: &str,
+    call_id: &str,
+    session_id: Option<String>,
+    arguments: Vec<String>,
+    timeout_ms: Option<u64>,
+) -> Result<String, FunctionCallError> {
+    let parsed_session_id = if let Some(session_id) = session_id {
+        match session_id.parse::<i32>() {
+            Ok(parsed) => Some(parsed),
+            Err(output) => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "invalid session_id: {session_id} due to error {output:?}"
+                )));
+            }
+        }
+    } else {
+        None
+    };
+
+    let manager = &sess.services.unified_exec_manager;
+    let (command, command_for_display) = match parsed_session_id {
+        Some(session_id) => {
+            let input = arguments.join(" ");
+            let (sandbox_type, lines) =
+                manager
+                    .submitted_lines(session_id, &input)
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("unified exec failed: {err:?}"))
+                    })?;
+            approve_pty_input(
+                sess,
+                turn_context,
+                sub_id,
+                call_id,
+                sandbox_type,
+                &input,
+                &lines,
+            )
+            .await?;
+            (None, submitted_lines_for_display(lines))
+        }
+        None if arguments.is_empty() => (None, None),
+        None => {
+            let sandbox_type =
+                approve_pty_command(sess, turn_context, sub_id, call_id, &arguments).await?;
+            let command = create_pty_command(
+                sess,
+                turn_context,
+                sub_id,
+                call_id,
+                arguments.clone(),
+                sandbox_type,
+            )?;
+            (Some(command), Some(arguments.clone()))
+        }
+    };
+
+    let request = crate::unified_exec::UnifiedExecRequest {
+        session_id: parsed_session_id,
+        input_chunks: &arguments,
+        timeout_ms,
+        command,
+    };
+    let value = match command_for_display {
+        Some(command_for_display) => {
+            run_pty_with_events(
+                sess,
+                turn_diff_tracker,
+                ExecCommandContext {
+                    sub_id: sub_id.to_string(),
+                    call_id: call_id.to_string(),
+                    command_for_display,
+                    cwd: turn_context.cwd.clone(),
+                    apply_patch: None,
+                },
+                manager.handle_request(request),
+                |result, duration| ExecToolCallOutput {
+                    exit_code: result.exit_code.unwrap_or(0),
+                    stdout: StreamOutput::new(result.output.clone()),
+                    stderr: StreamOutput::new(String::new()),
+                    aggregated_output: StreamOutput::new(result.output.clone()),
+                    duration,
+                    timed_out: false,
+                },
+            )
+            .await
+        }
+        None => manager.handle_request(request).await,
+    }
+    .map_err(|err| FunctionCallError::RespondToModel(format!("unified exec failed: {err:?}")))?;
+
+    #[derive(Serialize)]
+    struct SerializedUnifiedExecResult {
+        session_id: Option<String>,
+        output: String,
+    }
+
+    serde_json::to_string(&SerializedUnifiedExecResult {
+        session_id: value.session_id.map(|id| id.to_string()),
+        output: value.output,
+    })
+    .map_err(|err| {
+        FunctionCallError::RespondToModel(format!(
             "failed to serialize unified exec output: {err:?}"
         ))
     })
@@ -2339,6 +3139,279 @@ async fn handle_function_call(
     name: String,
     arguments: String,
     call_id: String,
+) -> Result<String, FunctionCallError> {
+    // Shell and apply_patch calls run their hooks in
+    // `handle_container_exec_with_params`, where the final argv is known.
+    if matches!(name.as_str(), "container.exec" | "shell" | "apply_patch") {
+        return dispatch_function_call(
+            sess,
+            turn_context,
+            turn_diff_tracker,
+            sub_id,
+            name,
+            arguments,
+            call_id,
+        )
+        .await;
+    }
+
+    let tool_input = tool_input_from_arguments(&arguments);
+    if let Err(reason) = run_pre_tool_use_hooks_without_command(
+        sess,
+        turn_context,
+        &sub_id,
+        &call_id,
+        &name,
+        tool_input.clone(),
+    )
+    .await
+    {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{name} call denied by hook: {reason}"
+        )));
+    }
+
+    let result = dispatch_function_call(
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        sub_id.clone(),
+        name.clone(),
+        arguments,
+        call_id.clone(),
+    )
+    .await;
+    run_post_tool_use_hooks(
+        sess,
+        turn_context,
+        sub_id,
+        call_id,
+        name,
+        tool_input,
+        result,
+    )
+    .await
+}
+
+/// Runs an MCP tool call with the same `pre_tool_use` and `post_tool_use`
+/// hooks as the other tools. `name` is the tool's qualified name, as the
+/// model sees it.
+#[allow(clippy::too_many_arguments)]
+async fn handle_mcp_tool_call_with_hooks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: String,
+    name: String,
+    server: String,
+    tool_name: String,
+    arguments: String,
+) -> ResponseInputItem {
+    let tool_input = tool_input_from_arguments(&arguments);
+    if let Err(reason) = run_pre_tool_use_hooks_without_command(
+        sess,
+        turn_context,
+        sub_id,
+        &call_id,
+        &name,
+        tool_input.clone(),
+    )
+    .await
+    {
+        return ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: format!("{name} call denied by hook: {reason}"),
+                success: Some(false),
+            },
+        };
+    }
+
+    let response = handle_mcp_tool_call(
+        sess,
+        sub_id,
+        call_id.clone(),
+        server,
+        tool_name,
+        arguments,
+        turn_context.approval_policy,
+    )
+    .await;
+    let output = match &response {
+        ResponseInputItem::McpToolCallOutput {
+            result: Ok(result), ..
+        } => convert_call_tool_result_to_function_call_output_payload(result),
+        ResponseInputItem::McpToolCallOutput { result: Err(e), .. } => FunctionCallOutputPayload {
+            content: e.clone(),
+            success: Some(false),
+        },
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.clone(),
+        _ => return response,
+    };
+    let Some(context) = post_tool_use_context(
+        sess,
+        turn_context,
+        sub_id.to_string(),
+        call_id.clone(),
+        name,
+        tool_input,
+        output.content.clone(),
+        output.success != Some(false),
+    )
+    .await
+    else {
+        return response;
+    };
+    // The model sees MCP results as function call output, so the context is
+    // added to that.
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content: format!("{}\n\n{context}", output.content),
+            ..output
+        },
+    }
+}
+
+/// The arguments of a tool call as hooks see them: parsed when they are
+/// JSON, otherwise as a string.
+fn tool_input_from_arguments(arguments: &str) -> serde_json::Value {
+    serde_json::from_str(arguments)
+        .unwrap_or_else(|_| serde_json::Value::String(arguments.to_string()))
+}
+
+/// Runs the `pre_tool_use` hooks for a tool call. Returns the command the
+/// hooks rewrote it to, if any, or the reason they denied the call.
+async fn run_pre_tool_use_hooks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    tool_name: &str,
+    tool_input: serde_json::Value,
+) -> Result<Option<Vec<String>>, String> {
+    let outcome = sess
+        .run_hooks(
+            sub_id,
+            &turn_context.cwd,
+            HookEvent::PreToolUse {
+                turn_id: sub_id.to_string(),
+                call_id: call_id.to_string(),
+                tool_name: tool_name.to_string(),
+                tool_input,
+            },
+        )
+        .await;
+    match outcome.denied {
+        Some(reason) => Err(reason),
+        None => Ok(outcome.command),
+    }
+}
+
+/// [`run_pre_tool_use_hooks`] for a tool that takes no command. A command
+/// returned by a hook cannot be applied, so the call is denied rather than
+/// run without the change the hook asked for.
+async fn run_pre_tool_use_hooks_without_command(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    tool_name: &str,
+    tool_input: serde_json::Value,
+) -> Result<(), String> {
+    match run_pre_tool_use_hooks(sess, turn_context, sub_id, call_id, tool_name, tool_input).await?
+    {
+        Some(_) => Err(format!(
+            "a hook returned a `command`, but {tool_name} has no command to replace"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Runs the `post_tool_use` hooks for a finished tool call. Context they
+/// return is appended to the output the model sees and follow-up messages are
+/// queued as user input for the next request.
+async fn run_post_tool_use_hooks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: String,
+    call_id: String,
+    tool_name: String,
+    tool_input: serde_json::Value,
+    result: Result<String, FunctionCallError>,
+) -> Result<String, FunctionCallError> {
+    let (tool_output, success) = match &result {
+        Ok(output) => (output.clone(), true),
+        Err(FunctionCallError::RespondToModel(output)) => (output.clone(), false),
+    };
+    let Some(context) = post_tool_use_context(
+        sess,
+        turn_context,
+        sub_id,
+        call_id,
+        tool_name,
+        tool_input,
+        tool_output,
+        success,
+    )
+    .await
+    else {
+        return result;
+    };
+    match result {
+        Ok(output) => Ok(format!("{output}\n\n{context}")),
+        Err(FunctionCallError::RespondToModel(output)) => Err(FunctionCallError::RespondToModel(
+            format!("{output}\n\n{context}"),
+        )),
+    }
+}
+
+/// Runs the `post_tool_use` hooks, queues their follow-up messages and
+/// returns the context they want added to the tool output.
+#[allow(clippy::too_many_arguments)]
+async fn post_tool_use_context(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: String,
+    call_id: String,
+    tool_name: String,
+    tool_input: serde_json::Value,
+    tool_output: String,
+    success: bool,
+) -> Option<String> {
+    let outcome = sess
+        .run_hooks(
+            &sub_id,
+            &turn_context.cwd,
+            HookEvent::PostToolUse {
+                turn_id: sub_id.clone(),
+                call_id,
+                tool_name,
+                tool_input,
+                tool_output,
+                success,
+            },
+        )
+        .await;
+    if let Some(message) = outcome.follow_up_message
+        && sess
+            .inject_input(vec![InputItem::Text { text: message }])
+            .await
+            .is_err()
+    {
+        warn!("dropping post_tool_use hook follow-up: no active task");
+    }
+    outcome.additional_context
+}
+
+async fn dispatch_function_call(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    name: String,
+    arguments: String,
+    call_id: String,
 ) -> Result<String, FunctionCallError> {
     match name.as_str() {
         "container.exec" | "shell" => {
@@ -2369,7 +3442,17 @@ async fn handle_function_call(
                 ))
             })?;
 
-            handle_unified_exec_tool_call(sess, args.session_id, args.input, args.timeout_ms).await
+            handle_unified_exec_tool_call(
+                sess,
+                turn_context,
+                turn_diff_tracker,
+                &sub_id,
+                &call_id,
+                args.session_id,
+                args.input,
+                args.timeout_ms,
+            )
+            .await
         }
         "view_image" => {
             #[derive(serde::Deserialize)]
@@ -2405,6 +3488,7 @@ async fn handle_function_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                resource_limits: turn_context.resource_limits,
             };
             handle_container_exec_with_params(
                 exec_params,
@@ -2417,18 +3501,82 @@ async fn handle_function_call(
             .await
         }
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
+        REMEMBER_TOOL_NAME => {
+            handle_remember(
+                sess,
+                turn_context,
+                &sess.services.codex_home,
+                &sub_id,
+                &call_id,
+                arguments,
+            )
+            .await
+        }
+        FORGET_TOOL_NAME => {
+            handle_forget(
+                sess,
+                turn_context,
+                &sess.services.codex_home,
+                &sub_id,
+                &call_id,
+                arguments,
+            )
+            .await
+        }
+        SEARCH_PAST_SESSIONS_TOOL_NAME => {
+            handle_search_past_sessions(
+                &sess.services.codex_home,
+                &turn_context.cwd,
+                sess.conversation_id,
+                arguments,
+            )
+            .await
+        }
+        DELEGATE_TASK_TOOL_NAME => {
+            handle_delegate_task(
+                sess,
+                &sess.services.delegate_tasks,
+                turn_context,
+                &sub_id,
+                &call_id,
+                arguments,
+            )
+            .await
+        }
         EXEC_COMMAND_TOOL_NAME => {
-            // TODO(mbolin): Sandbox check.
             let exec_params: ExecCommandParams = serde_json::from_str(&arguments).map_err(|e| {
                 FunctionCallError::RespondToModel(format!(
                     "failed to parse function arguments: {e:?}"
                 ))
             })?;
-            let result = sess
-                .services
-                .session_manager
-                .handle_exec_command_request(exec_params)
-                .await;
+            let command_for_display = exec_params.command();
+            let sandbox_type =
+                approve_pty_command(sess, turn_context, &sub_id, &call_id, &command_for_display)
+                    .await?;
+            let command = create_pty_command(
+                sess,
+                turn_context,
+                &sub_id,
+                &call_id,
+                command_for_display.clone(),
+                sandbox_type,
+            )?;
+            let result = run_pty_with_events(
+                sess,
+                turn_diff_tracker,
+                ExecCommandContext {
+                    sub_id,
+                    call_id,
+                    command_for_display,
+                    cwd: turn_context.cwd.clone(),
+                    apply_patch: None,
+                },
+                sess.services
+                    .session_manager
+                    .handle_exec_command_request(exec_params, command),
+                |output, _| output.to_exec_tool_call_output(),
+            )
+            .await;
             match result {
                 Ok(output) => Ok(output.to_text_output()),
                 Err(err) => Err(FunctionCallError::RespondToModel(err)),
@@ -2442,12 +3590,46 @@ async fn handle_function_call(
                     ))
                 })?;
 
-            let result = sess
-                .services
-                .session_manager
-                .handle_write_stdin_request(write_stdin_params)
+            let session_manager = &sess.services.session_manager;
+            let (sandbox_type, lines) = session_manager
+                .submitted_lines(write_stdin_params.session_id, &write_stdin_params.chars)
                 .await
                 .map_err(FunctionCallError::RespondToModel)?;
+            approve_pty_input(
+                sess,
+                turn_context,
+                &sub_id,
+                &call_id,
+                sandbox_type,
+                &write_stdin_params.chars,
+                &lines,
+            )
+            .await?;
+
+            let result = match submitted_lines_for_display(lines) {
+                Some(command_for_display) => {
+                    run_pty_with_events(
+                        sess,
+                        turn_diff_tracker,
+                        ExecCommandContext {
+                            sub_id,
+                            call_id,
+                            command_for_display,
+                            cwd: turn_context.cwd.clone(),
+                            apply_patch: None,
+                        },
+                        session_manager.handle_write_stdin_request(write_stdin_params),
+                        |output, _| output.to_exec_tool_call_output(),
+                    )
+                    .await
+                }
+                None => {
+                    session_manager
+                        .handle_write_stdin_request(write_stdin_params)
+                        .await
+                }
+            }
+            .map_err(FunctionCallError::RespondToModel)?;
 
             Ok(result.to_text_output())
         }
@@ -2476,6 +3658,7 @@ async fn handle_custom_tool_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                resource_limits: turn_context.resource_limits,
             };
 
             handle_container_exec_with_params(
@@ -2505,6 +3688,7 @@ fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> Ex
         env: create_env(&turn_context.shell_environment_policy),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
+        resource_limits: turn_context.resource_limits,
     }
 }
 
@@ -2548,6 +3732,66 @@ fn maybe_translate_shell_command(
 }
 
 async fn handle_container_exec_with_params(
+    mut params: ExecParams,
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    call_id: String,
+) -> Result<String, FunctionCallError> {
+    let tool_name = match params.command.first().map(String::as_str) {
+        Some("apply_patch") => "apply_patch",
+        _ => "shell",
+    };
+    match run_pre_tool_use_hooks(
+        sess,
+        turn_context,
+        &sub_id,
+        &call_id,
+        tool_name,
+        exec_tool_input(&params),
+    )
+    .await
+    {
+        Ok(Some(command)) => params.command = command,
+        Ok(None) => {}
+        Err(reason) => {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "exec command rejected by hook: {reason}"
+            )));
+        }
+    }
+
+    let tool_input = exec_tool_input(&params);
+    let result = run_container_exec(
+        params,
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        sub_id.clone(),
+        call_id.clone(),
+    )
+    .await;
+    run_post_tool_use_hooks(
+        sess,
+        turn_context,
+        sub_id,
+        call_id,
+        tool_name.to_string(),
+        tool_input,
+        result,
+    )
+    .await
+}
+
+fn exec_tool_input(params: &ExecParams) -> serde_json::Value {
+    serde_json::json!({
+        "command": params.command,
+        "workdir": params.cwd,
+    })
+}
+
+async fn run_container_exec(
     params: ExecParams,
     sess: &Session,
     turn_context: &TurnContext,
@@ -2565,7 +3809,12 @@ async fn handle_container_exec_with_params(
     }
 
     // check if this was a patch, and apply it if so
-    let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+    let seen_files = sess.state.lock().await.seen_files.clone();
+    let maybe_apply_patch =
+        maybe_parse_apply_patch_verified_with_bases(&params.command, &params.cwd, &|path| {
+            seen_files.get(path)
+        });
+    let apply_patch_exec = match maybe_apply_patch {
         MaybeApplyPatchVerified::Body(changes) => {
             match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await {
                 InternalApplyPatchInvocation::Output(item) => return item,
@@ -2614,6 +3863,7 @@ async fn handle_container_exec_with_params(
                 env: HashMap::new(),
                 with_escalated_permissions: params.with_escalated_permissions,
                 justification: params.justification.clone(),
+                resource_limits: params.resource_limits,
             };
             let safety = if *user_explicitly_approved_this_action {
                 SafetyCheck::AutoApprove {
@@ -2640,6 +3890,8 @@ async fn handle_container_exec_with_params(
                     turn_context.approval_policy,
                     &turn_context.sandbox_policy,
                     state.approved_commands_ref(),
+                    &sess.services.exec_policy,
+                    &params.cwd,
                     params.with_escalated_permissions.unwrap_or(false),
                 )
             };
@@ -2648,41 +3900,16 @@ async fn handle_container_exec_with_params(
         }
     };
 
-    let sandbox_type = match safety {
-        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
-        SafetyCheck::AskUser => {
-            let decision = sess
-                .request_command_approval(
-                    sub_id.clone(),
-                    call_id.clone(),
-                    params.command.clone(),
-                    params.cwd.clone(),
-                    params.justification.clone(),
-                )
-                .await;
-            match decision {
-                ReviewDecision::Approved => (),
-                ReviewDecision::ApprovedForSession => {
-                    sess.add_approved_command(params.command.clone()).await;
-                }
-                ReviewDecision::Denied | ReviewDecision::Abort => {
-                    return Err(FunctionCallError::RespondToModel(
-                        "exec command rejected by user".to_string(),
-                    ));
-                }
-            }
-            // No sandboxing is applied because the user has given
-            // explicit approval. Often, we end up in this case because
-            // the command cannot be run in a sandbox, such as
-            // installing a new dependency that requires network access.
-            SandboxType::None
-        }
-        SafetyCheck::Reject { reason } => {
-            return Err(FunctionCallError::RespondToModel(format!(
-                "exec command rejected: {reason:?}"
-            )));
-        }
-    };
+    let sandbox_type = sandbox_type_for_safety_check(
+        sess,
+        safety,
+        &sub_id,
+        &call_id,
+        &params.command,
+        &params.cwd,
+        params.justification.clone(),
+    )
+    .await?;
 
     let exec_command_context = ExecCommandContext {
         sub_id: sub_id.clone(),
@@ -2729,6 +3956,13 @@ async fn handle_container_exec_with_params(
             let ExecToolCallOutput { exit_code, .. } = &output;
             let content = format_exec_output(&output);
             if *exit_code == 0 {
+                let mut state = sess.state.lock().await;
+                match &exec_command_context.apply_patch {
+                    Some(apply_patch) => state.seen_files.record_patch(&apply_patch.changes),
+                    None => state
+                        .seen_files
+                        .record_read_command(&command_for_display, &params.cwd),
+                }
                 Ok(content)
             } else {
                 Err(FunctionCallError::RespondToModel(content))
@@ -2752,6 +3986,52 @@ async fn handle_container_exec_with_params(
     }
 }
 
+/// Turns the safety check for `command` into the sandbox to run it under,
+/// asking the user first when the check requires it.
+async fn sandbox_type_for_safety_check(
+    sess: &Session,
+    safety: SafetyCheck,
+    sub_id: &str,
+    call_id: &str,
+    command: &[String],
+    cwd: &Path,
+    justification: Option<String>,
+) -> Result<SandboxType, FunctionCallError> {
+    match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => Ok(sandbox_type),
+        SafetyCheck::AskUser => {
+            let decision = sess
+                .request_command_approval(
+                    sub_id.to_string(),
+                    call_id.to_string(),
+                    command.to_vec(),
+                    cwd.to_path_buf(),
+                    justification,
+                )
+                .await;
+            match decision {
+                ReviewDecision::Approved => (),
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(command.to_vec()).await;
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return Err(FunctionCallError::RespondToModel(
+                        "exec command rejected by user".to_string(),
+                    ));
+                }
+            }
+            // No sandboxing is applied because the user has given
+            // explicit approval. Often, we end up in this case because
+            // the command cannot be run in a sandbox, such as
+            // installing a new dependency that requires network access.
+            Ok(SandboxType::None)
+        }
+        SafetyCheck::Reject { reason } => Err(FunctionCallError::RespondToModel(format!(
+            "exec command rejected: {reason:?}"
+        ))),
+    }
+}
+
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
     params: ExecParams,
@@ -2770,6 +4050,26 @@ async fn handle_sandbox_error(
         return Err(FunctionCallError::RespondToModel(content));
     }
 
+    // Limits apply without a sandbox too, so there is nothing to retry.
+    if let SandboxErr::ResourceLimit { output, .. } = &error {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{error}\n{}",
+            format_exec_output(output)
+        )));
+    }
+
+    // Reads of `deny_read` paths are never retried outside the sandbox.
+    if let SandboxErr::Denied { output } = &error {
+        let denied = resolve_deny_read_paths(turn_context.sandbox_policy.deny_read(), &cwd);
+        if let Some(path) = find_denied_path_in_output(&output.stderr.text, &denied, &cwd) {
+            return Err(FunctionCallError::RespondToModel(format!(
+                "blocked by sandbox read policy: {} is listed in sandbox_workspace_write.deny_read\n{}",
+                path.display(),
+                format_exec_output(output)
+            )));
+        }
+    }
+
     // Early out if either the user never wants to be asked for approval, or
     // we're letting the model manage escalation requests. Otherwise, continue
     match turn_context.approval_policy {
@@ -3127,6 +4427,7 @@ pub(crate) use tests::make_session_and_context;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CodexAuth;
     use crate::config::ConfigOverrides;
     use crate::config::ConfigToml;
     use crate::protocol::CompactedItem;
@@ -3396,6 +4697,9 @@ mod tests {
             include_web_search_request: config.tools_web_search_request,
             use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
             include_view_image_tool: config.include_view_image_tool,
+            include_search_past_sessions: config.tools_search_past_sessions,
+            include_memory_tools: config.tools_memory,
+            include_delegate_task: config.tools_delegate_task,
             experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
         });
         let turn_context = TurnContext {
@@ -3406,6 +4710,7 @@ mod tests {
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.resource_limits,
             tools_config,
             is_review_mode: false,
             final_output_json_schema: None,
@@ -3415,10 +4720,18 @@ mod tests {
             session_manager: ExecSessionManager::default(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
             notifier: UserNotifier::default(),
+            hooks: HookRunner::default(),
+            delegate_tasks: DelegateTaskManager::new(
+                config.clone(),
+                AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key")),
+            ),
             rollout: Mutex::new(None),
             codex_linux_sandbox_exe: None,
+            codex_home: config.codex_home.clone(),
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            checkpoints: config.checkpoints,
+            exec_policy: ExecPolicy::default(),
         };
         let session = Session {
             conversation_id,
@@ -3463,6 +4776,9 @@ mod tests {
             include_web_search_request: config.tools_web_search_request,
             use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
             include_view_image_tool: config.include_view_image_tool,
+            include_search_past_sessions: config.tools_search_past_sessions,
+            include_memory_tools: config.tools_memory,
+            include_delegate_task: config.tools_delegate_task,
             experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
         });
         let turn_context = Arc::new(TurnContext {
@@ -3473,6 +4789,7 @@ mod tests {
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.resource_limits,
             tools_config,
             is_review_mode: false,
             final_output_json_schema: None,
@@ -3482,10 +4799,18 @@ mod tests {
             session_manager: ExecSessionManager::default(),
             unified_exec_manager: UnifiedExecSessionManager::default(),
             notifier: UserNotifier::default(),
+            hooks: HookRunner::default(),
+            delegate_tasks: DelegateTaskManager::new(
+                config.clone(),
+                AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key")),
+            ),
             rollout: Mutex::new(None),
             codex_linux_sandbox_exe: None,
+            codex_home: config.codex_home.clone(),
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            checkpoints: config.checkpoints,
+            exec_policy: ExecPolicy::default(),
         };
         let session = Arc::new(Session {
             conversation_id,
@@ -3526,6 +4851,81 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn mcp_calls_run_pre_tool_use_hooks_and_reject_command_rewrites() {
+        let (mut session, turn_context) = make_session_and_context();
+        session.services.hooks = HookRunner::new(crate::config_types::Hooks {
+            pre_tool_use: vec![crate::config_types::HookCommand {
+                command: vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    r#"echo '{"command": ["true"]}'"#.to_string(),
+                ],
+                timeout_ms: None,
+            }],
+            ..Default::default()
+        });
+
+        let response = handle_mcp_tool_call_with_hooks(
+            &session,
+            &turn_context,
+            "sub-1",
+            "call-1".to_string(),
+            "tickets__update".to_string(),
+            "tickets".to_string(),
+            "update".to_string(),
+            "{}".to_string(),
+        )
+        .await;
+
+        assert_eq!(
+            response,
+            ResponseInputItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "tickets__update call denied by hook: a hook returned a `command`, but tickets__update has no command to replace".to_string(),
+                    success: Some(false),
+                },
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn control_chars_written_to_an_unsandboxed_session_need_approval() {
+        let (session, mut turn_context) = make_session_and_context();
+        turn_context.approval_policy = AskForApproval::Never;
+        turn_context.sandbox_policy = SandboxPolicy::new_read_only_policy();
+
+        let input = "ls\u{7f}\u{7f}rm -rf ~\n";
+        let result = approve_pty_input(
+            &session,
+            &turn_context,
+            "sub-1",
+            "call-1",
+            SandboxType::None,
+            input,
+            &["ls\u{7f}\u{7f}rm -rf ~".to_string()],
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(FunctionCallError::RespondToModel(message)) if message.contains("control characters")
+        ));
+
+        let result = approve_pty_input(
+            &session,
+            &turn_context,
+            "sub-1",
+            "call-1",
+            SandboxType::LinuxSeccomp,
+            "\u{1b}[A\r",
+            &[],
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn abort_regular_task_emits_turn_aborted_only() {
         let (sess, tc, rx) = make_session_and_context_with_rx();
@@ -3732,6 +5132,7 @@ mod tests {
             env: HashMap::new(),
             with_escalated_permissions: Some(true),
             justification: Some("test".to_string()),
+            resource_limits: ResourceLimits::default(),
         };
 
         let params2 = ExecParams {
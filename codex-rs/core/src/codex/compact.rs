@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use super::Session;
 use super::TurnContext;
+use super::fall_back_to_next_provider;
 use super::get_last_assistant_message_from_turn;
 use crate::Prompt;
 use crate::client_common::ResponseEvent;
@@ -79,22 +80,16 @@ async fn run_compact_task_inner(
         ..Default::default()
     };
 
-    let max_retries = turn_context.client.get_provider().stream_max_retries();
     let mut retries = 0;
+    let mut fallbacks = turn_context.client.fallback_clients().into_iter();
+    let mut fallback_context: Option<TurnContext> = None;
 
-    let rollout_item = RolloutItem::TurnContext(TurnContextItem {
-        cwd: turn_context.cwd.clone(),
-        approval_policy: turn_context.approval_policy,
-        sandbox_policy: turn_context.sandbox_policy.clone(),
-        model: turn_context.client.get_model(),
-        effort: turn_context.client.get_reasoning_effort(),
-        summary: turn_context.client.get_reasoning_summary(),
-    });
-    sess.persist_rollout_items(&[rollout_item]).await;
+    persist_turn_context(&sess, turn_context.as_ref()).await;
 
     loop {
-        let attempt_result =
-            drain_to_completed(&sess, turn_context.as_ref(), &sub_id, &prompt).await;
+        let turn_context = fallback_context.as_ref().unwrap_or(turn_context.as_ref());
+        let max_retries = turn_context.client.get_provider().stream_max_retries();
+        let attempt_result = drain_to_completed(&sess, turn_context, &sub_id, &prompt).await;
 
         match attempt_result {
             Ok(()) => {
@@ -104,6 +99,21 @@ async fn run_compact_task_inner(
                 return;
             }
             Err(e) => {
+                if let Some(next) = fall_back_to_next_provider(
+                    &sess,
+                    &sub_id,
+                    turn_context,
+                    &e,
+                    retries >= max_retries,
+                    &mut fallbacks,
+                )
+                .await
+                {
+                    persist_turn_context(&sess, &next).await;
+                    fallback_context = Some(next);
+                    retries = 0;
+                    continue;
+                }
                 if retries < max_retries {
                     retries += 1;
                     let delay = backoff(retries);
@@ -151,6 +161,19 @@ async fn run_compact_task_inner(
     sess.send_event(event).await;
 }
 
+async fn persist_turn_context(sess: &Session, turn_context: &TurnContext) {
+    let rollout_item = RolloutItem::TurnContext(TurnContextItem {
+        cwd: turn_context.cwd.clone(),
+        approval_policy: turn_context.approval_policy,
+        sandbox_policy: turn_context.sandbox_policy.clone(),
+        model: turn_context.client.get_model(),
+        model_provider: Some(turn_context.client.get_model_provider_id()),
+        effort: turn_context.client.get_reasoning_effort(),
+        summary: turn_context.client.get_reasoning_summary(),
+    });
+    sess.persist_rollout_items(&[rollout_item]).await;
+}
+
 pub fn content_items_to_text(content: &[ContentItem]) -> Option<String> {
     let mut pieces = Vec::new();
     for item in content {
@@ -1,21 +1,25 @@
 use crate::config_profile::ConfigProfile;
 use crate::config_types::History;
+use crate::config_types::Hooks;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::config_types::ModelCapabilitiesToml;
 use crate::config_types::Notifications;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::ResourceLimits;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::git_info::resolve_root_git_project_for_trust;
+use crate::model_catalog::ModelCatalog;
+use crate::model_catalog::ModelCatalogEntry;
 use crate::model_family::ModelFamily;
 use crate::model_family::derive_default_model_family;
-use crate::model_family::find_family_for_model;
+use crate::model_provider_info::FallbackProvider;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
-use crate::openai_model_info::get_model_info;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 use anyhow::Context;
@@ -47,6 +51,14 @@ pub const GROK_CODE_FAST_MEDIUM_MODEL: &str = "grok-code-fast-1";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
+/// Maximum number of bytes of saved memory entries included in the user
+/// instructions.
+pub(crate) const MEMORY_MAX_BYTES: usize = 8 * 1024; // 8 KiB
+
+/// Number of `delegate_task` children that may run at the same time unless
+/// `tools.delegate_task_max_parallel` says otherwise.
+pub(crate) const DEFAULT_DELEGATE_TASK_MAX_PARALLEL: usize = 3;
+
 pub(crate) const CONFIG_TOML_FILE: &str = "config.toml";
 
 /// Application configuration loaded from disk and merged with overrides.
@@ -60,6 +72,10 @@ pub struct Config {
 
     pub model_family: ModelFamily,
 
+    /// Model capabilities from the `[models.<slug>]` table, used to resolve
+    /// the family and limits of models selected during the session.
+    pub model_catalog: ModelCatalog,
+
     /// Size of the context window for the model, in tokens.
     pub model_context_window: Option<u64>,
 
@@ -75,6 +91,10 @@ pub struct Config {
     /// Info needed to make an API request to the model.
     pub model_provider: ModelProviderInfo,
 
+    /// Providers (and optionally models) to retry a request with, in order,
+    /// when `model_provider` fails with a retryable error.
+    pub fallback_providers: Vec<FallbackProvider>,
+
     /// Approval policy for executing commands.
     pub approval_policy: AskForApproval,
 
@@ -82,6 +102,9 @@ pub struct Config {
 
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
+    /// Memory, CPU time, process and file size limits for every command.
+    pub resource_limits: ResourceLimits,
+
     /// When `true`, `AgentReasoning` events emitted by the backend will be
     /// suppressed from the frontend output. This can reduce visual noise when
     /// users are only interested in the final agent responses.
@@ -91,6 +114,10 @@ pub struct Config {
     /// Defaults to `false`.
     pub show_raw_agent_reasoning: bool,
 
+    /// When `true`, a ghost commit of the working tree is recorded at the
+    /// start of every turn so the user can return to it later.
+    pub checkpoints: bool,
+
     /// User-provided instructions from AGENTS.md.
     pub user_instructions: Option<String>,
 
@@ -119,6 +146,9 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Lifecycle hooks that can observe, veto or rewrite agent actions.
+    pub hooks: Hooks,
+
     /// TUI notifications preference. When set, the TUI will send OSC 9 notifications on approvals
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
@@ -131,12 +161,21 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Absolute paths to `codex-execpolicy` `.policy` files that are consulted
+    /// before every command the model asks to run. Global entries come first,
+    /// followed by those configured for the project containing `cwd`.
+    pub exec_policy_files: Vec<PathBuf>,
+
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
+    /// Maximum number of bytes of saved memory entries to include in the
+    /// instructions. `0` disables memory injection.
+    pub memory_max_bytes: usize,
+
     /// Directory containing all Codex state (defaults to `~/.codex` but can be
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
@@ -192,6 +231,21 @@ pub struct Config {
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
 
+    /// Include the `search_past_sessions` tool that lets the agent search the
+    /// messages of earlier sessions.
+    pub tools_search_past_sessions: bool,
+
+    /// Include the `remember` and `forget` tools that let the agent keep notes
+    /// in the memory files.
+    pub tools_memory: bool,
+
+    /// Include the `delegate_task` tool that lets the agent hand work to child
+    /// conversations.
+    pub tools_delegate_task: bool,
+
+    /// Maximum number of `delegate_task` children running at the same time.
+    pub delegate_task_max_parallel: usize,
+
     /// The active profile name used to derive this `Config` (if any).
     pub active_profile: Option<String>,
 
@@ -356,6 +410,23 @@ pub fn write_global_mcp_servers(
                 entry["tool_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
             }
 
+            if let Some(approval) = config.approval {
+                entry["approval"] = toml_edit::value(approval.as_str());
+            }
+
+            for (key, tools) in [
+                ("allowed_tools", &config.allowed_tools),
+                ("denied_tools", &config.denied_tools),
+            ] {
+                if !tools.is_empty() {
+                    let mut tools_array = TomlArray::new();
+                    for tool in tools {
+                        tools_array.push(tool.clone());
+                    }
+                    entry[key] = TomlItem::Value(tools_array.into());
+                }
+            }
+
             doc["mcp_servers"][name.as_str()] = TomlItem::Table(entry);
         }
     }
@@ -624,6 +695,10 @@ pub struct ConfigToml {
     /// Provider to use from the model_providers map.
     pub model_provider: Option<String>,
 
+    /// Providers to fall back to, in order, when `model_provider` is
+    /// unavailable. Entries are `"<provider_id>"` or `"<provider_id>/<model>"`.
+    pub fallback_providers: Option<Vec<String>>,
+
     /// Size of the context window for the model, in tokens.
     pub model_context_window: Option<u64>,
 
@@ -639,6 +714,9 @@ pub struct ConfigToml {
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
+    /// Limits applied to every command the agent runs.
+    pub resource_limits: Option<ResourceLimits>,
+
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
@@ -649,6 +727,13 @@ pub struct ConfigToml {
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// Commands to run at points in the agent's lifecycle.
+    pub hooks: Option<Hooks>,
+
+    /// `codex-execpolicy` `.policy` files used to auto-approve or forbid
+    /// commands. Relative paths are resolved against `CODEX_HOME`.
+    pub exec_policy_files: Option<Vec<PathBuf>>,
+
     /// System instructions.
     pub instructions: Option<String>,
 
@@ -660,9 +745,18 @@ pub struct ConfigToml {
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Per-model capabilities, keyed by model slug, layered over the
+    /// built-in model table.
+    #[serde(default)]
+    pub models: HashMap<String, ModelCapabilitiesToml>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
+    /// Maximum number of bytes of saved memory entries to include in the
+    /// instructions.
+    pub memory_max_bytes: Option<usize>,
+
     /// Profile to use from the `profiles` map.
     pub profile: Option<String>,
 
@@ -689,6 +783,10 @@ pub struct ConfigToml {
     /// Defaults to `false`.
     pub show_raw_agent_reasoning: Option<bool>,
 
+    /// Record a checkpoint of the working tree at the start of every turn.
+    /// Defaults to `false`.
+    pub checkpoints: Option<bool>,
+
     pub model_reasoning_effort: Option<ReasoningEffort>,
     pub model_reasoning_summary: Option<ReasoningSummary>,
     /// Optional verbosity control for GPT-5 models (Responses API `text.verbosity`).
@@ -747,6 +845,11 @@ impl From<ConfigToml> for UserSavedConfig {
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ProjectConfig {
     pub trust_level: Option<String>,
+
+    /// Project-specific `.policy` files, applied in addition to the global
+    /// `exec_policy_files`. Relative paths are resolved against the project
+    /// directory.
+    pub exec_policy_files: Option<Vec<PathBuf>>,
 }
 
 #[derive(Deserialize, Debug, Clone, Default, PartialEq)]
@@ -757,6 +860,25 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Enable the `search_past_sessions` tool that lets the agent search the
+    /// messages of earlier sessions.
+    #[serde(default)]
+    pub search_past_sessions: Option<bool>,
+
+    /// Enable the `remember` and `forget` tools that let the agent keep notes
+    /// in `.codex/memory.md` and `~/.codex/memory.md`.
+    #[serde(default)]
+    pub memory: Option<bool>,
+
+    /// Enable the `delegate_task` tool that lets the agent hand work to child
+    /// conversations.
+    #[serde(default)]
+    pub delegate_task: Option<bool>,
+
+    /// Maximum number of `delegate_task` children running at the same time.
+    #[serde(default)]
+    pub delegate_task_max_parallel: Option<usize>,
 }
 
 impl From<ToolsToml> for Tools {
@@ -780,11 +902,15 @@ impl ConfigToml {
                 Some(SandboxWorkspaceWrite {
                     writable_roots,
                     network_access,
+                    allowed_domains,
+                    deny_read,
                     exclude_tmpdir_env_var,
                     exclude_slash_tmp,
                 }) => SandboxPolicy::WorkspaceWrite {
                     writable_roots: writable_roots.clone(),
                     network_access: *network_access,
+                    allowed_domains: allowed_domains.clone(),
+                    deny_read: deny_read.clone(),
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
                 },
@@ -794,6 +920,43 @@ impl ConfigToml {
         }
     }
 
+    /// Resolve the exec policy files in effect for `resolved_cwd`: the global
+    /// list followed by the list configured for the enclosing project (or,
+    /// inside a git worktree, for the root git project).
+    fn resolve_exec_policy_files(&self, codex_home: &Path, resolved_cwd: &Path) -> Vec<PathBuf> {
+        let resolve = |base: &Path, paths: &[PathBuf]| -> Vec<PathBuf> {
+            paths
+                .iter()
+                .map(|p| {
+                    if p.is_relative() {
+                        base.join(p)
+                    } else {
+                        p.clone()
+                    }
+                })
+                .collect()
+        };
+
+        let mut files = resolve(
+            codex_home,
+            self.exec_policy_files.as_deref().unwrap_or_default(),
+        );
+
+        let projects = self.projects.as_ref();
+        let project_files = |path: &Path| {
+            projects
+                .and_then(|projects| projects.get(path.to_string_lossy().as_ref()))
+                .and_then(|project| project.exec_policy_files.as_deref())
+                .map(|paths| resolve(path, paths))
+        };
+        if let Some(project) = project_files(resolved_cwd).or_else(|| {
+            resolve_root_git_project_for_trust(resolved_cwd).and_then(|root| project_files(&root))
+        }) {
+            files.extend(project);
+        }
+        files
+    }
+
     pub fn is_cwd_trusted(&self, resolved_cwd: &Path) -> bool {
         let projects = self.projects.clone().unwrap_or_default();
 
@@ -907,6 +1070,27 @@ impl Config {
             None => ConfigProfile::default(),
         };
 
+        let resolved_cwd = {
+            use std::env;
+
+            match cwd {
+                None => {
+                    tracing::info!("cwd not set, using current dir");
+                    env::current_dir()?
+                }
+                Some(p) if p.is_absolute() => p,
+                Some(p) => {
+                    // Resolve relative path against the current working directory.
+                    tracing::info!("cwd is relative, resolving against current dir");
+                    let mut current = env::current_dir()?;
+                    current.push(p);
+                    current
+                }
+            }
+        };
+
+        let exec_policy_files = cfg.resolve_exec_policy_files(&codex_home, &resolved_cwd);
+
         let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
 
         let mut model_providers = built_in_model_providers();
@@ -929,26 +1113,33 @@ impl Config {
             })?
             .clone();
 
-        let shell_environment_policy = cfg.shell_environment_policy.into();
-
-        let resolved_cwd = {
-            use std::env;
+        let fallback_providers = config_profile
+            .fallback_providers
+            .or(cfg.fallback_providers)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| {
+                let (provider_id, model) = match entry.split_once('/') {
+                    Some((provider_id, model)) => {
+                        (provider_id.to_string(), Some(model.to_string()))
+                    }
+                    None => (entry, None),
+                };
+                let provider = model_providers.get(&provider_id).cloned().ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("Fallback model provider `{provider_id}` not found"),
+                    )
+                })?;
+                Ok(FallbackProvider {
+                    provider_id,
+                    provider,
+                    model,
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
 
-            match cwd {
-                None => {
-                    tracing::info!("cwd not set, using current dir");
-                    env::current_dir()?
-                }
-                Some(p) if p.is_absolute() => p,
-                Some(p) => {
-                    // Resolve relative path against the current working directory.
-                    tracing::info!("cwd is relative, resolving against current dir");
-                    let mut current = env::current_dir()?;
-                    current.push(p);
-                    current
-                }
-            }
-        };
+        let shell_environment_policy = cfg.shell_environment_policy.into();
 
         let history = cfg.history.unwrap_or_default();
 
@@ -960,13 +1151,56 @@ impl Config {
             .or(cfg.tools.as_ref().and_then(|t| t.view_image))
             .unwrap_or(true);
 
+        let tools_search_past_sessions = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.search_past_sessions)
+            .unwrap_or(false);
+
+        let tools_memory = cfg.tools.as_ref().and_then(|t| t.memory).unwrap_or(false);
+
+        let tools_delegate_task = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.delegate_task)
+            .unwrap_or(false);
+        let delegate_task_max_parallel = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.delegate_task_max_parallel)
+            .unwrap_or(DEFAULT_DELEGATE_TASK_MAX_PARALLEL)
+            .max(1);
+
         let model = model
             .or(config_profile.model)
             .or(cfg.model)
             .unwrap_or_else(default_model);
 
-        let mut model_family =
-            find_family_for_model(&model).unwrap_or_else(|| derive_default_model_family(&model));
+        let model_catalog = ModelCatalog::new(
+            cfg.models
+                .into_iter()
+                .map(|(slug, capabilities)| {
+                    let base_instructions_file = capabilities
+                        .base_instructions_file
+                        .as_ref()
+                        .map(|path| resolved_cwd.join(path));
+                    (
+                        slug,
+                        ModelCatalogEntry {
+                            capabilities,
+                            base_instructions_file,
+                        },
+                    )
+                })
+                .collect(),
+        );
+        // Other entries are only read when switched to, but a broken file for
+        // the configured model should fail loudly.
+        model_catalog.base_instructions(&model)?;
+
+        let mut model_family = model_catalog
+            .find_family(&model)
+            .unwrap_or_else(|| derive_default_model_family(&model));
 
         if let Some(supports_reasoning_summaries) = cfg.model_supports_reasoning_summaries {
             model_family.supports_reasoning_summaries = supports_reasoning_summaries;
@@ -975,14 +1209,16 @@ impl Config {
             model_family.reasoning_summary_format = model_reasoning_summary_format;
         }
 
-        let openai_model_info = get_model_info(&model_family);
-        let model_context_window = cfg
-            .model_context_window
-            .or_else(|| openai_model_info.as_ref().map(|info| info.context_window));
+        let openai_model_info = model_catalog.model_info(&model_family);
+        let model_context_window = cfg.model_context_window.or_else(|| {
+            openai_model_info
+                .as_ref()
+                .and_then(|info| info.context_window)
+        });
         let model_max_output_tokens = cfg.model_max_output_tokens.or_else(|| {
             openai_model_info
                 .as_ref()
-                .map(|info| info.max_output_tokens)
+                .and_then(|info| info.max_output_tokens)
         });
         let model_auto_compact_token_limit = cfg.model_auto_compact_token_limit.or_else(|| {
             openai_model_info
@@ -1010,11 +1246,13 @@ impl Config {
             model,
             review_model,
             model_family,
+            model_catalog,
             model_context_window,
             model_max_output_tokens,
             model_auto_compact_token_limit,
             model_provider_id,
             model_provider,
+            fallback_providers,
             cwd: resolved_cwd,
             approval_policy: approval_policy
                 .or(config_profile.approval_policy)
@@ -1022,12 +1260,16 @@ impl Config {
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
             shell_environment_policy,
+            resource_limits: cfg.resource_limits.unwrap_or_default(),
             notify: cfg.notify,
+            hooks: cfg.hooks.unwrap_or_default(),
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
+            exec_policy_files,
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
+            memory_max_bytes: cfg.memory_max_bytes.unwrap_or(MEMORY_MAX_BYTES),
             codex_home,
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
@@ -1038,6 +1280,7 @@ impl Config {
                 .show_raw_agent_reasoning
                 .or(show_raw_agent_reasoning)
                 .unwrap_or(false),
+            checkpoints: cfg.checkpoints.unwrap_or(false),
             model_reasoning_effort: config_profile
                 .model_reasoning_effort
                 .or(cfg.model_reasoning_effort),
@@ -1061,6 +1304,10 @@ impl Config {
                 .unwrap_or(false),
             use_experimental_use_rmcp_client: cfg.experimental_use_rmcp_client.unwrap_or(false),
             include_view_image_tool,
+            tools_search_past_sessions,
+            tools_memory,
+            tools_delegate_task,
+            delegate_task_max_parallel,
             active_profile: active_profile_name,
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
             tui_notifications: cfg
@@ -1178,7 +1425,11 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::HookCommand;
+    use crate::config_types::McpToolApproval;
     use crate::config_types::Notifications;
+    use crate::model_family::find_family_for_model;
+    use crate::tool_apply_patch::ApplyPatchToolType;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -1231,6 +1482,54 @@ persistence = "none"
         assert_eq!(tui.notifications, Notifications::Enabled(false));
     }
 
+    #[test]
+    fn hooks_config_parses_commands_per_event() {
+        let cfg = r#"
+[[hooks.pre_tool_use]]
+command = ["python3", "no_force_push.py"]
+
+[[hooks.post_tool_use]]
+command = ["cargo", "fmt"]
+timeout_ms = 30000
+"#;
+
+        let parsed = toml::from_str::<ConfigToml>(cfg).expect("hooks config should parse");
+        assert_eq!(
+            parsed.hooks,
+            Some(Hooks {
+                pre_tool_use: vec![HookCommand {
+                    command: vec!["python3".to_string(), "no_force_push.py".to_string()],
+                    timeout_ms: None,
+                }],
+                post_tool_use: vec![HookCommand {
+                    command: vec!["cargo".to_string(), "fmt".to_string()],
+                    timeout_ms: Some(30_000),
+                }],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn resource_limits_config_leaves_unset_limits_off() {
+        let cfg = r#"
+[resource_limits]
+memory_mb = 4096
+max_processes = 512
+"#;
+
+        let parsed =
+            toml::from_str::<ConfigToml>(cfg).expect("resource limits config should parse");
+        assert_eq!(
+            parsed.resource_limits,
+            Some(ResourceLimits {
+                memory_mb: Some(4096),
+                max_processes: Some(512),
+                ..Default::default()
+            })
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -1271,6 +1570,8 @@ writable_roots = [
 ]
 exclude_tmpdir_env_var = true
 exclude_slash_tmp = true
+allowed_domains = ["crates.io", "*.crates.io"]
+deny_read = ["~/.ssh", ".env*"]
 "#;
 
         let sandbox_workspace_write_cfg = toml::from_str::<ConfigToml>(sandbox_workspace_write)
@@ -1280,6 +1581,8 @@ exclude_slash_tmp = true
             SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![PathBuf::from("/my/workspace")],
                 network_access: false,
+                allowed_domains: vec!["crates.io".to_string(), "*.crates.io".to_string()],
+                deny_read: vec!["~/.ssh".to_string(), ".env*".to_string()],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             },
@@ -1312,6 +1615,9 @@ exclude_slash_tmp = true
                 },
                 startup_timeout_sec: Some(Duration::from_secs(3)),
                 tool_timeout_sec: Some(Duration::from_secs(5)),
+                approval: None,
+                allowed_tools: Vec::new(),
+                denied_tools: Vec::new(),
             },
         );
 
@@ -1361,6 +1667,158 @@ startup_timeout_ms = 2500
         Ok(())
     }
 
+    #[test]
+    fn exec_policy_files_combine_global_and_project_entries() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        let project_key = project.path().to_string_lossy().to_string();
+        let cfg: ConfigToml = toml::from_str(&format!(
+            r#"
+exec_policy_files = ["team.policy", "/etc/codex/site.policy"]
+
+[projects."{project_key}"]
+exec_policy_files = [".codex/project.policy"]
+"#
+        ))
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(project.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(
+            config.exec_policy_files,
+            vec![
+                codex_home.path().join("team.policy"),
+                PathBuf::from("/etc/codex/site.policy"),
+                project.path().join(".codex/project.policy"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn models_table_describes_selected_model() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cwd = TempDir::new()?;
+        std::fs::write(cwd.path().join("acme.md"), "You are Acme Coder.")?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+model = "acme-coder"
+
+[models.acme-coder]
+context_window = 64000
+auto_compact_token_limit = 48000
+apply_patch_tool_type = "function"
+base_instructions_file = "acme.md"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(cwd.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(config.model_context_window, Some(64_000));
+        assert_eq!(config.model_max_output_tokens, None);
+        assert_eq!(config.model_auto_compact_token_limit, Some(48_000));
+        assert_eq!(
+            config.model_family.apply_patch_tool_type,
+            Some(ApplyPatchToolType::Function)
+        );
+        assert_eq!(config.model_family.base_instructions, "You are Acme Coder.");
+        Ok(())
+    }
+
+    #[test]
+    fn models_table_reads_base_instructions_only_for_used_models() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cwd = TempDir::new()?;
+        let load = |model: &str| {
+            let cfg: ConfigToml = toml::from_str(&format!(
+                r#"
+model = "{model}"
+
+[models.other-coder]
+base_instructions_file = "missing.md"
+"#
+            ))
+            .expect("TOML deserialization should succeed");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides {
+                    cwd: Some(cwd.path().to_path_buf()),
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let config = load("acme-coder")?;
+        let other = config
+            .model_catalog
+            .find_family("other-coder")
+            .expect("catalog entry should yield a family");
+        assert_eq!(
+            other.base_instructions,
+            derive_default_model_family("other-coder").base_instructions
+        );
+
+        assert!(load("other-coder").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn profile_fallback_providers_resolve_models() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+fallback_providers = ["openai"]
+
+[profiles.grok]
+model_provider = "api_x"
+fallback_providers = ["openai/gpt-5", "oss"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg.clone(),
+            ConfigOverrides {
+                config_profile: Some("grok".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        let fallbacks: Vec<(&str, Option<&str>)> = config
+            .fallback_providers
+            .iter()
+            .map(|f| (f.provider_id.as_str(), f.model.as_deref()))
+            .collect();
+        assert_eq!(fallbacks, vec![("openai", Some("gpt-5")), ("oss", None)]);
+
+        let mut unknown = cfg;
+        unknown.fallback_providers = Some(vec!["nope".to_string()]);
+        let err = Config::load_from_base_config_with_overrides(
+            unknown,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect_err("unknown fallback provider");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        Ok(())
+    }
+
     #[test]
     fn write_global_mcp_servers_serializes_env_sorted() -> anyhow::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1378,6 +1836,9 @@ startup_timeout_ms = 2500
                 },
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                approval: None,
+                allowed_tools: Vec::new(),
+                denied_tools: Vec::new(),
             },
         )]);
 
@@ -1428,6 +1889,9 @@ ZIG_VAR = "3"
                 },
                 startup_timeout_sec: Some(Duration::from_secs(2)),
                 tool_timeout_sec: None,
+                approval: None,
+                allowed_tools: Vec::new(),
+                denied_tools: Vec::new(),
             },
         )]);
 
@@ -1464,6 +1928,9 @@ startup_timeout_sec = 2.0
                 },
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                approval: None,
+                allowed_tools: Vec::new(),
+                denied_tools: Vec::new(),
             },
         );
         write_global_mcp_servers(codex_home.path(), &servers)?;
@@ -1489,6 +1956,52 @@ url = "https://example.com/mcp"
         Ok(())
     }
 
+    #[test]
+    fn write_global_mcp_servers_serializes_tool_approval() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "docs-server".to_string(),
+                    args: Vec::new(),
+                    env: None,
+                },
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                approval: Some(McpToolApproval::OnWrite),
+                allowed_tools: vec!["search".to_string()],
+                denied_tools: vec!["delete_page".to_string(), "purge".to_string()],
+            },
+        )]);
+
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        let serialized = std::fs::read_to_string(&config_path)?;
+        assert_eq!(
+            serialized,
+            r#"[mcp_servers.docs]
+command = "docs-server"
+approval = "on-write"
+allowed_tools = ["search"]
+denied_tools = ["delete_page", "purge"]
+"#
+        );
+
+        let loaded = load_global_mcp_servers(codex_home.path())?;
+        let docs = loaded.get("docs").expect("docs entry");
+        assert_eq!(docs.approval, Some(McpToolApproval::OnWrite));
+        assert_eq!(docs.allowed_tools, vec!["search".to_string()]);
+        assert_eq!(
+            docs.denied_tools,
+            vec!["delete_page".to_string(), "purge".to_string()]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn persist_model_selection_updates_defaults() -> anyhow::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1718,6 +2231,10 @@ model_verbosity = "high"
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: Some(300_000),
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
         let model_provider_map = {
             let mut model_provider_map = built_in_model_providers();
@@ -1774,26 +2291,33 @@ model_verbosity = "high"
                 model: "o3".to_string(),
                 review_model: XAI_DEFAULT_REVIEW_MODEL.to_string(),
                 model_family: find_family_for_model("o3").expect("known model slug"),
+                model_catalog: ModelCatalog::default(),
                 model_context_window: Some(200_000),
                 model_max_output_tokens: Some(100_000),
                 model_auto_compact_token_limit: None,
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
+                fallback_providers: Vec::new(),
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
+                resource_limits: ResourceLimits::default(),
                 user_instructions: None,
                 notify: None,
+                hooks: Hooks::default(),
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
+                exec_policy_files: Vec::new(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+                memory_max_bytes: MEMORY_MAX_BYTES,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
                 show_raw_agent_reasoning: false,
+                checkpoints: false,
                 model_reasoning_effort: Some(ReasoningEffort::High),
                 model_reasoning_summary: ReasoningSummary::Detailed,
                 model_verbosity: None,
@@ -1806,6 +2330,10 @@ model_verbosity = "high"
                 use_experimental_unified_exec_tool: false,
                 use_experimental_use_rmcp_client: false,
                 include_view_image_tool: true,
+                tools_search_past_sessions: false,
+                tools_memory: false,
+                tools_delegate_task: false,
+                delegate_task_max_parallel: DEFAULT_DELEGATE_TASK_MAX_PARALLEL,
                 active_profile: Some("o3".to_string()),
                 disable_paste_burst: false,
                 tui_notifications: Default::default(),
@@ -1833,26 +2361,33 @@ model_verbosity = "high"
             model: "gpt-3.5-turbo".to_string(),
             review_model: XAI_DEFAULT_REVIEW_MODEL.to_string(),
             model_family: find_family_for_model("gpt-3.5-turbo").expect("known model slug"),
+            model_catalog: ModelCatalog::default(),
             model_context_window: Some(16_385),
             model_max_output_tokens: Some(4_096),
             model_auto_compact_token_limit: None,
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
+            fallback_providers: Vec::new(),
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
             user_instructions: None,
             notify: None,
+            hooks: Hooks::default(),
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            exec_policy_files: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            memory_max_bytes: MEMORY_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
+            checkpoints: false,
             model_reasoning_effort: None,
             model_reasoning_summary: ReasoningSummary::default(),
             model_verbosity: None,
@@ -1865,6 +2400,10 @@ model_verbosity = "high"
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            tools_search_past_sessions: false,
+            tools_memory: false,
+            tools_delegate_task: false,
+            delegate_task_max_parallel: DEFAULT_DELEGATE_TASK_MAX_PARALLEL,
             active_profile: Some("gpt3".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
@@ -1907,26 +2446,33 @@ model_verbosity = "high"
             model: "o3".to_string(),
             review_model: XAI_DEFAULT_REVIEW_MODEL.to_string(),
             model_family: find_family_for_model("o3").expect("known model slug"),
+            model_catalog: ModelCatalog::default(),
             model_context_window: Some(200_000),
             model_max_output_tokens: Some(100_000),
             model_auto_compact_token_limit: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            fallback_providers: Vec::new(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
             user_instructions: None,
             notify: None,
+            hooks: Hooks::default(),
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            exec_policy_files: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            memory_max_bytes: MEMORY_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
+            checkpoints: false,
             model_reasoning_effort: None,
             model_reasoning_summary: ReasoningSummary::default(),
             model_verbosity: None,
@@ -1939,6 +2485,10 @@ model_verbosity = "high"
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            tools_search_past_sessions: false,
+            tools_memory: false,
+            tools_delegate_task: false,
+            delegate_task_max_parallel: DEFAULT_DELEGATE_TASK_MAX_PARALLEL,
             active_profile: Some("alt_profile".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
@@ -1967,26 +2517,33 @@ model_verbosity = "high"
             model: "gpt-5".to_string(),
             review_model: XAI_DEFAULT_REVIEW_MODEL.to_string(),
             model_family: find_family_for_model("gpt-5").expect("known model slug"),
+            model_catalog: ModelCatalog::default(),
             model_context_window: Some(272_000),
             model_max_output_tokens: Some(128_000),
             model_auto_compact_token_limit: None,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
+            fallback_providers: Vec::new(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            resource_limits: ResourceLimits::default(),
             user_instructions: None,
             notify: None,
+            hooks: Hooks::default(),
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            exec_policy_files: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
+            memory_max_bytes: MEMORY_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
+            checkpoints: false,
             model_reasoning_effort: Some(ReasoningEffort::High),
             model_reasoning_summary: ReasoningSummary::Detailed,
             model_verbosity: Some(Verbosity::High),
@@ -1999,6 +2556,10 @@ model_verbosity = "high"
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            tools_search_past_sessions: false,
+            tools_memory: false,
+            tools_delegate_task: false,
+            delegate_task_max_parallel: DEFAULT_DELEGATE_TASK_MAX_PARALLEL,
             active_profile: Some("gpt5".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
@@ -14,6 +14,9 @@ pub struct ConfigProfile {
     /// The key in the `model_providers` map identifying the
     /// [`ModelProviderInfo`] to use.
     pub model_provider: Option<String>,
+    /// Providers to fall back to, in order, when `model_provider` is
+    /// unavailable. See [`crate::model_provider_info::FallbackProvider`].
+    pub fallback_providers: Option<Vec<String>>,
     pub approval_policy: Option<AskForApproval>,
     pub model_reasoning_effort: Option<ReasoningEffort>,
     pub model_reasoning_summary: Option<ReasoningSummary>,
@@ -3,6 +3,7 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use crate::tool_apply_patch::ApplyPatchToolType;
 use serde::Deserializer;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -29,6 +30,44 @@ pub struct McpServerConfig {
     /// Default timeout for MCP tool calls initiated via this server.
     #[serde(default, with = "option_duration_secs")]
     pub tool_timeout_sec: Option<Duration>,
+
+    /// When to ask the user before calling one of this server's tools. When
+    /// unset, this is `on-write`, or `never` under the `never` approval
+    /// policy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<McpToolApproval>,
+
+    /// Tools that run without asking, whatever `approval` says.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+
+    /// Tools that are never offered to the model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_tools: Vec<String>,
+}
+
+/// When Codex asks before calling the tools of an MCP server.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpToolApproval {
+    /// Ask before every call that was not approved for the session.
+    Always,
+    /// Run every call without asking.
+    Never,
+    /// Ask before calls to tools that do not declare themselves read-only
+    /// through the `readOnlyHint` annotation.
+    OnWrite,
+}
+
+impl McpToolApproval {
+    /// The value as written in `config.toml`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            McpToolApproval::Always => "always",
+            McpToolApproval::Never => "never",
+            McpToolApproval::OnWrite => "on-write",
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for McpServerConfig {
@@ -53,6 +92,13 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             startup_timeout_ms: Option<u64>,
             #[serde(default, with = "option_duration_secs")]
             tool_timeout_sec: Option<Duration>,
+
+            #[serde(default)]
+            approval: Option<McpToolApproval>,
+            #[serde(default)]
+            allowed_tools: Vec<String>,
+            #[serde(default)]
+            denied_tools: Vec<String>,
         }
 
         let raw = RawMcpServerConfig::deserialize(deserializer)?;
@@ -115,6 +161,9 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             transport,
             startup_timeout_sec,
             tool_timeout_sec: raw.tool_timeout_sec,
+            approval: raw.approval,
+            allowed_tools: raw.allowed_tools,
+            denied_tools: raw.denied_tools,
         })
     }
 }
@@ -241,12 +290,77 @@ pub struct Tui {
     pub notifications: Notifications,
 }
 
+/// Limits applied to every command the agent runs. Unset limits are not
+/// enforced.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// Memory the command may use, in MiB.
+    pub memory_mb: Option<u64>,
+
+    /// CPU time each process of the command may use, in seconds.
+    pub cpu_time_secs: Option<u64>,
+
+    /// Number of processes the command may run at once.
+    pub max_processes: Option<u64>,
+
+    /// Largest file the command may write, in MiB.
+    pub max_file_size_mb: Option<u64>,
+}
+
+/// Commands run at points in the agent's lifecycle. Each hook receives a JSON
+/// payload describing the event on stdin and may reply on stdout to change
+/// what happens next. Hooks for the same event run in the order listed.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Hooks {
+    /// Run once when a session starts or is resumed.
+    #[serde(default)]
+    pub session_start: Vec<HookCommand>,
+
+    /// Run before a user prompt is sent to the model.
+    #[serde(default)]
+    pub user_prompt_submit: Vec<HookCommand>,
+
+    /// Run before a tool call is executed.
+    #[serde(default)]
+    pub pre_tool_use: Vec<HookCommand>,
+
+    /// Run after a tool call has produced its output.
+    #[serde(default)]
+    pub post_tool_use: Vec<HookCommand>,
+
+    /// Run when the agent has finished processing a user submission.
+    #[serde(default)]
+    pub turn_complete: Vec<HookCommand>,
+
+    /// How many times `turn_complete` hooks may keep a task going with a
+    /// follow-up message. Defaults to 1.
+    #[serde(default)]
+    pub max_follow_ups: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct HookCommand {
+    /// Program and arguments to run.
+    pub command: Vec<String>,
+
+    /// Kill the hook if it runs longer than this. Defaults to 60 seconds.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
     #[serde(default)]
     pub writable_roots: Vec<PathBuf>,
     #[serde(default)]
     pub network_access: bool,
+    /// Domains reachable through the network proxy when `network_access` is
+    /// `false`.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Paths sandboxed commands may not read, such as `~/.ssh` or `.env*`.
+    #[serde(default)]
+    pub deny_read: Vec<String>,
     #[serde(default)]
     pub exclude_tmpdir_env_var: bool,
     #[serde(default)]
@@ -368,6 +482,64 @@ pub enum ReasoningSummaryFormat {
     Experimental,
 }
 
+/// Capabilities of a model declared under `[models.<slug>]`. Every field is
+/// optional; unset fields fall back to the built-in entry for the slug, if
+/// there is one.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModelCapabilitiesToml {
+    /// Size of the context window, in tokens.
+    pub context_window: Option<u64>,
+
+    /// Maximum number of output tokens.
+    pub max_output_tokens: Option<u64>,
+
+    /// Token usage threshold triggering auto-compaction of conversation history.
+    pub auto_compact_token_limit: Option<i64>,
+
+    /// Whether the Responses API `reasoning` parameter is accepted.
+    pub supports_reasoning_summaries: Option<bool>,
+
+    pub reasoning_summary_format: Option<ReasoningSummaryFormat>,
+
+    /// Whether the Chat Completions `reasoning_effort` parameter is accepted.
+    pub supports_reasoning_effort: Option<bool>,
+
+    /// Whether the Responses API `text.verbosity` parameter is accepted.
+    pub supports_verbosity: Option<bool>,
+
+    /// Offer `apply_patch` as a dedicated tool of this type instead of
+    /// describing it in the instructions.
+    pub apply_patch_tool_type: Option<ApplyPatchToolType>,
+
+    pub needs_special_apply_patch_instructions: Option<bool>,
+
+    /// Whether the model expects the native `local_shell` tool.
+    pub uses_local_shell_tool: Option<bool>,
+
+    /// File whose contents replace the built-in base instructions for this
+    /// model. Relative paths are resolved against the session cwd.
+    pub base_instructions_file: Option<PathBuf>,
+
+    /// Token prices used to report the cost of a session.
+    pub pricing: Option<ModelPricing>,
+}
+
+/// Prices of a model in USD per million tokens, declared under
+/// `[models.<slug>.pricing]`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+
+    /// Price of input tokens served from the prompt cache. Defaults to
+    /// `input`.
+    pub cached_input: Option<f64>,
+
+    pub output: f64,
+
+    /// Price of reasoning output tokens. Defaults to `output`.
+    pub reasoning: Option<f64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
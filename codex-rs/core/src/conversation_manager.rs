@@ -161,6 +161,18 @@ impl ConversationManager {
     }
 }
 
+/// Whether `item` is a message sent by the user, as opposed to the
+/// instructions and environment context recorded at the start of a session.
+/// These are the messages counted by [`ConversationManager::fork_conversation`].
+pub(crate) fn is_user_turn_message(item: &RolloutItem) -> bool {
+    if let RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. }) = item {
+        role == "user"
+            && content_items_to_text(content).is_some_and(|text| !is_session_prefix_message(&text))
+    } else {
+        false
+    }
+}
+
 /// Return a prefix of `items` obtained by cutting strictly before the nth user message
 /// (0-based) and all items that follow it.
 fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> InitialHistory {
@@ -170,10 +182,7 @@ fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> Initia
     // Find indices of user message inputs in rollout order.
     let mut user_positions: Vec<usize> = Vec::new();
     for (idx, item) in items.iter().enumerate() {
-        if let RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. }) = item
-            && role == "user"
-            && content_items_to_text(content).is_some_and(|text| !is_session_prefix_message(&text))
-        {
+        if is_user_turn_message(item) {
             user_positions.push(idx);
         }
     }
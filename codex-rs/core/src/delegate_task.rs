@@ -0,0 +1,539 @@
+//! The `delegate_task` tool. Each task runs in a child conversation created
+//! through a [`ConversationManager`], with its own instructions, tools and
+//! sandbox policy. The child's events are forwarded to the parent as
+//! `DelegateTaskProgress` events and its final message becomes the tool output.
+//! Children use the parent's approval policy; their approval requests are
+//! asked on the parent's event stream and the answer is sent back to them.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::LazyLock;
+
+use codex_protocol::mcp_protocol::ConversationId;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use futures::future::join_all;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::sync::Semaphore;
+
+use crate::AuthManager;
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::codex_conversation::CodexConversation;
+use crate::config::Config;
+use crate::conversation_manager::ConversationManager;
+use crate::conversation_manager::NewConversation;
+use crate::function_tool::FunctionCallError;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::ApplyPatchApprovalRequestEvent;
+use crate::protocol::DelegateTaskBeginEvent;
+use crate::protocol::DelegateTaskEndEvent;
+use crate::protocol::DelegateTaskProgressEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecApprovalRequestEvent;
+use crate::protocol::InputItem;
+use crate::protocol::Op;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::TaskCompleteEvent;
+
+pub(crate) const DELEGATE_TASK_TOOL_NAME: &str = "delegate_task";
+
+pub(crate) static DELEGATE_TASK_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let mut task_properties = BTreeMap::new();
+    task_properties.insert(
+        "task".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "What the worker should do. It does not see this conversation, so include every detail it needs."
+                    .to_string(),
+            ),
+        },
+    );
+    task_properties.insert(
+        "instructions".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Extra instructions for the worker, e.g. constraints or the format of its final message."
+                    .to_string(),
+            ),
+        },
+    );
+    task_properties.insert(
+        "tools".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some(
+                "Tools the worker may use besides the shell: apply_patch, web_search, view_image, update_plan. Defaults to the tools available to you."
+                    .to_string(),
+            ),
+        },
+    );
+    task_properties.insert(
+        "sandbox".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "One of: read-only, workspace-write. Defaults to your own sandbox and cannot be more permissive than it."
+                    .to_string(),
+            ),
+        },
+    );
+
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "tasks".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::Object {
+                properties: task_properties,
+                required: Some(vec!["task".to_string()]),
+                additional_properties: Some(false),
+            }),
+            description: Some("Independent tasks; they run in parallel.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: DELEGATE_TASK_TOOL_NAME.to_string(),
+        description: r#"Hands self-contained tasks to worker agents that run in parallel, each in a fresh conversation in the same working directory.
+Use it to split large changes into independent parts, e.g. one task per module. Tasks must not edit the same files.
+Returns the final message of each worker."#
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["tasks".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+});
+
+#[derive(Deserialize)]
+struct DelegateTaskArgs {
+    tasks: Vec<DelegatedTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DelegatedTask {
+    task: String,
+    #[serde(default)]
+    instructions: Option<String>,
+    #[serde(default)]
+    tools: Option<Vec<DelegateTool>>,
+    #[serde(default)]
+    sandbox: Option<DelegateSandbox>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DelegateTool {
+    ApplyPatch,
+    WebSearch,
+    ViewImage,
+    UpdatePlan,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum DelegateSandbox {
+    ReadOnly,
+    WorkspaceWrite,
+}
+
+/// Starts the child conversations for `delegate_task` calls and limits how
+/// many of them run at the same time.
+pub(crate) struct DelegateTaskManager {
+    config: Arc<Config>,
+    conversations: Arc<ConversationManager>,
+    permits: Arc<Semaphore>,
+    /// Held while a child's approval request is waiting for the user. The
+    /// parent keys pending approvals by its own submission id, so requests
+    /// from parallel children are asked one at a time.
+    approvals: Mutex<()>,
+}
+
+impl DelegateTaskManager {
+    pub(crate) fn new(config: Arc<Config>, auth_manager: Arc<AuthManager>) -> Self {
+        let permits = Arc::new(Semaphore::new(config.delegate_task_max_parallel));
+        Self {
+            config,
+            conversations: Arc::new(ConversationManager::new(auth_manager)),
+            permits,
+            approvals: Mutex::new(()),
+        }
+    }
+}
+
+/// Runs every task in the call's `tasks` list and returns their final
+/// messages. The future is boxed because the children are themselves
+/// sessions whose tasks could call back into this function.
+pub(crate) fn handle_delegate_task<'a>(
+    sess: &'a Session,
+    manager: &'a DelegateTaskManager,
+    turn_context: &'a TurnContext,
+    sub_id: &'a str,
+    call_id: &'a str,
+    arguments: String,
+) -> BoxFuture<'a, Result<String, FunctionCallError>> {
+    async move {
+        let args: DelegateTaskArgs = serde_json::from_str(&arguments).map_err(|e| {
+            FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e}"))
+        })?;
+        if args.tasks.is_empty() {
+            return Err(FunctionCallError::RespondToModel(
+                "tasks must not be empty".to_string(),
+            ));
+        }
+
+        let runs = args.tasks.into_iter().enumerate().map(|(index, task)| {
+            run_delegated_task(sess, manager, turn_context, sub_id, call_id, index, task)
+        });
+        let results = join_all(runs).await;
+
+        let sections: Vec<String> = results
+            .into_iter()
+            .enumerate()
+            .map(|(index, (task, result))| {
+                let number = index + 1;
+                match result {
+                    Ok(message) => format!("## Task {number}: {task}\n\n{message}"),
+                    Err(error) => format!("## Task {number}: {task}\n\nFailed: {error}"),
+                }
+            })
+            .collect();
+        Ok(sections.join("\n\n"))
+    }
+    .boxed()
+}
+
+/// Runs one task to completion, forwarding the child's events to the parent.
+/// Returns the task text alongside the child's final message.
+async fn run_delegated_task(
+    sess: &Session,
+    manager: &DelegateTaskManager,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    index: usize,
+    task: DelegatedTask,
+) -> (String, Result<String, String>) {
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::DelegateTaskBegin(DelegateTaskBeginEvent {
+            call_id: call_id.to_string(),
+            index,
+            task: task.task.clone(),
+        }),
+    })
+    .await;
+
+    let result = run_child(sess, manager, turn_context, sub_id, call_id, index, &task).await;
+
+    let (success, output) = match &result {
+        Ok(message) => (true, message.clone()),
+        Err(error) => (false, error.clone()),
+    };
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::DelegateTaskEnd(DelegateTaskEndEvent {
+            call_id: call_id.to_string(),
+            index,
+            success,
+            output,
+        }),
+    })
+    .await;
+
+    (task.task, result)
+}
+
+async fn run_child(
+    sess: &Session,
+    manager: &DelegateTaskManager,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    index: usize,
+    task: &DelegatedTask,
+) -> Result<String, String> {
+    let config = child_config(&manager.config, turn_context, task)?;
+    let _permit = manager
+        .permits
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let NewConversation {
+        conversation_id,
+        conversation,
+        ..
+    } = manager
+        .conversations
+        .new_conversation(config)
+        .await
+        .map_err(|e| format!("failed to start worker: {e}"))?;
+    let _child = ChildConversation {
+        conversations: manager.conversations.clone(),
+        conversation_id,
+        conversation: conversation.clone(),
+    };
+
+    conversation
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: task.task.clone(),
+            }],
+        })
+        .await
+        .map_err(|e| format!("failed to send task to worker: {e}"))?;
+
+    let mut last_error = None;
+    loop {
+        let event = conversation
+            .next_event()
+            .await
+            .map_err(|e| format!("worker stopped unexpectedly: {e}"))?;
+        match event.msg {
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                return last_agent_message.ok_or_else(|| {
+                    last_error.unwrap_or_else(|| "worker finished without a final message".into())
+                });
+            }
+            EventMsg::TurnAborted(_) | EventMsg::ShutdownComplete => {
+                return Err("worker was interrupted".to_string());
+            }
+            EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+                call_id: child_call_id,
+                command,
+                cwd,
+                reason,
+            }) => {
+                let decision = {
+                    let _approval = manager.approvals.lock().await;
+                    sess.request_command_approval(
+                        sub_id.to_string(),
+                        child_call_id,
+                        command,
+                        cwd,
+                        Some(worker_reason(index, reason)),
+                    )
+                    .await
+                };
+                conversation
+                    .submit(Op::ExecApproval {
+                        id: event.id,
+                        decision,
+                    })
+                    .await
+                    .map_err(|e| format!("failed to send approval to worker: {e}"))?;
+            }
+            EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+                call_id: child_call_id,
+                changes,
+                reason,
+                grant_root,
+            }) => {
+                let decision = {
+                    let _approval = manager.approvals.lock().await;
+                    sess.request_file_changes_approval(
+                        sub_id.to_string(),
+                        child_call_id,
+                        changes,
+                        Some(worker_reason(index, reason)),
+                        grant_root,
+                    )
+                    .await
+                    .await
+                    .unwrap_or_default()
+                };
+                conversation
+                    .submit(Op::PatchApproval {
+                        id: event.id,
+                        decision,
+                    })
+                    .await
+                    .map_err(|e| format!("failed to send approval to worker: {e}"))?;
+            }
+            msg if is_forwarded(&msg) => {
+                if let EventMsg::Error(error) = &msg {
+                    last_error = Some(error.message.clone());
+                }
+                sess.send_event(Event {
+                    id: sub_id.to_string(),
+                    msg: EventMsg::DelegateTaskProgress(DelegateTaskProgressEvent {
+                        call_id: call_id.to_string(),
+                        index,
+                        msg: Box::new(msg),
+                    }),
+                })
+                .await;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tells the user which worker is asking, since the request shows up in the
+/// parent's conversation.
+fn worker_reason(index: usize, reason: Option<String>) -> String {
+    let worker = format!("Delegated task {}", index + 1);
+    match reason {
+        Some(reason) => format!("{worker}: {reason}"),
+        None => worker,
+    }
+}
+
+/// Streaming deltas and bookkeeping events stay with the child; front-ends
+/// only need to see what the worker did.
+fn is_forwarded(msg: &EventMsg) -> bool {
+    matches!(
+        msg,
+        EventMsg::AgentMessage(_)
+            | EventMsg::ExecCommandBegin(_)
+            | EventMsg::ExecCommandEnd(_)
+            | EventMsg::PatchApplyBegin(_)
+            | EventMsg::PatchApplyEnd(_)
+            | EventMsg::WebSearchEnd(_)
+            | EventMsg::PlanUpdate(_)
+            | EventMsg::Error(_)
+            | EventMsg::StreamError(_)
+    )
+}
+
+/// Shuts the child down and forgets it once the task is over, including when
+/// the parent's turn is interrupted while the child is still running.
+struct ChildConversation {
+    conversations: Arc<ConversationManager>,
+    conversation_id: ConversationId,
+    conversation: Arc<CodexConversation>,
+}
+
+impl Drop for ChildConversation {
+    fn drop(&mut self) {
+        let conversations = self.conversations.clone();
+        let conversation_id = self.conversation_id;
+        let conversation = self.conversation.clone();
+        tokio::spawn(async move {
+            let _ = conversation.submit(Op::Shutdown).await;
+            conversations.remove_conversation(&conversation_id).await;
+        });
+    }
+}
+
+/// Workers run in the parent's working directory with the parent's model and
+/// approval policy, and get no MCP servers, memory tools or delegation.
+fn child_config(
+    parent: &Config,
+    turn_context: &TurnContext,
+    task: &DelegatedTask,
+) -> Result<Config, String> {
+    let mut config = parent.clone();
+    config.cwd = turn_context.cwd.clone();
+    config.model = turn_context.client.get_model();
+    config.model_family = turn_context.client.get_model_family();
+    config.approval_policy = turn_context.approval_policy;
+    config.sandbox_policy = child_sandbox_policy(&turn_context.sandbox_policy, task.sandbox)?;
+    config.user_instructions = match (&parent.user_instructions, &task.instructions) {
+        (Some(base), Some(extra)) => Some(format!("{base}\n\n{extra}")),
+        (None, Some(extra)) => Some(extra.clone()),
+        (base, None) => base.clone(),
+    };
+    config.mcp_servers.clear();
+    config.notify = None;
+    config.checkpoints = false;
+    config.tools_memory = false;
+    config.tools_search_past_sessions = false;
+    config.tools_delegate_task = false;
+
+    if let Some(tools) = &task.tools {
+        let apply_patch = tools.contains(&DelegateTool::ApplyPatch);
+        config.include_apply_patch_tool = apply_patch;
+        if !apply_patch {
+            config.model_family.apply_patch_tool_type = None;
+        }
+        config.tools_web_search_request &= tools.contains(&DelegateTool::WebSearch);
+        config.include_view_image_tool &= tools.contains(&DelegateTool::ViewImage);
+        config.include_plan_tool = tools.contains(&DelegateTool::UpdatePlan);
+    }
+    Ok(config)
+}
+
+fn child_sandbox_policy(
+    parent: &SandboxPolicy,
+    requested: Option<DelegateSandbox>,
+) -> Result<SandboxPolicy, String> {
+    match (requested, parent) {
+        (None, parent) => Ok(parent.clone()),
+        (Some(DelegateSandbox::ReadOnly), _) => Ok(SandboxPolicy::ReadOnly),
+        (Some(DelegateSandbox::WorkspaceWrite), SandboxPolicy::ReadOnly) => {
+            Err("workspace-write is more permissive than the current read-only sandbox".to_string())
+        }
+        (Some(DelegateSandbox::WorkspaceWrite), SandboxPolicy::WorkspaceWrite { .. }) => {
+            Ok(parent.clone())
+        }
+        (Some(DelegateSandbox::WorkspaceWrite), SandboxPolicy::DangerFullAccess) => {
+            Ok(SandboxPolicy::new_workspace_write_policy())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn arguments_parse_tools_and_sandbox() -> anyhow::Result<()> {
+        let args: DelegateTaskArgs = serde_json::from_str(
+            r#"{"tasks": [
+                {"task": "rename foo", "tools": ["apply_patch"], "sandbox": "workspace-write"},
+                {"task": "audit bar", "instructions": "do not edit files"}
+            ]}"#,
+        )?;
+        assert_eq!(args.tasks.len(), 2);
+        assert_eq!(args.tasks[0].tools, Some(vec![DelegateTool::ApplyPatch]));
+        assert_eq!(args.tasks[0].sandbox, Some(DelegateSandbox::WorkspaceWrite));
+        assert_eq!(
+            args.tasks[1].instructions.as_deref(),
+            Some("do not edit files")
+        );
+        assert!(
+            serde_json::from_str::<DelegateTaskArgs>(
+                r#"{"tasks": [{"task": "x", "sandbox": "danger-full-access"}]}"#
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn children_cannot_widen_the_parent_sandbox() {
+        let workspace_write = SandboxPolicy::new_workspace_write_policy();
+
+        assert_eq!(
+            child_sandbox_policy(&workspace_write, None),
+            Ok(workspace_write.clone())
+        );
+        assert_eq!(
+            child_sandbox_policy(&workspace_write, Some(DelegateSandbox::ReadOnly)),
+            Ok(SandboxPolicy::ReadOnly)
+        );
+        assert_eq!(
+            child_sandbox_policy(
+                &SandboxPolicy::DangerFullAccess,
+                Some(DelegateSandbox::WorkspaceWrite)
+            ),
+            Ok(workspace_write)
+        );
+        assert!(
+            child_sandbox_policy(
+                &SandboxPolicy::ReadOnly,
+                Some(DelegateSandbox::WorkspaceWrite)
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Resolution of the `deny_read` entries of a workspace-write sandbox policy
+//! into the paths that the Landlock and Seatbelt sandboxes keep from
+//! sandboxed commands.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use wildmatch::WildMatch;
+
+/// Resolves `deny_read` entries to the existing paths they name. `~` is the
+/// home directory, relative entries are resolved against `cwd`, and the last
+/// component may contain `*` and `?` wildcards. Unlike a .gitignore pattern,
+/// an entry only matches at the path it names: `.env*` matches `cwd/.env`
+/// but not `cwd/web/.env`. Paths are canonicalized so a
+/// symlink cannot be used to get around them; entries that match nothing are
+/// skipped.
+///
+/// Only paths that exist when this is called, i.e. when the command starts,
+/// are returned. A file created later that matches an entry, e.g. a new
+/// `.env.local` for `.env*`, is not denied to that command.
+pub fn resolve_deny_read_paths(patterns: &[String], cwd: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for pattern in patterns {
+        let Some(path) = expand_pattern(pattern, cwd) else {
+            continue;
+        };
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            continue;
+        };
+        let name = name.to_string_lossy();
+        if !name.contains(['*', '?']) {
+            paths.extend(path.canonicalize().ok());
+            continue;
+        }
+        let matcher = WildMatch::new(&name);
+        let Ok(entries) = std::fs::read_dir(parent) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if matcher.matches(&entry.file_name().to_string_lossy()) {
+                paths.extend(entry.path().canonicalize().ok());
+            }
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn expand_pattern(pattern: &str, cwd: &Path) -> Option<PathBuf> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return None;
+    }
+    if pattern == "~" {
+        return dirs::home_dir();
+    }
+    if let Some(rest) = pattern.strip_prefix("~/") {
+        return dirs::home_dir().map(|home| home.join(rest));
+    }
+    Some(cwd.join(pattern))
+}
+
+/// Returns the denied path that a failed command complains it could not
+/// access, so the model can be told the read policy blocked it.
+pub(crate) fn find_denied_path_in_output<'a>(
+    output: &str,
+    denied: &'a [PathBuf],
+    cwd: &Path,
+) -> Option<&'a Path> {
+    let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    let home = dirs::home_dir();
+    output
+        .lines()
+        .filter(|line| {
+            line.contains("Permission denied") || line.contains("Operation not permitted")
+        })
+        .find_map(|line| {
+            denied.iter().find(|path| {
+                spellings(path, &cwd, home.as_deref())
+                    .iter()
+                    .any(|spelling| line.contains(spelling.as_str()))
+            })
+        })
+        .map(PathBuf::as_path)
+}
+
+/// The ways a command's error message may spell `path`.
+fn spellings(path: &Path, cwd: &Path, home: Option<&Path>) -> Vec<String> {
+    let mut spellings = vec![path.to_string_lossy().to_string()];
+    if let Ok(relative) = path.strip_prefix(cwd)
+        && !relative.as_os_str().is_empty()
+    {
+        spellings.push(relative.to_string_lossy().to_string());
+    }
+    if let Some(relative) = home.and_then(|home| path.strip_prefix(home).ok()) {
+        spellings.push(format!("~/{}", relative.to_string_lossy()));
+    }
+    spellings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resolves_relative_globs_and_absolute_paths() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let cwd = tmp.path().canonicalize()?;
+        std::fs::write(cwd.join(".env"), "SECRET=1")?;
+        std::fs::write(cwd.join(".env.local"), "SECRET=2")?;
+        std::fs::write(cwd.join("main.rs"), "")?;
+        std::fs::create_dir(cwd.join("keys"))?;
+
+        let paths = resolve_deny_read_paths(
+            &[
+                ".env*".to_string(),
+                cwd.join("keys").to_string_lossy().to_string(),
+                "missing".to_string(),
+            ],
+            &cwd,
+        );
+        assert_eq!(
+            paths,
+            vec![cwd.join(".env"), cwd.join(".env.local"), cwd.join("keys")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn relative_globs_only_match_at_the_path_they_name() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let cwd = tmp.path().canonicalize()?;
+        std::fs::create_dir(cwd.join("web"))?;
+        std::fs::write(cwd.join("web").join(".env"), "SECRET=1")?;
+
+        assert_eq!(
+            resolve_deny_read_paths(&[".env*".to_string()], &cwd),
+            Vec::<PathBuf>::new()
+        );
+        assert_eq!(
+            resolve_deny_read_paths(&["web/.env*".to_string()], &cwd),
+            vec![cwd.join("web").join(".env")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn finds_denied_path_in_permission_errors() {
+        let cwd = PathBuf::from("/repo");
+        let denied = vec![PathBuf::from("/secrets"), PathBuf::from("/repo/.env")];
+
+        assert_eq!(
+            find_denied_path_in_output(
+                "cat: /secrets/id_ed25519: Permission denied\n",
+                &denied,
+                &cwd
+            ),
+            Some(Path::new("/secrets"))
+        );
+        assert_eq!(
+            find_denied_path_in_output("cat: .env: Permission denied\n", &denied, &cwd),
+            Some(Path::new("/repo/.env"))
+        );
+        assert_eq!(
+            find_denied_path_in_output(
+                "cat: /secrets/key: No such file or directory\n",
+                &denied,
+                &cwd
+            ),
+            None
+        );
+    }
+}
@@ -184,6 +184,8 @@ mod tests {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: writable_roots.into_iter().map(PathBuf::from).collect(),
             network_access,
+            allowed_domains: vec![],
+            deny_read: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         }
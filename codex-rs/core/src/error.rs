@@ -1,4 +1,5 @@
 use crate::exec::ExecToolCallOutput;
+use crate::resource_limits::ExceededLimit;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use codex_protocol::mcp_protocol::ConversationId;
@@ -39,6 +40,13 @@ pub enum SandboxErr {
     #[error("command was killed by a signal")]
     Signal(i32),
 
+    /// Command was killed for exceeding one of the configured resource limits
+    #[error("killed: exceeded {limit}")]
+    ResourceLimit {
+        limit: ExceededLimit,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Error from linux landlock
     #[error("Landlock was not able to fully enforce all sandbox rules")]
     LandlockRestrict,
@@ -15,16 +15,28 @@ use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 
+use crate::bwrap::BWRAP_EXECUTABLE;
+use crate::bwrap::create_bwrap_command_args;
+use crate::bwrap::spawn_command_under_bwrap;
+use crate::config_types::ResourceLimits;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::landlock::CODEX_LINUX_SANDBOX_ARG0;
+use crate::landlock::create_linux_sandbox_command_args;
 use crate::landlock::spawn_command_under_linux_sandbox;
+use crate::network_proxy::NetworkProxy;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::exceeded_limit;
+use crate::seatbelt::MACOS_PATH_TO_SEATBELT_EXECUTABLE;
+use crate::seatbelt::create_seatbelt_command_args;
 use crate::seatbelt::spawn_command_under_seatbelt;
+use crate::spawn::CODEX_SANDBOX_ENV_VAR;
+use crate::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 
@@ -53,6 +65,7 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    pub resource_limits: ResourceLimits,
 }
 
 impl ExecParams {
@@ -70,6 +83,9 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Only available on Linux; used when Landlock is not.
+    Bubblewrap,
 }
 
 #[derive(Clone)]
@@ -79,6 +95,112 @@ pub struct StdoutStream {
     pub tx_event: Sender<Event>,
 }
 
+/// A command wrapped for a [`SandboxType`], for callers that spawn it
+/// themselves rather than through [`process_exec_tool_call`], such as the PTY
+/// sessions of the interactive exec tools.
+#[derive(Debug)]
+pub(crate) struct SandboxedCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// The `argv[0]` the program must see, when it differs from `program`.
+    pub arg0: Option<String>,
+    pub cwd: PathBuf,
+    /// The complete environment of the command.
+    pub env: HashMap<String, String>,
+    pub sandbox_type: SandboxType,
+    /// Limits the spawner must apply; see [`crate::resource_limits`].
+    pub resource_limits: ResourceLimits,
+    /// Proxy serving the policy's `allowed_domains` under Landlock. It must
+    /// outlive the command.
+    pub network_proxy: Option<NetworkProxy>,
+}
+
+/// Wraps `command` the way [`process_exec_tool_call`] would for
+/// `sandbox_type`, starting the `allowed_domains` proxy when the command
+/// needs one. Requests through the proxy are reported to `events`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_sandboxed_command(
+    command: Vec<String>,
+    cwd: PathBuf,
+    mut env: HashMap<String, String>,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_cwd: &Path,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    resource_limits: ResourceLimits,
+    events: Option<StdoutStream>,
+) -> Result<SandboxedCommand> {
+    if command.is_empty() {
+        return Err(CodexErr::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "command args are empty",
+        )));
+    }
+    if !sandbox_policy.has_full_network_access() {
+        env.insert(
+            CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR.to_string(),
+            "1".to_string(),
+        );
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+    let mut network_proxy = None;
+    let (program, args, arg0) = match sandbox_type {
+        SandboxType::None => {
+            let mut command = command;
+            let program = PathBuf::from(command.remove(0));
+            (program, command, None)
+        }
+        SandboxType::MacosSeatbelt => {
+            env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
+            (
+                PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE),
+                create_seatbelt_command_args(command, sandbox_policy, sandbox_cwd),
+                None,
+            )
+        }
+        SandboxType::LinuxSeccomp => {
+            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                .as_ref()
+                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            #[cfg(target_os = "linux")]
+            if !sandbox_policy.allowed_domains().is_empty() {
+                network_proxy = Some(NetworkProxy::start(
+                    sandbox_policy.allowed_domains(),
+                    events,
+                )?);
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = events;
+            (
+                codex_linux_sandbox_exe.clone(),
+                create_linux_sandbox_command_args(
+                    command,
+                    sandbox_policy,
+                    sandbox_cwd,
+                    network_proxy.as_ref().map(NetworkProxy::socket_path),
+                ),
+                Some(CODEX_LINUX_SANDBOX_ARG0.to_string()),
+            )
+        }
+        SandboxType::Bubblewrap => (
+            PathBuf::from(BWRAP_EXECUTABLE),
+            create_bwrap_command_args(command, &cwd, sandbox_policy, sandbox_cwd),
+            None,
+        ),
+    };
+    Ok(SandboxedCommand {
+        program,
+        args,
+        arg0,
+        cwd,
+        env,
+        sandbox_type,
+        resource_limits,
+        network_proxy,
+    })
+}
+
 pub async fn process_exec_tool_call(
     params: ExecParams,
     sandbox_type: SandboxType,
@@ -90,6 +212,7 @@ pub async fn process_exec_tool_call(
     let start = Instant::now();
 
     let timeout_duration = params.timeout_duration();
+    let resource_limits = params.resource_limits;
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
@@ -106,6 +229,26 @@ pub async fn process_exec_tool_call(
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                &resource_limits,
+                StdioPolicy::RedirectForShellTool,
+                env,
+            )
+            .await?;
+            consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await
+        }
+        SandboxType::Bubblewrap => {
+            let ExecParams {
+                command,
+                cwd: command_cwd,
+                env,
+                ..
+            } = params;
+            let child = spawn_command_under_bwrap(
+                command,
+                command_cwd,
+                sandbox_policy,
+                sandbox_cwd,
+                &resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
@@ -123,12 +266,28 @@ pub async fn process_exec_tool_call(
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+
+            // Kept alive until the command exits.
+            #[cfg(target_os = "linux")]
+            let network_proxy = match sandbox_policy.allowed_domains() {
+                [] => None,
+                allowed_domains => {
+                    Some(NetworkProxy::start(allowed_domains, stdout_stream.clone())?)
+                }
+            };
+            #[cfg(target_os = "linux")]
+            let network_proxy_socket = network_proxy.as_ref().map(NetworkProxy::socket_path);
+            #[cfg(not(target_os = "linux"))]
+            let network_proxy_socket = None;
+
             let child = spawn_command_under_linux_sandbox(
                 codex_linux_sandbox_exe,
                 command,
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                network_proxy_socket,
+                &resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
@@ -140,6 +299,28 @@ pub async fn process_exec_tool_call(
     let duration = start.elapsed();
     match raw_output_result {
         Ok(raw_output) => {
+            if !raw_output.timed_out
+                && !raw_output.interrupted
+                && let Some(limit) = exceeded_limit(
+                    &resource_limits,
+                    raw_output.exit_status,
+                    raw_output.oom_killed,
+                )
+            {
+                let exec_output = ExecToolCallOutput {
+                    exit_code: raw_output.exit_status.code().unwrap_or(-1),
+                    stdout: raw_output.stdout.from_utf8_lossy(),
+                    stderr: raw_output.stderr.from_utf8_lossy(),
+                    aggregated_output: raw_output.aggregated_output.from_utf8_lossy(),
+                    duration,
+                    timed_out: false,
+                };
+                return Err(CodexErr::Sandbox(SandboxErr::ResourceLimit {
+                    limit,
+                    output: Box::new(exec_output),
+                }));
+            }
+
             #[allow(unused_mut)]
             let mut timed_out = raw_output.timed_out;
 
@@ -224,6 +405,10 @@ struct RawExecToolCallOutput {
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
     pub timed_out: bool,
+    /// The command was killed because Codex was interrupted.
+    pub interrupted: bool,
+    /// The command's systemd scope was stopped by an OOM kill.
+    pub oom_killed: bool,
 }
 
 impl StreamOutput<String> {
@@ -266,7 +451,11 @@ async fn exec(
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.timeout_duration();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        resource_limits,
+        ..
     } = params;
 
     let (program, args) = command.split_first().ok_or_else(|| {
@@ -282,6 +471,7 @@ async fn exec(
         arg0,
         cwd,
         sandbox_policy,
+        &resource_limits,
         StdioPolicy::RedirectForShellTool,
         env,
     )
@@ -296,6 +486,8 @@ async fn consume_truncated_output(
     timeout: Duration,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
+    let pid = child.id();
+
     // Both stdout and stderr were configured with `Stdio::piped()`
     // above, therefore `take()` should normally return `Some`.  If it doesn't
     // we treat it as an exceptional I/O error
@@ -326,27 +518,29 @@ async fn consume_truncated_output(
         Some(agg_tx.clone()),
     ));
 
-    let (exit_status, timed_out) = tokio::select! {
+    let (exit_status, timed_out, interrupted) = tokio::select! {
         result = tokio::time::timeout(timeout, child.wait()) => {
             match result {
                 Ok(status_result) => {
                     let exit_status = status_result?;
-                    (exit_status, false)
+                    (exit_status, false, false)
                 }
                 Err(_) => {
                     // timeout
                     child.start_kill()?;
                     // Debatable whether `child.wait().await` should be called here.
-                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true)
+                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true, false)
                 }
             }
         }
         _ = tokio::signal::ctrl_c() => {
             child.start_kill()?;
-            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false)
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), false, true)
         }
     };
 
+    let failed = !exit_status.success() && !timed_out && !interrupted;
+    let oom_killed = crate::resource_limits::scope_oom_killed(pid, failed).await;
     let stdout = stdout_handle.await??;
     let stderr = stderr_handle.await??;
 
@@ -367,6 +561,8 @@ async fn consume_truncated_output(
         stderr,
         aggregated_output,
         timed_out,
+        interrupted,
+        oom_killed,
     })
 }
 
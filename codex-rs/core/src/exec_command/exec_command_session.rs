@@ -1,9 +1,32 @@
+use std::ffi::OsStr;
+use std::io;
+#[cfg(unix)]
+use std::os::unix::fs::symlink;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Mutex as StdMutex;
+use std::sync::OnceLock;
 
+#[cfg(not(unix))]
+use portable_pty::CommandBuilder;
+use portable_pty::MasterPty;
+use portable_pty::PtyPair;
+use tempfile::TempDir;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
+use crate::exec::SandboxType;
+use crate::exec::SandboxedCommand;
+use crate::network_proxy::NetworkProxy;
+
+/// A process spawned on a pty.
+pub(crate) type PtyChild = Box<dyn portable_pty::Child + Send + Sync>;
+
+/// Control characters after which a shell discards the line typed so far:
+/// Ctrl-C and Ctrl-U.
+const LINE_DISCARDING_CHARS: [char; 2] = ['\u{3}', '\u{15}'];
+
 #[derive(Debug)]
 pub(crate) struct ExecCommandSession {
     /// Queue for writing bytes to the process stdin (PTY master write side).
@@ -27,9 +50,22 @@ pub(crate) struct ExecCommandSession {
 
     /// Tracks whether the underlying process has exited.
     exit_status: std::sync::Arc<std::sync::atomic::AtomicBool>,
+
+    /// Sandbox the process was started under; input written later runs in it
+    /// too.
+    sandbox_type: SandboxType,
+
+    /// Input written since the last newline, i.e. the command line being
+    /// typed.
+    pending_input: StdMutex<String>,
+
+    /// Proxy serving the session's `allowed_domains`; it stops when the
+    /// session is dropped.
+    _network_proxy: Option<NetworkProxy>,
 }
 
 impl ExecCommandSession {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         writer_tx: mpsc::Sender<Vec<u8>>,
         output_tx: broadcast::Sender<Vec<u8>>,
@@ -38,6 +74,8 @@ impl ExecCommandSession {
         writer_handle: JoinHandle<()>,
         wait_handle: JoinHandle<()>,
         exit_status: std::sync::Arc<std::sync::atomic::AtomicBool>,
+        sandbox_type: SandboxType,
+        network_proxy: Option<NetworkProxy>,
     ) -> (Self, broadcast::Receiver<Vec<u8>>) {
         let initial_output_rx = output_tx.subscribe();
         (
@@ -49,6 +87,9 @@ impl ExecCommandSession {
                 writer_handle: StdMutex::new(Some(writer_handle)),
                 wait_handle: StdMutex::new(Some(wait_handle)),
                 exit_status,
+                sandbox_type,
+                pending_input: StdMutex::new(String::new()),
+                _network_proxy: network_proxy,
             },
             initial_output_rx,
         )
@@ -65,6 +106,209 @@ impl ExecCommandSession {
     pub(crate) fn has_exited(&self) -> bool {
         self.exit_status.load(std::sync::atomic::Ordering::SeqCst)
     }
+
+    pub(crate) fn sandbox_type(&self) -> SandboxType {
+        self.sandbox_type
+    }
+
+    /// The command lines that writing `input` would submit.
+    pub(crate) fn submitted_lines(&self, input: &str) -> Vec<String> {
+        let pending = self
+            .pending_input
+            .lock()
+            .map(|pending| pending.clone())
+            .unwrap_or_default();
+        split_submitted_lines(&pending, input).0
+    }
+
+    /// Records that `input` was written to the process.
+    pub(crate) fn record_input(&self, input: &str) {
+        if let Ok(mut pending) = self.pending_input.lock() {
+            *pending = split_submitted_lines(&pending, input).1;
+        }
+    }
+}
+
+/// Spawns `command` on the slave side of `pair`, placed under its
+/// `resource_limits` like [`crate::spawn::spawn_child_async`] does: the
+/// rlimits are set between fork and exec, and on Linux the command runs in a
+/// systemd scope when one is available.
+///
+/// Returns the child and the master side of the pty. `command.network_proxy`
+/// is left for the caller to keep alive.
+#[cfg(unix)]
+pub(crate) async fn spawn_pty_command(
+    pair: PtyPair,
+    command: &SandboxedCommand,
+) -> anyhow::Result<(PtyChild, Box<dyn MasterPty + Send>)> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::process::CommandExt;
+
+    let program = match &command.arg0 {
+        Some(arg0) => program_alias(&command.program, arg0)?,
+        None => command.program.clone(),
+    };
+    let mut args = command.args.clone();
+    let resource_limits = command.resource_limits;
+
+    // The alias gives the program its arg0 as file name, so it keeps it
+    // when started through `systemd-run`.
+    #[cfg(target_os = "linux")]
+    let systemd_scope = crate::resource_limits::systemd_scope(&resource_limits)
+        .await
+        .map(|scope| scope.prefix);
+    #[cfg(not(target_os = "linux"))]
+    let systemd_scope: Option<Vec<String>> = None;
+    let in_systemd_scope = systemd_scope.is_some();
+    let program = match systemd_scope {
+        Some(mut prefix) => {
+            let wrapper = PathBuf::from(prefix.remove(0));
+            prefix.push(program.to_string_lossy().to_string());
+            prefix.append(&mut args);
+            args = prefix;
+            wrapper
+        }
+        None => program,
+    };
+
+    let tty_name = pair
+        .master
+        .tty_name()
+        .ok_or_else(|| anyhow::anyhow!("the pty has no slave device"))?;
+    let slave = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(tty_name)?;
+
+    let mut cmd = std::process::Command::new(&program);
+    cmd.args(args)
+        .current_dir(&command.cwd)
+        .env_clear()
+        .envs(&command.env)
+        .stdin(slave.try_clone()?)
+        .stdout(slave.try_clone()?)
+        .stderr(slave);
+    unsafe {
+        cmd.pre_exec(move || {
+            // What `portable_pty` does for the commands it spawns: reset the
+            // signal dispositions, then make the pty the controlling terminal
+            // of a new session.
+            for signo in [
+                libc::SIGCHLD,
+                libc::SIGHUP,
+                libc::SIGINT,
+                libc::SIGQUIT,
+                libc::SIGTERM,
+                libc::SIGALRM,
+            ] {
+                libc::signal(signo, libc::SIG_DFL);
+            }
+            let empty_set: libc::sigset_t = std::mem::zeroed();
+            libc::sigprocmask(libc::SIG_SETMASK, &empty_set, std::ptr::null_mut());
+            if libc::setsid() == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            crate::resource_limits::set_rlimits(&resource_limits, in_systemd_scope)
+        });
+    }
+    let child = cmd.spawn()?;
+    Ok((Box::new(child), pair.master))
+}
+
+/// Spawns `command` on the slave side of `pair` and returns the child and the
+/// master side of the pty. Resource limits are only enforced on unix.
+#[cfg(not(unix))]
+pub(crate) async fn spawn_pty_command(
+    pair: PtyPair,
+    command: &SandboxedCommand,
+) -> anyhow::Result<(PtyChild, Box<dyn MasterPty + Send>)> {
+    let program = match &command.arg0 {
+        Some(arg0) => program_alias(&command.program, arg0)?,
+        None => command.program.clone(),
+    };
+    let mut command_builder = CommandBuilder::new(program);
+    command_builder.args(&command.args);
+    command_builder.cwd(&command.cwd);
+    command_builder.env_clear();
+    for (key, value) in &command.env {
+        command_builder.env(key, value);
+    }
+    let child = pair.slave.spawn_command(command_builder)?;
+    Ok((child, pair.master))
+}
+
+/// Returns a path to `program` whose file name is `arg0`.
+///
+/// `systemd-run` and `portable_pty` pass the program path as `argv[0]` and
+/// cannot set it separately, so a program that dispatches on `argv[0]` is
+/// started through a symlink named `arg0`. The symlinks live in a directory that is kept for
+/// the life of the process.
+fn program_alias(program: &Path, arg0: &str) -> io::Result<PathBuf> {
+    if program.file_name() == Some(OsStr::new(arg0)) {
+        return Ok(program.to_path_buf());
+    }
+    if cfg!(not(unix)) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot start {} as `{arg0}`", program.display()),
+        ));
+    }
+
+    static ALIAS_DIR: OnceLock<Option<TempDir>> = OnceLock::new();
+    let Some(alias_dir) = ALIAS_DIR.get_or_init(|| TempDir::new().ok()) else {
+        return Err(io::Error::other(
+            "failed to create a directory for program aliases",
+        ));
+    };
+    let alias = alias_dir.path().join(arg0);
+    match std::fs::read_link(&alias) {
+        Ok(target) if target == program => return Ok(alias),
+        Ok(_) => std::fs::remove_file(&alias)?,
+        Err(_) => {}
+    }
+    #[cfg(unix)]
+    match symlink(program, &alias) {
+        // Another session created the same alias concurrently.
+        Err(e)
+            if e.kind() == io::ErrorKind::AlreadyExists
+                && std::fs::read_link(&alias).is_ok_and(|target| target == program) => {}
+        result => result?,
+    }
+    Ok(alias)
+}
+
+/// Whether `input` contains control characters or escape sequences other
+/// than newlines and the ones [`split_submitted_lines`] models. Backspace,
+/// Tab completion or arrow-key history can make the shell run a line other
+/// than the one the input appears to submit.
+pub(crate) fn has_unmodeled_control_chars(input: &str) -> bool {
+    input
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r') && !LINE_DISCARDING_CHARS.contains(&c))
+}
+
+/// Splits `input` typed after `pending` into the non-empty lines it submits
+/// and the input left pending afterwards.
+fn split_submitted_lines(pending: &str, input: &str) -> (Vec<String>, String) {
+    let mut lines = Vec::new();
+    let mut line = pending.to_string();
+    for c in input.chars() {
+        match c {
+            '\n' | '\r' => {
+                let submitted = std::mem::take(&mut line);
+                if !submitted.trim().is_empty() {
+                    lines.push(submitted);
+                }
+            }
+            c if LINE_DISCARDING_CHARS.contains(&c) => line.clear(),
+            c => line.push(c),
+        }
+    }
+    (lines, line)
 }
 
 impl Drop for ExecCommandSession {
@@ -94,3 +338,44 @@ impl Drop for ExecCommandSession {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn splits_submitted_lines_across_writes() {
+        assert_eq!(
+            split_submitted_lines("", "ls -la\ncargo"),
+            (vec!["ls -la".to_string()], "cargo".to_string())
+        );
+        assert_eq!(
+            split_submitted_lines("cargo", " test\r\n\n"),
+            (vec!["cargo test".to_string()], String::new())
+        );
+        assert_eq!(
+            split_submitted_lines("rm -rf /", "\u{3}echo hi\n"),
+            (vec!["echo hi".to_string()], String::new())
+        );
+    }
+
+    #[test]
+    fn flags_control_chars_that_change_the_submitted_line() {
+        assert!(!has_unmodeled_control_chars("ls -la\r\n"));
+        assert!(!has_unmodeled_control_chars("\u{3}echo hi\n"));
+        assert!(has_unmodeled_control_chars("ls\u{7f}\u{7f}rm -rf ~\n"));
+        assert!(has_unmodeled_control_chars("\u{1b}[A\r"));
+        assert!(has_unmodeled_control_chars("rm -rf /tm\t\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sandbox_helper_is_started_under_its_arg0() -> io::Result<()> {
+        let argv0 = program_alias(Path::new("/bin/sh"), "codex-linux-sandbox")?;
+
+        assert_eq!(argv0.file_name(), Some(OsStr::new("codex-linux-sandbox")));
+        assert_eq!(std::fs::read_link(&argv0)?, PathBuf::from("/bin/sh"));
+        Ok(())
+    }
+}
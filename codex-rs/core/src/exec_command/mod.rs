@@ -7,6 +7,8 @@ mod session_manager;
 pub use exec_command_params::ExecCommandParams;
 pub use exec_command_params::WriteStdinParams;
 pub(crate) use exec_command_session::ExecCommandSession;
+pub(crate) use exec_command_session::has_unmodeled_control_chars;
+pub(crate) use exec_command_session::spawn_pty_command;
 pub use responses_api::EXEC_COMMAND_TOOL_NAME;
 pub use responses_api::WRITE_STDIN_TOOL_NAME;
 pub use responses_api::create_exec_command_tool_for_responses_api;
@@ -6,7 +6,6 @@ use std::sync::Mutex as StdMutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU32;
 
-use portable_pty::CommandBuilder;
 use portable_pty::PtySize;
 use portable_pty::native_pty_system;
 use tokio::sync::Mutex;
@@ -16,9 +15,14 @@ use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio::time::timeout;
 
+use crate::exec::ExecToolCallOutput;
+use crate::exec::SandboxType;
+use crate::exec::SandboxedCommand;
+use crate::exec::StreamOutput;
 use crate::exec_command::exec_command_params::ExecCommandParams;
 use crate::exec_command::exec_command_params::WriteStdinParams;
 use crate::exec_command::exec_command_session::ExecCommandSession;
+use crate::exec_command::exec_command_session::spawn_pty_command;
 use crate::exec_command::session_id::SessionId;
 use crate::truncate::truncate_middle;
 
@@ -59,6 +63,23 @@ Output:
             output = self.output
         )
     }
+
+    /// The output as reported in `ExecCommandEnd` events. A session that is
+    /// still running reports exit code 0.
+    pub(crate) fn to_exec_tool_call_output(&self) -> ExecToolCallOutput {
+        let exit_code = match self.exit_status {
+            ExitStatus::Exited(code) => code,
+            ExitStatus::Ongoing(_) => 0,
+        };
+        ExecToolCallOutput {
+            exit_code,
+            stdout: StreamOutput::new(self.output.clone()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(self.output.clone()),
+            duration: self.wall_time,
+            timed_out: false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -69,9 +90,11 @@ pub enum ExitStatus {
 
 impl SessionManager {
     /// Processes the request and is required to send a response via `outgoing`.
-    pub async fn handle_exec_command_request(
+    /// `command` is `params.command()` wrapped for its sandbox.
+    pub(crate) async fn handle_exec_command_request(
         &self,
         params: ExecCommandParams,
+        command: SandboxedCommand,
     ) -> Result<ExecCommandOutput, String> {
         // Allocate a session id.
         let session_id = SessionId(
@@ -79,9 +102,8 @@ impl SessionManager {
                 .fetch_add(1, std::sync::atomic::Ordering::SeqCst),
         );
 
-        let (session, mut output_rx, mut exit_rx) = create_exec_command_session(params.clone())
-            .await
-            .map_err(|err| {
+        let (session, mut output_rx, mut exit_rx) =
+            create_exec_command_session(command).await.map_err(|err| {
                 format!(
                     "failed to create exec command session for session id {}: {err}",
                     session_id.0
@@ -161,6 +183,20 @@ impl SessionManager {
         })
     }
 
+    /// The sandbox of session `session_id` and the command lines that writing
+    /// `input` to it would submit.
+    pub(crate) async fn submitted_lines(
+        &self,
+        session_id: SessionId,
+        input: &str,
+    ) -> Result<(SandboxType, Vec<String>), String> {
+        let sessions = self.sessions.lock().await;
+        match sessions.get(&session_id) {
+            Some(session) => Ok((session.sandbox_type(), session.submitted_lines(input))),
+            None => Err(format!("unknown session id {}", session_id.0)),
+        }
+    }
+
     /// Write characters to a session's stdin and collect combined output for up to `yield_time_ms`.
     pub async fn handle_write_stdin_request(
         &self,
@@ -177,7 +213,10 @@ impl SessionManager {
         let (writer_tx, mut output_rx) = {
             let sessions = self.sessions.lock().await;
             match sessions.get(&session_id) {
-                Some(session) => (session.writer_sender(), session.output_receiver()),
+                Some(session) => {
+                    session.record_input(&chars);
+                    (session.writer_sender(), session.output_receiver())
+                }
                 None => {
                     return Err(format!("unknown session id {}", session_id.0));
                 }
@@ -228,20 +267,12 @@ impl SessionManager {
 
 /// Spawn PTY and child process per spawn_exec_command_session logic.
 async fn create_exec_command_session(
-    params: ExecCommandParams,
+    command: SandboxedCommand,
 ) -> anyhow::Result<(
     ExecCommandSession,
     tokio::sync::broadcast::Receiver<Vec<u8>>,
     oneshot::Receiver<i32>,
 )> {
-    let ExecCommandParams {
-        cmd,
-        yield_time_ms: _,
-        max_output_tokens: _,
-        shell,
-        login,
-    } = params;
-
     // Use the native pty implementation for the system
     let pty_system = native_pty_system();
 
@@ -253,13 +284,8 @@ async fn create_exec_command_session(
         pixel_height: 0,
     })?;
 
-    // Spawn a shell into the pty
-    let mut command_builder = CommandBuilder::new(shell);
-    let shell_mode_opt = if login { "-lc" } else { "-c" };
-    command_builder.arg(shell_mode_opt);
-    command_builder.arg(cmd);
-
-    let mut child = pair.slave.spawn_command(command_builder)?;
+    // Spawn the (possibly sandboxed) shell into the pty
+    let (mut child, master) = spawn_pty_command(pair, &command).await?;
     // Obtain a killer that can signal the process independently of `.wait()`.
     let killer = child.clone_killer();
 
@@ -268,7 +294,7 @@ async fn create_exec_command_session(
     // Broadcast for streaming PTY output to readers: subscribers receive from subscription time.
     let (output_tx, _) = tokio::sync::broadcast::channel::<Vec<u8>>(256);
     // Reader task: drain PTY and forward chunks to output channel.
-    let mut reader = pair.master.try_clone_reader()?;
+    let mut reader = master.try_clone_reader()?;
     let output_tx_clone = output_tx.clone();
     let reader_handle = tokio::task::spawn_blocking(move || {
         let mut buf = [0u8; 8192];
@@ -294,7 +320,7 @@ async fn create_exec_command_session(
     });
 
     // Writer task: apply stdin writes to the PTY writer.
-    let writer = pair.master.take_writer()?;
+    let writer = master.take_writer()?;
     let writer = Arc::new(StdMutex::new(writer));
     let writer_handle = tokio::spawn({
         let writer = writer.clone();
@@ -336,6 +362,8 @@ async fn create_exec_command_session(
         writer_handle,
         wait_handle,
         exit_status,
+        command.sandbox_type,
+        command.network_proxy,
     );
     Ok((session, initial_output_rx, exit_rx))
 }
@@ -353,8 +381,10 @@ mod tests {
     #[allow(clippy::print_stderr)]
     #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
     async fn session_manager_streams_and_truncates_from_now() {
+        use crate::exec::create_sandboxed_command;
         use crate::exec_command::exec_command_params::ExecCommandParams;
         use crate::exec_command::exec_command_params::WriteStdinParams;
+        use crate::protocol::SandboxPolicy;
         use tokio::time::sleep;
 
         let session_manager = SessionManager::default();
@@ -379,8 +409,21 @@ PY"#
             shell: "/bin/bash".to_string(),
             login: false,
         };
+        let cwd = std::env::current_dir().expect("current dir");
+        let command = create_sandboxed_command(
+            params.command(),
+            cwd.clone(),
+            std::env::vars().collect(),
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &cwd,
+            &None,
+            Default::default(),
+            None,
+        )
+        .expect("unsandboxed command");
         let initial_output = match session_manager
-            .handle_exec_command_request(params.clone())
+            .handle_exec_command_request(params.clone(), command)
             .await
         {
             Ok(v) => v,
@@ -453,6 +496,49 @@ PY"#
         );
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn sessions_run_under_the_resource_limits() {
+        use crate::config_types::ResourceLimits;
+        use crate::exec::create_sandboxed_command;
+        use crate::protocol::SandboxPolicy;
+
+        let params = ExecCommandParams {
+            cmd: "ulimit -t; ulimit -f".to_string(),
+            yield_time_ms: 5_000,
+            max_output_tokens: 1_000,
+            shell: "/bin/sh".to_string(),
+            login: false,
+        };
+        let cwd = std::env::current_dir().expect("current dir");
+        let command = create_sandboxed_command(
+            params.command(),
+            cwd.clone(),
+            std::env::vars().collect(),
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &cwd,
+            &None,
+            ResourceLimits {
+                cpu_time_secs: Some(7),
+                max_file_size_mb: Some(1),
+                ..Default::default()
+            },
+            None,
+        )
+        .expect("unsandboxed command");
+
+        let output = SessionManager::default()
+            .handle_exec_command_request(params, command)
+            .await
+            .expect("exec request");
+
+        // `ulimit -f` counts 512-byte blocks.
+        let lines: Vec<&str> = output.output.lines().map(str::trim).collect();
+        assert_eq!(lines, vec!["7", "2048"]);
+        assert!(matches!(output.exit_status, ExitStatus::Exited(0)));
+    }
+
     #[cfg(unix)]
     fn extract_monotonic_numbers(s: &str) -> Vec<i64> {
         s.lines()
@@ -0,0 +1,319 @@
+//! Evaluation of user-supplied `codex-execpolicy` `.policy` files.
+//!
+//! Policies are loaded once per session from the paths listed in
+//! [`crate::config::Config::exec_policy_files`] and consulted by
+//! [`crate::safety::assess_command_safety`] for every command the model asks
+//! to run.
+
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_execpolicy::ArgType;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::ValidExec;
+
+use crate::bash::parse_bash_lc_plain_commands;
+use crate::protocol::SandboxPolicy;
+
+/// Outcome of checking a command against every loaded policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ExecPolicyDecision {
+    /// Every program in the command matched a rule and none of them write
+    /// files.
+    Safe,
+
+    /// Every program in the command matched a rule, and every file they write
+    /// lives inside one of the sandbox's writable roots.
+    MatchWithinWritableRoots,
+
+    /// At least one program matched a `forbidden` rule.
+    Forbidden { reason: String },
+
+    /// The policies do not vouch for the command: either some program had no
+    /// matching rule, or a matched rule writes outside the writable roots.
+    Unverified,
+}
+
+/// The set of policies in effect for a session. An empty set never vouches
+/// for (or forbids) any command.
+#[derive(Default)]
+pub(crate) struct ExecPolicy {
+    policies: Vec<Policy>,
+}
+
+impl std::fmt::Debug for ExecPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecPolicy")
+            .field("policies", &self.policies.len())
+            .finish()
+    }
+}
+
+impl ExecPolicy {
+    /// Parse every policy file in `paths`. A missing or malformed file is an
+    /// error: silently dropping a policy could drop `forbidden` rules too.
+    pub(crate) fn load(paths: &[PathBuf]) -> anyhow::Result<Self> {
+        let mut policies = Vec::with_capacity(paths.len());
+        for path in paths {
+            let source = std::fs::read_to_string(path).map_err(|e| {
+                anyhow::anyhow!("failed to read exec policy {}: {e}", path.display())
+            })?;
+            policies.push(Self::parse(&path.to_string_lossy(), &source)?);
+        }
+        Ok(Self { policies })
+    }
+
+    fn parse(policy_source: &str, unparsed_policy: &str) -> anyhow::Result<Policy> {
+        PolicyParser::new(policy_source, unparsed_policy)
+            .parse()
+            .map_err(|e| anyhow::anyhow!("failed to parse exec policy {policy_source}: {e}"))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Check `command` against the loaded policies. `bash -lc "<script>"`
+    /// invocations are split into their plain commands, each of which must be
+    /// vouched for individually.
+    pub(crate) fn check(
+        &self,
+        command: &[String],
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> ExecPolicyDecision {
+        if self.is_empty() {
+            return ExecPolicyDecision::Unverified;
+        }
+
+        let commands = match parse_bash_lc_plain_commands(command) {
+            Some(commands) if !commands.is_empty() => commands,
+            _ => vec![command.to_vec()],
+        };
+
+        let mut decision = ExecPolicyDecision::Safe;
+        for command in &commands {
+            match self.check_single(command, sandbox_policy, cwd) {
+                forbidden @ ExecPolicyDecision::Forbidden { .. } => return forbidden,
+                ExecPolicyDecision::Unverified => decision = ExecPolicyDecision::Unverified,
+                ExecPolicyDecision::MatchWithinWritableRoots => {
+                    if decision == ExecPolicyDecision::Safe {
+                        decision = ExecPolicyDecision::MatchWithinWritableRoots;
+                    }
+                }
+                ExecPolicyDecision::Safe => {}
+            }
+        }
+        decision
+    }
+
+    fn check_single(
+        &self,
+        command: &[String],
+        sandbox_policy: &SandboxPolicy,
+        cwd: &Path,
+    ) -> ExecPolicyDecision {
+        let Some((program, args)) = command.split_first() else {
+            return ExecPolicyDecision::Unverified;
+        };
+        let exec_call = ExecCall {
+            program: program.clone(),
+            args: args.to_vec(),
+        };
+
+        // A `forbidden` verdict from any policy wins; otherwise the first
+        // policy that matches decides.
+        let mut matched: Option<ValidExec> = None;
+        for policy in &self.policies {
+            match policy.check(&exec_call) {
+                Ok(MatchedExec::Forbidden { reason, .. }) => {
+                    return ExecPolicyDecision::Forbidden { reason };
+                }
+                Ok(MatchedExec::Match { exec }) => {
+                    if matched.is_none() {
+                        matched = Some(exec);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+
+        match matched {
+            None => ExecPolicyDecision::Unverified,
+            Some(exec) if !exec.might_write_files() => ExecPolicyDecision::Safe,
+            Some(exec) => {
+                if writes_constrained_to_writable_roots(&exec, sandbox_policy, cwd) {
+                    ExecPolicyDecision::MatchWithinWritableRoots
+                } else {
+                    ExecPolicyDecision::Unverified
+                }
+            }
+        }
+    }
+}
+
+fn writes_constrained_to_writable_roots(
+    exec: &ValidExec,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> bool {
+    let writable_roots = match sandbox_policy {
+        SandboxPolicy::DangerFullAccess => return true,
+        SandboxPolicy::ReadOnly => return false,
+        SandboxPolicy::WorkspaceWrite { .. } => sandbox_policy.get_writable_roots_with_cwd(cwd),
+    };
+
+    let written_paths = exec
+        .args
+        .iter()
+        .map(|arg| (&arg.r#type, &arg.value))
+        .chain(exec.opts.iter().map(|opt| (&opt.r#type, &opt.value)))
+        .filter(|(arg_type, _)| matches!(arg_type, ArgType::WriteableFile))
+        .map(|(_, value)| normalize(&cwd.join(value)));
+
+    for path in written_paths {
+        if !writable_roots
+            .iter()
+            .any(|root| root.is_path_writable(&path))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Remove `.` and resolve `..` without touching the filesystem so that paths
+/// which do not exist yet can still be compared against writable roots.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const TEST_POLICY: &str = r#"
+define_program(
+    program="cargo",
+    options=[opt("-p", ARG_OPAQUE_VALUE)],
+    args=["test", ARG_UNVERIFIED_VARARGS],
+)
+
+define_program(
+    program="cp",
+    args=[ARG_RFILE, ARG_WFILE],
+)
+
+define_program(
+    program="deploy",
+    forbidden="deploys must go through CI",
+)
+"#;
+
+    fn policy() -> ExecPolicy {
+        ExecPolicy {
+            policies: vec![ExecPolicy::parse("#test", TEST_POLICY).expect("valid policy")],
+        }
+    }
+
+    fn workspace_write() -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        }
+    }
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn matched_program_without_writes_is_safe() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(
+            policy().check(
+                &vec_str(&["cargo", "test", "-p", "codex-core"]),
+                &workspace_write(),
+                tmp.path()
+            ),
+            ExecPolicyDecision::Safe
+        );
+    }
+
+    #[test]
+    fn writes_are_checked_against_writable_roots() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path();
+        let inside = vec_str(&["cp", "a.txt", "b.txt"]);
+        let outside = vec_str(&["cp", "a.txt", "../b.txt"]);
+
+        assert_eq!(
+            policy().check(&inside, &workspace_write(), cwd),
+            ExecPolicyDecision::MatchWithinWritableRoots
+        );
+        assert_eq!(
+            policy().check(&outside, &workspace_write(), cwd),
+            ExecPolicyDecision::Unverified
+        );
+        assert_eq!(
+            policy().check(&inside, &SandboxPolicy::ReadOnly, cwd),
+            ExecPolicyDecision::Unverified
+        );
+    }
+
+    #[test]
+    fn forbidden_program_wins_inside_bash_script() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(
+            policy().check(
+                &vec_str(&["bash", "-lc", "cargo test && deploy"]),
+                &workspace_write(),
+                tmp.path()
+            ),
+            ExecPolicyDecision::Forbidden {
+                reason: "deploys must go through CI".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_program_is_unverified() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(
+            policy().check(
+                &vec_str(&["bash", "-lc", "cargo test && make lint"]),
+                &workspace_write(),
+                tmp.path()
+            ),
+            ExecPolicyDecision::Unverified
+        );
+        assert_eq!(
+            ExecPolicy::default().check(
+                &vec_str(&["cargo", "test"]),
+                &workspace_write(),
+                tmp.path()
+            ),
+            ExecPolicyDecision::Unverified
+        );
+    }
+}
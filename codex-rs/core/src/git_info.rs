@@ -130,12 +130,41 @@ pub async fn recent_commits(cwd: &Path, limit: usize) -> Vec<CommitLogEntry> {
         return Vec::new();
     }
 
-    let fmt = "%H%x1f%ct%x1f%s"; // <sha> <US> <commit_time> <US> <subject>
     let n = limit.max(1).to_string();
-    let Some(log_out) =
-        run_git_command_with_timeout(&["log", "-n", &n, &format!("--pretty=format:{fmt}")], cwd)
-            .await
-    else {
+    git_log(cwd, &["-n", &n]).await
+}
+
+/// Resolve `rev` (a SHA, branch or other revision) to the commit it names.
+/// Returns `None` if not in a git repo or the revision does not exist.
+pub async fn resolve_commit(cwd: &Path, rev: &str) -> Option<CommitLogEntry> {
+    // `--end-of-options` keeps a `rev` such as `--output=FILE` from being
+    // taken as an option.
+    git_log(cwd, &["-n", "1", "--end-of-options", rev, "--"])
+        .await
+        .into_iter()
+        .next()
+}
+
+/// Returns the SHA of the best common ancestor of `HEAD` and `rev`, which is
+/// where the current branch forked from `rev`.
+pub async fn merge_base_with_head(cwd: &Path, rev: &str) -> Option<String> {
+    let out =
+        run_git_command_with_timeout(&["merge-base", "--end-of-options", "HEAD", rev], cwd).await?;
+    if !out.status.success() {
+        return None;
+    }
+    String::from_utf8(out.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|sha| !sha.is_empty())
+}
+
+async fn git_log(cwd: &Path, args: &[&str]) -> Vec<CommitLogEntry> {
+    let fmt = "%H%x1f%ct%x1f%s"; // <sha> <US> <commit_time> <US> <subject>
+    let pretty = format!("--pretty=format:{fmt}");
+    let mut log_args = vec!["log", pretty.as_str()];
+    log_args.extend_from_slice(args);
+    let Some(log_out) = run_git_command_with_timeout(&log_args, cwd).await else {
         return Vec::new();
     };
     if !log_out.status.success() {
@@ -727,6 +756,71 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_resolve_commit() {
+        skip_if_sandbox!();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+
+        let head = resolve_commit(&repo_path, "HEAD")
+            .await
+            .expect("HEAD should resolve");
+        assert_eq!(head.subject, "Initial commit");
+
+        let short = &head.sha[..7];
+        let by_sha = resolve_commit(&repo_path, short)
+            .await
+            .expect("short sha should resolve");
+        assert_eq!(by_sha.sha, head.sha);
+
+        assert!(resolve_commit(&repo_path, "no-such-rev").await.is_none());
+
+        let output_file = temp_dir.path().join("output.txt");
+        let option = format!("--output={}", output_file.display());
+        assert!(resolve_commit(&repo_path, &option).await.is_none());
+        assert!(!output_file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_merge_base_with_head() {
+        skip_if_sandbox!();
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        let fork_point = resolve_commit(&repo_path, "HEAD")
+            .await
+            .expect("HEAD should resolve")
+            .sha;
+
+        Command::new("git")
+            .args(["branch", "base"])
+            .current_dir(&repo_path)
+            .output()
+            .await
+            .expect("Failed to create branch");
+        fs::write(repo_path.join("feature.txt"), "feature").expect("Failed to write file");
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(&repo_path)
+            .output()
+            .await
+            .expect("Failed to add files");
+        Command::new("git")
+            .args(["commit", "-m", "Feature"])
+            .current_dir(&repo_path)
+            .output()
+            .await
+            .expect("Failed to commit");
+
+        assert_eq!(
+            merge_base_with_head(&repo_path, "base").await,
+            Some(fork_point)
+        );
+        assert_eq!(
+            merge_base_with_head(&repo_path, "no-such-branch").await,
+            None
+        );
+    }
+
     async fn create_test_git_repo_with_remote(temp_dir: &TempDir) -> (PathBuf, String) {
         let repo_path = create_test_git_repo(temp_dir).await;
         let remote_path = temp_dir.path().join("remote.git");
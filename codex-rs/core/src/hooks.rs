@@ -0,0 +1,468 @@
+//! Lifecycle hooks configured under `[hooks]`.
+//!
+//! Each hook is a local command that receives a JSON description of the event
+//! on stdin. A hook that exits with status 2 denies the action, using its
+//! stderr as the reason. A hook that exits with status 0 may print a JSON
+//! reply on stdout (see [`HookReply`]).
+//!
+//! Any other outcome is a failure, which is reported to the user. A failed
+//! `user_prompt_submit` or `pre_tool_use` hook denies the action, so a hook
+//! meant to block something does not let it through when it breaks; for the
+//! other events the failure is otherwise ignored.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+use crate::config_types::HookCommand;
+use crate::config_types::Hooks;
+
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long the `session_start` hooks may run in total. The session cannot
+/// accept input until they finish, so this is much shorter than the per-hook
+/// default.
+pub(crate) const SESSION_START_HOOKS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many `turn_complete` follow-ups a task accepts unless
+/// `hooks.max_follow_ups` says otherwise.
+const DEFAULT_MAX_FOLLOW_UPS: u32 = 1;
+
+/// Exit status with which a hook denies the action.
+const DENY_EXIT_CODE: i32 = 2;
+
+/// Event passed to the hooks on stdin, alongside the session id and cwd.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "hook_event_name", rename_all = "snake_case")]
+pub(crate) enum HookEvent {
+    SessionStart {
+        /// Whether the session continues an earlier conversation.
+        resumed: bool,
+    },
+    UserPromptSubmit {
+        turn_id: String,
+        prompt: String,
+    },
+    PreToolUse {
+        turn_id: String,
+        call_id: String,
+        tool_name: String,
+        /// For shell commands this is `{"command": [...], "workdir": "..."}`.
+        tool_input: serde_json::Value,
+    },
+    PostToolUse {
+        turn_id: String,
+        call_id: String,
+        tool_name: String,
+        tool_input: serde_json::Value,
+        tool_output: String,
+        success: bool,
+    },
+    TurnComplete {
+        turn_id: String,
+        input_messages: Vec<String>,
+        last_assistant_message: Option<String>,
+        /// Whether this turn was continued by a `turn_complete` hook's
+        /// follow-up message.
+        follow_up: bool,
+    },
+}
+
+impl HookEvent {
+    /// Whether hooks for this event guard an action, which they deny when
+    /// they fail.
+    fn fails_closed(&self) -> bool {
+        matches!(
+            self,
+            HookEvent::UserPromptSubmit { .. } | HookEvent::PreToolUse { .. }
+        )
+    }
+
+    /// Lets hooks later in the chain see the command as rewritten by earlier
+    /// ones.
+    fn set_command(&mut self, command: &[String]) {
+        if let HookEvent::PreToolUse { tool_input, .. } = self
+            && let Some(input) = tool_input.as_object_mut()
+            && input.contains_key("command")
+        {
+            input.insert("command".to_string(), command.into());
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HookInput<'a> {
+    session_id: &'a str,
+    cwd: &'a Path,
+    #[serde(flatten)]
+    event: &'a HookEvent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HookDecision {
+    Allow,
+    Deny,
+}
+
+/// JSON a hook may print on stdout when it exits with status 0. Every field
+/// is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct HookReply {
+    decision: Option<HookDecision>,
+    reason: Option<String>,
+    /// Replacement argv for a shell command (`pre_tool_use` only).
+    command: Option<Vec<String>>,
+    /// Text added to what the model sees for this event.
+    additional_context: Option<String>,
+    /// Message queued as if the user had sent it (`post_tool_use` and
+    /// `turn_complete` only).
+    follow_up_message: Option<String>,
+}
+
+/// Combined effect of all the hooks that ran for one event.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct HookOutcome {
+    /// Set when a hook denied the action, with the reason it gave.
+    pub(crate) denied: Option<String>,
+    pub(crate) command: Option<Vec<String>>,
+    pub(crate) additional_context: Option<String>,
+    pub(crate) follow_up_message: Option<String>,
+    /// Why each hook that failed did so, for showing to the user.
+    pub(crate) failures: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct HookRunner {
+    hooks: Hooks,
+}
+
+impl HookRunner {
+    pub(crate) fn new(hooks: Hooks) -> Self {
+        Self { hooks }
+    }
+
+    /// How many `turn_complete` follow-up messages one task accepts, so a
+    /// hook that always replies cannot keep the agent going forever.
+    pub(crate) fn max_follow_ups(&self) -> u32 {
+        self.hooks.max_follow_ups.unwrap_or(DEFAULT_MAX_FOLLOW_UPS)
+    }
+
+    /// Runs the hooks configured for `event` in order. A denial stops the
+    /// chain; rewritten commands are passed on to the next hook and context
+    /// and follow-up messages are concatenated.
+    pub(crate) async fn run(&self, session_id: &str, cwd: &Path, event: HookEvent) -> HookOutcome {
+        let commands = match event {
+            HookEvent::SessionStart { .. } => &self.hooks.session_start,
+            HookEvent::UserPromptSubmit { .. } => &self.hooks.user_prompt_submit,
+            HookEvent::PreToolUse { .. } => &self.hooks.pre_tool_use,
+            HookEvent::PostToolUse { .. } => &self.hooks.post_tool_use,
+            HookEvent::TurnComplete { .. } => &self.hooks.turn_complete,
+        };
+        let mut outcome = HookOutcome::default();
+        let mut event = event;
+        for hook in commands.iter().filter(|hook| !hook.command.is_empty()) {
+            let input = HookInput {
+                session_id,
+                cwd,
+                event: &event,
+            };
+            let payload = match serde_json::to_vec(&input) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("failed to serialize hook payload: {e}");
+                    return outcome;
+                }
+            };
+            let reply = match run_hook(hook, cwd, payload).await {
+                Ok(reply) => reply,
+                Err(failure) => {
+                    warn!("{failure}");
+                    if event.fails_closed() {
+                        outcome.denied = Some(failure.clone());
+                        outcome.failures.push(failure);
+                        return outcome;
+                    }
+                    outcome.failures.push(failure);
+                    continue;
+                }
+            };
+            if reply.decision == Some(HookDecision::Deny) {
+                outcome.denied = Some(reply.reason.unwrap_or_else(|| "denied by hook".to_string()));
+                return outcome;
+            }
+            if let Some(command) = reply.command.filter(|command| !command.is_empty()) {
+                event.set_command(&command);
+                outcome.command = Some(command);
+            }
+            append(&mut outcome.additional_context, reply.additional_context);
+            append(&mut outcome.follow_up_message, reply.follow_up_message);
+        }
+        outcome
+    }
+}
+
+fn append(target: &mut Option<String>, text: Option<String>) {
+    let Some(text) = text.filter(|text| !text.trim().is_empty()) else {
+        return;
+    };
+    match target {
+        Some(existing) => {
+            existing.push_str("\n\n");
+            existing.push_str(&text);
+        }
+        None => *target = Some(text),
+    }
+}
+
+/// Returns why the hook failed if it did not exit with status 0 or 2 and a
+/// valid reply.
+async fn run_hook(hook: &HookCommand, cwd: &Path, payload: Vec<u8>) -> Result<HookReply, String> {
+    let program = &hook.command[0];
+    let mut child = match tokio::process::Command::new(program)
+        .args(&hook.command[1..])
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return Err(format!("failed to spawn hook '{program}': {e}")),
+    };
+
+    // Hooks are free to ignore stdin, so write errors are not fatal.
+    let stdin = child.stdin.take();
+    let write_payload = async move {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&payload).await;
+        }
+    };
+    let timeout = hook
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_HOOK_TIMEOUT);
+    let output = match tokio::time::timeout(timeout, async {
+        tokio::join!(write_payload, child.wait_with_output()).1
+    })
+    .await
+    {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(format!("failed to wait for hook '{program}': {e}")),
+        Err(_) => return Err(format!("hook '{program}' timed out after {timeout:?}")),
+    };
+
+    match output.status.code() {
+        Some(0) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if stdout.trim().is_empty() {
+                return Ok(HookReply::default());
+            }
+            serde_json::from_str(&stdout).map_err(|e| {
+                format!("hook '{program}' printed a reply that is not valid JSON: {e}")
+            })
+        }
+        Some(DENY_EXIT_CODE) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            Ok(HookReply {
+                decision: Some(HookDecision::Deny),
+                reason: (!stderr.is_empty()).then_some(stderr),
+                ..Default::default()
+            })
+        }
+        _ => Err(format!("hook '{program}' failed with {}", output.status)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn sh(script: &str) -> HookCommand {
+        HookCommand {
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            timeout_ms: None,
+        }
+    }
+
+    fn pre_tool_use(command: &[&str]) -> HookEvent {
+        HookEvent::PreToolUse {
+            turn_id: "1".to_string(),
+            call_id: "call-1".to_string(),
+            tool_name: "shell".to_string(),
+            tool_input: serde_json::json!({ "command": command, "workdir": "/repo" }),
+        }
+    }
+
+    #[test]
+    fn payload_includes_session_and_event_fields() -> anyhow::Result<()> {
+        let event = pre_tool_use(&["git", "status"]);
+        let input = HookInput {
+            session_id: "session-1",
+            cwd: Path::new("/repo"),
+            event: &event,
+        };
+        assert_eq!(
+            serde_json::to_value(&input)?,
+            serde_json::json!({
+                "session_id": "session-1",
+                "cwd": "/repo",
+                "hook_event_name": "pre_tool_use",
+                "turn_id": "1",
+                "call_id": "call-1",
+                "tool_name": "shell",
+                "tool_input": { "command": ["git", "status"], "workdir": "/repo" },
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn exit_code_two_denies_with_stderr_as_reason() {
+        let runner = HookRunner::new(Hooks {
+            pre_tool_use: vec![
+                sh(
+                    "grep -q -- '--force' && { echo 'force push is not allowed' >&2; exit 2; }; exit 0",
+                ),
+                sh("echo '{\"additional_context\": \"never reached\"}'"),
+            ],
+            ..Default::default()
+        });
+        let cwd = std::env::temp_dir();
+
+        let outcome = runner
+            .run("s", &cwd, pre_tool_use(&["git", "push", "--force"]))
+            .await;
+        assert_eq!(
+            outcome,
+            HookOutcome {
+                denied: Some("force push is not allowed".to_string()),
+                ..Default::default()
+            }
+        );
+
+        let outcome = runner.run("s", &cwd, pre_tool_use(&["git", "push"])).await;
+        assert_eq!(outcome.denied, None);
+        assert_eq!(outcome.additional_context.as_deref(), Some("never reached"));
+    }
+
+    #[tokio::test]
+    async fn rewritten_command_is_passed_to_later_hooks() {
+        let runner = HookRunner::new(Hooks {
+            pre_tool_use: vec![
+                sh(
+                    r#"echo '{"command": ["git", "push", "--force-with-lease"], "additional_context": "first"}'"#,
+                ),
+                sh(r#"grep -q 'force-with-lease' && echo '{"additional_context": "second"}'"#),
+            ],
+            ..Default::default()
+        });
+
+        let outcome = runner
+            .run(
+                "s",
+                &std::env::temp_dir(),
+                pre_tool_use(&["git", "push", "--force"]),
+            )
+            .await;
+        assert_eq!(
+            outcome,
+            HookOutcome {
+                denied: None,
+                command: Some(vec![
+                    "git".to_string(),
+                    "push".to_string(),
+                    "--force-with-lease".to_string(),
+                ]),
+                additional_context: Some("first\n\nsecond".to_string()),
+                follow_up_message: None,
+                failures: Vec::new(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_hooks_are_reported_and_otherwise_ignored() {
+        let runner = HookRunner::new(Hooks {
+            turn_complete: vec![
+                sh("exit 1"),
+                sh("echo 'not json'"),
+                HookCommand {
+                    command: vec!["sleep".to_string(), "5".to_string()],
+                    timeout_ms: Some(50),
+                },
+                sh(r#"echo '{"follow_up_message": "run the tests"}'"#),
+            ],
+            ..Default::default()
+        });
+
+        let outcome = runner
+            .run(
+                "s",
+                &std::env::temp_dir(),
+                HookEvent::TurnComplete {
+                    turn_id: "1".to_string(),
+                    input_messages: Vec::new(),
+                    last_assistant_message: None,
+                    follow_up: false,
+                },
+            )
+            .await;
+        assert_eq!(
+            outcome,
+            HookOutcome {
+                follow_up_message: Some("run the tests".to_string()),
+                failures: vec![
+                    "hook 'sh' failed with exit status: 1".to_string(),
+                    "hook 'sh' printed a reply that is not valid JSON: expected ident at line 1 column 2".to_string(),
+                    "hook 'sleep' timed out after 50ms".to_string(),
+                ],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_pre_tool_use_hook_denies_the_call() {
+        let runner = HookRunner::new(Hooks {
+            pre_tool_use: vec![
+                HookCommand {
+                    command: vec!["/nonexistent/no_force_push".to_string()],
+                    timeout_ms: None,
+                },
+                sh("echo '{\"additional_context\": \"never reached\"}'"),
+            ],
+            ..Default::default()
+        });
+
+        let outcome = runner
+            .run(
+                "s",
+                &std::env::temp_dir(),
+                pre_tool_use(&["git", "push", "--force"]),
+            )
+            .await;
+
+        let failure = outcome.denied.clone().unwrap_or_default();
+        assert!(
+            failure.starts_with("failed to spawn hook '/nonexistent/no_force_push'"),
+            "{failure}"
+        );
+        assert_eq!(
+            outcome,
+            HookOutcome {
+                denied: Some(failure.clone()),
+                failures: vec![failure],
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config_types::ResourceLimits;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -6,6 +7,9 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+/// The `argv[0]` under which the codex binary runs as the sandbox helper.
+pub(crate) const CODEX_LINUX_SANDBOX_ARG0: &str = "codex-linux-sandbox";
+
 /// Spawn a shell tool command under the Linux Landlock+seccomp sandbox helper
 /// (codex-linux-sandbox).
 ///
@@ -13,26 +17,38 @@ use tokio::process::Child;
 /// helper accepts a list of `--sandbox-permission`/`-s` flags mirroring the
 /// public CLI. We convert the internal [`SandboxPolicy`] representation into
 /// the equivalent CLI options.
+///
+/// `network_proxy_socket` is the unix socket of the proxy that serves the
+/// policy's `allowed_domains`; see `NetworkProxy`.
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_command_under_linux_sandbox<P>(
     codex_linux_sandbox_exe: P,
     command: Vec<String>,
     command_cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    network_proxy_socket: Option<&Path>,
+    resource_limits: &ResourceLimits,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, sandbox_policy_cwd);
-    let arg0 = Some("codex-linux-sandbox");
+    let args = create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+        network_proxy_socket,
+    );
+    let arg0 = Some(CODEX_LINUX_SANDBOX_ARG0);
     spawn_child_async(
         codex_linux_sandbox_exe.as_ref().to_path_buf(),
         args,
         arg0,
         command_cwd,
         sandbox_policy,
+        resource_limits,
         stdio_policy,
         env,
     )
@@ -40,10 +56,11 @@ where
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
-fn create_linux_sandbox_command_args(
+pub(crate) fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    network_proxy_socket: Option<&Path>,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = sandbox_policy_cwd
@@ -55,16 +72,38 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(socket) = network_proxy_socket {
+        linux_cmd.push("--network-proxy-socket".to_string());
+        linux_cmd.push(socket.to_string_lossy().to_string());
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
 
     linux_cmd
 }
+
+/// Whether Landlock is available. Probes the kernel for the Landlock ABI
+/// version, which fails where the kernel lacks Landlock or a container's
+/// seccomp profile blocks it.
+#[cfg(target_os = "linux")]
+pub(crate) fn landlock_available() -> bool {
+    const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1 << 0;
+    let abi = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0usize,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    abi >= 1
+}
@@ -8,6 +8,7 @@
 mod apply_patch;
 pub mod auth;
 pub mod bash;
+pub mod bwrap;
 mod chat_completions;
 mod client;
 mod client_common;
@@ -22,24 +23,37 @@ pub mod config_profile;
 pub mod config_types;
 mod conversation_history;
 pub mod custom_prompts;
+mod delegate_task;
+pub mod deny_read;
 mod environment_context;
 pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+mod exec_policy;
 mod flags;
 pub mod git_info;
+mod hooks;
 pub mod landlock;
 mod mcp_connection_manager;
+mod mcp_input;
 mod mcp_tool_call;
+pub mod memory;
+mod memory_tool;
 mod message_history;
 mod model_provider_info;
+#[cfg(target_os = "linux")]
+mod network_proxy;
 pub mod parse_command;
+pub mod resource_limits;
 mod truncate;
 mod unified_exec;
 mod user_instructions;
 pub use model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
+pub use model_provider_info::FallbackProvider;
 pub use model_provider_info::ModelProviderInfo;
+pub use model_provider_info::RateLimitHeaders;
+pub use model_provider_info::RateLimitWindowHeaders;
 pub use model_provider_info::WireApi;
 pub use model_provider_info::built_in_model_providers;
 pub use model_provider_info::create_oss_provider_with_base_url;
@@ -53,14 +67,18 @@ pub use conversation_manager::NewConversation;
 pub use auth::AuthManager;
 pub use auth::CodexAuth;
 pub mod default_client;
+pub mod model_catalog;
 pub mod model_family;
 mod openai_model_info;
 mod openai_tools;
+pub mod output_schema;
 pub mod plan_tool;
 pub mod project_doc;
 mod rollout;
 pub(crate) mod safety;
 pub mod seatbelt;
+mod seen_files;
+mod session_search_tool;
 pub mod shell;
 pub mod spawn;
 pub mod terminal;
@@ -74,6 +92,16 @@ pub use rollout::find_conversation_path_by_id_str;
 pub use rollout::list::ConversationItem;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
+pub use rollout::sessions::RecordedSession;
+pub use rollout::sessions::SessionsPage;
+pub use rollout::sessions::TranscriptMessage;
+pub use rollout::sessions::TranscriptRole;
+pub use rollout::sessions::find_session_path;
+pub use rollout::sessions::list_sessions;
+pub use rollout::sessions::prune_sessions;
+pub use rollout::sessions::read_session;
+pub use rollout::usage::UsageRecord;
+pub use rollout::usage::collect_usage;
 mod function_tool;
 mod state;
 mod tasks;
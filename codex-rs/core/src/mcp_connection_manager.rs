@@ -4,11 +4,14 @@
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key. Resources and
+//! prompts are not cached: they are listed on demand from the servers that
+//! advertise the matching capability.
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -17,8 +20,19 @@ use anyhow::Result;
 use anyhow::anyhow;
 use codex_mcp_client::McpClient;
 use codex_rmcp_client::RmcpClient;
+use codex_rmcp_client::load_fresh_oauth_tokens;
+use futures::future::join_all;
 use mcp_types::ClientCapabilities;
+use mcp_types::GetPromptResult;
 use mcp_types::Implementation;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::Prompt;
+use mcp_types::ReadResourceResult;
+use mcp_types::Resource;
+use mcp_types::ResourceTemplate;
+use mcp_types::ServerCapabilities;
 use mcp_types::Tool;
 
 use serde_json::json;
@@ -30,6 +44,7 @@ use tracing::warn;
 
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::config_types::McpToolApproval;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -89,6 +104,7 @@ struct ToolInfo {
 
 struct ManagedClient {
     client: McpClientAdapter,
+    capabilities: ServerCapabilities,
     startup_timeout: Duration,
     tool_timeout: Option<Duration>,
 }
@@ -107,30 +123,46 @@ impl McpClientAdapter {
         env: Option<HashMap<String, String>>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
-    ) -> Result<Self> {
+    ) -> Result<(Self, ServerCapabilities)> {
         info!(
             "new_stdio_client use_rmcp_client: {use_rmcp_client} program: {program:?} args: {args:?} env: {env:?} params: {params:?} startup_timeout: {startup_timeout:?}"
         );
         if use_rmcp_client {
             let client = Arc::new(RmcpClient::new_stdio_client(program, args, env).await?);
-            client.initialize(params, Some(startup_timeout)).await?;
-            Ok(McpClientAdapter::Rmcp(client))
+            let result = client.initialize(params, Some(startup_timeout)).await?;
+            Ok((McpClientAdapter::Rmcp(client), result.capabilities))
         } else {
             let client = Arc::new(McpClient::new_stdio_client(program, args, env).await?);
-            client.initialize(params, Some(startup_timeout)).await?;
-            Ok(McpClientAdapter::Legacy(client))
+            let result = client.initialize(params, Some(startup_timeout)).await?;
+            Ok((McpClientAdapter::Legacy(client), result.capabilities))
         }
     }
 
     async fn new_streamable_http_client(
         url: String,
         bearer_token: Option<String>,
+        codex_home: &Path,
+        server_name: &str,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
-    ) -> Result<Self> {
-        let client = Arc::new(RmcpClient::new_streamable_http_client(url, bearer_token)?);
-        client.initialize(params, Some(startup_timeout)).await?;
-        Ok(McpClientAdapter::Rmcp(client))
+    ) -> Result<(Self, ServerCapabilities)> {
+        // A token in the config takes precedence over one stored by
+        // `codex mcp login`.
+        let client = match bearer_token {
+            Some(token) => RmcpClient::new_streamable_http_client(url, Some(token))?,
+            None => match load_fresh_oauth_tokens(codex_home, server_name, &url).await? {
+                Some(tokens) => RmcpClient::new_streamable_http_oauth_client(
+                    url,
+                    codex_home.to_path_buf(),
+                    server_name.to_string(),
+                    tokens,
+                )?,
+                None => RmcpClient::new_streamable_http_client(url, None)?,
+            },
+        };
+        let client = Arc::new(client);
+        let result = client.initialize(params, Some(startup_timeout)).await?;
+        Ok((McpClientAdapter::Rmcp(client), result.capabilities))
     }
 
     async fn list_tools(
@@ -155,6 +187,128 @@ impl McpClientAdapter {
             McpClientAdapter::Rmcp(client) => client.call_tool(name, arguments, timeout).await,
         }
     }
+
+    async fn list_resources(&self, timeout: Option<Duration>) -> Result<Vec<Resource>> {
+        let mut resources = Vec::new();
+        let mut cursor = None;
+        let mut seen_cursors = HashSet::new();
+        loop {
+            let params = Some(ListResourcesRequestParams { cursor });
+            let page = match self {
+                McpClientAdapter::Legacy(client) => client.list_resources(params, timeout).await?,
+                McpClientAdapter::Rmcp(client) => client.list_resources(params, timeout).await?,
+            };
+            resources.extend(page.resources);
+            match next_page_cursor(&mut seen_cursors, page.next_cursor) {
+                Some(next) => cursor = Some(next),
+                None => return Ok(resources),
+            }
+        }
+    }
+
+    async fn list_resource_templates(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<ResourceTemplate>> {
+        let mut templates = Vec::new();
+        let mut cursor = None;
+        let mut seen_cursors = HashSet::new();
+        loop {
+            let params = Some(ListResourceTemplatesRequestParams { cursor });
+            let page = match self {
+                McpClientAdapter::Legacy(client) => {
+                    client.list_resource_templates(params, timeout).await?
+                }
+                McpClientAdapter::Rmcp(client) => {
+                    client.list_resource_templates(params, timeout).await?
+                }
+            };
+            templates.extend(page.resource_templates);
+            match next_page_cursor(&mut seen_cursors, page.next_cursor) {
+                Some(next) => cursor = Some(next),
+                None => return Ok(templates),
+            }
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.read_resource(uri, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.read_resource(uri, timeout).await,
+        }
+    }
+
+    async fn list_prompts(&self, timeout: Option<Duration>) -> Result<Vec<Prompt>> {
+        let mut prompts = Vec::new();
+        let mut cursor = None;
+        let mut seen_cursors = HashSet::new();
+        loop {
+            let params = Some(ListPromptsRequestParams { cursor });
+            let page = match self {
+                McpClientAdapter::Legacy(client) => client.list_prompts(params, timeout).await?,
+                McpClientAdapter::Rmcp(client) => client.list_prompts(params, timeout).await?,
+            };
+            prompts.extend(page.prompts);
+            match next_page_cursor(&mut seen_cursors, page.next_cursor) {
+                Some(next) => cursor = Some(next),
+                None => return Ok(prompts),
+            }
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.get_prompt(name, arguments, timeout).await,
+            McpClientAdapter::Rmcp(client) => client.get_prompt(name, arguments, timeout).await,
+        }
+    }
+}
+
+/// Listing resources, resource templates or prompts stops after this many
+/// pages, in case a server keeps handing out new cursors.
+const MAX_LIST_PAGES: usize = 100;
+
+/// Cursor of the next page to request, or `None` once the listing is done.
+/// A server that repeats a cursor or exceeds [`MAX_LIST_PAGES`] is cut off
+/// with the pages received so far.
+fn next_page_cursor(seen: &mut HashSet<String>, next: Option<String>) -> Option<String> {
+    let next = next?;
+    if seen.len() + 1 >= MAX_LIST_PAGES {
+        warn!("MCP server returned more than {MAX_LIST_PAGES} pages; ignoring the rest");
+        return None;
+    }
+    if !seen.insert(next.clone()) {
+        warn!("MCP server repeated pagination cursor {next:?}; stopping");
+        return None;
+    }
+    Some(next)
+}
+
+/// Approval settings of one MCP server, taken from its [`McpServerConfig`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct McpToolPolicy {
+    pub(crate) approval: Option<McpToolApproval>,
+    pub(crate) allowed_tools: Vec<String>,
+    pub(crate) denied_tools: Vec<String>,
+}
+
+impl From<&McpServerConfig> for McpToolPolicy {
+    fn from(config: &McpServerConfig) -> Self {
+        Self {
+            approval: config.approval,
+            allowed_tools: config.allowed_tools.clone(),
+            denied_tools: config.denied_tools.clone(),
+        }
+    }
 }
 
 /// A thin wrapper around a set of running [`McpClient`] instances.
@@ -168,6 +322,9 @@ pub(crate) struct McpConnectionManager {
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Server-name -> approval settings.
+    policies: HashMap<String, McpToolPolicy>,
 }
 
 impl McpConnectionManager {
@@ -176,12 +333,15 @@ impl McpConnectionManager {
     /// * `mcp_servers` – Map loaded from the user configuration where *keys*
     ///   are human-readable server identifiers and *values* are the spawn
     ///   instructions.
+    /// * `codex_home` – Where `codex mcp login` stores OAuth tokens for
+    ///   streamable HTTP servers.
     ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
         use_rmcp_client: bool,
+        codex_home: &Path,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
@@ -191,6 +351,7 @@ impl McpConnectionManager {
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
+        let mut policies = HashMap::new();
 
         for (server_name, cfg) in mcp_servers {
             // Validate server name before spawning
@@ -215,10 +376,12 @@ impl McpConnectionManager {
                 continue;
             }
 
+            policies.insert(server_name.clone(), McpToolPolicy::from(&cfg));
             let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
             let tool_timeout = cfg.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT);
 
             let use_rmcp_client_flag = use_rmcp_client;
+            let codex_home = codex_home.to_path_buf();
             join_set.spawn(async move {
                 let McpServerConfig { transport, .. } = cfg;
                 let params = mcp_types::InitializeRequestParams {
@@ -260,13 +423,15 @@ impl McpConnectionManager {
                         McpClientAdapter::new_streamable_http_client(
                             url,
                             bearer_token,
+                            &codex_home,
+                            &server_name,
                             params,
                             startup_timeout,
                         )
                         .await
                     }
                 }
-                .map(|c| (c, startup_timeout));
+                .map(|(client, capabilities)| (client, capabilities, startup_timeout));
 
                 ((server_name, tool_timeout), client)
             });
@@ -284,11 +449,12 @@ impl McpConnectionManager {
             };
 
             match client_res {
-                Ok((client, startup_timeout)) => {
+                Ok((client, capabilities, startup_timeout)) => {
                     clients.insert(
                         server_name,
                         ManagedClient {
                             client,
+                            capabilities,
                             startup_timeout,
                             tool_timeout: Some(tool_timeout),
                         },
@@ -308,9 +474,16 @@ impl McpConnectionManager {
             }
         };
 
-        let tools = qualify_tools(all_tools);
+        let tools = qualify_tools(remove_denied_tools(all_tools, &policies));
 
-        Ok((Self { clients, tools }, errors))
+        Ok((
+            Self {
+                clients,
+                tools,
+                policies,
+            },
+            errors,
+        ))
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
@@ -342,11 +515,150 @@ impl McpConnectionManager {
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Lists the resources of every server that offers them, keyed by server
+    /// name. Servers that fail to answer are logged and left out.
+    pub async fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
+        let servers = self
+            .clients
+            .iter()
+            .filter(|(_, managed)| managed.capabilities.resources.is_some());
+        let results = join_all(servers.map(|(server, managed)| async move {
+            let result = managed
+                .client
+                .list_resources(Some(managed.startup_timeout))
+                .await;
+            (server.clone(), result)
+        }))
+        .await;
+        collect_server_results(results, "resources")
+    }
+
+    /// Lists the resource templates of every server that offers resources,
+    /// keyed by server name.
+    pub async fn list_all_resource_templates(&self) -> HashMap<String, Vec<ResourceTemplate>> {
+        let servers = self
+            .clients
+            .iter()
+            .filter(|(_, managed)| managed.capabilities.resources.is_some());
+        let results = join_all(servers.map(|(server, managed)| async move {
+            let result = managed
+                .client
+                .list_resource_templates(Some(managed.startup_timeout))
+                .await;
+            (server.clone(), result)
+        }))
+        .await;
+        collect_server_results(results, "resource templates")
+    }
+
+    /// Lists the prompts of every server that offers them, keyed by server
+    /// name.
+    pub async fn list_all_prompts(&self) -> HashMap<String, Vec<Prompt>> {
+        let servers = self
+            .clients
+            .iter()
+            .filter(|(_, managed)| managed.capabilities.prompts.is_some());
+        let results = join_all(servers.map(|(server, managed)| async move {
+            let result = managed
+                .client
+                .list_prompts(Some(managed.startup_timeout))
+                .await;
+            (server.clone(), result)
+        }))
+        .await;
+        collect_server_results(results, "prompts")
+    }
+
+    /// Read the resource `uri` from `server`.
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<ReadResourceResult> {
+        let managed = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        managed
+            .client
+            .read_resource(uri.to_string(), managed.tool_timeout)
+            .await
+            .with_context(|| format!("reading resource `{uri}` from `{server}` failed"))
+    }
+
+    /// Render the prompt `name` of `server` with `arguments`.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<GetPromptResult> {
+        let managed = self
+            .clients
+            .get(server)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+        managed
+            .client
+            .get_prompt(name.to_string(), arguments, managed.tool_timeout)
+            .await
+            .with_context(|| format!("getting prompt `{name}` from `{server}` failed"))
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
         self.tools
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
+
+    /// Approval settings configured for `server`.
+    pub fn tool_policy(&self, server: &str) -> Option<&McpToolPolicy> {
+        self.policies.get(server)
+    }
+
+    /// Whether the server annotated `tool` with `readOnlyHint: true`.
+    pub fn is_read_only_tool(&self, server: &str, tool: &str) -> bool {
+        self.tools.values().any(|info| {
+            info.server_name == server
+                && info.tool_name == tool
+                && info
+                    .tool
+                    .annotations
+                    .as_ref()
+                    .and_then(|annotations| annotations.read_only_hint)
+                    .unwrap_or(false)
+        })
+    }
+}
+
+fn collect_server_results<T>(
+    results: Vec<(String, Result<Vec<T>>)>,
+    what: &str,
+) -> HashMap<String, Vec<T>> {
+    results
+        .into_iter()
+        .filter_map(|(server, result)| match result {
+            Ok(items) => Some((server, items)),
+            Err(e) => {
+                warn!("Failed to list {what} for MCP server '{server}': {e:#}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Drops the tools listed in `denied_tools` of their server so they are never
+/// offered to the model.
+fn remove_denied_tools(
+    tools: Vec<ToolInfo>,
+    policies: &HashMap<String, McpToolPolicy>,
+) -> Vec<ToolInfo> {
+    tools
+        .into_iter()
+        .filter(|tool| {
+            policies.get(&tool.server_name).is_none_or(|policy| {
+                !policy
+                    .denied_tools
+                    .iter()
+                    .any(|denied| denied == &tool.tool_name)
+            })
+        })
+        .collect()
 }
 
 /// Query every server for its available tools and return a single map that
@@ -434,6 +746,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_next_page_cursor_stops_on_repeats_and_page_limit() {
+        let mut seen = HashSet::new();
+        assert_eq!(next_page_cursor(&mut seen, None), None);
+        assert_eq!(
+            next_page_cursor(&mut seen, Some("a".to_string())),
+            Some("a".to_string())
+        );
+        assert_eq!(next_page_cursor(&mut seen, Some("a".to_string())), None);
+
+        let mut seen = HashSet::new();
+        let pages = (0..)
+            .map_while(|page| next_page_cursor(&mut seen, Some(format!("page-{page}"))))
+            .count();
+        assert_eq!(pages + 1, MAX_LIST_PAGES);
+    }
+
+    #[test]
+    fn test_remove_denied_tools() {
+        let tools = vec![
+            create_test_tool("server1", "read"),
+            create_test_tool("server1", "delete"),
+            create_test_tool("server2", "delete"),
+        ];
+        let policies = HashMap::from([(
+            "server1".to_string(),
+            McpToolPolicy {
+                denied_tools: vec!["delete".to_string()],
+                ..Default::default()
+            },
+        )]);
+
+        let qualified_tools = qualify_tools(remove_denied_tools(tools, &policies));
+
+        assert_eq!(qualified_tools.len(), 2);
+        assert!(qualified_tools.contains_key("server1__read"));
+        assert!(qualified_tools.contains_key("server2__delete"));
+    }
+
     #[test]
     fn test_qualify_tools_short_non_duplicated_names() {
         let tools = vec![
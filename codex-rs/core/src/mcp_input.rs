@@ -0,0 +1,210 @@
+//! Replaces the MCP resources and prompts referenced in user input with their
+//! contents before the input reaches the model.
+
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::GetPromptResult;
+use mcp_types::ReadResourceResult;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Role;
+
+use crate::mcp_connection_manager::McpConnectionManager;
+use crate::protocol::InputItem;
+
+/// Resolves every `InputItem::McpResource` and `InputItem::McpPrompt` in
+/// `items`. Items that cannot be resolved are replaced with a short note so
+/// the model knows the reference was not available; the errors are returned
+/// so they can be shown to the user.
+pub(crate) async fn resolve_mcp_input(
+    manager: &McpConnectionManager,
+    items: Vec<InputItem>,
+) -> (Vec<InputItem>, Vec<String>) {
+    let mut resolved = Vec::with_capacity(items.len());
+    let mut errors = Vec::new();
+    for item in items {
+        match item {
+            InputItem::McpResource { server, uri } => {
+                match manager.read_resource(&server, &uri).await {
+                    Ok(result) => resolved.extend(resource_input_items(&server, &uri, result)),
+                    Err(e) => {
+                        let error = format!("{e:#}");
+                        resolved.push(InputItem::Text {
+                            text: format!(
+                                "[MCP resource {server}:{uri} could not be read: {error}]"
+                            ),
+                        });
+                        errors.push(error);
+                    }
+                }
+            }
+            InputItem::McpPrompt {
+                server,
+                name,
+                arguments,
+            } => match manager.get_prompt(&server, &name, arguments).await {
+                Ok(result) => resolved.extend(prompt_input_items(result)),
+                Err(e) => errors.push(format!("{e:#}")),
+            },
+            other => resolved.push(other),
+        }
+    }
+    (resolved, errors)
+}
+
+/// Text contents are wrapped in an `<mcp_resource>` block, images are attached
+/// as images and other binary contents are only named.
+fn resource_input_items(server: &str, uri: &str, result: ReadResourceResult) -> Vec<InputItem> {
+    result
+        .contents
+        .into_iter()
+        .map(|contents| match contents {
+            ReadResourceResultContents::TextResourceContents(text) => InputItem::Text {
+                text: format!(
+                    "<mcp_resource server=\"{server}\" uri=\"{}\">\n{}\n</mcp_resource>",
+                    text.uri, text.text
+                ),
+            },
+            ReadResourceResultContents::BlobResourceContents(blob) => {
+                let mime_type = blob.mime_type.as_deref().unwrap_or_default();
+                if mime_type.starts_with("image/") {
+                    InputItem::Image {
+                        image_url: format!("data:{mime_type};base64,{}", blob.blob),
+                    }
+                } else {
+                    InputItem::Text {
+                        text: format!(
+                            "[MCP resource {server}:{uri} contains binary data ({}) that was not included]",
+                            if mime_type.is_empty() {
+                                "unknown type"
+                            } else {
+                                mime_type
+                            }
+                        ),
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// The prompt's messages are sent as part of the user's message. Assistant
+/// messages are labelled so the model can tell them apart.
+fn prompt_input_items(result: GetPromptResult) -> Vec<InputItem> {
+    result
+        .messages
+        .into_iter()
+        .filter_map(|message| {
+            let prefix = match message.role {
+                Role::User => "",
+                Role::Assistant => "[assistant] ",
+            };
+            match message.content {
+                ContentBlock::TextContent(content) => Some(InputItem::Text {
+                    text: format!("{prefix}{}", content.text),
+                }),
+                ContentBlock::ImageContent(image) => Some(InputItem::Image {
+                    image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+                }),
+                ContentBlock::EmbeddedResource(embedded) => match embedded.resource {
+                    EmbeddedResourceResource::TextResourceContents(text) => Some(InputItem::Text {
+                        text: format!(
+                            "{prefix}<mcp_resource uri=\"{}\">\n{}\n</mcp_resource>",
+                            text.uri, text.text
+                        ),
+                    }),
+                    EmbeddedResourceResource::BlobResourceContents(_) => None,
+                },
+                ContentBlock::ResourceLink(link) => Some(InputItem::Text {
+                    text: format!("{prefix}[resource: {}]", link.uri),
+                }),
+                ContentBlock::AudioContent(_) => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::PromptMessage;
+    use mcp_types::TextContent;
+    use mcp_types::TextResourceContents;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resource_contents_become_input_items() {
+        let result = ReadResourceResult {
+            contents: vec![
+                ReadResourceResultContents::TextResourceContents(TextResourceContents {
+                    mime_type: Some("text/markdown".to_string()),
+                    text: "# Guide".to_string(),
+                    uri: "docs://guide".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "iVBORw0K".to_string(),
+                    mime_type: Some("image/png".to_string()),
+                    uri: "docs://guide/diagram".to_string(),
+                }),
+                ReadResourceResultContents::BlobResourceContents(BlobResourceContents {
+                    blob: "UEsDBA".to_string(),
+                    mime_type: Some("application/zip".to_string()),
+                    uri: "docs://guide/archive".to_string(),
+                }),
+            ],
+        };
+
+        assert_eq!(
+            resource_input_items("docs", "docs://guide", result),
+            vec![
+                InputItem::Text {
+                    text: "<mcp_resource server=\"docs\" uri=\"docs://guide\">\n# Guide\n</mcp_resource>"
+                        .to_string(),
+                },
+                InputItem::Image {
+                    image_url: "data:image/png;base64,iVBORw0K".to_string(),
+                },
+                InputItem::Text {
+                    text: "[MCP resource docs:docs://guide contains binary data (application/zip) that was not included]"
+                        .to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn prompt_messages_become_input_items() {
+        let text = |text: &str| {
+            ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            })
+        };
+        let result = GetPromptResult {
+            description: None,
+            messages: vec![
+                PromptMessage {
+                    content: text("Summarize the release notes."),
+                    role: Role::User,
+                },
+                PromptMessage {
+                    content: text("Which release?"),
+                    role: Role::Assistant,
+                },
+            ],
+        };
+
+        assert_eq!(
+            prompt_input_items(result),
+            vec![
+                InputItem::Text {
+                    text: "Summarize the release notes.".to_string(),
+                },
+                InputItem::Text {
+                    text: "[assistant] Which release?".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -3,16 +3,22 @@ use std::time::Instant;
 use tracing::error;
 
 use crate::codex::Session;
+use crate::protocol::AskForApproval;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::ReviewDecision;
+use crate::safety::SafetyCheck;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 
 /// Handles the specified tool call dispatches the appropriate
 /// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+///
+/// Calls that the server's approval settings gate are confirmed with the
+/// user first; a rejected call is reported back to the model as a failure.
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -20,6 +26,7 @@ pub(crate) async fn handle_mcp_tool_call(
     server: String,
     tool_name: String,
     arguments: String,
+    approval_policy: AskForApproval,
 ) -> ResponseInputItem {
     // Parse the `arguments` as JSON. An empty string is OK, but invalid JSON
     // is not.
@@ -47,6 +54,36 @@ pub(crate) async fn handle_mcp_tool_call(
         arguments: arguments_value.clone(),
     };
 
+    match sess
+        .assess_mcp_tool_call(&server, &tool_name, approval_policy)
+        .await
+    {
+        SafetyCheck::AutoApprove { .. } => {}
+        SafetyCheck::AskUser => {
+            let decision = sess
+                .request_mcp_tool_call_approval(
+                    sub_id.to_string(),
+                    call_id.clone(),
+                    invocation.clone(),
+                    None,
+                )
+                .await;
+            match decision {
+                ReviewDecision::Approved => {}
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_mcp_tool(server.clone(), tool_name.clone())
+                        .await;
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return rejected_output(call_id, "MCP tool call rejected by user".to_string());
+                }
+            }
+        }
+        SafetyCheck::Reject { reason } => {
+            return rejected_output(call_id, format!("MCP tool call rejected: {reason}"));
+        }
+    }
+
     let tool_call_begin_event = EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
         call_id: call_id.clone(),
         invocation: invocation.clone(),
@@ -71,6 +108,16 @@ pub(crate) async fn handle_mcp_tool_call(
     ResponseInputItem::McpToolCallOutput { call_id, result }
 }
 
+fn rejected_output(call_id: String, content: String) -> ResponseInputItem {
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(false),
+        },
+    }
+}
+
 async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMsg) {
     sess.send_event(Event {
         id: sub_id.to_string(),
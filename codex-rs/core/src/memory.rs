@@ -0,0 +1,344 @@
+//! Persistent notes the agent keeps across sessions with the `remember` and
+//! `forget` tools.
+//!
+//! Entries are Markdown list items stored in `.codex/memory.md` at the project
+//! root (the Git root, or the working directory outside a repository) or in
+//! `~/.codex/memory.md` for notes that apply to every project:
+//!
+//! ```text
+//! - [3f9c2a1b] 2025-10-01: Sessions are stored in SQLite, not JSONL.
+//! ```
+//!
+//! Any other line in these files is left untouched, so they can also be
+//! edited by hand.
+
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use time::OffsetDateTime;
+use time::macros::format_description;
+use tracing::error;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::git_info::get_git_repo_root;
+
+/// Filename of the memory file, both in `<project>/.codex` and in `~/.codex`.
+pub const MEMORY_FILENAME: &str = "memory.md";
+
+/// Entries longer than this are rejected; memory is for short facts.
+const MAX_ENTRY_CHARS: usize = 1_000;
+
+const MEMORY_FILE_HEADER: &str = "# Codex memory\n\nNotes saved by Codex with the `remember` tool. Remove an entry with `/memory` or by deleting its line.\n\n";
+
+const MEMORY_PREAMBLE: &str = "Notes saved in earlier sessions. Treat them like project instructions; each starts with the id used to `forget` it.\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryScope {
+    /// `.codex/memory.md` at the project root.
+    #[default]
+    Project,
+    /// `~/.codex/memory.md`.
+    User,
+}
+
+impl MemoryScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            MemoryScope::Project => "project",
+            MemoryScope::User => "user",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub scope: MemoryScope,
+    /// Day the entry was saved, as `YYYY-MM-DD`.
+    pub date: String,
+    pub text: String,
+}
+
+/// Location of the memory file for `scope`.
+pub fn memory_file_path(scope: MemoryScope, codex_home: &Path, cwd: &Path) -> PathBuf {
+    match scope {
+        MemoryScope::Project => get_git_repo_root(cwd)
+            .unwrap_or_else(|| cwd.to_path_buf())
+            .join(".codex")
+            .join(MEMORY_FILENAME),
+        MemoryScope::User => codex_home.join(MEMORY_FILENAME),
+    }
+}
+
+/// Project entries followed by user entries, each in file order.
+pub fn load_memory(codex_home: &Path, cwd: &Path) -> io::Result<Vec<MemoryEntry>> {
+    let mut entries = Vec::new();
+    for scope in [MemoryScope::Project, MemoryScope::User] {
+        let path = memory_file_path(scope, codex_home, cwd);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        entries.extend(text.lines().filter_map(|line| parse_entry(line, scope)));
+    }
+    Ok(entries)
+}
+
+/// Appends `text` to the memory file of `scope`, creating the file if needed.
+/// Saving a note that is already present returns the existing entry.
+pub fn remember(
+    codex_home: &Path,
+    cwd: &Path,
+    scope: MemoryScope,
+    text: &str,
+) -> io::Result<MemoryEntry> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "memory entry is empty",
+        ));
+    }
+    if text.chars().count() > MAX_ENTRY_CHARS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("memory entry is longer than {MAX_ENTRY_CHARS} characters"),
+        ));
+    }
+
+    let path = memory_file_path(scope, codex_home, cwd);
+    let mut contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => MEMORY_FILE_HEADER.to_string(),
+        Err(e) => return Err(e),
+    };
+    if let Some(existing) = contents
+        .lines()
+        .filter_map(|line| parse_entry(line, scope))
+        .find(|entry| entry.text == text)
+    {
+        return Ok(existing);
+    }
+
+    let entry = MemoryEntry {
+        id: Uuid::new_v4().simple().to_string()[..8].to_string(),
+        scope,
+        date: today(),
+        text,
+    };
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&format_entry(&entry));
+    contents.push('\n');
+
+    write_atomically(&path, &contents)?;
+    Ok(entry)
+}
+
+/// Removes the entry with `id` from whichever memory file contains it.
+pub fn forget(codex_home: &Path, cwd: &Path, id: &str) -> io::Result<Option<MemoryEntry>> {
+    for scope in [MemoryScope::Project, MemoryScope::User] {
+        if let Some(entry) = forget_in_scope(codex_home, cwd, scope, id)? {
+            return Ok(Some(entry));
+        }
+    }
+    Ok(None)
+}
+
+/// Removes the entry with `id` from the memory file of `scope` only.
+pub fn forget_in_scope(
+    codex_home: &Path,
+    cwd: &Path,
+    scope: MemoryScope,
+    id: &str,
+) -> io::Result<Option<MemoryEntry>> {
+    let path = memory_file_path(scope, codex_home, cwd);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut removed = None;
+    let mut kept = String::with_capacity(contents.len());
+    for line in contents.lines() {
+        match parse_entry(line, scope) {
+            Some(entry) if removed.is_none() && entry.id == id => removed = Some(entry),
+            _ => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+    }
+    if removed.is_some() {
+        write_atomically(&path, &kept)?;
+    }
+    Ok(removed)
+}
+
+/// Replaces `path` through a temporary file in the same directory, so that
+/// an interrupted write never leaves a truncated memory file behind.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let mut tmp = NamedTempFile::new_in(dir)?;
+    tmp.write_all(contents.as_bytes())?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        tmp.as_file().set_permissions(metadata.permissions())?;
+    }
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Memory formatted for the user instructions, limited to
+/// `config.memory_max_bytes`. Entries that do not fit are left out.
+pub(crate) fn read_memory_for_instructions(config: &Config) -> Option<String> {
+    if config.memory_max_bytes == 0 {
+        return None;
+    }
+    match load_memory(&config.codex_home, &config.cwd) {
+        Ok(entries) => render_memory(&entries, config.memory_max_bytes),
+        Err(e) => {
+            error!("error reading memory files: {e:#}");
+            None
+        }
+    }
+}
+
+fn render_memory(entries: &[MemoryEntry], max_bytes: usize) -> Option<String> {
+    let mut out = MEMORY_PREAMBLE.to_string();
+    let mut included = 0;
+    for entry in entries {
+        let line = format!(
+            "- [{}] ({}) {}\n",
+            entry.id,
+            entry.scope.label(),
+            entry.text
+        );
+        if out.len() + line.len() > max_bytes {
+            warn!(
+                "Memory exceeds memory_max_bytes ({max_bytes} bytes) - leaving out {} entries.",
+                entries.len() - included
+            );
+            break;
+        }
+        out.push_str(&line);
+        included += 1;
+    }
+    (included > 0).then(|| out.trim_end().to_string())
+}
+
+fn format_entry(entry: &MemoryEntry) -> String {
+    format!("- [{}] {}: {}", entry.id, entry.date, entry.text)
+}
+
+fn parse_entry(line: &str, scope: MemoryScope) -> Option<MemoryEntry> {
+    let rest = line.trim().strip_prefix("- [")?;
+    let (id, rest) = rest.split_once("] ")?;
+    let (date, text) = rest.split_once(": ")?;
+    let valid_id = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric());
+    let valid_date = date.len() == 10 && date.chars().all(|c| c.is_ascii_digit() || c == '-');
+    (valid_id && valid_date && !text.trim().is_empty()).then(|| MemoryEntry {
+        id: id.to_string(),
+        scope,
+        date: date.to_string(),
+        text: text.trim().to_string(),
+    })
+}
+
+fn today() -> String {
+    OffsetDateTime::now_utc()
+        .format(format_description!("[year]-[month]-[day]"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn remembers_and_forgets_entries() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        std::fs::create_dir(project.path().join(".git"))?;
+        let cwd = project.path().join("crates/app");
+        std::fs::create_dir_all(&cwd)?;
+
+        let decision = remember(
+            codex_home.path(),
+            &cwd,
+            MemoryScope::Project,
+            "Sessions are stored in\nSQLite.",
+        )?;
+        let style = remember(
+            codex_home.path(),
+            &cwd,
+            MemoryScope::User,
+            "Prefer small commits.",
+        )?;
+        // Saving the same note again does not duplicate it.
+        let again = remember(
+            codex_home.path(),
+            &cwd,
+            MemoryScope::Project,
+            "Sessions are stored in SQLite.",
+        )?;
+        assert_eq!(again, decision);
+
+        let project_file = project.path().join(".codex/memory.md");
+        let contents = std::fs::read_to_string(&project_file)?;
+        assert!(contents.starts_with("# Codex memory\n"));
+        assert!(contents.ends_with(&format!(
+            "- [{}] {}: Sessions are stored in SQLite.\n",
+            decision.id, decision.date
+        )));
+        assert_eq!(
+            load_memory(codex_home.path(), &cwd)?,
+            vec![decision.clone(), style.clone()]
+        );
+
+        assert_eq!(
+            forget(codex_home.path(), &cwd, &decision.id)?,
+            Some(decision)
+        );
+        assert_eq!(forget(codex_home.path(), &cwd, "missing")?, None);
+        assert_eq!(load_memory(codex_home.path(), &cwd)?, vec![style]);
+        assert!(std::fs::read_to_string(&project_file)?.starts_with("# Codex memory\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn render_respects_byte_budget() {
+        let entry = |id: &str, text: &str| MemoryEntry {
+            id: id.to_string(),
+            scope: MemoryScope::Project,
+            date: "2025-10-01".to_string(),
+            text: text.to_string(),
+        };
+        let entries = vec![entry("a1", "first"), entry("b2", "second")];
+
+        let full = render_memory(&entries, 4096).expect("memory expected");
+        assert_eq!(
+            full,
+            format!("{MEMORY_PREAMBLE}- [a1] (project) first\n- [b2] (project) second")
+        );
+
+        let budget = MEMORY_PREAMBLE.len() + "- [a1] (project) first\n".len();
+        assert_eq!(
+            render_memory(&entries, budget),
+            Some(format!("{MEMORY_PREAMBLE}- [a1] (project) first"))
+        );
+        assert_eq!(render_memory(&entries, MEMORY_PREAMBLE.len()), None);
+    }
+}
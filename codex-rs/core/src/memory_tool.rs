@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::function_tool::FunctionCallError;
+use crate::memory::MemoryScope;
+use crate::memory::forget_in_scope;
+use crate::memory::load_memory;
+use crate::memory::memory_file_path;
+use crate::memory::remember;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::AskForApproval;
+use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxPolicy;
+
+pub(crate) const REMEMBER_TOOL_NAME: &str = "remember";
+pub(crate) const FORGET_TOOL_NAME: &str = "forget";
+
+pub(crate) static REMEMBER_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "text".to_string(),
+        JsonSchema::String {
+            description: Some("The fact to remember, as one short sentence.".to_string()),
+        },
+    );
+    properties.insert(
+        "scope".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "One of: project (default; saved in the repository's .codex/memory.md), user (saved in ~/.codex/memory.md and shared by all projects)"
+                    .to_string(),
+            ),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: REMEMBER_TOOL_NAME.to_string(),
+        description: r#"Saves a note that is included in the instructions of future sessions.
+Use it for durable facts the user would otherwise have to repeat: architectural decisions, conventions, preferences.
+Do not save secrets or details that only matter for the current task."#
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["text".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+});
+
+pub(crate) static FORGET_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("Id of the memory entry, shown in brackets.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: FORGET_TOOL_NAME.to_string(),
+        description: "Removes a saved memory entry that is outdated or wrong.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+});
+
+#[derive(Deserialize)]
+struct RememberArgs {
+    text: String,
+    #[serde(default)]
+    scope: MemoryScope,
+}
+
+#[derive(Deserialize)]
+struct ForgetArgs {
+    id: String,
+}
+
+pub(crate) async fn handle_remember(
+    sess: &Session,
+    turn_context: &TurnContext,
+    codex_home: &Path,
+    sub_id: &str,
+    call_id: &str,
+    arguments: String,
+) -> Result<String, FunctionCallError> {
+    let args: RememberArgs = parse_arguments(&arguments)?;
+    let cwd = &turn_context.cwd;
+    let path = memory_file_path(args.scope, codex_home, cwd);
+    let command = vec![
+        REMEMBER_TOOL_NAME.to_string(),
+        args.scope.label().to_string(),
+        args.text.clone(),
+    ];
+    authorize_change(
+        sess,
+        turn_context,
+        sub_id,
+        call_id,
+        args.scope,
+        &path,
+        command,
+    )
+    .await?;
+
+    let entry = remember(codex_home, cwd, args.scope, &args.text).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to save memory entry: {e}"))
+    })?;
+    Ok(format!("Remembered [{}] in {}", entry.id, path.display()))
+}
+
+pub(crate) async fn handle_forget(
+    sess: &Session,
+    turn_context: &TurnContext,
+    codex_home: &Path,
+    sub_id: &str,
+    call_id: &str,
+    arguments: String,
+) -> Result<String, FunctionCallError> {
+    let args: ForgetArgs = parse_arguments(&arguments)?;
+    let cwd = &turn_context.cwd;
+    let entry = load_memory(codex_home, cwd)
+        .map_err(|e| {
+            FunctionCallError::RespondToModel(format!("failed to read memory entries: {e}"))
+        })?
+        .into_iter()
+        .find(|entry| entry.id == args.id)
+        .ok_or_else(|| {
+            FunctionCallError::RespondToModel(format!("no memory entry with id {}", args.id))
+        })?;
+    let path = memory_file_path(entry.scope, codex_home, cwd);
+    let command = vec![
+        FORGET_TOOL_NAME.to_string(),
+        entry.scope.label().to_string(),
+        entry.text.clone(),
+    ];
+    authorize_change(
+        sess,
+        turn_context,
+        sub_id,
+        call_id,
+        entry.scope,
+        &path,
+        command,
+    )
+    .await?;
+
+    match forget_in_scope(codex_home, cwd, entry.scope, &entry.id) {
+        Ok(Some(entry)) => Ok(format!("Forgot [{}]: {}", entry.id, entry.text)),
+        Ok(None) => Err(FunctionCallError::RespondToModel(format!(
+            "no memory entry with id {}",
+            args.id
+        ))),
+        Err(e) => Err(FunctionCallError::RespondToModel(format!(
+            "failed to remove memory entry: {e}"
+        ))),
+    }
+}
+
+/// Whether changing the memory file of `scope` at `path` has to be confirmed
+/// by the user first. The project memory follows the sandbox while it is in
+/// one of the writable roots for `cwd`; it is not when the session runs in a
+/// subdirectory of the repository. Like any other write outside the sandbox,
+/// changing a memory file elsewhere (such as the user memory) needs the
+/// user's approval.
+fn requires_approval(
+    scope: MemoryScope,
+    path: &Path,
+    cwd: &Path,
+    approval_policy: AskForApproval,
+    sandbox_policy: &SandboxPolicy,
+) -> Result<bool, FunctionCallError> {
+    match (scope, sandbox_policy) {
+        (_, SandboxPolicy::DangerFullAccess) => Ok(false),
+        (MemoryScope::Project, SandboxPolicy::ReadOnly) => Err(FunctionCallError::RespondToModel(
+            "the project memory cannot be changed in read-only mode".to_string(),
+        )),
+        (MemoryScope::Project, SandboxPolicy::WorkspaceWrite { .. })
+            if sandbox_policy
+                .get_writable_roots_with_cwd(cwd)
+                .iter()
+                .any(|root| root.is_path_writable(path)) =>
+        {
+            Ok(false)
+        }
+        _ if approval_policy == AskForApproval::Never => {
+            Err(FunctionCallError::RespondToModel(format!(
+                "the {} memory is outside the sandbox and approvals are disabled",
+                scope.label()
+            )))
+        }
+        _ => Ok(true),
+    }
+}
+
+async fn authorize_change(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    call_id: &str,
+    scope: MemoryScope,
+    path: &Path,
+    command: Vec<String>,
+) -> Result<(), FunctionCallError> {
+    if !requires_approval(
+        scope,
+        path,
+        &turn_context.cwd,
+        turn_context.approval_policy,
+        &turn_context.sandbox_policy,
+    )? {
+        return Ok(());
+    }
+    let decision = sess
+        .request_command_approval(
+            sub_id.to_string(),
+            call_id.to_string(),
+            command,
+            turn_context.cwd.clone(),
+            Some(format!(
+                "Change {}, which is included in every future session",
+                path.display()
+            )),
+        )
+        .await;
+    match decision {
+        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => Ok(()),
+        ReviewDecision::Denied | ReviewDecision::Abort => Err(FunctionCallError::RespondToModel(
+            "the user rejected the memory change".to_string(),
+        )),
+    }
+}
+
+fn parse_arguments<'a, T: Deserialize<'a>>(arguments: &'a str) -> Result<T, FunctionCallError> {
+    serde_json::from_str(arguments).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn workspace_write() -> SandboxPolicy {
+        SandboxPolicy::new_workspace_write_policy()
+    }
+
+    fn check(
+        scope: MemoryScope,
+        cwd: &str,
+        approval_policy: AskForApproval,
+        sandbox_policy: &SandboxPolicy,
+    ) -> Result<bool, FunctionCallError> {
+        let path = match scope {
+            MemoryScope::Project => Path::new("/repo/.codex/memory.md"),
+            MemoryScope::User => Path::new("/home/user/.codex/memory.md"),
+        };
+        requires_approval(scope, path, Path::new(cwd), approval_policy, sandbox_policy)
+    }
+
+    #[test]
+    fn project_memory_follows_the_sandbox() {
+        assert!(
+            check(
+                MemoryScope::Project,
+                "/repo",
+                AskForApproval::OnRequest,
+                &SandboxPolicy::ReadOnly
+            )
+            .is_err()
+        );
+        assert_eq!(
+            check(
+                MemoryScope::Project,
+                "/repo",
+                AskForApproval::Never,
+                &workspace_write()
+            )
+            .ok(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn project_memory_above_the_cwd_needs_approval() {
+        assert_eq!(
+            check(
+                MemoryScope::Project,
+                "/repo/app",
+                AskForApproval::OnRequest,
+                &workspace_write()
+            )
+            .ok(),
+            Some(true)
+        );
+        assert!(
+            check(
+                MemoryScope::Project,
+                "/repo/app",
+                AskForApproval::Never,
+                &workspace_write()
+            )
+            .is_err()
+        );
+        let repo_writable = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/repo")],
+            network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+        };
+        assert_eq!(
+            check(
+                MemoryScope::Project,
+                "/repo/app",
+                AskForApproval::Never,
+                &repo_writable
+            )
+            .ok(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn user_memory_needs_approval_outside_full_access() {
+        assert_eq!(
+            check(
+                MemoryScope::User,
+                "/repo",
+                AskForApproval::OnRequest,
+                &SandboxPolicy::ReadOnly
+            )
+            .ok(),
+            Some(true)
+        );
+        assert_eq!(
+            check(
+                MemoryScope::User,
+                "/repo",
+                AskForApproval::UnlessTrusted,
+                &workspace_write()
+            )
+            .ok(),
+            Some(true)
+        );
+        assert!(
+            check(
+                MemoryScope::User,
+                "/repo",
+                AskForApproval::Never,
+                &workspace_write()
+            )
+            .is_err()
+        );
+        assert_eq!(
+            check(
+                MemoryScope::User,
+                "/repo",
+                AskForApproval::Never,
+                &SandboxPolicy::DangerFullAccess
+            )
+            .ok(),
+            Some(false)
+        );
+    }
+}
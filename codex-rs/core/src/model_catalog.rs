@@ -0,0 +1,240 @@
+//! Model capabilities declared under `[models.<slug>]` in `config.toml`.
+//!
+//! Entries are layered over the built-in tables in [`crate::model_family`] and
+//! [`crate::openai_model_info`] so that new models can be described without
+//! waiting for a release.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config_types::ModelCapabilitiesToml;
+use crate::config_types::ModelPricing;
+use crate::model_family::ModelFamily;
+use crate::model_family::derive_default_model_family;
+use crate::model_family::find_family_for_model;
+use crate::openai_model_info::ModelInfo;
+use crate::openai_model_info::get_model_info;
+use crate::protocol::TokenUsage;
+
+/// A `[models.<slug>]` entry. `base_instructions_file` has been resolved
+/// against the config's cwd but is only read once the model is used, so a
+/// missing file only matters for the models that need it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModelCatalogEntry {
+    pub capabilities: ModelCapabilitiesToml,
+    pub base_instructions_file: Option<PathBuf>,
+}
+
+/// User-declared model entries, keyed by exact model slug.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModelCatalog {
+    entries: HashMap<String, ModelCatalogEntry>,
+}
+
+impl ModelCatalog {
+    pub fn new(entries: HashMap<String, ModelCatalogEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Returns the model family for `slug`: the built-in family (if any) with
+    /// the matching catalog entry applied on top. Returns `None` only when
+    /// neither knows about the slug. An unreadable `base_instructions_file`
+    /// is logged and the built-in instructions are kept; use
+    /// [`Self::base_instructions`] to surface the error instead.
+    pub fn find_family(&self, slug: &str) -> Option<ModelFamily> {
+        let built_in = find_family_for_model(slug);
+        let Some(entry) = self.entries.get(slug) else {
+            return built_in;
+        };
+
+        let mut family = built_in.unwrap_or_else(|| derive_default_model_family(slug));
+        let caps = &entry.capabilities;
+        if let Some(value) = caps.supports_reasoning_summaries {
+            family.supports_reasoning_summaries = value;
+        }
+        if let Some(value) = &caps.reasoning_summary_format {
+            family.reasoning_summary_format = value.clone();
+        }
+        if let Some(value) = caps.supports_reasoning_effort {
+            family.supports_reasoning_effort = value;
+        }
+        if let Some(value) = caps.supports_verbosity {
+            family.supports_verbosity = value;
+        }
+        if let Some(value) = &caps.apply_patch_tool_type {
+            family.apply_patch_tool_type = Some(value.clone());
+        }
+        if let Some(value) = caps.needs_special_apply_patch_instructions {
+            family.needs_special_apply_patch_instructions = value;
+        }
+        if let Some(value) = caps.uses_local_shell_tool {
+            family.uses_local_shell_tool = value;
+        }
+        match self.base_instructions(slug) {
+            Ok(Some(value)) => family.base_instructions = value,
+            Ok(None) => {}
+            Err(e) => tracing::warn!("{e}"),
+        }
+        Some(family)
+    }
+
+    /// Reads the `base_instructions_file` declared for `slug`, if any.
+    pub fn base_instructions(&self, slug: &str) -> std::io::Result<Option<String>> {
+        let Some(path) = self
+            .entries
+            .get(slug)
+            .and_then(|entry| entry.base_instructions_file.as_ref())
+        else {
+            return Ok(None);
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!(
+                    "failed to read base instructions file {} for model {slug}: {e}",
+                    path.display()
+                ),
+            )
+        })?;
+        let contents = contents.trim();
+        if contents.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("base instructions file is empty: {}", path.display()),
+            ));
+        }
+        Ok(Some(contents.to_string()))
+    }
+
+    /// Returns the pricing declared for `slug`, if any.
+    pub fn pricing(&self, slug: &str) -> Option<&ModelPricing> {
+        self.entries.get(slug)?.capabilities.pricing.as_ref()
+    }
+
+    /// Returns the context/output limits for `model_family`, with the
+    /// matching catalog entry applied over the built-in values.
+    pub(crate) fn model_info(&self, model_family: &ModelFamily) -> Option<ModelInfo> {
+        let built_in = get_model_info(model_family);
+        let Some(entry) = self.entries.get(&model_family.slug) else {
+            return built_in;
+        };
+
+        let mut info = built_in.unwrap_or_default();
+        let caps = &entry.capabilities;
+        info.context_window = caps.context_window.or(info.context_window);
+        info.max_output_tokens = caps.max_output_tokens.or(info.max_output_tokens);
+        info.auto_compact_token_limit = caps
+            .auto_compact_token_limit
+            .or(info.auto_compact_token_limit);
+        Some(info)
+    }
+}
+
+impl ModelPricing {
+    /// Cost of `usage` in USD.
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        let reasoning_tokens = usage.reasoning_output_tokens.min(usage.output_tokens);
+        let output_tokens = usage.output_tokens - reasoning_tokens;
+
+        let total = usage.non_cached_input() as f64 * self.input
+            + usage.cached_input() as f64 * self.cached_input.unwrap_or(self.input)
+            + output_tokens as f64 * self.output
+            + reasoning_tokens as f64 * self.reasoning.unwrap_or(self.output);
+        total / 1_000_000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_apply_patch::ApplyPatchToolType;
+    use pretty_assertions::assert_eq;
+
+    fn catalog(slug: &str, capabilities: ModelCapabilitiesToml) -> ModelCatalog {
+        ModelCatalog::new(HashMap::from([(
+            slug.to_string(),
+            ModelCatalogEntry {
+                capabilities,
+                base_instructions_file: None,
+            },
+        )]))
+    }
+
+    #[test]
+    fn entry_overrides_built_in_family_and_info() {
+        let catalog = catalog(
+            "grok-code-fast-1",
+            ModelCapabilitiesToml {
+                context_window: Some(300_000),
+                supports_reasoning_effort: Some(true),
+                ..Default::default()
+            },
+        );
+
+        let family = catalog
+            .find_family("grok-code-fast-1")
+            .expect("known model");
+        assert!(family.supports_reasoning_effort);
+        // Untouched capabilities keep their built-in values.
+        assert_eq!(
+            family.apply_patch_tool_type,
+            Some(ApplyPatchToolType::Function)
+        );
+
+        let info = catalog.model_info(&family).expect("model info");
+        assert_eq!(info.context_window, Some(300_000));
+        assert_eq!(info.max_output_tokens, None);
+    }
+
+    #[test]
+    fn entry_describes_unknown_model() {
+        let catalog = catalog(
+            "acme-coder",
+            ModelCapabilitiesToml {
+                context_window: Some(64_000),
+                max_output_tokens: Some(8_000),
+                uses_local_shell_tool: Some(true),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(ModelCatalog::default().find_family("acme-coder"), None);
+
+        let family = catalog.find_family("acme-coder").expect("catalog model");
+        assert_eq!(family.family, "acme-coder");
+        assert!(family.uses_local_shell_tool);
+
+        let info = catalog.model_info(&family).expect("model info");
+        assert_eq!(info.context_window, Some(64_000));
+        assert_eq!(info.max_output_tokens, Some(8_000));
+    }
+
+    #[test]
+    fn pricing_splits_cached_input_and_reasoning() {
+        let catalog = catalog(
+            "grok-code-fast-1",
+            ModelCapabilitiesToml {
+                pricing: Some(ModelPricing {
+                    input: 0.2,
+                    cached_input: Some(0.02),
+                    output: 1.5,
+                    reasoning: None,
+                }),
+                ..Default::default()
+            },
+        );
+        assert_eq!(catalog.pricing("grok-4"), None);
+
+        let pricing = catalog.pricing("grok-code-fast-1").expect("pricing");
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            cached_input_tokens: 500_000,
+            output_tokens: 200_000,
+            reasoning_output_tokens: 100_000,
+            total_tokens: 1_200_000,
+        };
+        // 0.5M * 0.2 + 0.5M * 0.02 + 0.2M * 1.5
+        let cost = pricing.cost_usd(&usage);
+        assert!((cost - 0.41).abs() < 1e-9, "unexpected cost: {cost}");
+    }
+}
@@ -26,6 +26,14 @@ pub struct ModelFamily {
     // `summary` is optional).
     pub supports_reasoning_summaries: bool,
 
+    /// Whether the Chat Completions `reasoning_effort` parameter is accepted
+    /// by this model family.
+    pub supports_reasoning_effort: bool,
+
+    /// Whether the Responses API `text.verbosity` parameter is accepted by
+    /// this model family.
+    pub supports_verbosity: bool,
+
     // Define if we need a special handling of reasoning summary
     pub reasoning_summary_format: ReasoningSummaryFormat,
 
@@ -53,6 +61,8 @@ macro_rules! model_family {
             family: $family.to_string(),
             needs_special_apply_patch_instructions: false,
             supports_reasoning_summaries: false,
+            supports_reasoning_effort: false,
+            supports_verbosity: false,
             reasoning_summary_format: ReasoningSummaryFormat::None,
             uses_local_shell_tool: false,
             apply_patch_tool_type: None,
@@ -110,8 +120,15 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
         model_family!(
             slug, "gpt-5",
             supports_reasoning_summaries: true,
+            supports_verbosity: true,
             needs_special_apply_patch_instructions: true,
         )
+    } else if slug.starts_with("grok-3-mini") {
+        model_family!(
+            slug, "grok",
+            supports_reasoning_effort: true,
+            apply_patch_tool_type: Some(ApplyPatchToolType::Function)
+        )
     } else if slug.starts_with("grok-") {
         model_family!(
             slug, "grok",
@@ -128,6 +145,8 @@ pub fn derive_default_model_family(model: &str) -> ModelFamily {
         family: model.to_string(),
         needs_special_apply_patch_instructions: false,
         supports_reasoning_summaries: false,
+        supports_reasoning_effort: false,
+        supports_verbosity: false,
         reasoning_summary_format: ReasoningSummaryFormat::None,
         uses_local_shell_tool: false,
         apply_patch_tool_type: None,
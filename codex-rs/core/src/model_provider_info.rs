@@ -39,6 +39,59 @@ pub enum WireApi {
     Chat,
 }
 
+/// An entry of the `fallback_providers` list: a provider to switch to when the
+/// active one keeps failing with retryable errors. Written in config as
+/// `"<provider_id>"` or `"<provider_id>/<model>"`; without a model the
+/// session's model is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FallbackProvider {
+    pub provider_id: String,
+    pub provider: ModelProviderInfo,
+    pub model: Option<String>,
+}
+
+/// Response headers a Chat Completions provider uses to report its rate
+/// limits, in the `x-ratelimit-*` style. Each window becomes one row of the
+/// rate-limit snapshot shown in `/status`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RateLimitHeaders {
+    pub primary: Option<RateLimitWindowHeaders>,
+    pub secondary: Option<RateLimitWindowHeaders>,
+}
+
+/// Header names describing a single rate-limit window.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct RateLimitWindowHeaders {
+    /// Header with the total allowance for the window, e.g.
+    /// `x-ratelimit-limit-tokens`.
+    pub limit: String,
+    /// Header with the allowance left in the window.
+    pub remaining: String,
+    /// Header with the time until the window resets, either in seconds or as
+    /// a duration such as `6m0s`.
+    pub reset: Option<String>,
+    /// Length of the window in minutes. Providers do not report this, so it
+    /// is configured alongside the header names.
+    pub window_minutes: Option<u64>,
+}
+
+impl Default for RateLimitHeaders {
+    /// The headers sent by OpenAI-compatible APIs such as xAI: requests per
+    /// minute as the primary window and tokens per minute as the secondary.
+    fn default() -> Self {
+        let window = |kind: &str| RateLimitWindowHeaders {
+            limit: format!("x-ratelimit-limit-{kind}"),
+            remaining: format!("x-ratelimit-remaining-{kind}"),
+            reset: Some(format!("x-ratelimit-reset-{kind}")),
+            window_minutes: Some(1),
+        };
+        Self {
+            primary: Some(window("requests")),
+            secondary: Some(window("tokens")),
+        }
+    }
+}
+
 /// Serializable representation of a provider definition.
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct ModelProviderInfo {
@@ -86,6 +139,30 @@ pub struct ModelProviderInfo {
     /// and API key (if needed) comes from the "env_key" environment variable.
     #[serde(default)]
     pub requires_openai_auth: bool,
+
+    /// Chat Completions only: name of the field in streamed deltas (and the
+    /// final message) that carries the model's reasoning text, e.g.
+    /// `"reasoning_content"` for xAI. When unset, both `reasoning` and
+    /// `reasoning_content` are recognized.
+    pub reasoning_content_key: Option<String>,
+
+    /// Chat Completions only: response headers that report rate limits. When
+    /// unset, the standard `x-ratelimit-{limit,remaining,reset}-{requests,tokens}`
+    /// headers are read.
+    pub rate_limit_headers: Option<RateLimitHeaders>,
+
+    /// Chat Completions only: whether the provider accepts a `json_schema`
+    /// `response_format`. Required for `--output-schema`; the Responses API
+    /// always supports it.
+    #[serde(default)]
+    pub supports_response_format: bool,
+
+    /// Chat Completions only: whether the provider accepts
+    /// `stream_options.include_usage` and reports token usage in a trailing
+    /// chunk. Some OpenAI-compatible servers reject unknown fields, so this is
+    /// opt-in.
+    #[serde(default)]
+    pub supports_stream_usage: bool,
 }
 
 impl ModelProviderInfo {
@@ -245,8 +322,26 @@ impl ModelProviderInfo {
             .map(Duration::from_millis)
             .unwrap_or(Duration::from_millis(DEFAULT_STREAM_IDLE_TIMEOUT_MS))
     }
+
+    /// Field names that may carry reasoning text in Chat Completions chunks.
+    pub(crate) fn reasoning_content_keys(&self) -> Vec<String> {
+        match &self.reasoning_content_key {
+            Some(key) => vec![key.clone()],
+            None => DEFAULT_REASONING_CONTENT_KEYS
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+
+    /// Rate-limit headers to read from Chat Completions responses.
+    pub(crate) fn rate_limit_headers(&self) -> RateLimitHeaders {
+        self.rate_limit_headers.clone().unwrap_or_default()
+    }
 }
 
+const DEFAULT_REASONING_CONTENT_KEYS: &[&str] = &["reasoning", "reasoning_content"];
+
 const DEFAULT_OLLAMA_PORT: u32 = 11434;
 
 pub const BUILT_IN_OSS_MODEL_PROVIDER_ID: &str = "oss";
@@ -274,6 +369,10 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 stream_max_retries: Some(10),
                 stream_idle_timeout_ms: None,
                 requires_openai_auth: false,
+                reasoning_content_key: Some("reasoning_content".into()),
+                rate_limit_headers: None,
+                supports_response_format: true,
+                supports_stream_usage: true,
             },
         ),
         (
@@ -313,6 +412,10 @@ pub fn built_in_model_providers() -> HashMap<String, ModelProviderInfo> {
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
                 requires_openai_auth: true,
+                reasoning_content_key: None,
+                rate_limit_headers: None,
+                supports_response_format: false,
+                supports_stream_usage: true,
             },
         ),
         (BUILT_IN_OSS_MODEL_PROVIDER_ID, create_oss_provider()),
@@ -357,6 +460,10 @@ pub fn create_oss_provider_with_base_url(base_url: &str) -> ModelProviderInfo {
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
     }
 }
 
@@ -396,6 +503,10 @@ base_url = "http://localhost:11434/v1"
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -425,6 +536,10 @@ query_params = { api-version = "2025-04-01-preview" }
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -457,6 +572,10 @@ env_http_headers = { "X-Example-Env-Header" = "EXAMPLE_ENV_VAR" }
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(azure_provider_toml).unwrap();
@@ -486,6 +605,10 @@ stream_max_retries = 10
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
 
         let provider: ModelProviderInfo = toml::from_str(xai_provider_toml).unwrap();
@@ -508,6 +631,10 @@ stream_max_retries = 10
                 stream_max_retries: None,
                 stream_idle_timeout_ms: None,
                 requires_openai_auth: false,
+                reasoning_content_key: None,
+                rate_limit_headers: None,
+                supports_response_format: false,
+                supports_stream_usage: false,
             }
         }
 
@@ -540,6 +667,10 @@ stream_max_retries = 10
             stream_max_retries: None,
             stream_idle_timeout_ms: None,
             requires_openai_auth: false,
+            reasoning_content_key: None,
+            rate_limit_headers: None,
+            supports_response_format: false,
+            supports_stream_usage: false,
         };
         assert!(named_provider.is_azure_responses_endpoint());
 
@@ -0,0 +1,360 @@
+//! HTTP(S) proxy through which sandboxed commands reach `allowed_domains`.
+//!
+//! The proxy listens on a unix socket that `codex-linux-sandbox` bridges into
+//! the command's otherwise empty network namespace. It accepts `CONNECT`
+//! tunnels and absolute-form plain HTTP requests, lets through the hosts on
+//! the allowlist and reports every request as a [`NetworkRequestEvent`].
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tempfile::TempDir;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::net::TcpStream;
+use tokio::net::UnixListener;
+use tokio::net::UnixStream;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::info;
+
+use crate::exec::StdoutStream;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::NetworkRequestEvent;
+
+/// Upper bound on the request line plus headers read before forwarding.
+const MAX_REQUEST_HEAD_BYTES: usize = 64 * 1024;
+
+const DEFAULT_HTTP_PORT: u16 = 80;
+
+/// Proxy for a single command. It stops accepting connections when dropped.
+#[derive(Debug)]
+pub(crate) struct NetworkProxy {
+    socket_path: PathBuf,
+    accept_task: JoinHandle<()>,
+    _dir: TempDir,
+}
+
+impl NetworkProxy {
+    pub(crate) fn start(
+        allowed_domains: &[String],
+        events: Option<StdoutStream>,
+    ) -> std::io::Result<Self> {
+        let dir = tempfile::Builder::new().prefix("codex-proxy-").tempdir()?;
+        let socket_path = dir.path().join("proxy.sock");
+        let listener = UnixListener::bind(&socket_path)?;
+        let allowlist = Arc::new(DomainAllowlist::new(allowed_domains));
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let allowlist = allowlist.clone();
+                let events = events.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, &allowlist, events.as_ref()).await {
+                        debug!("network proxy connection failed: {e}");
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            socket_path,
+            accept_task,
+            _dir: dir,
+        })
+    }
+
+    pub(crate) fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+}
+
+impl Drop for NetworkProxy {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
+
+/// `example.com` allows that host only; `*.example.com` allows any of its
+/// subdomains.
+#[derive(Debug)]
+struct DomainAllowlist {
+    hosts: Vec<String>,
+    parent_domains: Vec<String>,
+}
+
+impl DomainAllowlist {
+    fn new(allowed_domains: &[String]) -> Self {
+        let mut hosts = Vec::new();
+        let mut parent_domains = Vec::new();
+        for domain in allowed_domains {
+            let domain = normalize_host(domain);
+            match domain.strip_prefix("*.") {
+                Some(parent) => parent_domains.push(parent.to_string()),
+                None => hosts.push(domain),
+            }
+        }
+        Self {
+            hosts,
+            parent_domains,
+        }
+    }
+
+    fn allows(&self, host: &str) -> bool {
+        let host = normalize_host(host);
+        self.hosts.contains(&host)
+            || self.parent_domains.iter().any(|parent| {
+                host.strip_suffix(parent.as_str())
+                    .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.'))
+            })
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[derive(Debug, PartialEq)]
+struct ProxyRequest {
+    method: String,
+    host: String,
+    port: u16,
+}
+
+/// Parses the request line of `CONNECT host:port` and of absolute-form
+/// requests such as `GET http://host/path`. Other requests are rejected.
+fn parse_request_line(line: &str) -> Option<ProxyRequest> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    let (host, port) = if method.eq_ignore_ascii_case("CONNECT") {
+        split_host_port(target, None)?
+    } else {
+        let rest = target.strip_prefix("http://")?;
+        let authority = rest.split(['/', '?', '#']).next()?;
+        split_host_port(authority, Some(DEFAULT_HTTP_PORT))?
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some(ProxyRequest {
+        method: method.to_ascii_uppercase(),
+        host,
+        port,
+    })
+}
+
+fn split_host_port(authority: &str, default_port: Option<u16>) -> Option<(String, u16)> {
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => {
+            let (host, after) = rest.split_once(']')?;
+            (host, after.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port?,
+    };
+    Some((host.to_string(), port))
+}
+
+async fn handle_connection(
+    stream: UnixStream,
+    allowlist: &DomainAllowlist,
+    events: Option<&StdoutStream>,
+) -> std::io::Result<()> {
+    let mut client = BufReader::new(stream);
+    let mut head = String::new();
+    loop {
+        let read = client.read_line(&mut head).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        if head.ends_with("\r\n\r\n") || head.ends_with("\n\n") {
+            break;
+        }
+        if head.len() > MAX_REQUEST_HEAD_BYTES {
+            return respond(client.get_mut(), "431 Request Header Fields Too Large").await;
+        }
+    }
+
+    let Some(request) = head.lines().next().and_then(parse_request_line) else {
+        return respond(client.get_mut(), "400 Bad Request").await;
+    };
+    let allowed = allowlist.allows(&request.host);
+    info!(
+        "network proxy: {} {}:{} {}",
+        request.method,
+        request.host,
+        request.port,
+        if allowed { "allowed" } else { "blocked" }
+    );
+    if let Some(events) = events {
+        let event = Event {
+            id: events.sub_id.clone(),
+            msg: EventMsg::NetworkRequest(NetworkRequestEvent {
+                call_id: events.call_id.clone(),
+                method: request.method.clone(),
+                host: request.host.clone(),
+                port: request.port,
+                allowed,
+            }),
+        };
+        let _ = events.tx_event.send(event).await;
+    }
+    if !allowed {
+        return respond(client.get_mut(), "403 Forbidden").await;
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            debug!("network proxy could not reach {}: {e}", request.host);
+            return respond(client.get_mut(), "502 Bad Gateway").await;
+        }
+    };
+    if request.method == "CONNECT" {
+        client
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await?;
+    } else {
+        upstream.write_all(head.as_bytes()).await?;
+    }
+    // Bytes the client sent right after the head are still in the reader.
+    upstream.write_all(client.buffer()).await?;
+    let mut client = client.into_inner();
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+async fn respond(stream: &mut UnixStream, status: &str) -> std::io::Result<()> {
+    let body = format!("codex network proxy: {status}\n");
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn allowlist_matches_hosts_and_subdomains() {
+        let allowlist = DomainAllowlist::new(&["crates.io".to_string(), "*.npmjs.org".to_string()]);
+
+        assert!(allowlist.allows("crates.io"));
+        assert!(allowlist.allows("Crates.IO."));
+        assert!(!allowlist.allows("static.crates.io"));
+        assert!(allowlist.allows("registry.npmjs.org"));
+        assert!(!allowlist.allows("npmjs.org"));
+        assert!(!allowlist.allows("evilnpmjs.org"));
+        assert!(!allowlist.allows("example.com"));
+    }
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        assert_eq!(
+            parse_request_line("CONNECT index.crates.io:443 HTTP/1.1"),
+            Some(ProxyRequest {
+                method: "CONNECT".to_string(),
+                host: "index.crates.io".to_string(),
+                port: 443,
+            })
+        );
+        assert_eq!(
+            parse_request_line("get http://example.com/a/b?c HTTP/1.1"),
+            Some(ProxyRequest {
+                method: "GET".to_string(),
+                host: "example.com".to_string(),
+                port: 80,
+            })
+        );
+        assert_eq!(
+            parse_request_line("CONNECT [::1]:8443 HTTP/1.1"),
+            Some(ProxyRequest {
+                method: "CONNECT".to_string(),
+                host: "::1".to_string(),
+                port: 8443,
+            })
+        );
+        assert_eq!(parse_request_line("CONNECT example.com HTTP/1.1"), None);
+        assert_eq!(parse_request_line("GET /relative HTTP/1.1"), None);
+    }
+
+    async fn send_connect(proxy: &NetworkProxy, target: &str) -> anyhow::Result<UnixStream> {
+        let mut stream = UnixStream::connect(proxy.socket_path()).await?;
+        stream
+            .write_all(format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n\r\n").as_bytes())
+            .await?;
+        Ok(stream)
+    }
+
+    #[tokio::test]
+    async fn tunnels_to_allowed_hosts_and_reports_every_request() -> anyhow::Result<()> {
+        let upstream = TcpListener::bind("127.0.0.1:0").await?;
+        let target = upstream.local_addr()?.to_string();
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = upstream.accept().await {
+                let mut buf = [0u8; 4];
+                if socket.read_exact(&mut buf).await.is_ok() {
+                    let _ = socket.write_all(&buf).await;
+                }
+            }
+        });
+
+        let (tx_event, rx_event) = async_channel::unbounded();
+        let proxy = NetworkProxy::start(
+            &["127.0.0.1".to_string()],
+            Some(StdoutStream {
+                sub_id: "sub".to_string(),
+                call_id: "call".to_string(),
+                tx_event,
+            }),
+        )?;
+        let mut stream = send_connect(&proxy, &target).await?;
+        let mut reply = [0u8; 39];
+        stream.read_exact(&mut reply).await?;
+        assert_eq!(&reply, b"HTTP/1.1 200 Connection Established\r\n\r\n");
+        stream.write_all(b"ping").await?;
+        let mut echo = [0u8; 4];
+        stream.read_exact(&mut echo).await?;
+        assert_eq!(&echo, b"ping");
+
+        let mut stream = send_connect(&proxy, "example.com:443").await?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await?;
+        assert!(reply.starts_with("HTTP/1.1 403 Forbidden"), "{reply}");
+
+        let mut seen = Vec::new();
+        for _ in 0..2 {
+            let Event { msg, .. } = rx_event.recv().await?;
+            let EventMsg::NetworkRequest(request) = msg else {
+                panic!("unexpected event: {msg:?}");
+            };
+            seen.push((request.host, request.allowed));
+        }
+        assert_eq!(
+            seen,
+            vec![
+                ("127.0.0.1".to_string(), true),
+                ("example.com".to_string(), false),
+            ]
+        );
+        Ok(())
+    }
+}
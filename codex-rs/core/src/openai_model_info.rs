@@ -1,17 +1,19 @@
 use crate::model_family::ModelFamily;
 
-/// Metadata about a model, particularly OpenAI models.
+/// Metadata about a model, particularly OpenAI models. Entries from the
+/// `[models.<slug>]` config table are layered on top of these built-ins by
+/// [`crate::model_catalog::ModelCatalog::model_info`].
 /// We may want to consider including details like the pricing for
 /// input tokens, output tokens, etc., though users will need to be able to
 /// override this in config.toml, as this information can get out of date.
 /// Though this would help present more accurate pricing information in the UI.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub(crate) struct ModelInfo {
     /// Size of the context window in tokens. This is the maximum size of the input context.
-    pub(crate) context_window: u64,
+    pub(crate) context_window: Option<u64>,
 
     /// Maximum number of output tokens that can be generated for the model.
-    pub(crate) max_output_tokens: u64,
+    pub(crate) max_output_tokens: Option<u64>,
 
     /// Token threshold where we should automatically compact conversation history. This considers
     /// input tokens + output tokens of this turn.
@@ -21,8 +23,18 @@ pub(crate) struct ModelInfo {
 impl ModelInfo {
     const fn new(context_window: u64, max_output_tokens: u64) -> Self {
         Self {
-            context_window,
-            max_output_tokens,
+            context_window: Some(context_window),
+            max_output_tokens: Some(max_output_tokens),
+            auto_compact_token_limit: None,
+        }
+    }
+
+    /// For models whose output shares the context window rather than having a
+    /// separate documented cap.
+    const fn with_context_window(context_window: u64) -> Self {
+        Self {
+            context_window: Some(context_window),
+            max_output_tokens: None,
             auto_compact_token_limit: None,
         }
     }
@@ -63,8 +75,8 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         "gpt-3.5-turbo" => Some(ModelInfo::new(16_385, 4_096)),
 
         _ if slug.starts_with("gpt-5-codex") => Some(ModelInfo {
-            context_window: 272_000,
-            max_output_tokens: 128_000,
+            context_window: Some(272_000),
+            max_output_tokens: Some(128_000),
             auto_compact_token_limit: Some(350_000),
         }),
 
@@ -72,6 +84,13 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
 
         _ if slug.starts_with("codex-") => Some(ModelInfo::new(272_000, 128_000)),
 
+        // https://docs.x.ai/docs/models
+        _ if slug.starts_with("grok-code-fast-1") => Some(ModelInfo::with_context_window(256_000)),
+        _ if slug.starts_with("grok-4-fast") => Some(ModelInfo::with_context_window(2_000_000)),
+        _ if slug.starts_with("grok-4") => Some(ModelInfo::with_context_window(256_000)),
+        _ if slug.starts_with("grok-3") => Some(ModelInfo::with_context_window(131_072)),
+        "grok-2-1212" => Some(ModelInfo::with_context_window(131_072)),
+
         _ => None,
     }
 }
@@ -5,8 +5,12 @@ use serde_json::json;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 
+use crate::delegate_task::DELEGATE_TASK_TOOL;
+use crate::memory_tool::FORGET_TOOL;
+use crate::memory_tool::REMEMBER_TOOL;
 use crate::model_family::ModelFamily;
 use crate::plan_tool::PLAN_TOOL;
+use crate::session_search_tool::SEARCH_PAST_SESSIONS_TOOL;
 use crate::tool_apply_patch::ApplyPatchToolType;
 use crate::tool_apply_patch::create_apply_patch_freeform_tool;
 use crate::tool_apply_patch::create_apply_patch_json_tool;
@@ -67,6 +71,9 @@ pub(crate) struct ToolsConfig {
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
+    pub search_past_sessions: bool,
+    pub memory_tools: bool,
+    pub delegate_task: bool,
     pub experimental_unified_exec_tool: bool,
 }
 
@@ -77,6 +84,9 @@ pub(crate) struct ToolsConfigParams<'a> {
     pub(crate) include_web_search_request: bool,
     pub(crate) use_streamable_shell_tool: bool,
     pub(crate) include_view_image_tool: bool,
+    pub(crate) include_search_past_sessions: bool,
+    pub(crate) include_memory_tools: bool,
+    pub(crate) include_delegate_task: bool,
     pub(crate) experimental_unified_exec_tool: bool,
 }
 
@@ -89,6 +99,9 @@ impl ToolsConfig {
             include_web_search_request,
             use_streamable_shell_tool,
             include_view_image_tool,
+            include_search_past_sessions,
+            include_memory_tools,
+            include_delegate_task,
             experimental_unified_exec_tool,
         } = params;
         let shell_type = if *use_streamable_shell_tool {
@@ -117,6 +130,9 @@ impl ToolsConfig {
             apply_patch_tool_type,
             web_search_request: *include_web_search_request,
             include_view_image_tool: *include_view_image_tool,
+            search_past_sessions: *include_search_past_sessions,
+            memory_tools: *include_memory_tools,
+            delegate_task: *include_delegate_task,
             experimental_unified_exec_tool: *experimental_unified_exec_tool,
         }
     }
@@ -526,6 +542,20 @@ pub(crate) fn get_openai_tools(
     if config.include_view_image_tool {
         tools.push(create_view_image_tool());
     }
+
+    if config.search_past_sessions {
+        tools.push(SEARCH_PAST_SESSIONS_TOOL.clone());
+    }
+
+    if config.memory_tools {
+        tools.push(REMEMBER_TOOL.clone());
+        tools.push(FORGET_TOOL.clone());
+    }
+
+    if config.delegate_task {
+        tools.push(DELEGATE_TASK_TOOL.clone());
+    }
+
     if let Some(mcp_tools) = mcp_tools {
         // Ensure deterministic ordering to maximize prompt cache hits.
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
@@ -587,6 +617,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -597,6 +630,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_openai_tools_with_session_search() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            include_search_past_sessions: true,
+            include_memory_tools: false,
+            include_delegate_task: false,
+            experimental_unified_exec_tool: true,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(&tools, &["unified_exec", "search_past_sessions"]);
+    }
+
+    #[test]
+    fn test_get_openai_tools_with_memory_tools() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            include_search_past_sessions: false,
+            include_memory_tools: true,
+            include_delegate_task: false,
+            experimental_unified_exec_tool: true,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(&tools, &["unified_exec", "remember", "forget"]);
+    }
+
+    #[test]
+    fn test_get_openai_tools_with_delegate_task() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: false,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: true,
+            experimental_unified_exec_tool: true,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(&tools, &["unified_exec", "delegate_task"]);
+    }
+
     #[test]
     fn test_get_openai_tools_default_shell() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
@@ -607,6 +700,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -627,6 +723,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(
@@ -731,6 +830,9 @@ mod tests {
             include_web_search_request: false,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -807,6 +909,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -868,6 +973,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -924,6 +1032,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -983,6 +1094,9 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_search_past_sessions: false,
+            include_memory_tools: false,
+            include_delegate_task: false,
             experimental_unified_exec_tool: true,
         });
 
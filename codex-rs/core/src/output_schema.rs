@@ -0,0 +1,404 @@
+//! Local validation of the final assistant message against the JSON schema
+//! passed with `--output-schema`.
+//!
+//! Chat Completions providers do not all enforce `response_format` strictly,
+//! so the message is checked before the turn ends. Only the keywords that
+//! structured outputs accept are understood: `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`, `anyOf` and
+//! local `$ref`s into `$defs`/`definitions`, plus annotations such as
+//! `description`. Schemas using any other keyword are rejected when they are
+//! loaded, rather than having the keyword silently ignored.
+
+use serde_json::Map;
+use serde_json::Value;
+
+/// Keywords that constrain the document and are checked by the validator.
+const VALIDATED_KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "anyOf",
+    "$ref",
+    "$defs",
+    "definitions",
+];
+
+/// Keywords that do not constrain the document.
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+/// Checks that `schema` only uses keywords that structured outputs and the
+/// local validator understand. The error names the first unsupported keyword
+/// and where it occurs.
+pub fn check_supported_keywords(schema: &Value) -> Result<(), String> {
+    check_keywords(schema, "")
+}
+
+fn check_keywords(schema: &Value, path: &str) -> Result<(), String> {
+    let Value::Object(schema) = schema else {
+        return Ok(());
+    };
+    for (keyword, value) in schema {
+        if !VALIDATED_KEYWORDS.contains(&keyword.as_str())
+            && !ANNOTATION_KEYWORDS.contains(&keyword.as_str())
+        {
+            let location = if path.is_empty() {
+                "the top level of the schema".to_string()
+            } else {
+                format!("`{path}`")
+            };
+            return Err(format!("unsupported keyword \"{keyword}\" at {location}"));
+        }
+        let keyword_path = format!("{path}/{}", pointer_segment(keyword));
+        match (keyword.as_str(), value) {
+            ("properties" | "$defs" | "definitions", Value::Object(subschemas)) => {
+                for (name, subschema) in subschemas {
+                    check_keywords(
+                        subschema,
+                        &format!("{keyword_path}/{}", pointer_segment(name)),
+                    )?;
+                }
+            }
+            ("anyOf", Value::Array(subschemas)) => {
+                for (index, subschema) in subschemas.iter().enumerate() {
+                    check_keywords(subschema, &format!("{keyword_path}/{index}"))?;
+                }
+            }
+            ("items" | "additionalProperties", subschema) => {
+                check_keywords(subschema, &keyword_path)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `message` is a JSON document matching `schema`. The error
+/// describes the first mismatch and is meant to be shown to the model.
+pub(crate) fn validate_final_output(schema: &Value, message: Option<&str>) -> Result<(), String> {
+    let Some(message) = message else {
+        return Err("the turn ended without a final message".to_string());
+    };
+    let instance: Value = serde_json::from_str(message.trim())
+        .map_err(|e| format!("the final message is not valid JSON: {e}"))?;
+    Validator { root: schema }.validate(schema, &instance, "")
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl Validator<'_> {
+    fn validate(&self, schema: &Value, instance: &Value, path: &str) -> Result<(), String> {
+        let schema = match schema {
+            // `true` accepts anything, `false` nothing.
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => return Err(format!("{} is not allowed", describe(path))),
+            Value::Object(schema) => schema,
+            _ => return Ok(()),
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let target = self
+                .resolve(reference)
+                .ok_or_else(|| format!("unsupported schema reference {reference}"))?;
+            return self.validate(target, instance, path);
+        }
+
+        if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array) {
+            let mut first_error = None;
+            for option in any_of {
+                match self.validate(option, instance, path) {
+                    Ok(()) => {
+                        first_error = None;
+                        break;
+                    }
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let matches = match expected {
+                Value::String(name) => has_type(instance, name),
+                Value::Array(names) => names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|name| has_type(instance, name)),
+                _ => true,
+            };
+            if !matches {
+                return Err(format!(
+                    "{} must be of type {}, got {}",
+                    describe(path),
+                    type_list(expected),
+                    type_name(instance)
+                ));
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.contains(instance)
+        {
+            return Err(format!(
+                "{} must be one of {}",
+                describe(path),
+                Value::Array(allowed.clone())
+            ));
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != instance
+        {
+            return Err(format!("{} must be {expected}", describe(path)));
+        }
+
+        match instance {
+            Value::Object(object) => self.validate_object(schema, object, path),
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        self.validate(item_schema, item, &format!("{path}/{index}"))?;
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+    ) -> Result<(), String> {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    return Err(format!(
+                        "{} is missing required property \"{name}\"",
+                        describe(path)
+                    ));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in object {
+            let property_path = format!("{path}/{}", pointer_segment(name));
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(property_schema) => self.validate(property_schema, value, &property_path)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(format!(
+                            "{} has unexpected property \"{name}\"",
+                            describe(path)
+                        ));
+                    }
+                    Some(additional) => self.validate(additional, value, &property_path)?,
+                    None => {}
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "boolean" => instance.is_boolean(),
+        "null" => instance.is_null(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_list(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Escapes a property name for use as a JSON pointer segment (RFC 6901).
+fn pointer_segment(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+/// Names a location in the document as a JSON pointer.
+fn describe(path: &str) -> String {
+    if path.is_empty() {
+        "the top-level value".to_string()
+    } else {
+        format!("`{path}`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "string", "enum": ["ok", "failed"] },
+                "count": { "type": "integer" },
+                "files": { "type": "array", "items": { "$ref": "#/$defs/file" } },
+                "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+            },
+            "required": ["status", "count", "files", "note"],
+            "additionalProperties": false,
+            "$defs": {
+                "file": {
+                    "type": "object",
+                    "properties": { "path": { "type": "string" } },
+                    "required": ["path"],
+                    "additionalProperties": false
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn accepts_matching_output() {
+        let message = r#"{"status": "ok", "count": 2, "files": [{"path": "a.rs"}], "note": null}"#;
+        assert_eq!(validate_final_output(&schema(), Some(message)), Ok(()));
+    }
+
+    #[test]
+    fn reports_first_mismatch() {
+        let schema = schema();
+        let cases = [
+            (None, "the turn ended without a final message"),
+            (
+                Some(r#"{"status": "ok", "count": 2, "files": [], "note": null"#),
+                "the final message is not valid JSON: EOF while parsing an object at line 1 column 54",
+            ),
+            (
+                Some(r#"{"status": "ok", "count": 2, "files": []}"#),
+                "the top-level value is missing required property \"note\"",
+            ),
+            (
+                Some(r#"{"status": "done", "count": 2, "files": [], "note": null}"#),
+                "`/status` must be one of [\"ok\",\"failed\"]",
+            ),
+            (
+                Some(r#"{"status": "ok", "count": 2.5, "files": [], "note": null}"#),
+                "`/count` must be of type integer, got number",
+            ),
+            (
+                Some(r#"{"status": "ok", "count": 2, "files": [{"path": 1}], "note": null}"#),
+                "`/files/0/path` must be of type string, got number",
+            ),
+            (
+                Some(r#"{"status": "ok", "count": 2, "files": [], "note": 3}"#),
+                "`/note` must be of type string, got number",
+            ),
+            (
+                Some(r#"{"status": "ok", "count": 2, "files": [], "note": null, "extra": true}"#),
+                "the top-level value has unexpected property \"extra\"",
+            ),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(
+                validate_final_output(&schema, message),
+                Err(expected.to_string()),
+                "message: {message:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn escapes_property_names_in_pointers() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "a/b~c": { "type": "string" } }
+        });
+        assert_eq!(
+            validate_final_output(&schema, Some(r#"{"a/b~c": 1}"#)),
+            Err("`/a~1b~0c` must be of type string, got number".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_keywords() {
+        assert_eq!(check_supported_keywords(&schema()), Ok(()));
+
+        let cases = [
+            (
+                json!({ "type": "object", "minProperties": 1 }),
+                "unsupported keyword \"minProperties\" at the top level of the schema",
+            ),
+            (
+                json!({ "properties": { "count": { "type": "integer", "minimum": 0 } } }),
+                "unsupported keyword \"minimum\" at `/properties/count`",
+            ),
+            (
+                json!({ "items": { "anyOf": [{ "type": "string", "pattern": "^a" }] } }),
+                "unsupported keyword \"pattern\" at `/items/anyOf/0`",
+            ),
+            (
+                json!({ "$defs": { "a/b": { "oneOf": [] } } }),
+                "unsupported keyword \"oneOf\" at `/$defs/a~1b`",
+            ),
+            (
+                json!({ "type": "array", "items": { "type": "string" }, "minItems": 1 }),
+                "unsupported keyword \"minItems\" at the top level of the schema",
+            ),
+        ];
+        for (schema, expected) in cases {
+            assert_eq!(
+                check_supported_keywords(&schema),
+                Err(expected.to_string()),
+                "schema: {schema}"
+            );
+        }
+    }
+}
@@ -13,6 +13,7 @@
 //! 3.  We do **not** walk past the Git root.
 
 use crate::config::Config;
+use crate::memory::read_memory_for_instructions;
 use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
 use tracing::error;
@@ -24,10 +25,13 @@ const CANDIDATE_FILENAMES: &[&str] = &["AGENTS.md"];
 /// be concatenated with the following separator.
 const PROJECT_DOC_SEPARATOR: &str = "\n\n--- project-doc ---\n\n";
 
-/// Combines `Config::instructions` and `AGENTS.md` (if present) into a single
-/// string of instructions.
+/// Separator placed before the saved memory entries.
+const MEMORY_SEPARATOR: &str = "\n\n--- memory ---\n\n";
+
+/// Combines `Config::instructions`, `AGENTS.md` and the saved memory entries
+/// (if present) into a single string of instructions.
 pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
-    match read_project_docs(config).await {
+    let instructions = match read_project_docs(config).await {
         Ok(Some(project_doc)) => match &config.user_instructions {
             Some(original_instructions) => Some(format!(
                 "{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}"
@@ -39,6 +43,14 @@ pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
             error!("error trying to find project doc: {e:#}");
             config.user_instructions.clone()
         }
+    };
+
+    match (instructions, read_memory_for_instructions(config)) {
+        (Some(instructions), Some(memory)) => {
+            Some(format!("{instructions}{MEMORY_SEPARATOR}{memory}"))
+        }
+        (None, Some(memory)) => Some(memory),
+        (instructions, None) => instructions,
     }
 }
 
@@ -347,4 +359,29 @@ mod tests {
         let res = get_user_instructions(&cfg).await.expect("doc expected");
         assert_eq!(res, "root doc\n\ncrate doc");
     }
+
+    /// Saved memory entries are appended after the project doc.
+    #[tokio::test]
+    async fn appends_memory_after_project_doc() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        fs::write(tmp.path().join("AGENTS.md"), "proj doc").unwrap();
+        std::fs::create_dir(tmp.path().join(".codex")).unwrap();
+        fs::write(
+            tmp.path().join(".codex/memory.md"),
+            "# Codex memory\n\n- [3f9c2a1b] 2025-10-01: Use SQLite for the cache.\n",
+        )
+        .unwrap();
+
+        let res = get_user_instructions(&make_config(&tmp, 4096, None))
+            .await
+            .expect("instructions expected");
+
+        assert!(res.starts_with(&format!("proj doc{MEMORY_SEPARATOR}")));
+        assert!(res.ends_with("- [3f9c2a1b] (project) Use SQLite for the cache."));
+
+        let mut cfg = make_config(&tmp, 4096, None);
+        cfg.memory_max_bytes = 0;
+        let res = get_user_instructions(&cfg).await;
+        assert_eq!(res, Some("proj doc".to_string()));
+    }
 }
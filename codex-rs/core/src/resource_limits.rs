@@ -0,0 +1,347 @@
+//! Enforcement of the `resource_limits` configured for agent commands.
+//!
+//! CPU time and file size are capped with rlimits, which every process of the
+//! command inherits. Memory and process count are capped for the command's
+//! whole process tree when it can be started in a transient systemd scope (a
+//! cgroup v2 scope with `MemoryMax` and `TasksMax`). Without one they fall
+//! back to `RLIMIT_AS`, which applies to each process separately, and
+//! `RLIMIT_NPROC`, which counts every process of the user.
+//!
+//! A command is only reported as killed for a limit when that can be
+//! confirmed, so that other failures still go through the usual sandbox
+//! handling.
+
+use std::fmt;
+use std::process::ExitStatus;
+
+use crate::config_types::ResourceLimits;
+
+const MIB: u64 = 1024 * 1024;
+
+/// The limit a command was killed for exceeding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceededLimit {
+    /// Memory, in MiB.
+    Memory(u64),
+    /// CPU time, in seconds.
+    CpuTime(u64),
+    /// File size, in MiB.
+    FileSize(u64),
+}
+
+impl fmt::Display for ExceededLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Memory(mb) => write!(f, "{} memory", format_mib(*mb)),
+            Self::CpuTime(secs) => write!(f, "{secs}s of CPU time"),
+            Self::FileSize(mb) => write!(f, "{} file size", format_mib(*mb)),
+        }
+    }
+}
+
+fn format_mib(mb: u64) -> String {
+    if mb >= 1024 && mb.is_multiple_of(1024) {
+        format!("{} GiB", mb / 1024)
+    } else {
+        format!("{mb} MiB")
+    }
+}
+
+/// Sets the rlimits for `limits` on the current process. Runs between fork and
+/// exec, so it only makes async-signal-safe calls. `in_systemd_scope` is set
+/// when the scope already caps memory and processes.
+#[cfg(unix)]
+pub(crate) fn set_rlimits(limits: &ResourceLimits, in_systemd_scope: bool) -> std::io::Result<()> {
+    if let Some(secs) = limits.cpu_time_secs {
+        // The soft limit delivers SIGXCPU, by which the kill is recognized;
+        // the hard limit a second later delivers SIGKILL.
+        set_rlimit(libc::RLIMIT_CPU, secs, secs.saturating_add(1))?;
+    }
+    if let Some(mb) = limits.max_file_size_mb {
+        let bytes = mb.saturating_mul(MIB);
+        set_rlimit(libc::RLIMIT_FSIZE, bytes, bytes)?;
+    }
+    if !in_systemd_scope {
+        if let Some(mb) = limits.memory_mb {
+            let bytes = mb.saturating_mul(MIB);
+            set_rlimit(libc::RLIMIT_AS, bytes, bytes)?;
+        }
+        if let Some(count) = limits.max_processes {
+            set_rlimit(libc::RLIMIT_NPROC, count, count)?;
+        }
+    }
+    Ok(())
+}
+
+/// Lowers `resource` to `soft`/`hard`, never above the current hard limit,
+/// which an unprivileged process cannot raise.
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    limit.rlim_cur = soft.min(limit.rlim_max);
+    limit.rlim_max = hard.min(limit.rlim_max);
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+/// A transient systemd scope capping the memory and process count of a
+/// command's whole process tree.
+#[cfg(target_os = "linux")]
+pub(crate) struct SystemdScope {
+    /// Arguments that start the command in the scope.
+    pub(crate) prefix: Vec<String>,
+    /// Name of the scope unit, used to look up why it stopped.
+    pub(crate) unit: String,
+}
+
+/// Scope units of the commands that are running, by the pid of the
+/// `systemd-run` process they were started as.
+#[cfg(target_os = "linux")]
+static SCOPE_UNITS: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<u32, String>>> =
+    std::sync::LazyLock::new(Default::default);
+
+/// Returns the scope to start a command in. `None` when neither limit is set
+/// or the user's systemd instance cannot create scopes with those
+/// controllers.
+#[cfg(target_os = "linux")]
+pub(crate) async fn systemd_scope(limits: &ResourceLimits) -> Option<SystemdScope> {
+    static SCOPES_AVAILABLE: tokio::sync::OnceCell<bool> = tokio::sync::OnceCell::const_new();
+
+    if limits.memory_mb.is_none() && limits.max_processes.is_none() {
+        return None;
+    }
+    if !*SCOPES_AVAILABLE.get_or_init(systemd_scopes_available).await {
+        return None;
+    }
+
+    // Without `--collect` a scope stopped by an OOM kill stays around as
+    // failed, so `scope_oom_killed` can still read its result.
+    let unit = format!("codex-exec-{}.scope", uuid::Uuid::new_v4());
+    let mut prefix: Vec<String> = ["systemd-run", "--user", "--scope", "--quiet"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    prefix.push(format!("--unit={unit}"));
+    if let Some(mb) = limits.memory_mb {
+        prefix.push(format!("--property=MemoryMax={}", mb.saturating_mul(MIB)));
+        prefix.push("--property=MemorySwapMax=0".to_string());
+    }
+    if let Some(count) = limits.max_processes {
+        prefix.push(format!("--property=TasksMax={count}"));
+    }
+    prefix.push("--".to_string());
+    Some(SystemdScope { prefix, unit })
+}
+
+/// Remembers the scope that the command started as `pid` runs in.
+#[cfg(target_os = "linux")]
+pub(crate) fn register_scope(pid: u32, unit: String) {
+    SCOPE_UNITS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(pid, unit);
+}
+
+/// Whether the command started as `pid` failed because its scope ran out of
+/// memory; only looked up when `failed` is set. systemd stops the scope with the result `oom-kill` when the
+/// `oom_kill` counter in the scope cgroup's `memory.events` goes up; the
+/// failed scope is cleared once it has been read. Always false for commands
+/// that did not run in a scope.
+pub(crate) async fn scope_oom_killed(pid: Option<u32>, failed: bool) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let unit = pid.and_then(|pid| {
+            SCOPE_UNITS
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .remove(&pid)
+        });
+        let Some(unit) = unit else {
+            return false;
+        };
+        if !failed {
+            return false;
+        }
+        // systemd may still be stopping the scope when the command's exit is
+        // noticed.
+        for _ in 0..20 {
+            let Ok(output) = tokio::process::Command::new("systemctl")
+                .args(["--user", "show", "--property=ActiveState,Result", &unit])
+                .stdin(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .output()
+                .await
+            else {
+                return false;
+            };
+            let properties = String::from_utf8_lossy(&output.stdout);
+            if properties.lines().any(|line| line == "Result=oom-kill") {
+                let _ = tokio::process::Command::new("systemctl")
+                    .args(["--user", "reset-failed", &unit])
+                    .stdin(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .status()
+                    .await;
+                return true;
+            }
+            if !properties
+                .lines()
+                .any(|line| line == "ActiveState=active" || line == "ActiveState=deactivating")
+            {
+                return false;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        false
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, failed);
+        false
+    }
+}
+
+/// Whether the user's systemd instance delegates the memory and pids
+/// controllers and can start a scope.
+#[cfg(target_os = "linux")]
+async fn systemd_scopes_available() -> bool {
+    let uid = unsafe { libc::getuid() };
+    let controllers = std::fs::read_to_string(format!(
+        "/sys/fs/cgroup/user.slice/user-{uid}.slice/user@{uid}.service/cgroup.controllers"
+    ))
+    .unwrap_or_default();
+    let delegated: Vec<&str> = controllers.split_whitespace().collect();
+    if !delegated.contains(&"memory") || !delegated.contains(&"pids") {
+        return false;
+    }
+
+    tokio::process::Command::new("systemd-run")
+        .args(["--user", "--scope", "--quiet", "--collect", "--", "true"])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
+}
+
+/// Returns the limit that ended a failed command, when that can be
+/// confirmed: the scope's OOM kill (see [`scope_oom_killed`]) or the
+/// `SIGXCPU`/`SIGXFSZ` that only the CPU time and file size rlimits send.
+/// A plain `SIGKILL`, a failed allocation or a failed fork may have other
+/// causes and is not reported. Only configured limits are reported.
+pub(crate) fn exceeded_limit(
+    limits: &ResourceLimits,
+    exit_status: ExitStatus,
+    oom_killed: bool,
+) -> Option<ExceededLimit> {
+    if exit_status.success() {
+        return None;
+    }
+    if oom_killed {
+        return limits.memory_mb.map(ExceededLimit::Memory);
+    }
+
+    #[cfg(unix)]
+    match termination_signal(exit_status) {
+        Some(libc::SIGXCPU) => limits.cpu_time_secs.map(ExceededLimit::CpuTime),
+        Some(libc::SIGXFSZ) => limits.max_file_size_mb.map(ExceededLimit::FileSize),
+        _ => None,
+    }
+    #[cfg(not(unix))]
+    None
+}
+
+/// The signal that ended the command, also when a shell reports it as the
+/// conventional `128 + signal` exit code.
+#[cfg(unix)]
+fn termination_signal(exit_status: ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+
+    exit_status.signal().or_else(|| {
+        exit_status
+            .code()
+            .filter(|code| (129..192).contains(code))
+            .map(|code| code - 128)
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::os::unix::process::ExitStatusExt;
+
+    const LIMITS: ResourceLimits = ResourceLimits {
+        memory_mb: Some(4096),
+        cpu_time_secs: Some(60),
+        max_processes: Some(256),
+        max_file_size_mb: Some(512),
+    };
+
+    fn exit_code(code: i32) -> ExitStatus {
+        ExitStatus::from_raw(code << 8)
+    }
+
+    #[test]
+    fn reports_limits_from_rlimit_signals_and_shell_exit_codes() {
+        let killed_by = ExitStatus::from_raw;
+        assert_eq!(
+            exceeded_limit(&LIMITS, killed_by(libc::SIGXCPU), false),
+            Some(ExceededLimit::CpuTime(60))
+        );
+        assert_eq!(
+            exceeded_limit(&LIMITS, exit_code(128 + libc::SIGXFSZ), false),
+            Some(ExceededLimit::FileSize(512))
+        );
+        assert_eq!(
+            exceeded_limit(&ResourceLimits::default(), killed_by(libc::SIGXCPU), false),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_memory_only_for_a_confirmed_oom_kill() {
+        let killed_by = ExitStatus::from_raw;
+        assert_eq!(
+            exceeded_limit(&LIMITS, killed_by(libc::SIGKILL), true),
+            Some(ExceededLimit::Memory(4096))
+        );
+        assert_eq!(
+            exceeded_limit(&LIMITS, killed_by(libc::SIGKILL), false),
+            None
+        );
+        assert_eq!(exceeded_limit(&LIMITS, exit_code(137), false), None);
+        assert_eq!(exceeded_limit(&LIMITS, exit_code(1), false), None);
+        assert_eq!(
+            exceeded_limit(&ResourceLimits::default(), killed_by(libc::SIGKILL), true),
+            None
+        );
+    }
+
+    #[test]
+    fn describes_limits_in_readable_units() {
+        assert_eq!(
+            format!("killed: exceeded {}", ExceededLimit::Memory(4096)),
+            "killed: exceeded 4 GiB memory"
+        );
+        assert_eq!(
+            ExceededLimit::FileSize(1536).to_string(),
+            "1536 MiB file size"
+        );
+        assert_eq!(ExceededLimit::CpuTime(60).to_string(), "60s of CPU time");
+    }
+}
@@ -0,0 +1,508 @@
+//! Inverted index over the user and assistant messages recorded in rollout
+//! files, persisted at `~/.codex/session_index.json` so that earlier sessions
+//! can be recalled without rereading every rollout.
+//!
+//! Rollouts are append-only, so the index remembers how far into each file it
+//! has read and only parses the new lines on [`SessionIndex::update`].
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::InputMessageKind;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tracing::warn;
+use walkdir::WalkDir;
+
+use super::SESSIONS_SUBDIR;
+use super::sessions::TranscriptRole;
+
+/// Filename of the index inside `~/.codex`.
+pub(crate) const SESSION_INDEX_FILENAME: &str = "session_index.json";
+
+/// Bumped whenever the on-disk layout changes; older indexes are rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+/// Messages are truncated to this many characters before being indexed.
+const MAX_INDEXED_CHARS: usize = 4_000;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct SessionIndex {
+    version: u32,
+    /// Indexing progress of each rollout file.
+    files: HashMap<PathBuf, IndexedFile>,
+    /// Indexed messages; `None` once the rollout they came from is deleted.
+    messages: Vec<Option<IndexedMessage>>,
+    /// Term -> ids (positions in `messages`) of the messages containing it.
+    postings: HashMap<String, Vec<usize>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Number of bytes of complete lines already indexed.
+    offset: u64,
+    session_id: Option<String>,
+    cwd: Option<PathBuf>,
+    message_ids: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct IndexedMessage {
+    pub(crate) session_id: String,
+    pub(crate) timestamp: String,
+    pub(crate) role: TranscriptRole,
+    pub(crate) cwd: PathBuf,
+    pub(crate) text: String,
+}
+
+impl SessionIndex {
+    /// Loads the index from `codex_home`, starting from scratch when it is
+    /// missing, unreadable or was written by an older version.
+    pub(crate) fn load(codex_home: &Path) -> Self {
+        let path = codex_home.join(SESSION_INDEX_FILENAME);
+        let index = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<SessionIndex>(&bytes).unwrap_or_else(|e| {
+                warn!(
+                    "rebuilding unreadable session index {}: {e}",
+                    path.display()
+                );
+                SessionIndex::default()
+            }),
+            Err(_) => SessionIndex::default(),
+        };
+        if index.version == INDEX_VERSION {
+            index
+        } else {
+            SessionIndex {
+                version: INDEX_VERSION,
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Writes the index next to the sessions directory, replacing the
+    /// previous file atomically. Each save goes through its own temporary
+    /// file so that concurrent sessions never interleave their writes.
+    pub(crate) fn save(&self, codex_home: &Path) -> io::Result<()> {
+        let mut tmp = NamedTempFile::new_in(codex_home)?;
+        serde_json::to_writer(tmp.as_file_mut(), self)?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(codex_home.join(SESSION_INDEX_FILENAME))
+            .map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Indexes the lines appended to rollouts since the last update and drops
+    /// the messages of rollouts that no longer exist. Rollouts that cannot be
+    /// read are skipped with a warning and keep what was indexed of them.
+    /// Returns whether the index changed.
+    pub(crate) fn update(&mut self, codex_home: &Path) -> bool {
+        let root = codex_home.join(SESSIONS_SUBDIR);
+        let mut changed = false;
+        let mut present: HashSet<PathBuf> = HashSet::new();
+        // Rollouts missing from an incomplete walk may still exist.
+        let mut complete = true;
+        if root.exists() {
+            for entry in WalkDir::new(&root).sort_by_file_name() {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        warn!(
+                            "skipping part of {} in the session index: {e}",
+                            root.display()
+                        );
+                        complete = false;
+                        continue;
+                    }
+                };
+                let name = entry.file_name().to_string_lossy();
+                let is_rollout = entry.file_type().is_file()
+                    && name.starts_with("rollout-")
+                    && name.ends_with(".jsonl");
+                if is_rollout {
+                    let path = entry.into_path();
+                    match self.index_file(&path) {
+                        Ok(file_changed) => changed |= file_changed,
+                        Err(e) => warn!("skipping {} in the session index: {e}", path.display()),
+                    }
+                    present.insert(path);
+                }
+            }
+        }
+
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| complete && !present.contains(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            self.forget_file(&path);
+            changed = true;
+        }
+        if self.messages.iter().filter(|m| m.is_none()).count() > self.messages.len() / 2 {
+            self.compact();
+        }
+        changed
+    }
+
+    /// Returns up to `limit` messages matching `query`, best match first.
+    /// Only messages recorded in `cwd` or one of its subdirectories are
+    /// considered, and those from `exclude_session` are skipped.
+    pub(crate) fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        cwd: &Path,
+        exclude_session: Option<&str>,
+    ) -> Vec<&IndexedMessage> {
+        let total = self.messages.len() as f64;
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for term in tokenize(query) {
+            let Some(ids) = self.postings.get(&term) else {
+                continue;
+            };
+            // Rare terms say more about a message than common ones.
+            let idf = (1.0 + total / ids.len() as f64).ln();
+            for id in ids {
+                *scores.entry(*id).or_default() += idf;
+            }
+        }
+
+        let mut hits: Vec<(f64, &IndexedMessage)> = scores
+            .into_iter()
+            .filter_map(|(id, score)| {
+                let message = self.messages.get(id)?.as_ref()?;
+                (message.cwd.starts_with(cwd)
+                    && exclude_session != Some(message.session_id.as_str()))
+                .then_some((score, message))
+            })
+            .collect();
+        hits.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+        hits.into_iter()
+            .take(limit)
+            .map(|(_, message)| message)
+            .collect()
+    }
+
+    /// Indexes the complete lines appended to the rollout at `path`. Returns
+    /// whether the index changed.
+    fn index_file(&mut self, path: &Path) -> io::Result<bool> {
+        let len = std::fs::metadata(path)?.len();
+        let rewritten = self.files.get(path).is_some_and(|file| len < file.offset);
+        if rewritten {
+            // The rollout was rewritten; start over.
+            self.forget_file(path);
+        }
+        let offset = self.files.get(path).map_or(0, |file| file.offset);
+        if len == offset {
+            return Ok(rewritten);
+        }
+
+        let mut file = std::fs::File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        // Leave a partially written last line for the next update.
+        let Some(end) = bytes.iter().rposition(|b| *b == b'\n').map(|i| i + 1) else {
+            return Ok(rewritten);
+        };
+        let text = String::from_utf8_lossy(&bytes[..end]).into_owned();
+
+        // Forked sessions start with a copy of their parent's messages, which
+        // are already indexed under the parent.
+        let seen: HashSet<u64> = self.messages.iter().flatten().map(fingerprint).collect();
+
+        let mut state = self.files.remove(path).unwrap_or_default();
+        for line in text.lines() {
+            let Ok(RolloutLine { timestamp, item }) = serde_json::from_str::<RolloutLine>(line)
+            else {
+                continue;
+            };
+            let (role, message) = match item {
+                RolloutItem::SessionMeta(meta_line) => {
+                    if state.session_id.is_none() {
+                        state.session_id = Some(meta_line.meta.id.to_string());
+                        state.cwd = Some(meta_line.meta.cwd);
+                    }
+                    continue;
+                }
+                RolloutItem::TurnContext(ctx) => {
+                    state.cwd = Some(ctx.cwd);
+                    continue;
+                }
+                RolloutItem::EventMsg(EventMsg::UserMessage(ev)) => {
+                    if matches!(
+                        ev.kind,
+                        Some(
+                            InputMessageKind::UserInstructions
+                                | InputMessageKind::EnvironmentContext
+                        )
+                    ) {
+                        continue;
+                    }
+                    (TranscriptRole::User, ev.message)
+                }
+                RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+                    (TranscriptRole::Assistant, ev.message)
+                }
+                RolloutItem::ResponseItem(_)
+                | RolloutItem::Compacted(_)
+                | RolloutItem::Checkpoint(_)
+                | RolloutItem::EventMsg(_) => continue,
+            };
+            let Some(session_id) = state.session_id.clone() else {
+                continue;
+            };
+            let message = IndexedMessage {
+                session_id,
+                timestamp,
+                role,
+                cwd: state.cwd.clone().unwrap_or_default(),
+                text: message.chars().take(MAX_INDEXED_CHARS).collect(),
+            };
+            if message.text.trim().is_empty() || seen.contains(&fingerprint(&message)) {
+                continue;
+            }
+            let id = self.add_message(message);
+            state.message_ids.push(id);
+        }
+        state.offset = offset + end as u64;
+        self.files.insert(path.to_path_buf(), state);
+        Ok(true)
+    }
+
+    fn add_message(&mut self, message: IndexedMessage) -> usize {
+        let id = self.messages.len();
+        for term in tokenize(&message.text) {
+            self.postings.entry(term).or_default().push(id);
+        }
+        self.messages.push(Some(message));
+        id
+    }
+
+    fn forget_file(&mut self, path: &Path) {
+        if let Some(file) = self.files.remove(path) {
+            for id in file.message_ids {
+                if let Some(message) = self.messages.get_mut(id) {
+                    *message = None;
+                }
+            }
+        }
+    }
+
+    /// Drops forgotten messages and renumbers the remaining ones.
+    fn compact(&mut self) {
+        let mut new_ids: HashMap<usize, usize> = HashMap::new();
+        let messages = std::mem::take(&mut self.messages);
+        self.postings.clear();
+        for (old_id, message) in messages.into_iter().enumerate() {
+            if let Some(message) = message {
+                new_ids.insert(old_id, self.add_message(message));
+            }
+        }
+        for file in self.files.values_mut() {
+            file.message_ids = file
+                .message_ids
+                .iter()
+                .filter_map(|id| new_ids.get(id).copied())
+                .collect();
+        }
+    }
+}
+
+/// Lowercased alphanumeric words of at least two characters, deduplicated.
+pub(crate) fn tokenize(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn fingerprint(message: &IndexedMessage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    message.timestamp.hash(&mut hasher);
+    message.role.hash(&mut hasher);
+    message.text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const PARENT_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    const FORK_ID: &str = "9b2c1c1e-4f5d-4b8a-9a43-2f0a5d6c7e81";
+
+    fn session_meta(id: &str) -> serde_json::Value {
+        json!({
+            "timestamp": "2025-10-01T10:00:00.000Z",
+            "type": "session_meta",
+            "payload": {
+                "id": id,
+                "timestamp": "2025-10-01T10:00:00.000Z",
+                "cwd": "/work/app",
+                "originator": "codex_cli_rs",
+                "cli_version": "0.0.0",
+                "instructions": null,
+            },
+        })
+    }
+
+    fn message(timestamp: &str, kind: &str, text: &str) -> serde_json::Value {
+        json!({
+            "timestamp": timestamp,
+            "type": "event_msg",
+            "payload": { "type": kind, "message": text },
+        })
+    }
+
+    fn append(path: &Path, lines: &[serde_json::Value]) -> io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        for line in lines {
+            writeln!(file, "{line}")?;
+        }
+        Ok(())
+    }
+
+    fn texts(hits: &[&IndexedMessage]) -> Vec<(String, String)> {
+        hits.iter()
+            .map(|m| (m.session_id.clone(), m.text.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn indexes_appended_lines_and_skips_fork_copies() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let dir = codex_home.path().join("sessions/2025/10/01");
+        std::fs::create_dir_all(&dir)?;
+        let parent = dir.join(format!("rollout-2025-10-01T10-00-00-{PARENT_ID}.jsonl"));
+        let decision = message(
+            "2025-10-01T10:00:05.000Z",
+            "agent_message",
+            "We store sessions in SQLite because JSONL scans were too slow.",
+        );
+        append(
+            &parent,
+            &[
+                session_meta(PARENT_ID),
+                message("2025-10-01T10:00:01.000Z", "user_message", "Why SQLite?"),
+                decision.clone(),
+            ],
+        )?;
+
+        let mut index = SessionIndex::load(codex_home.path());
+        index.update(codex_home.path());
+        index.save(codex_home.path())?;
+
+        let fork = dir.join(format!("rollout-2025-10-02T10-00-00-{FORK_ID}.jsonl"));
+        append(
+            &fork,
+            &[
+                session_meta(FORK_ID),
+                session_meta(PARENT_ID),
+                decision,
+                message(
+                    "2025-10-02T10:00:05.000Z",
+                    "agent_message",
+                    "Switched the cache to sqlite too.",
+                ),
+            ],
+        )?;
+
+        let mut index = SessionIndex::load(codex_home.path());
+        index.update(codex_home.path());
+        assert_eq!(
+            texts(&index.search("sqlite sessions", 10, Path::new("/work"), None)),
+            vec![
+                (
+                    PARENT_ID.to_string(),
+                    "We store sessions in SQLite because JSONL scans were too slow.".to_string()
+                ),
+                (
+                    FORK_ID.to_string(),
+                    "Switched the cache to sqlite too.".to_string()
+                ),
+                (PARENT_ID.to_string(), "Why SQLite?".to_string()),
+            ]
+        );
+        assert_eq!(
+            texts(&index.search("sqlite", 10, Path::new("/work/app"), Some(PARENT_ID))),
+            vec![(
+                FORK_ID.to_string(),
+                "Switched the cache to sqlite too.".to_string()
+            )]
+        );
+
+        assert_eq!(
+            index.search("sqlite", 10, Path::new("/work/other"), None),
+            Vec::<&IndexedMessage>::new()
+        );
+
+        std::fs::remove_file(&fork)?;
+        index.update(codex_home.path());
+        assert_eq!(
+            index.search("cache", 10, Path::new("/work/app"), None),
+            Vec::<&IndexedMessage>::new()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn skips_unreadable_rollouts_and_reports_changes() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let dir = codex_home.path().join("sessions/2025/10/01");
+        std::fs::create_dir_all(&dir)?;
+        let readable = dir.join(format!("rollout-2025-10-01T10-00-00-{PARENT_ID}.jsonl"));
+        append(
+            &readable,
+            &[
+                session_meta(PARENT_ID),
+                message("2025-10-01T10:00:01.000Z", "user_message", "Why SQLite?"),
+            ],
+        )?;
+        let unreadable = dir.join(format!("rollout-2025-10-02T10-00-00-{FORK_ID}.jsonl"));
+        append(&unreadable, &[session_meta(FORK_ID)])?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&unreadable, std::fs::Permissions::from_mode(0o000))?;
+        }
+
+        let mut index = SessionIndex::load(codex_home.path());
+        assert!(index.update(codex_home.path()));
+        assert_eq!(
+            texts(&index.search("sqlite", 10, Path::new("/work"), None)),
+            vec![(PARENT_ID.to_string(), "Why SQLite?".to_string())]
+        );
+
+        // Nothing was appended since, so there is nothing to save.
+        assert!(!index.update(codex_home.path()));
+        Ok(())
+    }
+}
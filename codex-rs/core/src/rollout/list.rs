@@ -269,7 +269,7 @@ where
     Ok(collected)
 }
 
-fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
+pub(crate) fn parse_timestamp_uuid_from_filename(name: &str) -> Option<(OffsetDateTime, Uuid)> {
     // Expected: rollout-YYYY-MM-DDThh-mm-ss-<uuid>.jsonl
     let core = name.strip_prefix("rollout-")?.strip_suffix(".jsonl")?;
 
@@ -325,7 +325,7 @@ async fn read_head_and_flags(
             RolloutItem::TurnContext(_) => {
                 // Not included in `head`; skip.
             }
-            RolloutItem::Compacted(_) => {
+            RolloutItem::Compacted(_) | RolloutItem::Checkpoint(_) => {
                 // Not included in `head`; skip.
             }
             RolloutItem::EventMsg(ev) => {
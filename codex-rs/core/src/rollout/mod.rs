@@ -3,9 +3,12 @@
 pub const SESSIONS_SUBDIR: &str = "sessions";
 pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 
+pub(crate) mod index;
 pub mod list;
 pub(crate) mod policy;
 pub mod recorder;
+pub mod sessions;
+pub mod usage;
 
 pub use codex_protocol::protocol::SessionMeta;
 pub use list::find_conversation_path_by_id_str;
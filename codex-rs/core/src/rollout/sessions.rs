@@ -0,0 +1,415 @@
+//! Reading recorded sessions back as transcripts so they can be listed,
+//! searched, exported and pruned outside of a running conversation.
+
+use std::cmp::Reverse;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::InputMessageKind;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::warn;
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use super::ARCHIVED_SESSIONS_SUBDIR;
+use super::SESSIONS_SUBDIR;
+use super::list::Cursor;
+use super::list::find_conversation_path_by_id_str;
+use super::list::get_conversations;
+use super::list::parse_timestamp_uuid_from_filename;
+
+/// A rollout file read back as the conversation it recorded.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordedSession {
+    pub id: String,
+    pub path: PathBuf,
+    /// Time the session was started, as recorded in its `SessionMeta`.
+    pub timestamp: String,
+    pub cwd: PathBuf,
+    pub git_branch: Option<String>,
+    /// Models used by the session's turns, in order of first use.
+    pub models: Vec<String>,
+    /// User and assistant messages in the order they were recorded.
+    pub messages: Vec<TranscriptMessage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptRole {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptMessage {
+    pub timestamp: String,
+    pub role: TranscriptRole,
+    pub text: String,
+}
+
+/// A page of recorded sessions, newest first.
+#[derive(Debug)]
+pub struct SessionsPage {
+    pub sessions: Vec<RecordedSession>,
+    /// Pass back to [`list_sessions`] to continue after the last session, or
+    /// `None` once every session has been listed.
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Reads up to `page_size` rollouts under `<codex_home>/sessions`, newest
+/// first, resuming after `cursor`. Uses the same pagination as the resume
+/// picker ([`super::list::get_conversations`]) so only the rollouts on the
+/// requested page are read in full.
+pub async fn list_sessions(
+    codex_home: &Path,
+    page_size: usize,
+    cursor: Option<&Cursor>,
+) -> io::Result<SessionsPage> {
+    let page = get_conversations(codex_home, page_size, cursor).await?;
+    let mut sessions = Vec::with_capacity(page.items.len());
+    for item in page.items {
+        match read_session(&item.path).await {
+            Ok(session) if !session.messages.is_empty() => sessions.push(session),
+            Ok(_) => {}
+            Err(e) => warn!("skipping rollout {}: {e}", item.path.display()),
+        }
+    }
+    Ok(SessionsPage {
+        sessions,
+        next_cursor: page.next_cursor,
+    })
+}
+
+/// Parses the rollout at `path`. Lines that cannot be parsed are skipped.
+pub async fn read_session(path: &Path) -> io::Result<RecordedSession> {
+    let text = tokio::fs::read_to_string(path).await?;
+    let mut session: Option<RecordedSession> = None;
+    let mut models: Vec<String> = Vec::new();
+    let mut messages: Vec<TranscriptMessage> = Vec::new();
+
+    for line in text.lines() {
+        let Ok(RolloutLine { timestamp, item }) = serde_json::from_str::<RolloutLine>(line) else {
+            continue;
+        };
+        match item {
+            // Forked sessions also carry a copy of their parent's metadata;
+            // the first entry describes the session itself.
+            RolloutItem::SessionMeta(meta_line) if session.is_none() => {
+                session = Some(RecordedSession {
+                    id: meta_line.meta.id.to_string(),
+                    path: path.to_path_buf(),
+                    timestamp: meta_line.meta.timestamp,
+                    cwd: meta_line.meta.cwd,
+                    git_branch: meta_line.git.and_then(|git| git.branch),
+                    models: Vec::new(),
+                    messages: Vec::new(),
+                });
+            }
+            RolloutItem::TurnContext(ctx) => {
+                if !models.contains(&ctx.model) {
+                    models.push(ctx.model);
+                }
+            }
+            RolloutItem::EventMsg(EventMsg::UserMessage(ev)) => {
+                if matches!(
+                    ev.kind,
+                    Some(InputMessageKind::UserInstructions | InputMessageKind::EnvironmentContext)
+                ) {
+                    continue;
+                }
+                messages.push(TranscriptMessage {
+                    timestamp,
+                    role: TranscriptRole::User,
+                    text: ev.message,
+                });
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+                messages.push(TranscriptMessage {
+                    timestamp,
+                    role: TranscriptRole::Assistant,
+                    text: ev.message,
+                });
+            }
+            RolloutItem::SessionMeta(_)
+            | RolloutItem::ResponseItem(_)
+            | RolloutItem::Compacted(_)
+            | RolloutItem::Checkpoint(_)
+            | RolloutItem::EventMsg(_) => {}
+        }
+    }
+
+    let mut session = session.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has no session metadata", path.display()),
+        )
+    })?;
+    session.models = models;
+    session.messages = messages;
+    Ok(session)
+}
+
+/// Locates the rollout of session `id`, looking in `archived_sessions` when
+/// it is not among the active sessions.
+pub async fn find_session_path(codex_home: &Path, id: &str) -> io::Result<Option<PathBuf>> {
+    if let Some(path) = find_conversation_path_by_id_str(codex_home, id).await? {
+        return Ok(Some(path));
+    }
+    let Ok(id) = Uuid::parse_str(id) else {
+        return Ok(None);
+    };
+    Ok(rollout_files(&codex_home.join(ARCHIVED_SESSIONS_SUBDIR))?
+        .into_iter()
+        .find(|(_, file_id, _)| *file_id == id)
+        .map(|(_, _, path)| path))
+}
+
+/// Deletes rollouts last written to more than `older_than` ago and returns
+/// their paths. A resumed session is appended to the rollout it started in,
+/// so its age is that of the file's modification time rather than the start
+/// time in its name. Archived sessions are kept unless `include_archived` is
+/// set; with `dry_run` nothing is deleted.
+pub async fn prune_sessions(
+    codex_home: &Path,
+    older_than: Duration,
+    include_archived: bool,
+    dry_run: bool,
+) -> io::Result<Vec<PathBuf>> {
+    let Some(cutoff) = SystemTime::now().checked_sub(older_than) else {
+        return Ok(Vec::new());
+    };
+
+    let sessions_root = codex_home.join(SESSIONS_SUBDIR);
+    let mut roots = vec![sessions_root.clone()];
+    if include_archived {
+        roots.push(codex_home.join(ARCHIVED_SESSIONS_SUBDIR));
+    }
+
+    let mut pruned = Vec::new();
+    for root in roots {
+        for (_, _, path) in rollout_files(&root)? {
+            if tokio::fs::metadata(&path).await?.modified()? >= cutoff {
+                continue;
+            }
+            if !dry_run {
+                tokio::fs::remove_file(&path).await?;
+            }
+            pruned.push(path);
+        }
+    }
+
+    if !dry_run && sessions_root.exists() {
+        remove_empty_dirs(&sessions_root).await?;
+    }
+    pruned.sort();
+    Ok(pruned)
+}
+
+/// Rollout files anywhere under `root`, newest first.
+fn rollout_files(root: &Path) -> io::Result<Vec<(OffsetDateTime, Uuid, PathBuf)>> {
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in WalkDir::new(root) {
+        let entry = entry.map_err(io::Error::other)?;
+        if entry.file_type().is_file()
+            && let Some(name) = entry.file_name().to_str()
+            && let Some((ts, id)) = parse_timestamp_uuid_from_filename(name)
+        {
+            files.push((ts, id, entry.into_path()));
+        }
+    }
+    files.sort_by_key(|(ts, id, _)| (Reverse(*ts), Reverse(*id)));
+    Ok(files)
+}
+
+/// Removes the `YYYY/MM/DD` directories left empty under `root`.
+async fn remove_empty_dirs(root: &Path) -> io::Result<()> {
+    let dirs: Vec<PathBuf> = WalkDir::new(root)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir())
+        .map(walkdir::DirEntry::into_path)
+        .collect();
+    for dir in dirs {
+        if std::fs::read_dir(&dir)?.next().is_none() {
+            tokio::fs::remove_dir(&dir).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    const OLD_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    const NEW_ID: &str = "9b2c1c1e-4f5d-4b8a-9a43-2f0a5d6c7e81";
+
+    fn write_rollout(path: &Path, id: &str, timestamp: &str) -> io::Result<()> {
+        let lines = [
+            json!({
+                "timestamp": timestamp,
+                "type": "session_meta",
+                "payload": {
+                    "id": id,
+                    "timestamp": timestamp,
+                    "cwd": "/work/app",
+                    "originator": "codex_cli_rs",
+                    "cli_version": "0.0.0",
+                    "instructions": null,
+                    "git": { "branch": "main" },
+                },
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "event_msg",
+                "payload": {
+                    "type": "user_message",
+                    "message": "<environment_context>...</environment_context>",
+                    "kind": "environment_context",
+                },
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "turn_context",
+                "payload": {
+                    "cwd": "/work/app",
+                    "approval_policy": "on-request",
+                    "sandbox_policy": { "mode": "read-only" },
+                    "model": "grok-code-fast-1",
+                    "summary": "auto",
+                },
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "event_msg",
+                "payload": { "type": "user_message", "message": "fix the build", "kind": "plain" },
+            }),
+            json!({
+                "timestamp": timestamp,
+                "type": "event_msg",
+                "payload": { "type": "agent_message", "message": "Done." },
+            }),
+        ];
+        let text = lines
+            .iter()
+            .map(serde_json::Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, text)
+    }
+
+    #[tokio::test]
+    async fn reads_transcript_without_context_messages() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let path = codex_home.path().join(format!(
+            "sessions/2025/10/01/rollout-2025-10-01T10-00-00-{OLD_ID}.jsonl"
+        ));
+        write_rollout(&path, OLD_ID, "2025-10-01T10:00:00.000Z")?;
+
+        let session = read_session(&path).await?;
+        assert_eq!(session.id, OLD_ID);
+        assert_eq!(session.git_branch.as_deref(), Some("main"));
+        assert_eq!(session.models, vec!["grok-code-fast-1".to_string()]);
+        let messages: Vec<_> = session
+            .messages
+            .iter()
+            .map(|m| (m.role, m.text.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (TranscriptRole::User, "fix the build"),
+                (TranscriptRole::Assistant, "Done."),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lists_sessions_one_page_at_a_time() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        write_rollout(
+            &codex_home.path().join(format!(
+                "sessions/2025/10/01/rollout-2025-10-01T10-00-00-{OLD_ID}.jsonl"
+            )),
+            OLD_ID,
+            "2025-10-01T10:00:00.000Z",
+        )?;
+        write_rollout(
+            &codex_home.path().join(format!(
+                "sessions/2025/10/02/rollout-2025-10-02T10-00-00-{NEW_ID}.jsonl"
+            )),
+            NEW_ID,
+            "2025-10-02T10:00:00.000Z",
+        )?;
+
+        let first = list_sessions(codex_home.path(), 1, None).await?;
+        let ids: Vec<_> = first.sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec![NEW_ID]);
+
+        let second = list_sessions(codex_home.path(), 1, first.next_cursor.as_ref()).await?;
+        let ids: Vec<_> = second.sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec![OLD_ID]);
+
+        let third = list_sessions(codex_home.path(), 1, second.next_cursor.as_ref()).await?;
+        assert!(third.sessions.is_empty());
+        assert!(third.next_cursor.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_recent_and_archived_sessions() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let old = codex_home.path().join(format!(
+            "sessions/2020/01/01/rollout-2020-01-01T10-00-00-{OLD_ID}.jsonl"
+        ));
+        write_rollout(&old, OLD_ID, "2020-01-01T10:00:00.000Z")?;
+        let now = OffsetDateTime::now_utc();
+        let recent = codex_home.path().join(format!(
+            "sessions/{:04}/{:02}/{:02}/rollout-{:04}-{:02}-{:02}T00-00-00-{NEW_ID}.jsonl",
+            now.year(),
+            u8::from(now.month()),
+            now.day(),
+            now.year(),
+            u8::from(now.month()),
+            now.day(),
+        ));
+        write_rollout(&recent, NEW_ID, "2025-10-01T10:00:00.000Z")?;
+        let archived = codex_home.path().join(format!(
+            "{ARCHIVED_SESSIONS_SUBDIR}/rollout-2020-01-02T10-00-00-{NEW_ID}.jsonl"
+        ));
+        write_rollout(&archived, NEW_ID, "2020-01-02T10:00:00.000Z")?;
+
+        let thirty_days = Duration::from_secs(30 * 24 * 60 * 60);
+        let pruned = prune_sessions(codex_home.path(), thirty_days, false, false).await?;
+        assert_eq!(pruned, vec![old.clone()]);
+        assert!(!old.exists());
+        assert!(!codex_home.path().join("sessions/2020").exists());
+        assert!(recent.exists());
+        assert!(archived.exists());
+
+        let pruned = prune_sessions(codex_home.path(), thirty_days, true, true).await?;
+        assert_eq!(pruned, vec![archived.clone()]);
+        assert!(archived.exists());
+        Ok(())
+    }
+}
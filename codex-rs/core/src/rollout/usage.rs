@@ -0,0 +1,351 @@
+//! Aggregation of the token usage recorded in rollout files, for reporting
+//! spend across sessions.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TokenUsage;
+use walkdir::WalkDir;
+
+use super::SESSIONS_SUBDIR;
+use crate::model_catalog::ModelCatalog;
+
+/// Placeholder for the model or provider of turns recorded before that
+/// information was written to rollouts.
+const UNKNOWN: &str = "unknown";
+
+/// Token usage of one day/model/provider/cwd combination across all recorded
+/// sessions.
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    /// UTC day the usage was recorded on, as `YYYY-MM-DD`.
+    pub day: String,
+    pub model: String,
+    pub model_provider: String,
+    pub cwd: PathBuf,
+    pub token_usage: TokenUsage,
+    /// Cost in USD, or `None` when the model has no configured pricing.
+    pub cost_usd: Option<f64>,
+}
+
+type UsageKey = (String, String, String, PathBuf);
+
+/// Identifies a `TokenCount` event: the session it was recorded in plus its
+/// total and last-turn counters.
+type EventKey = (String, [u64; 10]);
+
+/// Reads every rollout under `<codex_home>/sessions` and sums the token usage
+/// of each turn, priced with the `[models.<slug>.pricing]` entries in
+/// `catalog`. Each turn is attributed to the model and provider of the turn
+/// context recorded before it, which is the one that served the turn (a
+/// fallback provider records its own). Lines that cannot be parsed are
+/// skipped.
+pub fn collect_usage(codex_home: &Path, catalog: &ModelCatalog) -> io::Result<Vec<UsageRecord>> {
+    let root = codex_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut totals: BTreeMap<UsageKey, TokenUsage> = BTreeMap::new();
+    let mut seen: HashSet<EventKey> = HashSet::new();
+    // Rollout file names start with their creation time, so sorting by name
+    // visits a forked session after the session it was forked from.
+    for entry in WalkDir::new(&root).sort_by_file_name() {
+        let entry = entry.map_err(io::Error::other)?;
+        let path = entry.path();
+        let is_rollout = entry.file_type().is_file()
+            && path.extension().is_some_and(|ext| ext == "jsonl")
+            && entry.file_name().to_string_lossy().starts_with("rollout-");
+        if is_rollout {
+            let text = std::fs::read_to_string(path)?;
+            accumulate_rollout(&text, &mut seen, &mut totals);
+        }
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|((day, model, model_provider, cwd), token_usage)| {
+            let cost_usd = catalog
+                .pricing(&model)
+                .map(|pricing| pricing.cost_usd(&token_usage));
+            UsageRecord {
+                day,
+                model,
+                model_provider,
+                cwd,
+                token_usage,
+                cost_usd,
+            }
+        })
+        .collect())
+}
+
+/// Adds the last-turn usage of each `TokenCount` event in a rollout file to
+/// `totals`.
+///
+/// An event is counted once per session: rate-limit updates repeat the
+/// previous event, and forked sessions start with a copy of their parent's
+/// rollout (including its `SessionMeta`), so both are skipped as already
+/// `seen`.
+fn accumulate_rollout(
+    text: &str,
+    seen: &mut HashSet<EventKey>,
+    totals: &mut BTreeMap<UsageKey, TokenUsage>,
+) {
+    let mut session_id = String::new();
+    let mut cwd = PathBuf::new();
+    let mut model = UNKNOWN.to_string();
+    let mut model_provider = UNKNOWN.to_string();
+
+    for line in text.lines() {
+        let Ok(RolloutLine { timestamp, item }) = serde_json::from_str::<RolloutLine>(line) else {
+            continue;
+        };
+        match item {
+            RolloutItem::SessionMeta(meta_line) => {
+                session_id = meta_line.meta.id.to_string();
+                cwd = meta_line.meta.cwd;
+            }
+            RolloutItem::TurnContext(ctx) => {
+                cwd = ctx.cwd;
+                model = ctx.model;
+                model_provider = ctx.model_provider.unwrap_or_else(|| UNKNOWN.to_string());
+            }
+            RolloutItem::EventMsg(EventMsg::TokenCount(ev)) => {
+                let Some(info) = ev.info else {
+                    continue;
+                };
+                let turn = info.last_token_usage;
+                let key = (session_id.clone(), counters(&info.total_token_usage, &turn));
+                if turn.is_zero() || !seen.insert(key) {
+                    continue;
+                }
+                let day = timestamp.get(..10).unwrap_or(&timestamp).to_string();
+                totals
+                    .entry((day, model.clone(), model_provider.clone(), cwd.clone()))
+                    .or_default()
+                    .add_assign(&turn);
+            }
+            RolloutItem::ResponseItem(_)
+            | RolloutItem::Compacted(_)
+            | RolloutItem::Checkpoint(_)
+            | RolloutItem::EventMsg(_) => {}
+        }
+    }
+}
+
+fn counters(total: &TokenUsage, last: &TokenUsage) -> [u64; 10] {
+    [
+        total.input_tokens,
+        total.cached_input_tokens,
+        total.output_tokens,
+        total.reasoning_output_tokens,
+        total.total_tokens,
+        last.input_tokens,
+        last.cached_input_tokens,
+        last.output_tokens,
+        last.reasoning_output_tokens,
+        last.total_tokens,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_types::ModelCapabilitiesToml;
+    use crate::config_types::ModelPricing;
+    use crate::model_catalog::ModelCatalogEntry;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    const PARENT_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    const FORK_ID: &str = "9b2c1c1e-4f5d-4b8a-9a43-2f0a5d6c7e81";
+
+    fn usage(input: u64, output: u64) -> serde_json::Value {
+        json!({
+            "input_tokens": input,
+            "cached_input_tokens": 0,
+            "output_tokens": output,
+            "reasoning_output_tokens": 0,
+            "total_tokens": input + output,
+        })
+    }
+
+    fn token_count(timestamp: &str, total: (u64, u64), last: (u64, u64)) -> serde_json::Value {
+        json!({
+            "timestamp": timestamp,
+            "type": "event_msg",
+            "payload": {
+                "type": "token_count",
+                "info": {
+                    "total_token_usage": usage(total.0, total.1),
+                    "last_token_usage": usage(last.0, last.1),
+                    "model_context_window": null,
+                },
+                "rate_limits": null,
+            },
+        })
+    }
+
+    fn session_meta(timestamp: &str, id: &str) -> serde_json::Value {
+        json!({
+            "timestamp": timestamp,
+            "type": "session_meta",
+            "payload": {
+                "id": id,
+                "timestamp": timestamp,
+                "cwd": "/work/app",
+                "originator": "codex_cli_rs",
+                "cli_version": "0.0.0",
+                "instructions": null,
+            },
+        })
+    }
+
+    fn turn_context(timestamp: &str, model: &str, provider: &str) -> serde_json::Value {
+        json!({
+            "timestamp": timestamp,
+            "type": "turn_context",
+            "payload": {
+                "cwd": "/work/app",
+                "approval_policy": "on-request",
+                "sandbox_policy": { "mode": "read-only" },
+                "model": model,
+                "model_provider": provider,
+                "summary": "auto",
+            },
+        })
+    }
+
+    fn write_rollout(dir: &Path, name: &str, lines: &[serde_json::Value]) -> io::Result<()> {
+        let text = lines
+            .iter()
+            .map(serde_json::Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(dir.join(name), text)
+    }
+
+    #[test]
+    fn sums_each_turn_once_by_day_and_model() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let dir = codex_home.path().join("sessions/2025/10/01");
+        std::fs::create_dir_all(&dir)?;
+
+        let parent = [
+            session_meta("2025-10-01T10:00:00.000Z", PARENT_ID),
+            turn_context("2025-10-01T10:00:01.000Z", "grok-code-fast-1", "api_x"),
+            token_count("2025-10-01T10:00:05.000Z", (1_000, 100), (1_000, 100)),
+            // Rate-limit-only update repeating the previous event.
+            token_count("2025-10-01T10:00:06.000Z", (1_000, 100), (1_000, 100)),
+            turn_context("2025-10-02T09:00:00.000Z", "gpt-5", "openai"),
+            token_count("2025-10-02T09:00:05.000Z", (3_000, 300), (2_000, 200)),
+        ];
+        write_rollout(&dir, "rollout-2025-10-01T10-00-00-a.jsonl", &parent)?;
+
+        // A fork copies the parent's first turn before recording its own.
+        let fork = [
+            session_meta("2025-10-03T08:00:00.000Z", FORK_ID),
+            parent[0].clone(),
+            parent[1].clone(),
+            parent[2].clone(),
+            turn_context("2025-10-03T08:00:01.000Z", "grok-code-fast-1", "api_x"),
+            token_count("2025-10-03T08:00:05.000Z", (1_500, 50), (1_500, 50)),
+        ];
+        write_rollout(&dir, "rollout-2025-10-03T08-00-00-b.jsonl", &fork)?;
+
+        let catalog = ModelCatalog::new(HashMap::from([(
+            "grok-code-fast-1".to_string(),
+            ModelCatalogEntry {
+                capabilities: ModelCapabilitiesToml {
+                    pricing: Some(ModelPricing {
+                        input: 1.0,
+                        cached_input: None,
+                        output: 10.0,
+                        reasoning: None,
+                    }),
+                    ..Default::default()
+                },
+                base_instructions_file: None,
+            },
+        )]));
+
+        let records = collect_usage(codex_home.path(), &catalog)?;
+        let summary: Vec<_> = records
+            .iter()
+            .map(|r| {
+                (
+                    r.day.as_str(),
+                    r.model.as_str(),
+                    r.model_provider.as_str(),
+                    r.token_usage.input_tokens,
+                    r.token_usage.output_tokens,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2025-10-01", "grok-code-fast-1", "api_x", 1_000, 100),
+                ("2025-10-02", "gpt-5", "openai", 2_000, 200),
+                ("2025-10-03", "grok-code-fast-1", "api_x", 1_500, 50),
+            ]
+        );
+        assert_eq!(records[0].cwd, PathBuf::from("/work/app"));
+        // 1K input at $1/M + 100 output at $10/M.
+        let cost = records[0].cost_usd.expect("priced model");
+        assert!((cost - 0.002).abs() < 1e-12, "unexpected cost: {cost}");
+        assert_eq!(records[1].cost_usd, None);
+        Ok(())
+    }
+
+    #[test]
+    fn prices_turns_at_the_model_that_served_them() -> io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let dir = codex_home.path().join("sessions/2025/10/01");
+        std::fs::create_dir_all(&dir)?;
+
+        // The primary provider failed, so the turn was retried on a fallback,
+        // which records its own turn context before streaming.
+        let rollout = [
+            session_meta("2025-10-01T10:00:00.000Z", PARENT_ID),
+            turn_context("2025-10-01T10:00:01.000Z", "grok-code-fast-1", "api_x"),
+            turn_context("2025-10-01T10:00:02.000Z", "backup-model", "backup"),
+            token_count("2025-10-01T10:00:05.000Z", (1_000, 100), (1_000, 100)),
+        ];
+        write_rollout(&dir, "rollout-2025-10-01T10-00-00-a.jsonl", &rollout)?;
+
+        let pricing = |input: f64| ModelCatalogEntry {
+            capabilities: ModelCapabilitiesToml {
+                pricing: Some(ModelPricing {
+                    input,
+                    cached_input: None,
+                    output: 0.0,
+                    reasoning: None,
+                }),
+                ..Default::default()
+            },
+            base_instructions_file: None,
+        };
+        let catalog = ModelCatalog::new(HashMap::from([
+            ("grok-code-fast-1".to_string(), pricing(1.0)),
+            ("backup-model".to_string(), pricing(2.0)),
+        ]));
+
+        let records = collect_usage(codex_home.path(), &catalog)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].model, "backup-model");
+        assert_eq!(records[0].model_provider, "backup");
+        let cost = records[0].cost_usd.expect("priced model");
+        assert!((cost - 0.002).abs() < 1e-12, "unexpected cost: {cost}");
+        Ok(())
+    }
+}
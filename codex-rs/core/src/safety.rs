@@ -6,10 +6,14 @@ use std::path::PathBuf;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
+use crate::config_types::McpToolApproval;
 use crate::exec::SandboxType;
 
 use crate::command_safety::is_dangerous_command::command_might_be_dangerous;
 use crate::command_safety::is_safe_command::is_known_safe_command;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::ExecPolicyDecision;
+use crate::mcp_connection_manager::McpToolPolicy;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 
@@ -80,13 +84,56 @@ pub fn assess_patch_safety(
 /// - the user has explicitly approved the command
 /// - the command is on the "known safe" list
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
-pub fn assess_command_safety(
+///
+/// Commands the exec policy marks as `forbidden` are always rejected, and
+/// those it vouches for as `safe` are approved but still sandboxed.
+pub(crate) fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
+    exec_policy: &ExecPolicy,
+    cwd: &Path,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
+    // The exec policy is consulted first so that `forbidden` rules cannot be
+    // bypassed, not even by a command the user approved for the session.
+    let exec_policy_decision = exec_policy.check(command, sandbox_policy, cwd);
+    match exec_policy_decision {
+        ExecPolicyDecision::Forbidden { reason } => {
+            return SafetyCheck::Reject {
+                reason: format!("forbidden by exec policy: {reason}"),
+            };
+        }
+        ExecPolicyDecision::Safe => {
+            // A program the policy vouches for may still run arbitrary code,
+            // such as the build scripts of `cargo test`.
+            return match get_platform_sandbox() {
+                _ if sandbox_policy == &SandboxPolicy::DangerFullAccess => {
+                    SafetyCheck::AutoApprove {
+                        sandbox_type: SandboxType::None,
+                    }
+                }
+                Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+                None => SafetyCheck::AskUser,
+            };
+        }
+        ExecPolicyDecision::MatchWithinWritableRoots if !with_escalated_permissions => {
+            // Every file the command writes is inside a writable root, so it
+            // can run under the sandbox without asking.
+            return match get_platform_sandbox() {
+                Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+                None if sandbox_policy == &SandboxPolicy::DangerFullAccess => {
+                    SafetyCheck::AutoApprove {
+                        sandbox_type: SandboxType::None,
+                    }
+                }
+                None => SafetyCheck::AskUser,
+            };
+        }
+        ExecPolicyDecision::MatchWithinWritableRoots | ExecPolicyDecision::Unverified => {}
+    }
+
     // Some commands look dangerous. Even if they are run inside a sandbox,
     // unless the user has explicitly approved them, we should ask,
     // or reject if the approval_policy tells us not to ask.
@@ -107,8 +154,8 @@ pub fn assess_command_safety(
     //
     // Currently, whether a command is "trusted" is a simple boolean, but we
     // should include more metadata on this command test to indicate whether it
-    // should be run inside a sandbox or not. (This could be something the user
-    // defines as part of `execpolicy`.)
+    // should be run inside a sandbox or not. (The user's `execpolicy`, checked
+    // above, is the first step in that direction.)
     //
     // For example, when `is_known_safe_command(command)` returns `true`, it
     // would probably be fine to run the command in a sandbox, but when
@@ -183,16 +230,82 @@ pub(crate) fn assess_safety_for_untrusted_command(
     }
 }
 
+/// Decides whether a call to the MCP tool `server`/`tool` needs the user's
+/// approval. MCP tools run outside the sandbox, so approved calls always get
+/// `SandboxType::None`.
+pub(crate) fn assess_mcp_tool_call_safety(
+    server: &str,
+    tool: &str,
+    policy: Option<&McpToolPolicy>,
+    read_only: bool,
+    approval_policy: AskForApproval,
+    approved_tools: &HashSet<(String, String)>,
+) -> SafetyCheck {
+    let auto_approve = SafetyCheck::AutoApprove {
+        sandbox_type: SandboxType::None,
+    };
+    if policy.is_some_and(|policy| policy.allowed_tools.iter().any(|allowed| allowed == tool)) {
+        return auto_approve;
+    }
+
+    // Servers that do not configure `approval` are gated like `on-write`,
+    // unless the approval policy rules out asking; `never` has to be set
+    // explicitly to call every tool unprompted.
+    let approval = policy
+        .and_then(|policy| policy.approval)
+        .unwrap_or(match approval_policy {
+            AskForApproval::Never => McpToolApproval::Never,
+            _ => McpToolApproval::OnWrite,
+        });
+    let needs_approval = match approval {
+        McpToolApproval::Always => true,
+        McpToolApproval::Never => false,
+        McpToolApproval::OnWrite => !read_only,
+    };
+    if !needs_approval || approved_tools.contains(&(server.to_string(), tool.to_string())) {
+        return auto_approve;
+    }
+
+    if approval_policy == AskForApproval::Never {
+        // Nobody can be asked, and the server must not run this tool unprompted.
+        return SafetyCheck::Reject {
+            reason: format!(
+                "calling `{server}/{tool}` requires approval, which is not available with approval policy `never`"
+            ),
+        };
+    }
+    SafetyCheck::AskUser
+}
+
 pub fn get_platform_sandbox() -> Option<SandboxType> {
     if cfg!(target_os = "macos") {
         Some(SandboxType::MacosSeatbelt)
     } else if cfg!(target_os = "linux") {
-        Some(SandboxType::LinuxSeccomp)
+        Some(linux_sandbox_type())
     } else {
         None
     }
 }
 
+/// Landlock where the kernel offers it, otherwise bubblewrap if it works here.
+/// Probed once per process.
+#[cfg(target_os = "linux")]
+fn linux_sandbox_type() -> SandboxType {
+    static SANDBOX_TYPE: std::sync::OnceLock<SandboxType> = std::sync::OnceLock::new();
+    *SANDBOX_TYPE.get_or_init(|| {
+        if !crate::landlock::landlock_available() && crate::bwrap::bwrap_available() {
+            SandboxType::Bubblewrap
+        } else {
+            SandboxType::LinuxSeccomp
+        }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn linux_sandbox_type() -> SandboxType {
+    SandboxType::LinuxSeccomp
+}
+
 fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     sandbox_policy: &SandboxPolicy,
@@ -291,6 +404,8 @@ mod tests {
         let policy_workspace_only = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -312,6 +427,8 @@ mod tests {
         let policy_with_parent = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![parent],
             network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -336,6 +453,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -356,6 +475,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -380,6 +501,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -405,6 +528,8 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
+            Path::new("/"),
             request_escalated_privileges,
         );
 
@@ -414,4 +539,181 @@ mod tests {
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn exec_policy_forbidden_overrides_session_approval() {
+        let tmp = TempDir::new().unwrap();
+        let policy_path = tmp.path().join("team.policy");
+        std::fs::write(
+            &policy_path,
+            r#"
+define_program(
+    program="cargo",
+    args=["test"],
+)
+
+define_program(
+    program="git",
+    args=["push", "origin", "main"],
+    forbidden="push through a pull request instead",
+)
+"#,
+        )
+        .unwrap();
+        let exec_policy = ExecPolicy::load(&[policy_path]).unwrap();
+
+        let push = vec![
+            "git".to_string(),
+            "push".to_string(),
+            "origin".to_string(),
+            "main".to_string(),
+        ];
+        let mut approved: HashSet<Vec<String>> = HashSet::new();
+        approved.insert(push.clone());
+        assert_eq!(
+            assess_command_safety(
+                &push,
+                AskForApproval::OnRequest,
+                &SandboxPolicy::DangerFullAccess,
+                &approved,
+                &exec_policy,
+                tmp.path(),
+                false,
+            ),
+            SafetyCheck::Reject {
+                reason: "forbidden by exec policy: push through a pull request instead".to_string(),
+            }
+        );
+
+        // `cargo test` is vouched for by the policy, so it is auto-approved
+        // even when the user asked to approve every untrusted command, but
+        // its build scripts still run in the sandbox.
+        let cargo_test = ["cargo".to_string(), "test".to_string()];
+        assert_eq!(
+            assess_command_safety(
+                &cargo_test,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::ReadOnly,
+                &HashSet::new(),
+                &exec_policy,
+                tmp.path(),
+                false,
+            ),
+            match get_platform_sandbox() {
+                Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+                None => SafetyCheck::AskUser,
+            }
+        );
+        assert_eq!(
+            assess_command_safety(
+                &cargo_test,
+                AskForApproval::UnlessTrusted,
+                &SandboxPolicy::DangerFullAccess,
+                &HashSet::new(),
+                &exec_policy,
+                tmp.path(),
+                false,
+            ),
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+    }
+
+    #[test]
+    fn mcp_tool_call_follows_server_approval_settings() {
+        let policy = |approval| McpToolPolicy {
+            approval,
+            allowed_tools: vec!["search".to_string()],
+            denied_tools: Vec::new(),
+        };
+        let auto = SafetyCheck::AutoApprove {
+            sandbox_type: SandboxType::None,
+        };
+        let none = HashSet::new();
+        let assess = |policy: &McpToolPolicy, tool, read_only, approval_policy, approved| {
+            assess_mcp_tool_call_safety(
+                "docs",
+                tool,
+                Some(policy),
+                read_only,
+                approval_policy,
+                approved,
+            )
+        };
+
+        // Without a setting, only tools that may write need approval, unless
+        // nobody can be asked.
+        let default = policy(None);
+        assert_eq!(
+            assess(&default, "update", false, AskForApproval::OnRequest, &none),
+            SafetyCheck::AskUser
+        );
+        assert_eq!(
+            assess(&default, "fetch", true, AskForApproval::OnRequest, &none),
+            auto
+        );
+        assert_eq!(
+            assess(&default, "update", false, AskForApproval::Never, &none),
+            auto
+        );
+        assert_eq!(
+            assess_mcp_tool_call_safety(
+                "docs",
+                "update",
+                None,
+                false,
+                AskForApproval::UnlessTrusted,
+                &none
+            ),
+            SafetyCheck::AskUser
+        );
+
+        // With `on-write`, only tools that may write need approval.
+        let on_write = policy(Some(McpToolApproval::OnWrite));
+        assert_eq!(
+            assess(&on_write, "fetch", true, AskForApproval::OnRequest, &none),
+            auto
+        );
+        assert_eq!(
+            assess(&on_write, "update", false, AskForApproval::OnRequest, &none),
+            SafetyCheck::AskUser
+        );
+
+        // Allowed tools and tools approved for the session are never gated.
+        let always = policy(Some(McpToolApproval::Always));
+        assert_eq!(
+            assess(&always, "search", false, AskForApproval::OnRequest, &none),
+            auto
+        );
+        assert_eq!(
+            assess(&always, "fetch", true, AskForApproval::OnRequest, &none),
+            SafetyCheck::AskUser
+        );
+        let approved = HashSet::from([("docs".to_string(), "fetch".to_string())]);
+        assert_eq!(
+            assess(&always, "fetch", true, AskForApproval::OnRequest, &approved),
+            auto
+        );
+
+        // Nobody can approve the call under `never`.
+        assert_eq!(
+            assess(&always, "fetch", true, AskForApproval::Never, &none),
+            SafetyCheck::Reject {
+                reason: "calling `docs/fetch` requires approval, which is not available with approval policy `never`".to_string(),
+            }
+        );
+
+        let never = policy(Some(McpToolApproval::Never));
+        assert_eq!(
+            assess(
+                &never,
+                "update",
+                false,
+                AskForApproval::UnlessTrusted,
+                &none
+            ),
+            auto
+        );
+    }
 }
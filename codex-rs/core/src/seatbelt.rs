@@ -3,6 +3,8 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::config_types::ResourceLimits;
+use crate::deny_read::resolve_deny_read_paths;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
@@ -14,13 +16,14 @@ const MACOS_SEATBELT_BASE_POLICY: &str = include_str!("seatbelt_base_policy.sbpl
 /// to defend against an attacker trying to inject a malicious version on the
 /// PATH. If /usr/bin/sandbox-exec has been tampered with, then the attacker
 /// already has root access.
-const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
+pub(crate) const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
 
 pub async fn spawn_command_under_seatbelt(
     command: Vec<String>,
     command_cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
+    resource_limits: &ResourceLimits,
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
@@ -33,13 +36,14 @@ pub async fn spawn_command_under_seatbelt(
         arg0,
         command_cwd,
         sandbox_policy,
+        resource_limits,
         stdio_policy,
         env,
     )
     .await
 }
 
-fn create_seatbelt_command_args(
+pub(crate) fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     sandbox_policy_cwd: &Path,
@@ -110,12 +114,25 @@ fn create_seatbelt_command_args(
         ""
     };
 
-    let full_policy = format!(
+    let mut full_policy = format!(
         "{MACOS_SEATBELT_BASE_POLICY}\n{file_read_policy}\n{file_write_policy}\n{network_policy}"
     );
 
+    // Later rules win in SBPL, so the denials go last to override the allows
+    // above.
+    let deny_read_paths = resolve_deny_read_paths(sandbox_policy.deny_read(), sandbox_policy_cwd);
+    let mut deny_read_args: Vec<String> = Vec::new();
+    for (index, path) in deny_read_paths.iter().enumerate() {
+        let param = format!("DENY_READ_{index}");
+        full_policy.push_str(&format!(
+            "\n(deny file-read* file-write* (subpath (param \"{param}\")))"
+        ));
+        deny_read_args.push(format!("-D{param}={}", path.to_string_lossy()));
+    }
+
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
+    seatbelt_args.extend(deny_read_args);
     seatbelt_args.push("--".to_string());
     seatbelt_args.extend(command);
     seatbelt_args
@@ -156,6 +173,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![root_with_git, root_without_git],
             network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
         };
@@ -231,6 +250,8 @@ mod tests {
         let policy = SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         };
@@ -301,6 +322,49 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_denies_deny_read_paths() {
+        if cfg!(target_os = "windows") {
+            // /tmp does not exist on Windows, so skip this test.
+            return;
+        }
+
+        let tmp = TempDir::new().expect("tempdir");
+        let cwd = tmp.path().canonicalize().expect("canonicalize tempdir");
+        fs::write(cwd.join(".env"), "SECRET=1").expect("write .env");
+        fs::write(cwd.join(".env.local"), "SECRET=2").expect("write .env.local");
+
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![".env*".to_string()],
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+
+        let args = create_seatbelt_command_args(
+            vec!["/bin/cat".to_string(), ".env".to_string()],
+            &policy,
+            &cwd,
+        );
+
+        assert!(args[1].ends_with(
+            "\n(deny file-read* file-write* (subpath (param \"DENY_READ_0\")))\n(deny file-read* file-write* (subpath (param \"DENY_READ_1\")))"
+        ));
+        assert_eq!(
+            args[2..],
+            [
+                format!("-DWRITABLE_ROOT_0={}", cwd.to_string_lossy()),
+                format!("-DDENY_READ_0={}", cwd.join(".env").to_string_lossy()),
+                format!("-DDENY_READ_1={}", cwd.join(".env.local").to_string_lossy()),
+                "--".to_string(),
+                "/bin/cat".to_string(),
+                ".env".to_string(),
+            ]
+        );
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
@@ -0,0 +1,220 @@
+//! The contents of files as the model last saw them, either because a command
+//! printed them or because a patch wrote them. When a later patch no longer
+//! applies because the file has changed since (typically because a formatter
+//! rewrote it), these contents serve as the base of a three-way merge.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+
+use crate::parse_command::ParsedCommand;
+use crate::parse_command::parse_command;
+use crate::protocol::FileChange;
+
+/// Larger files are not kept.
+const MAX_SEEN_FILE_BYTES: usize = 1024 * 1024;
+
+/// Once the kept files add up to more than this, the least recently seen
+/// ones are dropped.
+const MAX_SEEN_FILES_TOTAL_BYTES: usize = 16 * 1024 * 1024;
+
+/// Cloning is cheap: the contents are shared, so callers can take a copy and
+/// release the session lock before reading files.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SeenFiles {
+    /// Ordered from least to most recently seen.
+    contents: IndexMap<PathBuf, Arc<str>>,
+    total_bytes: usize,
+}
+
+impl SeenFiles {
+    /// The contents of `path` when the model last saw it.
+    pub(crate) fn get(&self, path: &Path) -> Option<String> {
+        self.contents
+            .get(&canonical_file_path(path))
+            .map(ToString::to_string)
+    }
+
+    /// Records the files that `command`, which ran successfully in `cwd`,
+    /// printed.
+    ///
+    /// The whole file is recorded even when the command printed only part of
+    /// it, as `sed -n` and `head` do: it is read right after the command, so
+    /// it is still the file the model looked at, and a later patch's context
+    /// lines come from the part that was printed.
+    pub(crate) fn record_read_command(&mut self, command: &[String], cwd: &Path) {
+        for parsed in parse_command(command) {
+            let ParsedCommand::Read { cmd, name } = parsed else {
+                continue;
+            };
+            let Some(path) = shlex::split(&cmd)
+                .and_then(|args| args.last().cloned())
+                .map(|arg| cwd.join(arg))
+            else {
+                continue;
+            };
+            if path
+                .file_name()
+                .is_some_and(|file_name| file_name == name.as_str())
+                && let Ok(contents) = std::fs::read_to_string(&path)
+            {
+                self.record(&path, contents);
+            }
+        }
+    }
+
+    /// Records the files that a successfully applied patch wrote.
+    pub(crate) fn record_patch(&mut self, changes: &HashMap<PathBuf, FileChange>) {
+        for (path, change) in changes {
+            let written = match change {
+                FileChange::Add { .. } => path,
+                FileChange::Delete { .. } => {
+                    self.forget(path);
+                    continue;
+                }
+                FileChange::Update {
+                    move_path: Some(move_path),
+                    ..
+                } => {
+                    self.forget(path);
+                    move_path
+                }
+                FileChange::Update {
+                    move_path: None, ..
+                } => path,
+            };
+            match std::fs::read_to_string(written) {
+                Ok(contents) => self.record(written, contents),
+                Err(_) => self.forget(written),
+            }
+        }
+    }
+
+    fn record(&mut self, path: &Path, contents: String) {
+        let path = canonical_file_path(path);
+        self.remove(&path);
+        if contents.len() > MAX_SEEN_FILE_BYTES {
+            return;
+        }
+        self.total_bytes += contents.len();
+        self.contents.insert(path, contents.into());
+        while self.total_bytes > MAX_SEEN_FILES_TOTAL_BYTES
+            && let Some((_, evicted)) = self.contents.shift_remove_index(0)
+        {
+            self.total_bytes -= evicted.len();
+        }
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.remove(&canonical_file_path(path));
+    }
+
+    fn remove(&mut self, canonical_path: &Path) {
+        if let Some(contents) = self.contents.shift_remove(canonical_path) {
+            self.total_bytes -= contents.len();
+        }
+    }
+}
+
+/// Canonicalizes `path`, or only its parent directory when the file itself
+/// no longer exists.
+fn canonical_file_path(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map_or_else(|_| path.to_path_buf(), |parent| parent.join(name)),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn records_files_printed_by_read_commands() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let cwd = tmp.path();
+        std::fs::create_dir(cwd.join("src"))?;
+        std::fs::write(cwd.join("src/lib.rs"), "fn main() {}\n")?;
+        std::fs::write(cwd.join("notes.txt"), "notes\n")?;
+
+        let mut seen = SeenFiles::default();
+        seen.record_read_command(
+            &[
+                "bash".to_string(),
+                "-lc".to_string(),
+                "sed -n '1,200p' src/lib.rs && ls".to_string(),
+            ],
+            cwd,
+        );
+
+        assert_eq!(
+            seen.get(&cwd.join("src/../src/lib.rs")),
+            Some("fn main() {}\n".to_string())
+        );
+        assert_eq!(seen.get(&cwd.join("notes.txt")), None);
+        Ok(())
+    }
+
+    #[test]
+    fn records_patched_contents_and_forgets_deleted_files() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let deleted = tmp.path().join("deleted.txt");
+        let moved = tmp.path().join("moved.txt");
+        let mut seen = SeenFiles::default();
+        seen.record(&deleted, "old\n".to_string());
+        seen.record(&tmp.path().join("a.txt"), "a\n".to_string());
+
+        // The patch has already been applied when it is recorded.
+        std::fs::write(&moved, "a, updated\n")?;
+        seen.record_patch(&HashMap::from([
+            (
+                deleted.clone(),
+                FileChange::Delete {
+                    content: "old\n".to_string(),
+                },
+            ),
+            (
+                tmp.path().join("a.txt"),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: Some(moved.clone()),
+                },
+            ),
+        ]));
+
+        assert_eq!(seen.get(&deleted), None);
+        assert_eq!(seen.get(&tmp.path().join("a.txt")), None);
+        assert_eq!(seen.get(&moved), Some("a, updated\n".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn least_recently_seen_files_are_dropped_over_the_total_bound() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let contents = "x".repeat(MAX_SEEN_FILE_BYTES);
+        let mut seen = SeenFiles::default();
+        let count = MAX_SEEN_FILES_TOTAL_BYTES / MAX_SEEN_FILE_BYTES;
+        for i in 0..count {
+            seen.record(&tmp.path().join(format!("{i}.txt")), contents.clone());
+        }
+        // Seeing the first file again makes the second the oldest.
+        seen.record(&tmp.path().join("0.txt"), contents.clone());
+
+        seen.record(&tmp.path().join("new.txt"), contents);
+
+        assert!(seen.get(&tmp.path().join("0.txt")).is_some());
+        assert_eq!(seen.get(&tmp.path().join("1.txt")), None);
+        assert!(seen.get(&tmp.path().join("new.txt")).is_some());
+        assert_eq!(seen.total_bytes, MAX_SEEN_FILES_TOTAL_BYTES);
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use codex_protocol::mcp_protocol::ConversationId;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::function_tool::FunctionCallError;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::rollout::index::IndexedMessage;
+use crate::rollout::index::SessionIndex;
+use crate::rollout::index::tokenize;
+use crate::rollout::sessions::TranscriptRole;
+
+pub(crate) const SEARCH_PAST_SESSIONS_TOOL_NAME: &str = "search_past_sessions";
+
+const DEFAULT_LIMIT: usize = 5;
+const MAX_LIMIT: usize = 20;
+/// Length of the excerpt shown for each hit, in characters.
+const EXCERPT_CHARS: usize = 400;
+/// Characters of context kept before the first matching term.
+const EXCERPT_LEAD_CHARS: usize = 80;
+
+pub(crate) static SEARCH_PAST_SESSIONS_TOOL: LazyLock<OpenAiTool> = LazyLock::new(|| {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "query".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Keywords to look for in earlier messages, e.g. `sqlite cache decision`."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some(format!(
+                "Maximum number of messages to return (default {DEFAULT_LIMIT}, at most {MAX_LIMIT})."
+            )),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: SEARCH_PAST_SESSIONS_TOOL_NAME.to_string(),
+        description: r#"Searches the user and assistant messages of the user's earlier Codex sessions in the current working directory.
+Use it to recall decisions, conventions or context discussed previously instead of asking the user to repeat them.
+When relying on a result, cite it as [session <id> at <timestamp>]."#
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+});
+
+#[derive(Deserialize)]
+struct SearchPastSessionsArgs {
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Brings the on-disk session index up to date with the rollouts in
+/// `codex_home` and returns the messages matching the query that were
+/// recorded under `cwd`, leaving out the current conversation.
+pub(crate) async fn handle_search_past_sessions(
+    codex_home: &Path,
+    cwd: &Path,
+    conversation_id: ConversationId,
+    arguments: String,
+) -> Result<String, FunctionCallError> {
+    let args: SearchPastSessionsArgs = serde_json::from_str(&arguments).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e}"))
+    })?;
+    if tokenize(&args.query).is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "query must contain at least one word".to_string(),
+        ));
+    }
+    let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let codex_home = codex_home.to_path_buf();
+    let cwd = cwd.to_path_buf();
+    let current_session = conversation_id.to_string();
+    tokio::task::spawn_blocking(move || {
+        let mut index = SessionIndex::load(&codex_home);
+        if index.update(&codex_home)
+            && let Err(e) = index.save(&codex_home)
+        {
+            warn!("failed to save session index: {e}");
+        }
+        let hits = index.search(&args.query, limit, &cwd, Some(&current_session));
+        Ok(format_hits(&args.query, &hits))
+    })
+    .await
+    .map_err(|e| FunctionCallError::RespondToModel(format!("session search failed: {e}")))?
+}
+
+fn format_hits(query: &str, hits: &[&IndexedMessage]) -> String {
+    if hits.is_empty() {
+        return format!("No messages in past sessions matched \"{query}\".");
+    }
+
+    let terms = tokenize(query);
+    let noun = if hits.len() == 1 {
+        "message"
+    } else {
+        "messages"
+    };
+    let mut out = format!("Found {} {noun} in past sessions:\n", hits.len());
+    for hit in hits {
+        let role = match hit.role {
+            TranscriptRole::User => "user",
+            TranscriptRole::Assistant => "assistant",
+        };
+        out.push_str(&format!(
+            "\n[session {} at {}] {role} in {}:\n{}\n",
+            hit.session_id,
+            hit.timestamp,
+            hit.cwd.display(),
+            excerpt(&hit.text, &terms)
+        ));
+    }
+    out
+}
+
+/// A window of `text` starting shortly before the first matching term, with
+/// whitespace collapsed.
+fn excerpt<'a>(text: &str, terms: impl IntoIterator<Item = &'a String>) -> String {
+    let lower = text.to_lowercase();
+    // Byte offsets only carry over when lowercasing kept the length.
+    let first_match = if lower.len() == text.len() {
+        terms
+            .into_iter()
+            .filter_map(|term| lower.find(term.as_str()))
+            .min()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let start = text
+        .get(..first_match)
+        .map_or(0, |prefix| prefix.chars().count())
+        .saturating_sub(EXCERPT_LEAD_CHARS);
+
+    let window: String = text.chars().skip(start).take(EXCERPT_CHARS).collect();
+    // Drop the word the window cuts into.
+    let skip_words = usize::from(start > 0);
+    let mut excerpt = window
+        .split_whitespace()
+        .skip(skip_words)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if start > 0 {
+        excerpt.insert_str(0, "… ");
+    }
+    if text.chars().count() > start + EXCERPT_CHARS {
+        excerpt.push_str(" …");
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn formats_hits_as_citations() {
+        let text = format!(
+            "{} We picked SQLite for the cache.\nIt is faster.",
+            "filler ".repeat(30)
+        );
+        let hit = IndexedMessage {
+            session_id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            timestamp: "2025-10-01T10:00:05.000Z".to_string(),
+            role: TranscriptRole::Assistant,
+            cwd: PathBuf::from("/work/app"),
+            text,
+        };
+        let expected = format!(
+            "Found 1 message in past sessions:\n\n[session 67e55044-10b1-426f-9247-bb680e5fe0c8 at 2025-10-01T10:00:05.000Z] assistant in /work/app:\n… {} We picked SQLite for the cache. It is faster.\n",
+            "filler ".repeat(9).trim_end()
+        );
+        assert_eq!(format_hits("sqlite", &[&hit]), expected);
+        assert_eq!(
+            format_hits("postgres", &[]),
+            "No messages in past sessions matched \"postgres\"."
+        );
+    }
+}
@@ -305,6 +305,7 @@ mod tests {
         for (input, expected_cmd, expected_output) in cases {
             use std::collections::HashMap;
 
+            use crate::config_types::ResourceLimits;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -347,6 +348,7 @@ mod tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    resource_limits: ResourceLimits::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
@@ -412,6 +414,7 @@ mod macos_tests {
             use std::collections::HashMap;
             use std::path::PathBuf;
 
+            use crate::config_types::ResourceLimits;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -455,6 +458,7 @@ mod macos_tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    resource_limits: ResourceLimits::default(),
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
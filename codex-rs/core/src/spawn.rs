@@ -5,6 +5,7 @@ use tokio::process::Child;
 use tokio::process::Command;
 use tracing::trace;
 
+use crate::config_types::ResourceLimits;
 use crate::protocol::SandboxPolicy;
 
 /// Experimental environment variable that will be set to some non-empty value
@@ -35,19 +36,49 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
+///
+/// `resource_limits` are applied to the child; see [`crate::resource_limits`].
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn spawn_child_async(
     program: PathBuf,
     args: Vec<String>,
     #[cfg_attr(not(unix), allow(unused_variables))] arg0: Option<&str>,
     cwd: PathBuf,
     sandbox_policy: &SandboxPolicy,
+    resource_limits: &ResourceLimits,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
     trace!(
-        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?}"
+        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {resource_limits:?} {stdio_policy:?} {env:?}"
     );
 
+    // `systemd-run` cannot pass on a custom arg0, so such commands only get
+    // the rlimits.
+    #[cfg(target_os = "linux")]
+    let systemd_scope = match arg0 {
+        Some(arg0) if program.file_name() != Some(std::ffi::OsStr::new(arg0)) => None,
+        _ => crate::resource_limits::systemd_scope(resource_limits).await,
+    };
+    #[cfg(target_os = "linux")]
+    let (systemd_scope, scope_unit) = match systemd_scope {
+        Some(scope) => (Some(scope.prefix), Some(scope.unit)),
+        None => (None, None),
+    };
+    #[cfg(not(target_os = "linux"))]
+    let systemd_scope: Option<Vec<String>> = None;
+    let in_systemd_scope = systemd_scope.is_some();
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    let (program, args, arg0) = match systemd_scope {
+        Some(mut prefix) => {
+            let wrapper = PathBuf::from(prefix.remove(0));
+            prefix.push(program.to_string_lossy().to_string());
+            prefix.extend(args);
+            (wrapper, prefix, None)
+        }
+        None => (program, args, arg0),
+    };
+
     let mut cmd = Command::new(&program);
     #[cfg(unix)]
     cmd.arg0(arg0.map_or_else(|| program.to_string_lossy().to_string(), String::from));
@@ -85,6 +116,18 @@ pub(crate) async fn spawn_child_async(
         });
     }
 
+    #[cfg(unix)]
+    {
+        let resource_limits = *resource_limits;
+        unsafe {
+            cmd.pre_exec(move || {
+                crate::resource_limits::set_rlimits(&resource_limits, in_systemd_scope)
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = in_systemd_scope;
+
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
             // Do not create a file descriptor for stdin because otherwise some
@@ -103,5 +146,10 @@ pub(crate) async fn spawn_child_async(
         }
     }
 
-    cmd.kill_on_drop(true).spawn()
+    let child = cmd.kill_on_drop(true).spawn()?;
+    #[cfg(target_os = "linux")]
+    if let (Some(unit), Some(pid)) = (scope_unit, child.id()) {
+        crate::resource_limits::register_scope(pid, unit);
+    }
+    Ok(child)
 }
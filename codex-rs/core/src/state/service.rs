@@ -1,5 +1,8 @@
 use crate::RolloutRecorder;
+use crate::delegate_task::DelegateTaskManager;
 use crate::exec_command::ExecSessionManager;
+use crate::exec_policy::ExecPolicy;
+use crate::hooks::HookRunner;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_notification::UserNotifier;
@@ -11,8 +14,13 @@ pub(crate) struct SessionServices {
     pub(crate) session_manager: ExecSessionManager,
     pub(crate) unified_exec_manager: UnifiedExecSessionManager,
     pub(crate) notifier: UserNotifier,
+    pub(crate) hooks: HookRunner,
+    pub(crate) delegate_tasks: DelegateTaskManager,
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) codex_linux_sandbox_exe: Option<PathBuf>,
+    pub(crate) codex_home: PathBuf,
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
+    pub(crate) checkpoints: bool,
+    pub(crate) exec_policy: ExecPolicy,
 }
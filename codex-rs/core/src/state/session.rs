@@ -8,14 +8,23 @@ use crate::conversation_history::ConversationHistory;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::seen_files::SeenFiles;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
 #[derive(Default)]
 pub(crate) struct SessionState {
     pub(crate) approved_commands: HashSet<Vec<String>>,
+    /// MCP tools approved for the rest of the session, as (server, tool).
+    pub(crate) approved_mcp_tools: HashSet<(String, String)>,
     pub(crate) history: ConversationHistory,
     pub(crate) token_info: Option<TokenUsageInfo>,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    /// Number of user messages in the rollout, counted the way
+    /// `fork_conversation` counts them.
+    pub(crate) rollout_user_messages: usize,
+    /// Contents of files as the model last saw them, used as merge bases for
+    /// patches to files that changed since.
+    pub(crate) seen_files: SeenFiles,
 }
 
 impl SessionState {
@@ -53,17 +62,29 @@ impl SessionState {
         &self.approved_commands
     }
 
+    pub(crate) fn add_approved_mcp_tool(&mut self, server: String, tool: String) {
+        self.approved_mcp_tools.insert((server, tool));
+    }
+
+    pub(crate) fn approved_mcp_tools_ref(&self) -> &HashSet<(String, String)> {
+        &self.approved_mcp_tools
+    }
+
     // Token/rate limit helpers
     pub(crate) fn update_token_info_from_usage(
         &mut self,
         usage: &TokenUsage,
         model_context_window: Option<u64>,
+        cost_usd: Option<f64>,
     ) {
         self.token_info = TokenUsageInfo::new_or_append(
             &self.token_info,
             &Some(usage.clone()),
             model_context_window,
         );
+        if let (Some(info), Some(cost_usd)) = (self.token_info.as_mut(), cost_usd) {
+            info.add_cost(cost_usd);
+        }
     }
 
     pub(crate) fn set_rate_limits(&mut self, snapshot: RateLimitSnapshot) {
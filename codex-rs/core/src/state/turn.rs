@@ -6,9 +6,9 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::task::AbortHandle;
 
-use codex_protocol::models::ResponseInputItem;
 use tokio::sync::oneshot;
 
+use crate::protocol::InputItem;
 use crate::protocol::ReviewDecision;
 use crate::tasks::SessionTask;
 
@@ -60,7 +60,9 @@ impl ActiveTurn {
 #[derive(Default)]
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
-    pending_input: Vec<ResponseInputItem>,
+    /// Input submitted while the turn was running, one entry per submission.
+    /// MCP references in it are resolved when the turn picks it up.
+    pending_input: Vec<Vec<InputItem>>,
 }
 
 impl TurnState {
@@ -84,11 +86,11 @@ impl TurnState {
         self.pending_input.clear();
     }
 
-    pub(crate) fn push_pending_input(&mut self, input: ResponseInputItem) {
+    pub(crate) fn push_pending_input(&mut self, input: Vec<InputItem>) {
         self.pending_input.push(input);
     }
 
-    pub(crate) fn take_pending_input(&mut self) -> Vec<ResponseInputItem> {
+    pub(crate) fn take_pending_input(&mut self) -> Vec<Vec<InputItem>> {
         if self.pending_input.is_empty() {
             Vec::with_capacity(0)
         } else {
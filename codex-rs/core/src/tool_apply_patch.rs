@@ -110,6 +110,7 @@ It is important to remember:
 - You must include a header with your intended action (Add/Delete/Update)
 - You must prefix new lines with `+` even when creating a new file
 - File references can only be relative, NEVER ABSOLUTE.
+- A standard unified diff (`--- a/path`, `+++ b/path`, `@@` hunks), such as `git diff` prints, is also accepted in place of the envelope.
 "#
         .to_string(),
         strict: false,
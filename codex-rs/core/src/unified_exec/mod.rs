@@ -1,4 +1,3 @@
-use portable_pty::CommandBuilder;
 use portable_pty::PtySize;
 use portable_pty::native_pty_system;
 use std::collections::HashMap;
@@ -7,6 +6,7 @@ use std::io::ErrorKind;
 use std::io::Read;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
@@ -17,7 +17,10 @@ use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tokio::time::Instant;
 
+use crate::exec::SandboxType;
+use crate::exec::SandboxedCommand;
 use crate::exec_command::ExecCommandSession;
+use crate::exec_command::spawn_pty_command;
 use crate::truncate::truncate_middle;
 
 mod errors;
@@ -33,12 +36,17 @@ pub(crate) struct UnifiedExecRequest<'a> {
     pub session_id: Option<i32>,
     pub input_chunks: &'a [String],
     pub timeout_ms: Option<u64>,
+    /// `input_chunks` wrapped for the sandbox of a new session; required
+    /// when `session_id` is `None`.
+    pub command: Option<SandboxedCommand>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct UnifiedExecResult {
     pub session_id: Option<i32>,
     pub output: String,
+    /// Set once the session's process has exited.
+    pub exit_code: Option<i32>,
 }
 
 #[derive(Debug, Default)]
@@ -55,6 +63,7 @@ struct ManagedUnifiedExecSession {
     /// `output_buffer`, allowing clients to poll for fresh data.
     output_notify: Arc<Notify>,
     output_task: JoinHandle<()>,
+    exit_code: Arc<OnceLock<i32>>,
 }
 
 #[derive(Debug, Default)]
@@ -103,6 +112,7 @@ impl ManagedUnifiedExecSession {
     fn new(
         session: ExecCommandSession,
         initial_output_rx: tokio::sync::broadcast::Receiver<Vec<u8>>,
+        exit_code: Arc<OnceLock<i32>>,
     ) -> Self {
         let output_buffer = Arc::new(Mutex::new(OutputBufferState::default()));
         let output_notify = Arc::new(Notify::new());
@@ -123,6 +133,7 @@ impl ManagedUnifiedExecSession {
             output_buffer,
             output_notify,
             output_task,
+            exit_code,
         }
     }
 
@@ -140,6 +151,10 @@ impl ManagedUnifiedExecSession {
     fn has_exited(&self) -> bool {
         self.session.has_exited()
     }
+
+    fn exit_code(&self) -> Option<i32> {
+        self.exit_code.get().copied()
+    }
 }
 
 impl Drop for ManagedUnifiedExecSession {
@@ -149,6 +164,23 @@ impl Drop for ManagedUnifiedExecSession {
 }
 
 impl UnifiedExecSessionManager {
+    /// The sandbox of session `session_id` and the command lines that writing
+    /// `input` to it would submit.
+    pub(crate) async fn submitted_lines(
+        &self,
+        session_id: i32,
+        input: &str,
+    ) -> Result<(SandboxType, Vec<String>), UnifiedExecError> {
+        let sessions = self.sessions.lock().await;
+        match sessions.get(&session_id) {
+            Some(session) => Ok((
+                session.session.sandbox_type(),
+                session.session.submitted_lines(input),
+            )),
+            None => Err(UnifiedExecError::UnknownSessionId { session_id }),
+        }
+    }
+
     pub async fn handle_request(
         &self,
         request: UnifiedExecRequest<'_>,
@@ -181,6 +213,9 @@ impl UnifiedExecSessionManager {
                         });
                     }
                     let (buffer, notify) = session.output_handles();
+                    session
+                        .session
+                        .record_input(&request.input_chunks.join(" "));
                     session_id = existing_id;
                     writer_tx = session.writer_sender();
                     output_buffer = buffer;
@@ -194,10 +229,14 @@ impl UnifiedExecSessionManager {
             }
             drop(sessions);
         } else {
-            let command = request.input_chunks.to_vec();
+            let Some(command) = request.command else {
+                return Err(UnifiedExecError::MissingCommandLine);
+            };
             let new_id = self.next_session_id.fetch_add(1, Ordering::SeqCst);
-            let (session, initial_output_rx) = create_unified_exec_session(&command).await?;
-            let managed_session = ManagedUnifiedExecSession::new(session, initial_output_rx);
+            let (session, initial_output_rx, exit_code) =
+                create_unified_exec_session(command).await?;
+            let managed_session =
+                ManagedUnifiedExecSession::new(session, initial_output_rx, exit_code);
             let (buffer, notify) = managed_session.output_handles();
             writer_tx = managed_session.writer_sender();
             output_buffer = buffer;
@@ -263,12 +302,15 @@ impl UnifiedExecSessionManager {
             output
         };
 
+        let mut exit_code = None;
         let should_store_session = if let Some(session) = new_session.as_ref() {
+            exit_code = session.exit_code();
             !session.has_exited()
         } else if request.session_id.is_some() {
             let mut sessions = self.sessions.lock().await;
             if let Some(existing) = sessions.get(&session_id) {
                 if existing.has_exited() {
+                    exit_code = existing.exit_code();
                     sessions.remove(&session_id);
                     false
                 } else {
@@ -288,29 +330,28 @@ impl UnifiedExecSessionManager {
             Ok(UnifiedExecResult {
                 session_id: Some(session_id),
                 output,
+                exit_code: None,
             })
         } else {
             Ok(UnifiedExecResult {
                 session_id: None,
                 output,
+                exit_code,
             })
         }
     }
 }
 
 async fn create_unified_exec_session(
-    command: &[String],
+    command: SandboxedCommand,
 ) -> Result<
     (
         ExecCommandSession,
         tokio::sync::broadcast::Receiver<Vec<u8>>,
+        Arc<OnceLock<i32>>,
     ),
     UnifiedExecError,
 > {
-    if command.is_empty() {
-        return Err(UnifiedExecError::MissingCommandLine);
-    }
-
     let pty_system = native_pty_system();
 
     let pair = pty_system
@@ -322,23 +363,15 @@ async fn create_unified_exec_session(
         })
         .map_err(UnifiedExecError::create_session)?;
 
-    // Safe thanks to the check at the top of the function.
-    let mut command_builder = CommandBuilder::new(command[0].clone());
-    for arg in &command[1..] {
-        command_builder.arg(arg);
-    }
-
-    let mut child = pair
-        .slave
-        .spawn_command(command_builder)
+    let (mut child, master) = spawn_pty_command(pair, &command)
+        .await
         .map_err(UnifiedExecError::create_session)?;
     let killer = child.clone_killer();
 
     let (writer_tx, mut writer_rx) = mpsc::channel::<Vec<u8>>(128);
     let (output_tx, _) = tokio::sync::broadcast::channel::<Vec<u8>>(256);
 
-    let mut reader = pair
-        .master
+    let mut reader = master
         .try_clone_reader()
         .map_err(UnifiedExecError::create_session)?;
     let output_tx_clone = output_tx.clone();
@@ -360,8 +393,7 @@ async fn create_unified_exec_session(
         }
     });
 
-    let writer = pair
-        .master
+    let writer = master
         .take_writer()
         .map_err(UnifiedExecError::create_session)?;
     let writer = Arc::new(StdMutex::new(writer));
@@ -384,8 +416,14 @@ async fn create_unified_exec_session(
 
     let exit_status = Arc::new(AtomicBool::new(false));
     let wait_exit_status = Arc::clone(&exit_status);
+    let exit_code = Arc::new(OnceLock::new());
+    let wait_exit_code = Arc::clone(&exit_code);
     let wait_handle = tokio::task::spawn_blocking(move || {
-        let _ = child.wait();
+        let code = match child.wait() {
+            Ok(status) => status.exit_code() as i32,
+            Err(_) => -1,
+        };
+        let _ = wait_exit_code.set(code);
         wait_exit_status.store(true, Ordering::SeqCst);
     });
 
@@ -397,16 +435,40 @@ async fn create_unified_exec_session(
         writer_handle,
         wait_handle,
         exit_status,
+        command.sandbox_type,
+        command.network_proxy,
     );
-    Ok((session, initial_output_rx))
+    Ok((session, initial_output_rx, exit_code))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     #[cfg(unix)]
+    use crate::exec::create_sandboxed_command;
+    #[cfg(unix)]
+    use crate::protocol::SandboxPolicy;
+    #[cfg(unix)]
     use core_test_support::skip_if_sandbox;
 
+    #[cfg(unix)]
+    fn unsandboxed(command: &[String]) -> Option<SandboxedCommand> {
+        let cwd = std::env::current_dir().expect("current dir");
+        let command = create_sandboxed_command(
+            command.to_vec(),
+            cwd.clone(),
+            std::env::vars().collect(),
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &cwd,
+            &None,
+            Default::default(),
+            None,
+        )
+        .expect("unsandboxed command");
+        Some(command)
+    }
+
     #[test]
     fn push_chunk_trims_only_excess_bytes() {
         let mut buffer = OutputBufferState::default();
@@ -436,6 +498,7 @@ mod tests {
                 session_id: None,
                 input_chunks: &["bash".to_string(), "-i".to_string()],
                 timeout_ms: Some(2_500),
+                command: unsandboxed(&["bash".to_string(), "-i".to_string()]),
             })
             .await?;
         let session_id = open_shell.session_id.expect("expected session_id");
@@ -448,6 +511,7 @@ mod tests {
                     "CODEX_INTERACTIVE_SHELL_VAR=codex\n".to_string(),
                 ],
                 timeout_ms: Some(2_500),
+                command: None,
             })
             .await?;
 
@@ -456,6 +520,7 @@ mod tests {
                 session_id: Some(session_id),
                 input_chunks: &["echo $CODEX_INTERACTIVE_SHELL_VAR\n".to_string()],
                 timeout_ms: Some(2_500),
+                command: None,
             })
             .await?;
         assert!(out_2.output.contains("codex"));
@@ -475,6 +540,7 @@ mod tests {
                 session_id: None,
                 input_chunks: &["/bin/bash".to_string(), "-i".to_string()],
                 timeout_ms: Some(2_500),
+                command: unsandboxed(&["/bin/bash".to_string(), "-i".to_string()]),
             })
             .await?;
         let session_a = shell_a.session_id.expect("expected session id");
@@ -484,6 +550,7 @@ mod tests {
                 session_id: Some(session_a),
                 input_chunks: &["export CODEX_INTERACTIVE_SHELL_VAR=codex\n".to_string()],
                 timeout_ms: Some(2_500),
+                command: None,
             })
             .await?;
 
@@ -495,6 +562,10 @@ mod tests {
                     "$CODEX_INTERACTIVE_SHELL_VAR\n".to_string(),
                 ],
                 timeout_ms: Some(2_500),
+                command: unsandboxed(&[
+                    "echo".to_string(),
+                    "$CODEX_INTERACTIVE_SHELL_VAR\n".to_string(),
+                ]),
             })
             .await?;
         assert!(!out_2.output.contains("codex"));
@@ -504,6 +575,7 @@ mod tests {
                 session_id: Some(session_a),
                 input_chunks: &["echo $CODEX_INTERACTIVE_SHELL_VAR\n".to_string()],
                 timeout_ms: Some(2_500),
+                command: None,
             })
             .await?;
         assert!(out_3.output.contains("codex"));
@@ -523,6 +595,7 @@ mod tests {
                 session_id: None,
                 input_chunks: &["bash".to_string(), "-i".to_string()],
                 timeout_ms: Some(2_500),
+                command: unsandboxed(&["bash".to_string(), "-i".to_string()]),
             })
             .await?;
         let session_id = open_shell.session_id.expect("expected session id");
@@ -535,6 +608,7 @@ mod tests {
                     "CODEX_INTERACTIVE_SHELL_VAR=codex\n".to_string(),
                 ],
                 timeout_ms: Some(2_500),
+                command: None,
             })
             .await?;
 
@@ -543,6 +617,7 @@ mod tests {
                 session_id: Some(session_id),
                 input_chunks: &["sleep 5 && echo $CODEX_INTERACTIVE_SHELL_VAR\n".to_string()],
                 timeout_ms: Some(10),
+                command: None,
             })
             .await?;
         assert!(!out_2.output.contains("codex"));
@@ -555,6 +630,7 @@ mod tests {
                 session_id: Some(session_id),
                 input_chunks: &empty,
                 timeout_ms: Some(100),
+                command: None,
             })
             .await?;
 
@@ -574,6 +650,7 @@ mod tests {
                 session_id: None,
                 input_chunks: &["echo".to_string(), "codex".to_string()],
                 timeout_ms: Some(120_000),
+                command: unsandboxed(&["echo".to_string(), "codex".to_string()]),
             })
             .await?;
 
@@ -595,6 +672,7 @@ mod tests {
                 session_id: None,
                 input_chunks: &["/bin/echo".to_string(), "codex".to_string()],
                 timeout_ms: Some(2_500),
+                command: unsandboxed(&["/bin/echo".to_string(), "codex".to_string()]),
             })
             .await?;
 
@@ -618,6 +696,7 @@ mod tests {
                 session_id: None,
                 input_chunks: &["/bin/bash".to_string(), "-i".to_string()],
                 timeout_ms: Some(2_500),
+                command: unsandboxed(&["/bin/bash".to_string(), "-i".to_string()]),
             })
             .await?;
         let session_id = open_shell.session_id.expect("expected session id");
@@ -627,6 +706,7 @@ mod tests {
                 session_id: Some(session_id),
                 input_chunks: &["exit\n".to_string()],
                 timeout_ms: Some(2_500),
+                command: None,
             })
             .await?;
 
@@ -637,6 +717,7 @@ mod tests {
                 session_id: Some(session_id),
                 input_chunks: &[],
                 timeout_ms: Some(100),
+                command: None,
             })
             .await
             .expect_err("expected unknown session error");
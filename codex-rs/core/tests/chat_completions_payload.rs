@@ -27,6 +27,13 @@ fn network_disabled() -> bool {
 }
 
 async fn run_request(input: Vec<ResponseItem>) -> Value {
+    run_request_with_output_schema(input, None).await
+}
+
+async fn run_request_with_output_schema(
+    input: Vec<ResponseItem>,
+    output_schema: Option<Value>,
+) -> Value {
     let server = MockServer::start().await;
 
     let template = ResponseTemplate::new(200)
@@ -56,6 +63,10 @@ async fn run_request(input: Vec<ResponseItem>) -> Value {
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: true,
+        supports_stream_usage: false,
     };
 
     let codex_home = match TempDir::new() {
@@ -81,6 +92,7 @@ async fn run_request(input: Vec<ResponseItem>) -> Value {
 
     let mut prompt = Prompt::default();
     prompt.input = input;
+    prompt.output_schema = output_schema;
 
     let mut stream = match client.stream(&prompt).await {
         Ok(s) => s,
@@ -343,3 +355,61 @@ async fn suppresses_duplicate_assistant_messages() {
         Value::String("dup".into())
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn sends_output_schema_as_response_format() {
+    if network_disabled() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": { "answer": { "type": "string" } },
+        "required": ["answer"],
+        "additionalProperties": false
+    });
+    let body = run_request_with_output_schema(vec![user_message("u1")], Some(schema.clone())).await;
+
+    assert_eq!(
+        body["response_format"],
+        serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": "codex_output_schema",
+                "schema": schema,
+                "strict": true,
+            },
+        })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn omits_response_format_without_output_schema() {
+    if network_disabled() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let body = run_request(vec![user_message("u1")]).await;
+
+    assert!(body.get("response_format").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn omits_stream_options_unless_provider_supports_usage() {
+    if network_disabled() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let body = run_request(vec![user_message("u1")]).await;
+
+    assert!(body.get("stream_options").is_none());
+}
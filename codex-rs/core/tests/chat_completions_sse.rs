@@ -49,6 +49,10 @@ async fn run_stream(sse_body: &str) -> Vec<ResponseEvent> {
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
     };
 
     let codex_home = match TempDir::new() {
@@ -318,3 +322,50 @@ async fn streams_reasoning_before_tool_call() {
 
     assert!(matches!(events[3], ResponseEvent::Completed { .. }));
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streams_reasoning_content_and_trailing_usage() {
+    if network_disabled() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    // xAI-style stream: reasoning under `reasoning_content`, then a usage-only
+    // chunk after `finish_reason` that reports reasoning tokens separately.
+    let sse = concat!(
+        "data: {\"choices\":[{\"delta\":{\"reasoning_content\":\"hmm\"}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"content\":\"done\"}}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":10,\"completion_tokens\":2,\"total_tokens\":17,\"prompt_tokens_details\":{\"cached_tokens\":4},\"completion_tokens_details\":{\"reasoning_tokens\":5}}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    let events = run_stream(sse).await;
+    assert_eq!(events.len(), 5, "unexpected events: {events:?}");
+
+    match &events[0] {
+        ResponseEvent::ReasoningContentDelta(text) => assert_eq!(text, "hmm"),
+        other => panic!("expected reasoning delta, got {other:?}"),
+    }
+
+    match &events[2] {
+        ResponseEvent::OutputItemDone(item) => assert_reasoning(item, "hmm"),
+        other => panic!("expected reasoning item, got {other:?}"),
+    }
+
+    match &events[4] {
+        ResponseEvent::Completed {
+            token_usage: Some(usage),
+            ..
+        } => {
+            assert_eq!(usage.input_tokens, 10);
+            assert_eq!(usage.cached_input_tokens, 4);
+            assert_eq!(usage.output_tokens, 7);
+            assert_eq!(usage.reasoning_output_tokens, 5);
+            assert_eq!(usage.total_tokens, 17);
+        }
+        other => panic!("expected completed with usage, got {other:?}"),
+    }
+}
@@ -0,0 +1,120 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningSummary;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::body_string_contains;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const SCHEMA: &str = r#"
+{
+    "type": "object",
+    "properties": {
+        "final_answer": { "type": "string" }
+    },
+    "required": ["final_answer"],
+    "additionalProperties": false
+}
+"#;
+
+fn chat_sse(content: &str) -> String {
+    let delta = serde_json::json!({ "choices": [{ "delta": { "content": content } }] });
+    let stop = serde_json::json!({ "choices": [{ "delta": {}, "finish_reason": "stop" }] });
+    format!("data: {delta}\n\ndata: {stop}\n\ndata: [DONE]\n\n")
+}
+
+fn chat_response(content: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(chat_sse(content), "text/event-stream")
+}
+
+/// A final message that does not match the schema is sent back to the model
+/// once with the validation error.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reprompts_once_when_output_does_not_match_schema() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    let valid = r#"{"final_answer": "42"}"#;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_string_contains(
+            "does not match the required output schema",
+        ))
+        .respond_with(chat_response(valid))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(body_string_contains("\"response_format\""))
+        .respond_with(chat_response(r#"{"answer": "42"}"#))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = ModelProviderInfo {
+        name: "mock-chat".into(),
+        base_url: Some(format!("{}/v1", server.uri())),
+        env_key: Some("PATH".into()),
+        env_key_instructions: None,
+        wire_api: WireApi::Chat,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(5_000),
+        requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: true,
+        supports_stream_usage: false,
+    };
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(move |config| {
+            config.model_provider = provider;
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "what is the answer?".into(),
+            }],
+            final_output_json_schema: Some(serde_json::from_str(SCHEMA)?),
+            cwd: cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: "grok-code-fast-1".to_string(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    let complete = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        anyhow::bail!("expected task complete event");
+    };
+    assert_eq!(complete.last_agent_message.as_deref(), Some(valid));
+
+    Ok(())
+}
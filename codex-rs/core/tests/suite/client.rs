@@ -654,6 +654,10 @@ async fn azure_responses_request_includes_store_and_reasoning_ids() {
         stream_max_retries: Some(0),
         stream_idle_timeout_ms: Some(5_000),
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
     };
 
     let codex_home = TempDir::new().unwrap();
@@ -1031,6 +1035,10 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
     };
 
     // Init session
@@ -1108,6 +1116,10 @@ async fn env_var_overrides_loaded_auth() {
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
     };
 
     // Init session
@@ -0,0 +1,93 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::mount_sse_sequence;
+use responses::sse;
+use responses::start_mock_server;
+
+/// A worker started under an `untrusted` parent asks the parent's user before
+/// running a command, and does not run it when the request is denied.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn worker_commands_need_the_parents_approval() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let delegate = serde_json::json!({ "tasks": [{ "task": "create a file" }] }).to_string();
+    let touch = serde_json::json!({ "command": ["touch", "created-by-worker"] }).to_string();
+    // Requests arrive in order: the parent's first turn, the worker's two
+    // turns and the parent's turn with the worker's result.
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "delegate_task", &delegate),
+                ev_completed("r1"),
+            ]),
+            sse(vec![
+                ev_function_call("call-2", "shell", &touch),
+                ev_completed("r2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m1", "Not allowed"),
+                ev_completed("r3"),
+            ]),
+            sse(vec![ev_assistant_message("m2", "Done"), ev_completed("r4")]),
+        ],
+    )
+    .await;
+
+    // No sandbox, so only the approval policy stops the worker.
+    let TestCodex { codex, cwd, .. } = test_codex()
+        .with_config(|cfg| {
+            cfg.tools_delegate_task = true;
+            cfg.approval_policy = AskForApproval::UnlessTrusted;
+            cfg.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        })
+        .build(&server)
+        .await?;
+
+    let sub_id = codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "delegate it".into(),
+            }],
+        })
+        .await?;
+
+    let EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+        command, reason, ..
+    }) = wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecApprovalRequest(_))).await
+    else {
+        unreachable!("wait_for_event returned a different event");
+    };
+    assert_eq!(command, vec!["touch", "created-by-worker"]);
+    assert_eq!(reason.as_deref(), Some("Delegated task 1"));
+
+    codex
+        .submit(Op::ExecApproval {
+            id: sub_id,
+            decision: ReviewDecision::Denied,
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    assert!(!cwd.path().join("created-by-worker").exists());
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 4);
+    Ok(())
+}
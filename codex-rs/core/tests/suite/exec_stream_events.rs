@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use async_channel::Receiver;
+use codex_core::config_types::ResourceLimits;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
 use codex_core::exec::ExecParams;
@@ -57,6 +58,7 @@ async fn test_exec_stdout_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -109,6 +111,7 @@ async fn test_exec_stderr_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -164,6 +167,7 @@ async fn test_aggregated_output_interleaves_in_order() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -202,6 +206,7 @@ async fn test_exec_timeout_returns_partial_output() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use codex_core::FallbackProvider;
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use core_test_support::load_sse_fixture;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event_with_timeout;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+fn mock_provider(name: &str, base_url: String) -> ModelProviderInfo {
+    ModelProviderInfo {
+        name: name.into(),
+        base_url: Some(base_url),
+        env_key: Some("PATH".into()),
+        env_key_instructions: None,
+        wire_api: WireApi::Responses,
+        query_params: None,
+        http_headers: None,
+        env_http_headers: None,
+        request_max_retries: Some(0),
+        stream_max_retries: Some(0),
+        stream_idle_timeout_ms: Some(2_000),
+        requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unavailable_provider_falls_back_to_next_provider() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/primary/v1/responses"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let ok = ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(
+            load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp_fallback"),
+            "text/event-stream",
+        );
+    Mock::given(method("POST"))
+        .and(path("/fallback/v1/responses"))
+        .respond_with(ok)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let primary = mock_provider("primary", format!("{}/primary/v1", server.uri()));
+    let fallback = mock_provider("backup", format!("{}/fallback/v1", server.uri()));
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(move |config| {
+            config.model_provider = primary;
+            config.fallback_providers = vec![FallbackProvider {
+                provider_id: "backup".to_string(),
+                provider: fallback,
+                model: Some("backup-model".to_string()),
+            }];
+        })
+        .build(&server)
+        .await
+        .unwrap();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::BackgroundEvent(event) = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::BackgroundEvent(_)),
+        Duration::from_secs(5),
+    )
+    .await
+    else {
+        unreachable!()
+    };
+    assert!(
+        event.message.contains("backup (backup-model)"),
+        "unexpected message: {}",
+        event.message
+    );
+
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failing_stream_falls_back_and_records_served_model() {
+    skip_if_no_network!();
+
+    let server = MockServer::start().await;
+
+    let incomplete = ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(
+            load_sse_fixture("tests/fixtures/incomplete_sse.json"),
+            "text/event-stream",
+        );
+    Mock::given(method("POST"))
+        .and(path("/primary/v1/responses"))
+        .respond_with(incomplete)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let ok = ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(
+            load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp_fallback"),
+            "text/event-stream",
+        );
+    Mock::given(method("POST"))
+        .and(path("/fallback/v1/responses"))
+        .respond_with(ok)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let primary = mock_provider("primary", format!("{}/primary/v1", server.uri()));
+    let fallback = mock_provider("backup", format!("{}/fallback/v1", server.uri()));
+
+    // The rollout lives under the test's CODEX_HOME, so keep it around.
+    let TestCodex {
+        home: _home,
+        codex,
+        session_configured,
+        ..
+    } = test_codex()
+        .with_config(move |config| {
+            config.model_provider = primary;
+            config.fallback_providers = vec![FallbackProvider {
+                provider_id: "backup".to_string(),
+                provider: fallback,
+                model: Some("backup-model".to_string()),
+            }];
+        })
+        .build(&server)
+        .await
+        .unwrap();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::TaskComplete(_)),
+        Duration::from_secs(5),
+    )
+    .await;
+    codex.submit(Op::Shutdown).await.unwrap();
+    wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::ShutdownComplete),
+        Duration::from_secs(5),
+    )
+    .await;
+
+    let rollout = std::fs::read_to_string(&session_configured.rollout_path).unwrap();
+    let last_turn_context = rollout
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+        .filter_map(|line| match line.item {
+            RolloutItem::TurnContext(ctx) => Some(ctx),
+            _ => None,
+        })
+        .next()
+        .expect("turn context recorded");
+    assert_eq!(last_turn_context.model, "backup-model");
+    assert_eq!(last_turn_context.model_provider.as_deref(), Some("backup"));
+}
@@ -0,0 +1,148 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::config_types::HookCommand;
+use codex_core::config_types::Hooks;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+
+use responses::ev_assistant_message;
+use responses::ev_completed;
+use responses::ev_function_call;
+use responses::mount_sse_sequence;
+use responses::sse;
+use responses::start_mock_server;
+
+fn sh(script: &str) -> HookCommand {
+    HookCommand {
+        command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+        timeout_ms: None,
+    }
+}
+
+/// A `pre_tool_use` hook vetoes a force push and the model is told why; a
+/// `user_prompt_submit` hook adds context to the prompt.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hooks_deny_commands_and_add_prompt_context() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let arguments = serde_json::json!({ "command": ["git", "push", "--force"] }).to_string();
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &arguments),
+                ev_completed("r1"),
+            ]),
+            sse(vec![ev_assistant_message("m1", "Done"), ev_completed("r2")]),
+        ],
+    )
+    .await;
+
+    // Hooks run in the session's working directory, so it has to outlive the
+    // turn.
+    let TestCodex { codex, cwd: _cwd, .. } = test_codex()
+        .with_config(|cfg| {
+            cfg.hooks = Hooks {
+                user_prompt_submit: vec![sh(
+                    r#"echo '{"additional_context": "Use --force-with-lease instead of --force."}'"#,
+                )],
+                pre_tool_use: vec![sh(
+                    "grep -q -- '\"--force\"' && { echo 'force push is not allowed' >&2; exit 2; }; exit 0",
+                )],
+                ..Default::default()
+            };
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "push my branch".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    let first = String::from_utf8_lossy(&requests[0].body).to_string();
+    assert!(first.contains("Use --force-with-lease instead of --force."));
+    let second = String::from_utf8_lossy(&requests[1].body).to_string();
+    assert!(second.contains("exec command rejected by hook: force push is not allowed"));
+
+    Ok(())
+}
+
+/// A slow `session_start` hook is cut short instead of holding up the session.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn slow_session_start_hook_does_not_block_the_session() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let started = std::time::Instant::now();
+    let TestCodex { cwd: _cwd, .. } = test_codex()
+        .with_config(|cfg| {
+            cfg.hooks = Hooks {
+                session_start: vec![sh("sleep 60")],
+                ..Default::default()
+            };
+        })
+        .build(&server)
+        .await?;
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(30));
+    Ok(())
+}
+
+/// A `turn_complete` hook that always asks for more work keeps the task going
+/// only as often as `max_follow_ups` allows.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn turn_complete_follow_ups_are_capped() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![ev_assistant_message("m1", "Done"), ev_completed("r1")]),
+            sse(vec![ev_assistant_message("m2", "Done"), ev_completed("r2")]),
+        ],
+    )
+    .await;
+
+    let TestCodex {
+        codex, cwd: _cwd, ..
+    } = test_codex()
+        .with_config(|cfg| {
+            cfg.hooks = Hooks {
+                turn_complete: vec![sh(r#"echo '{"follow_up_message": "keep going"}'"#)],
+                ..Default::default()
+            };
+        })
+        .build(&server)
+        .await?;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "fix the build".into(),
+            }],
+        })
+        .await?;
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 2);
+    let second = String::from_utf8_lossy(&requests[1].body).to_string();
+    assert!(second.contains("keep going"));
+
+    Ok(())
+}
@@ -2,13 +2,17 @@
 
 #[cfg(not(target_os = "windows"))]
 mod abort_tasks;
+mod chat_output_schema;
 mod cli_stream;
 mod client;
 mod compact;
 mod compact_resume_fork;
+mod delegate_task;
 mod exec;
 mod exec_stream_events;
+mod fallback_providers;
 mod fork_conversation;
+mod hooks;
 mod json_result;
 mod live_cli;
 mod model_overrides;
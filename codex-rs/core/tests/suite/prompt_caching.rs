@@ -413,6 +413,8 @@ async fn overrides_turn_context_but_keeps_cached_prefix_and_key_constant() {
             sandbox_policy: Some(SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
                 network_access: true,
+                allowed_domains: vec![],
+                deny_read: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             }),
@@ -546,6 +548,8 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
             sandbox_policy: SandboxPolicy::WorkspaceWrite {
                 writable_roots: vec![writable.path().to_path_buf()],
                 network_access: true,
+                allowed_domains: vec![],
+                deny_read: vec![],
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
             },
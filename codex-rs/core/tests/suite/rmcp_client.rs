@@ -84,6 +84,9 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
                     },
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
+                    approval: None,
+                    allowed_tools: Vec::new(),
+                    denied_tools: Vec::new(),
                 },
             );
         })
@@ -235,6 +238,9 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
                     },
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
+                    approval: None,
+                    allowed_tools: Vec::new(),
+                    denied_tools: Vec::new(),
                 },
             );
         })
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 
+use codex_core::config_types::ResourceLimits;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::CODEX_SANDBOX_ENV_VAR;
@@ -78,6 +79,8 @@ async fn if_parent_of_repo_is_writable_then_dot_git_folder_is_writable() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![test_scenario.repo_parent.clone()],
         network_access: false,
+        allowed_domains: vec![],
+        deny_read: vec![],
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
@@ -104,6 +107,8 @@ async fn if_git_repo_is_writable_root_then_dot_git_folder_is_read_only() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![test_scenario.repo_root.clone()],
         network_access: false,
+        allowed_domains: vec![],
+        deny_read: vec![],
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
@@ -184,6 +189,7 @@ async fn python_getpwuid_works_under_seatbelt() {
         command_cwd,
         &policy,
         sandbox_cwd.as_path(),
+        &ResourceLimits::default(),
         StdioPolicy::RedirectForShellTool,
         HashMap::new(),
     )
@@ -229,6 +235,7 @@ async fn touch(path: &Path, policy: &SandboxPolicy) -> bool {
         command_cwd,
         policy,
         sandbox_cwd.as_path(),
+        &ResourceLimits::default(),
         StdioPolicy::RedirectForShellTool,
         HashMap::new(),
     )
@@ -74,6 +74,10 @@ async fn continue_after_stream_error() {
         stream_max_retries: Some(1),
         stream_idle_timeout_ms: Some(2_000),
         requires_openai_auth: false,
+        reasoning_content_key: None,
+        rate_limit_headers: None,
+        supports_response_format: false,
+        supports_stream_usage: false,
     };
 
     let TestCodex { codex, .. } = test_codex()
@@ -8,6 +8,9 @@ use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::DelegateTaskBeginEvent;
+use codex_core::protocol::DelegateTaskEndEvent;
+use codex_core::protocol::DelegateTaskProgressEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -17,6 +20,7 @@ use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::NetworkRequestEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
@@ -26,6 +30,7 @@ use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
+use codex_protocol::num_format::format_usd;
 use codex_protocol::num_format::format_with_separators;
 use owo_colors::OwoColorize;
 use owo_colors::Style;
@@ -192,11 +197,14 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             }
             EventMsg::TokenCount(ev) => {
                 if let Some(usage_info) = ev.info {
-                    ts_println!(
-                        self,
-                        "tokens used: {}",
-                        format_with_separators(usage_info.total_token_usage.blended_total())
-                    );
+                    let tokens =
+                        format_with_separators(usage_info.total_token_usage.blended_total());
+                    match usage_info.total_cost_usd {
+                        Some(cost) => {
+                            ts_println!(self, "tokens used: {tokens} (cost: {})", format_usd(cost));
+                        }
+                        None => ts_println!(self, "tokens used: {tokens}"),
+                    }
                 }
             }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
@@ -501,6 +509,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ApplyPatchApprovalRequest(_) => {
                 // Should we exit?
             }
+            EventMsg::McpToolCallApprovalRequest(_) => {
+                // Should we exit?
+            }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
                     if !self.reasoning_started {
@@ -580,6 +591,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::McpListPromptsResponse(_) | EventMsg::McpListResourcesResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::CheckpointCreated(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
                 TurnAbortReason::Interrupted => {
                     ts_println!(self, "task interrupted");
@@ -596,6 +613,65 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
+            EventMsg::DelegateTaskBegin(DelegateTaskBeginEvent { index, task, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    format!("delegate [{index}]").style(self.magenta),
+                    task.style(self.bold)
+                );
+            }
+            EventMsg::DelegateTaskProgress(DelegateTaskProgressEvent { index, msg, .. }) => {
+                if let EventMsg::ExecCommandBegin(ExecCommandBeginEvent { command, .. }) = *msg {
+                    ts_println!(
+                        self,
+                        "{} {}",
+                        format!("delegate [{index}] exec").style(self.dimmed),
+                        escape_command(&command)
+                    );
+                }
+            }
+            EventMsg::DelegateTaskEnd(DelegateTaskEndEvent {
+                index,
+                success,
+                output,
+                ..
+            }) => {
+                if success {
+                    ts_println!(
+                        self,
+                        "{}",
+                        format!("delegate [{index}] finished").style(self.green)
+                    );
+                } else {
+                    ts_println!(
+                        self,
+                        "{} {output}",
+                        format!("delegate [{index}] failed:").style(self.red)
+                    );
+                }
+            }
+            EventMsg::NetworkRequest(NetworkRequestEvent {
+                method,
+                host,
+                port,
+                allowed,
+                ..
+            }) => {
+                if allowed {
+                    ts_println!(
+                        self,
+                        "{}",
+                        format!("network {method} {host}:{port}").style(self.dimmed)
+                    );
+                } else {
+                    ts_println!(
+                        self,
+                        "{} {method} {host}:{port}",
+                        "network blocked:".style(self.red)
+                    );
+                }
+            }
         }
         CodexStatus::Running
     }
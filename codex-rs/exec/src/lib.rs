@@ -17,6 +17,7 @@ use codex_core::NewConversation;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::git_info::get_git_repo_root;
+use codex_core::output_schema::check_supported_keywords;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -390,8 +391,8 @@ fn load_output_schema(path: Option<PathBuf>) -> Option<Value> {
         }
     };
 
-    match serde_json::from_str::<Value>(&schema_str) {
-        Ok(value) => Some(value),
+    let schema = match serde_json::from_str::<Value>(&schema_str) {
+        Ok(value) => value,
         Err(err) => {
             eprintln!(
                 "Output schema file {} is not valid JSON: {err}",
@@ -399,5 +400,14 @@ fn load_output_schema(path: Option<PathBuf>) -> Option<Value> {
             );
             std::process::exit(1);
         }
+    };
+
+    if let Err(err) = check_supported_keywords(&schema) {
+        eprintln!(
+            "Output schema file {} is not supported: {err}",
+            path.display()
+        );
+        std::process::exit(1);
     }
+    Some(schema)
 }
@@ -626,6 +626,7 @@ fn task_complete_produces_turn_completed_with_usage() {
         total_token_usage: usage.clone(),
         last_token_usage: usage,
         model_context_window: None,
+        total_cost_usd: None,
     };
     let token_count_event = event(
         "e1",
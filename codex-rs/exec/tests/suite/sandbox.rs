@@ -1,4 +1,5 @@
 #![cfg(unix)]
+use codex_core::config_types::ResourceLimits;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::spawn::StdioPolicy;
 use std::collections::HashMap;
@@ -25,6 +26,7 @@ async fn spawn_command_under_sandbox(
         command_cwd,
         sandbox_policy,
         sandbox_cwd,
+        &ResourceLimits::default(),
         stdio_policy,
         env,
     )
@@ -48,6 +50,8 @@ async fn spawn_command_under_sandbox(
         command_cwd,
         sandbox_policy,
         sandbox_cwd,
+        None,
+        &ResourceLimits::default(),
         stdio_policy,
         env,
     )
@@ -70,6 +74,8 @@ async fn python_multiprocessing_lock_works_under_sandbox() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots,
         network_access: false,
+        allowed_domains: vec![],
+        deny_read: vec![],
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
     };
@@ -130,6 +136,8 @@ async fn sandbox_distinguishes_command_and_policy_cwds() {
     let policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: vec![],
         network_access: false,
+        allowed_domains: vec![],
+        deny_read: vec![],
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
     };
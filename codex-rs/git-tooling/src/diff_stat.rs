@@ -0,0 +1,140 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::GitToolingError;
+use crate::operations::ensure_git_repository;
+use crate::operations::repo_subdir;
+use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_stdout;
+
+/// Summary of the changes between two repository states.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Summarize the changes from commit `from` to commit `to`, or to the working
+/// tree when `to` is `None`. Only paths below `repo_path` are counted.
+///
+/// Comparing against the working tree only covers tracked files, so files
+/// created since `from` are not counted; compare against a fresh ghost commit
+/// to include them.
+pub fn diff_stat(
+    repo_path: &Path,
+    from: &str,
+    to: Option<&str>,
+) -> Result<DiffStat, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+
+    let repo_root = resolve_repository_root(repo_path)?;
+    let repo_prefix = repo_subdir(repo_root.as_path(), repo_path);
+
+    let mut args = vec![
+        OsString::from("diff"),
+        OsString::from("--numstat"),
+        OsString::from(from),
+    ];
+    if let Some(to) = to {
+        args.push(OsString::from(to));
+    }
+    args.push(OsString::from("--"));
+    match repo_prefix.as_deref() {
+        Some(prefix) => args.push(prefix.as_os_str().to_os_string()),
+        None => args.push(OsString::from(".")),
+    }
+
+    let output = run_git_for_stdout(repo_root.as_path(), args, None)?;
+    Ok(parse_numstat(&output))
+}
+
+/// Parses `git diff --numstat` output. Binary files count as changed files
+/// without insertions or deletions.
+fn parse_numstat(output: &str) -> DiffStat {
+    let mut stat = DiffStat::default();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(added), Some(removed), Some(_path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        stat.files_changed += 1;
+        stat.insertions += added.parse::<usize>().unwrap_or(0);
+        stat.deletions += removed.parse::<usize>().unwrap_or(0);
+    }
+    stat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CreateGhostCommitOptions;
+    use crate::create_ghost_commit;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+
+    fn run_git_in(repo_path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo_path)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    #[test]
+    fn parses_numstat_including_binary_files() {
+        let output = "3\t1\tsrc/lib.rs\n-\t-\tassets/logo.png\n10\t0\tREADME.md";
+        assert_eq!(
+            parse_numstat(output),
+            DiffStat {
+                files_changed: 3,
+                insertions: 13,
+                deletions: 1,
+            }
+        );
+        assert_eq!(parse_numstat(""), DiffStat::default());
+    }
+
+    #[test]
+    /// Counts the changes between two ghost commits and against the worktree.
+    fn diff_stat_between_snapshots() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path();
+        run_git_in(repo, &["init", "--initial-branch=main"]);
+        run_git_in(repo, &["config", "core.autocrlf", "false"]);
+        std::fs::write(repo.join("a.txt"), "one\ntwo\n")?;
+        run_git_in(repo, &["add", "a.txt"]);
+        run_git_in(
+            repo,
+            &[
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+
+        let before = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+        std::fs::write(repo.join("a.txt"), "one\nthree\nfour\n")?;
+        std::fs::write(repo.join("b.txt"), "new\n")?;
+        run_git_in(repo, &["add", "b.txt"]);
+        let after = create_ghost_commit(&CreateGhostCommitOptions::new(repo))?;
+
+        assert_eq!(
+            diff_stat(repo, before.id(), Some(after.id()))?,
+            DiffStat {
+                files_changed: 2,
+                insertions: 3,
+                deletions: 1,
+            }
+        );
+        assert_eq!(diff_stat(repo, after.id(), None)?, DiffStat::default());
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
 use std::fmt;
 
+mod diff_stat;
 mod errors;
 mod ghost_commits;
 mod operations;
 mod platform;
 
+pub use diff_stat::DiffStat;
+pub use diff_stat::diff_stat;
 pub use errors::GitToolingError;
 pub use ghost_commits::CreateGhostCommitOptions;
 pub use ghost_commits::create_ghost_commit;
@@ -1,7 +1,11 @@
 use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 use std::path::PathBuf;
 
+use codex_core::deny_read::resolve_deny_read_paths;
 use codex_core::error::CodexErr;
 use codex_core::error::Result;
 use codex_core::error::SandboxErr;
@@ -25,14 +29,20 @@ use seccompiler::SeccompRule;
 use seccompiler::TargetArch;
 use seccompiler::apply_filter;
 
+use crate::user_namespace::unshare_user_namespace;
+
 /// Apply sandbox policies inside this thread so only the child inherits
 /// them, not the entire CLI process.
+///
+/// `behind_network_proxy` is set when the thread already runs in a network
+/// namespace whose only way out is the proxy for `allowed_domains`.
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    behind_network_proxy: bool,
 ) -> Result<()> {
     if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+        install_network_seccomp_filter_on_current_thread(behind_network_proxy)?;
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
@@ -41,7 +51,8 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        let deny_read = resolve_deny_read_paths(sandbox_policy.deny_read(), cwd);
+        install_filesystem_landlock_rules_on_current_thread(writable_roots, &deny_read)?;
     }
 
     // TODO(ragona): Add appropriate restrictions if
@@ -51,26 +62,52 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
 }
 
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to the entire file-system except `deny_read` while restricting
+/// write access to `/dev/null` and the provided list of `writable_roots`.
+///
+/// Landlock cannot take read access away below a writable root, so denied
+/// paths inside one are covered up with [`hide_paths`] instead and the
+/// writable roots are granted whole.
 ///
 /// # Errors
-/// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+/// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply and
+/// [`CodexErr::Io`] when denied paths inside a writable root cannot be hidden.
+fn install_filesystem_landlock_rules_on_current_thread(
+    writable_roots: Vec<PathBuf>,
+    deny_read: &[PathBuf],
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
 
+    let readable_paths = paths_excluding(Path::new("/"), deny_read);
+    let writable_paths: Vec<PathBuf> = writable_roots
+        .into_iter()
+        .map(|root| root.canonicalize().unwrap_or(root))
+        .collect();
+    let hidden: Vec<&PathBuf> = deny_read
+        .iter()
+        .filter(|denied| writable_paths.iter().any(|root| denied.starts_with(root)))
+        .collect();
+    // Mounts cannot be changed once the Landlock rules are in place.
+    hide_paths(&hidden)?;
+
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
+        .add_rules(landlock::path_beneath_rules(&readable_paths, access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
-    if !writable_roots.is_empty() {
-        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    if !deny_read.is_empty() {
+        // Directories stay listable everywhere, including the denied ones;
+        // only the contents of denied files are out of reach.
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&["/"], AccessFs::ReadDir))?;
+    }
+
+    if !writable_paths.is_empty() {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_paths, access_rw))?;
     }
 
     let status = ruleset.restrict_self()?;
@@ -82,9 +119,108 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
+/// Covers up `paths` in a private mount namespace: directories with an empty
+/// read-only tmpfs and files with `/dev/null`, so they read as empty and
+/// writes to them are discarded. This needs unprivileged user namespaces;
+/// without them the command is refused rather than run with the paths
+/// readable.
+fn hide_paths(paths: &[&PathBuf]) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    unshare_user_namespace(libc::CLONE_NEWNS).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("deny_read paths inside writable roots need unprivileged user namespaces: {e}"),
+        )
+    })?;
+    // Keep the mounts below from propagating back to the parent namespace.
+    mount(None, Path::new("/"), None, libc::MS_REC | libc::MS_PRIVATE)?;
+    for path in paths {
+        let result = if path.is_dir() {
+            mount(
+                Some(Path::new("tmpfs")),
+                path,
+                Some("tmpfs"),
+                libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV,
+            )
+        } else {
+            mount(Some(Path::new("/dev/null")), path, None, libc::MS_BIND)
+        };
+        result.map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to hide deny_read path {}: {e}", path.display()),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn mount(
+    source: Option<&Path>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+) -> io::Result<()> {
+    let to_cstring = |bytes: &[u8]| CString::new(bytes).map_err(io::Error::other);
+    let source = source
+        .map(|source| to_cstring(source.as_os_str().as_bytes()))
+        .transpose()?;
+    let target = to_cstring(target.as_os_str().as_bytes())?;
+    let fstype = fstype
+        .map(|fstype| to_cstring(fstype.as_bytes()))
+        .transpose()?;
+    let result = unsafe {
+        libc::mount(
+            source.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            target.as_ptr(),
+            fstype.as_ref().map_or(std::ptr::null(), |s| s.as_ptr()),
+            flags,
+            std::ptr::null(),
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Landlock can only grant access, so a directory that contains a denied path
+/// is replaced by its entries, recursively, leaving out the denied paths.
+/// Symlinks among those entries are left out as well: a rule on a symlink
+/// applies to its target, which keeps its own rules when reached through it.
+/// Only used for read access; writable roots are granted whole.
+fn paths_excluding(root: &Path, deny_read: &[PathBuf]) -> Vec<PathBuf> {
+    if deny_read.iter().any(|denied| root.starts_with(denied)) {
+        return Vec::new();
+    }
+    if !deny_read.iter().any(|denied| denied.starts_with(root)) {
+        return vec![root.to_path_buf()];
+    }
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut paths = Vec::new();
+    for entry in entries.flatten() {
+        if entry
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_symlink())
+        {
+            continue;
+        }
+        paths.extend(paths_excluding(&entry.path(), deny_read));
+    }
+    paths
+}
+
 /// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+/// AF_UNIX domain sockets. Behind the network proxy, IP sockets are allowed
+/// as well so the command can reach the bridge on loopback; the network
+/// namespace offers nothing else to connect to.
+fn install_network_seccomp_filter_on_current_thread(
+    behind_network_proxy: bool,
+) -> std::result::Result<(), SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,36 +229,49 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
-    deny_syscall(libc::SYS_connect);
-    deny_syscall(libc::SYS_accept);
-    deny_syscall(libc::SYS_accept4);
-    deny_syscall(libc::SYS_bind);
-    deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
-    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
-    // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
+    if !behind_network_proxy {
+        deny_syscall(libc::SYS_connect);
+        deny_syscall(libc::SYS_accept);
+        deny_syscall(libc::SYS_accept4);
+        deny_syscall(libc::SYS_bind);
+        deny_syscall(libc::SYS_listen);
+        deny_syscall(libc::SYS_getpeername);
+        deny_syscall(libc::SYS_getsockname);
+        deny_syscall(libc::SYS_shutdown);
+        deny_syscall(libc::SYS_sendto);
+        deny_syscall(libc::SYS_sendmsg);
+        deny_syscall(libc::SYS_sendmmsg);
+        // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
+        // with their socketpair + child processes for sub-proc management
+        // deny_syscall(libc::SYS_recvfrom);
+        deny_syscall(libc::SYS_recvmsg);
+        deny_syscall(libc::SYS_recvmmsg);
+        deny_syscall(libc::SYS_getsockopt);
+        deny_syscall(libc::SYS_setsockopt);
+    }
     deny_syscall(libc::SYS_ptrace);
 
-    // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
-    let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
-        0, // first argument (domain)
-        SeccompCmpArgLen::Dword,
-        SeccompCmpOp::Ne,
-        libc::AF_UNIX as u64,
-    )?])?;
+    // For `socket` we allow the permitted domains (arg0) and deny everything
+    // else.
+    let mut socket_domains = vec![libc::AF_UNIX];
+    if behind_network_proxy {
+        socket_domains.extend([libc::AF_INET, libc::AF_INET6]);
+    }
+    let conditions = socket_domains
+        .into_iter()
+        .map(|domain| {
+            SeccompCondition::new(
+                0, // first argument (domain)
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::Ne,
+                domain as u64,
+            )
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let other_domains_rule = SeccompRule::new(conditions)?;
 
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
-    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+    rules.insert(libc::SYS_socket, vec![other_domains_rule.clone()]);
+    rules.insert(libc::SYS_socketpair, vec![other_domains_rule]);
 
     let filter = SeccompFilter::new(
         rules,
@@ -1,11 +1,22 @@
 use clap::Parser;
 use std::ffi::CString;
+use std::net::Ipv4Addr;
+use std::net::TcpListener;
 use std::path::PathBuf;
 
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::proxy_bridge::enter_isolated_network_namespace;
+use crate::proxy_bridge::exit_like_child;
+use crate::proxy_bridge::set_proxy_env;
+use crate::proxy_bridge::spawn_bridge;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Unix socket of the proxy that enforces the policy's `allowed_domains`.
+    /// When set, the command gets no network except a bridge to this socket.
+    #[arg(long)]
+    pub network_proxy_socket: Option<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,12 +30,22 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        network_proxy_socket,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+    let behind_network_proxy = network_proxy_socket.is_some();
+    if let Some(proxy_socket) = network_proxy_socket {
+        fork_behind_network_proxy(proxy_socket);
+    }
+
+    if let Err(e) = apply_sandbox_policy_to_current_thread(
+        &sandbox_policy,
+        &sandbox_policy_cwd,
+        behind_network_proxy,
+    ) {
         panic!("error running landlock: {e:?}");
     }
 
@@ -52,3 +73,36 @@ pub fn run_main() -> ! {
     let err = std::io::Error::last_os_error();
     panic!("Failed to execvp {}: {err}", command[0].as_str());
 }
+
+/// Isolates the network and forks. The parent forwards connections from the
+/// command to the proxy until the command exits and never returns; the child
+/// returns to run the command.
+fn fork_behind_network_proxy(proxy_socket: PathBuf) {
+    if let Err(e) = enter_isolated_network_namespace() {
+        panic!("error isolating the network for the proxy: {e}");
+    }
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+        Ok(listener) => listener,
+        Err(e) => panic!("error starting the network proxy bridge: {e}"),
+    };
+    let port = match listener.local_addr() {
+        Ok(addr) => addr.port(),
+        Err(e) => panic!("error starting the network proxy bridge: {e}"),
+    };
+
+    match unsafe { libc::fork() } {
+        -1 => panic!("failed to fork: {}", std::io::Error::last_os_error()),
+        0 => {
+            drop(listener);
+            // SAFETY: the helper has not started any threads yet.
+            unsafe {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+                set_proxy_env(port);
+            }
+        }
+        pid => {
+            spawn_bridge(listener, proxy_socket);
+            exit_like_child(pid);
+        }
+    }
+}
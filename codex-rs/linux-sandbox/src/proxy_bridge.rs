@@ -0,0 +1,117 @@
+//! Network access limited to Codex's proxy for `allowed_domains`.
+//!
+//! The command runs in new user and network namespaces whose only interface
+//! is loopback. The helper listens on a loopback port in that namespace and
+//! forwards every connection to the proxy's unix socket, which lives on the
+//! file system and is therefore reachable across network namespaces. The
+//! usual proxy environment variables point the command at that port.
+
+use std::io;
+use std::net::Shutdown;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+
+use crate::user_namespace::unshare_user_namespace;
+
+const PROXY_ENV_VARS: [&str; 4] = ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"];
+const NO_PROXY_ENV_VARS: [&str; 2] = ["NO_PROXY", "no_proxy"];
+
+/// Moves the current process into new user and network namespaces and brings
+/// up their loopback interface. The process keeps its uid and gid. Must be
+/// called while the process is still single-threaded.
+pub(crate) fn enter_isolated_network_namespace() -> io::Result<()> {
+    unshare_user_namespace(libc::CLONE_NEWNET)?;
+    bring_up_loopback()
+}
+
+fn bring_up_loopback() -> io::Result<()> {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let mut request: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (dst, src) in request.ifr_name.iter_mut().zip(b"lo") {
+        *dst = *src as libc::c_char;
+    }
+    request.ifr_ifru.ifru_flags = (libc::IFF_UP | libc::IFF_RUNNING) as libc::c_short;
+    if unsafe { libc::ioctl(socket.as_raw_fd(), libc::SIOCSIFFLAGS as _, &request) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Points the proxy environment variables at the bridge on `port`.
+///
+/// # Safety
+/// Modifies the environment, so no other thread may be running.
+pub(crate) unsafe fn set_proxy_env(port: u16) {
+    let proxy_url = format!("http://127.0.0.1:{port}");
+    for var in PROXY_ENV_VARS {
+        unsafe { std::env::set_var(var, &proxy_url) };
+    }
+    for var in NO_PROXY_ENV_VARS {
+        unsafe { std::env::remove_var(var) };
+    }
+}
+
+/// Forwards every connection accepted on `listener` to `proxy_socket`.
+pub(crate) fn spawn_bridge(listener: TcpListener, proxy_socket: PathBuf) {
+    thread::spawn(move || {
+        for client in listener.incoming().flatten() {
+            let proxy_socket = proxy_socket.clone();
+            thread::spawn(move || {
+                let _ = forward(client, &proxy_socket);
+            });
+        }
+    });
+}
+
+fn forward(client: TcpStream, proxy_socket: &Path) -> io::Result<()> {
+    let upstream = UnixStream::connect(proxy_socket)?;
+    let mut client_reader = client.try_clone()?;
+    let mut upstream_writer = upstream.try_clone()?;
+    let to_proxy = thread::spawn(move || {
+        let _ = io::copy(&mut client_reader, &mut upstream_writer);
+        let _ = upstream_writer.shutdown(Shutdown::Write);
+    });
+
+    let (mut upstream_reader, mut client_writer) = (upstream, client);
+    let _ = io::copy(&mut upstream_reader, &mut client_writer);
+    let _ = client_writer.shutdown(Shutdown::Write);
+    let _ = to_proxy.join();
+    Ok(())
+}
+
+/// Waits for `pid` and exits the way it did, so the caller sees the
+/// command's own exit code or signal.
+pub(crate) fn exit_like_child(pid: libc::pid_t) -> ! {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } >= 0 {
+            break;
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            panic!("failed to wait for sandboxed command: {err}");
+        }
+    }
+
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        std::process::exit(128 + signal);
+    }
+    std::process::exit(libc::WEXITSTATUS(status));
+}
@@ -0,0 +1,16 @@
+use std::io;
+
+/// Moves the current process into a new user namespace, together with the
+/// other namespaces in `flags`, keeping its uid and gid. Must be called while
+/// the process is still single-threaded.
+pub(crate) fn unshare_user_namespace(flags: libc::c_int) -> io::Result<()> {
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | flags) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+    Ok(())
+}
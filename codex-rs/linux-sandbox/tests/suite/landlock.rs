@@ -1,4 +1,5 @@
 #![cfg(target_os = "linux")]
+use codex_core::config_types::ResourceLimits;
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
@@ -33,9 +34,29 @@ fn create_env_from_core_vars() -> HashMap<String, String> {
     create_env(&policy)
 }
 
-#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
 async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
+    run_cmd_with_deny_read(cmd, writable_roots, &[], timeout_ms).await;
+}
+
+#[expect(clippy::expect_used)]
+async fn run_cmd_with_deny_read(
+    cmd: &[&str],
+    writable_roots: &[PathBuf],
+    deny_read: &[String],
+    timeout_ms: u64,
+) {
     let cwd = std::env::current_dir().expect("cwd should exist");
+    run_cmd_in(cwd, cmd, writable_roots, deny_read, timeout_ms).await;
+}
+
+#[expect(clippy::print_stdout, clippy::unwrap_used)]
+async fn run_cmd_in(
+    cwd: PathBuf,
+    cmd: &[&str],
+    writable_roots: &[PathBuf],
+    deny_read: &[String],
+    timeout_ms: u64,
+) {
     let sandbox_cwd = cwd.clone();
     let params = ExecParams {
         command: cmd.iter().copied().map(str::to_owned).collect(),
@@ -44,11 +65,14 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: writable_roots.to_vec(),
         network_access: false,
+        allowed_domains: vec![],
+        deny_read: deny_read.to_vec(),
         // Exclude tmp-related folders from writable roots because we need a
         // folder that is writable by tests but that we intentionally disallow
         // writing to in the sandbox.
@@ -123,6 +147,64 @@ async fn test_writable_root() {
     .await;
 }
 
+#[tokio::test]
+#[should_panic(expected = "Sandbox(Denied")]
+async fn test_deny_read_blocks_file() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("id_ed25519");
+    std::fs::write(&secret, "secret").unwrap();
+    run_cmd_with_deny_read(
+        &["cat", &secret.to_string_lossy()],
+        &[],
+        &[secret.to_string_lossy().to_string()],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_deny_read_keeps_siblings_readable() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("id_ed25519");
+    let public = tmpdir.path().join("id_ed25519.pub");
+    std::fs::write(&secret, "secret").unwrap();
+    std::fs::write(&public, "public").unwrap();
+    run_cmd_with_deny_read(
+        &["cat", &public.to_string_lossy()],
+        &[],
+        &[secret.to_string_lossy().to_string()],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+/// A denied `.env` in the cwd must not keep the rest of the cwd from being
+/// used: entries can still be created, renamed and removed at its top level.
+#[tokio::test]
+async fn test_deny_read_inside_writable_root() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let cwd = tmpdir.path().canonicalize().unwrap();
+    std::fs::write(cwd.join(".env"), "SECRET=1").unwrap();
+    run_cmd_in(
+        cwd.clone(),
+        &[
+            "sh",
+            "-c",
+            "echo x > created && mv created renamed && cat renamed && rm renamed \
+             && ! grep -q SECRET .env",
+        ],
+        std::slice::from_ref(&cwd),
+        &[".env".to_string()],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+    assert!(!cwd.join("renamed").exists());
+    assert_eq!(
+        std::fs::read_to_string(cwd.join(".env")).unwrap(),
+        "SECRET=1"
+    );
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout")]
 async fn test_timeout() {
@@ -146,6 +228,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
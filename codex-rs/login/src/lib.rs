@@ -1,6 +1,10 @@
+mod mcp_oauth;
 mod pkce;
 mod server;
 
+pub use mcp_oauth::McpLoginOptions;
+pub use mcp_oauth::McpLoginServer;
+pub use mcp_oauth::run_mcp_login_server;
 pub use server::LoginServer;
 pub use server::ServerOptions;
 pub use server::ShutdownHandle;
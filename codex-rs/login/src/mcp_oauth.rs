@@ -0,0 +1,414 @@
+//! OAuth 2.1 login for streamable HTTP MCP servers.
+//!
+//! Follows the MCP authorization spec: the authorization server is found
+//! through the server's protected resource metadata (RFC 9728) and its own
+//! metadata (RFC 8414), Codex registers itself as a client (RFC 7591), and
+//! the user authorizes it with the authorization code flow and PKCE through a
+//! loopback redirect. The tokens are stored with
+//! [`codex_rmcp_client::save_oauth_tokens`], which also refreshes them.
+
+use std::io::Cursor;
+use std::io::{self};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+use codex_rmcp_client::OAuthTokenResponse;
+use codex_rmcp_client::StoredOAuthTokens;
+use codex_rmcp_client::save_oauth_tokens;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tiny_http::Request;
+use tiny_http::Response;
+
+use crate::pkce::PkceCodes;
+use crate::pkce::generate_pkce;
+use crate::server::bind_server;
+use crate::server::generate_state;
+
+const CALLBACK_PATH: &str = "/callback";
+
+#[derive(Debug, Clone)]
+pub struct McpLoginOptions {
+    pub codex_home: PathBuf,
+    pub server_name: String,
+    /// URL of the streamable HTTP MCP server.
+    pub server_url: String,
+    /// Port for the loopback redirect; `0` picks a free one.
+    pub port: u16,
+    pub open_browser: bool,
+}
+
+impl McpLoginOptions {
+    pub fn new(codex_home: PathBuf, server_name: String, server_url: String) -> Self {
+        Self {
+            codex_home,
+            server_name,
+            server_url,
+            port: 0,
+            open_browser: true,
+        }
+    }
+}
+
+pub struct McpLoginServer {
+    pub auth_url: String,
+    pub actual_port: u16,
+    server_handle: tokio::task::JoinHandle<io::Result<()>>,
+}
+
+impl McpLoginServer {
+    /// Waits until the browser has been redirected back and the tokens are
+    /// stored, or the authorization failed.
+    pub async fn block_until_done(self) -> io::Result<()> {
+        self.server_handle
+            .await
+            .map_err(|err| io::Error::other(format!("login server thread panicked: {err:?}")))?
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AuthorizationServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+/// State shared by the loopback server while it waits for the redirect.
+struct PendingAuthorization {
+    opts: McpLoginOptions,
+    metadata: AuthorizationServerMetadata,
+    registration: ClientRegistration,
+    redirect_uri: String,
+    pkce: PkceCodes,
+    state: String,
+}
+
+/// Discovers the server's authorization server, registers Codex as a client
+/// and starts the loopback server that completes the login. Open
+/// [`McpLoginServer::auth_url`] in a browser (done automatically when
+/// `open_browser` is set) and wait with [`McpLoginServer::block_until_done`].
+pub async fn run_mcp_login_server(opts: McpLoginOptions) -> io::Result<McpLoginServer> {
+    let client = reqwest::Client::new();
+    let (metadata, scopes) = discover_authorization_server(&client, &opts.server_url).await?;
+
+    let server = bind_server(opts.port)?;
+    let actual_port = match server.server_addr().to_ip() {
+        Some(addr) => addr.port(),
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "Unable to determine the server port",
+            ));
+        }
+    };
+    let server = Arc::new(server);
+    let redirect_uri = format!("http://127.0.0.1:{actual_port}{CALLBACK_PATH}");
+
+    let registration = register_client(&client, &metadata, &redirect_uri).await?;
+    let pkce = generate_pkce();
+    let state = generate_state();
+    let auth_url = build_authorize_url(
+        &metadata,
+        &registration.client_id,
+        &redirect_uri,
+        &pkce,
+        &state,
+        &opts.server_url,
+        &scopes,
+    )?;
+
+    if opts.open_browser {
+        let _ = webbrowser::open(&auth_url);
+    }
+
+    // Map blocking reads from server.recv() to an async channel.
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Request>(16);
+    {
+        let server = server.clone();
+        thread::spawn(move || -> io::Result<()> {
+            while let Ok(request) = server.recv() {
+                tx.blocking_send(request)
+                    .map_err(|_| io::Error::other("Failed to send request to channel"))?;
+            }
+            Ok(())
+        });
+    }
+
+    let pending = PendingAuthorization {
+        opts,
+        metadata,
+        registration,
+        redirect_uri,
+        pkce,
+        state,
+    };
+    let server_handle = tokio::spawn(async move {
+        let result = loop {
+            let Some(req) = rx.recv().await else {
+                break Err(io::Error::other("Login was not completed"));
+            };
+            let (response, result) = handle_request(req.url(), &pending, &client).await;
+            let _ = tokio::task::spawn_blocking(move || req.respond(response)).await;
+            if let Some(result) = result {
+                break result;
+            }
+        };
+        // Unblock the thread running `server.recv()` so it exits cleanly.
+        server.unblock();
+        result
+    });
+
+    Ok(McpLoginServer {
+        auth_url,
+        actual_port,
+        server_handle,
+    })
+}
+
+/// Handles one request to the loopback server. The second value is set once
+/// the login is finished, successfully or not.
+async fn handle_request(
+    url_raw: &str,
+    pending: &PendingAuthorization,
+    client: &reqwest::Client,
+) -> (Response<Cursor<Vec<u8>>>, Option<io::Result<()>>) {
+    let Ok(parsed_url) = url::Url::parse(&format!("http://localhost{url_raw}")) else {
+        return (
+            Response::from_string("Bad Request").with_status_code(400),
+            None,
+        );
+    };
+    if parsed_url.path() != CALLBACK_PATH {
+        return (
+            Response::from_string("Not Found").with_status_code(404),
+            None,
+        );
+    }
+
+    let params: std::collections::HashMap<String, String> =
+        parsed_url.query_pairs().into_owned().collect();
+    if params.get("state").map(String::as_str) != Some(pending.state.as_str()) {
+        return (
+            Response::from_string("State mismatch").with_status_code(400),
+            None,
+        );
+    }
+    if let Some(error) = params.get("error") {
+        let message = format!("Authorization failed: {error}");
+        return (
+            Response::from_string(message.clone()).with_status_code(400),
+            Some(Err(io::Error::other(message))),
+        );
+    }
+    let Some(code) = params.get("code").filter(|code| !code.is_empty()) else {
+        return (
+            Response::from_string("Missing authorization code").with_status_code(400),
+            None,
+        );
+    };
+
+    let stored = exchange_code_for_tokens(client, pending, code)
+        .await
+        .and_then(|tokens| {
+            save_oauth_tokens(&pending.opts.codex_home, &pending.opts.server_name, &tokens)
+        });
+    match stored {
+        Ok(()) => (
+            Response::from_string(format!(
+                "Codex is now authorized to use the `{}` MCP server. You can close this window.",
+                pending.opts.server_name
+            )),
+            Some(Ok(())),
+        ),
+        Err(err) => (
+            Response::from_string(format!("Login failed: {err}")).with_status_code(500),
+            Some(Err(err)),
+        ),
+    }
+}
+
+/// Finds the authorization server for `server_url` and the scopes to request.
+/// Servers without protected resource metadata are assumed to be their own
+/// authorization server, and one without metadata uses the default endpoint
+/// paths, as in the 2025-03-26 revision of the spec.
+async fn discover_authorization_server(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> io::Result<(AuthorizationServerMetadata, Vec<String>)> {
+    let mut resource_metadata: Option<ProtectedResourceMetadata> = None;
+    for url in well_known_urls(server_url, "oauth-protected-resource")? {
+        if let Some(metadata) = get_json(client, &url).await {
+            resource_metadata = Some(metadata);
+            break;
+        }
+    }
+    let (issuer, scopes) = match resource_metadata {
+        Some(metadata) => {
+            let issuer = metadata
+                .authorization_servers
+                .into_iter()
+                .next()
+                .ok_or_else(|| {
+                    io::Error::other("protected resource metadata lists no authorization servers")
+                })?;
+            (issuer, metadata.scopes_supported)
+        }
+        None => (origin(server_url)?, Vec::new()),
+    };
+
+    let mut candidates = well_known_urls(&issuer, "oauth-authorization-server")?;
+    candidates.extend(well_known_urls(&issuer, "openid-configuration")?);
+    for url in candidates {
+        if let Some(metadata) = get_json(client, &url).await {
+            return Ok((metadata, scopes));
+        }
+    }
+
+    let base = origin(&issuer)?;
+    Ok((
+        AuthorizationServerMetadata {
+            authorization_endpoint: format!("{base}/authorize"),
+            token_endpoint: format!("{base}/token"),
+            registration_endpoint: Some(format!("{base}/register")),
+        },
+        scopes,
+    ))
+}
+
+async fn register_client(
+    client: &reqwest::Client,
+    metadata: &AuthorizationServerMetadata,
+    redirect_uri: &str,
+) -> io::Result<ClientRegistration> {
+    let endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+        io::Error::other("the authorization server does not support dynamic client registration")
+    })?;
+    let body = serde_json::json!({
+        "client_name": "Codex",
+        "redirect_uris": [redirect_uri],
+        "grant_types": ["authorization_code", "refresh_token"],
+        "response_types": ["code"],
+        "token_endpoint_auth_method": "none",
+    });
+    let resp = client
+        .post(endpoint)
+        .json(&body)
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+    if !resp.status().is_success() {
+        return Err(io::Error::other(format!(
+            "client registration returned status {}",
+            resp.status()
+        )));
+    }
+    resp.json().await.map_err(io::Error::other)
+}
+
+fn build_authorize_url(
+    metadata: &AuthorizationServerMetadata,
+    client_id: &str,
+    redirect_uri: &str,
+    pkce: &PkceCodes,
+    state: &str,
+    resource: &str,
+    scopes: &[String],
+) -> io::Result<String> {
+    let mut url = url::Url::parse(&metadata.authorization_endpoint).map_err(io::Error::other)?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("code_challenge", &pkce.code_challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", state)
+            .append_pair("resource", resource);
+        if !scopes.is_empty() {
+            query.append_pair("scope", &scopes.join(" "));
+        }
+    }
+    Ok(url.to_string())
+}
+
+async fn exchange_code_for_tokens(
+    client: &reqwest::Client,
+    pending: &PendingAuthorization,
+    code: &str,
+) -> io::Result<StoredOAuthTokens> {
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", pending.redirect_uri.as_str()),
+        ("client_id", pending.registration.client_id.as_str()),
+        ("code_verifier", pending.pkce.code_verifier.as_str()),
+        ("resource", pending.opts.server_url.as_str()),
+    ];
+    if let Some(secret) = &pending.registration.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let resp = client
+        .post(&pending.metadata.token_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+    if !resp.status().is_success() {
+        return Err(io::Error::other(format!(
+            "token endpoint returned status {}",
+            resp.status()
+        )));
+    }
+    let response: OAuthTokenResponse = resp.json().await.map_err(io::Error::other)?;
+    Ok(StoredOAuthTokens::from_token_response(
+        pending.opts.server_url.clone(),
+        pending.registration.client_id.clone(),
+        pending.registration.client_secret.clone(),
+        pending.metadata.token_endpoint.clone(),
+        response,
+    ))
+}
+
+async fn get_json<T: DeserializeOwned>(client: &reqwest::Client, url: &str) -> Option<T> {
+    let resp = client.get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.json().await.ok()
+}
+
+/// Well-known metadata locations for `base`: the path-aware location first
+/// (`/.well-known/<suffix>/<path>`), then the one at the root.
+fn well_known_urls(base: &str, suffix: &str) -> io::Result<Vec<String>> {
+    let url = url::Url::parse(base).map_err(io::Error::other)?;
+    let origin = url.origin().ascii_serialization();
+    let path = url.path().trim_end_matches('/');
+    let mut urls = Vec::new();
+    if !path.is_empty() {
+        urls.push(format!("{origin}/.well-known/{suffix}{path}"));
+    }
+    urls.push(format!("{origin}/.well-known/{suffix}"));
+    Ok(urls)
+}
+
+fn origin(url: &str) -> io::Result<String> {
+    let url = url::Url::parse(url).map_err(io::Error::other)?;
+    Ok(url.origin().ascii_serialization())
+}
@@ -0,0 +1,129 @@
+#![allow(clippy::unwrap_used)]
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::Result;
+use codex_login::McpLoginOptions;
+use codex_login::run_mcp_login_server;
+use codex_rmcp_client::load_oauth_tokens;
+use core_test_support::skip_if_no_network;
+use tempfile::tempdir;
+
+/// Stand-in for an MCP server's authorization server. The authorization
+/// server lives under `/tenant` so path-aware metadata discovery is used.
+/// Returns the base URL and the form bodies posted to the token endpoint.
+fn start_mock_authorization_server() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let server = tiny_http::Server::from_listener(listener, None).unwrap();
+    let token_requests = Arc::new(Mutex::new(Vec::new()));
+
+    let issuer = format!("{base}/tenant");
+    let resource = format!("{base}/mcp");
+    let requests = token_requests.clone();
+    thread::spawn(move || {
+        while let Ok(mut req) = server.recv() {
+            let mut body = String::new();
+            let _ = req.as_reader().read_to_string(&mut body);
+            let url = req.url().to_string();
+            let json = match url.as_str() {
+                "/.well-known/oauth-protected-resource/mcp" => serde_json::json!({
+                    "resource": resource,
+                    "authorization_servers": [issuer],
+                    "scopes_supported": ["files:read"],
+                }),
+                "/.well-known/oauth-authorization-server/tenant" => serde_json::json!({
+                    "issuer": issuer,
+                    "authorization_endpoint": format!("{issuer}/authorize"),
+                    "token_endpoint": format!("{issuer}/token"),
+                    "registration_endpoint": format!("{issuer}/register"),
+                }),
+                "/tenant/register" => serde_json::json!({ "client_id": "client-abc" }),
+                "/tenant/token" => {
+                    requests.lock().unwrap().push(body);
+                    serde_json::json!({
+                        "access_token": "mcp-access",
+                        "token_type": "Bearer",
+                        "refresh_token": "mcp-refresh",
+                        "expires_in": 3600,
+                    })
+                }
+                _ => {
+                    let _ = req.respond(
+                        tiny_http::Response::from_string("not found").with_status_code(404),
+                    );
+                    continue;
+                }
+            };
+            let mut resp = tiny_http::Response::from_data(serde_json::to_vec(&json).unwrap());
+            resp.add_header(
+                tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                    .unwrap_or_else(|_| panic!("header bytes")),
+            );
+            let _ = req.respond(resp);
+        }
+    });
+
+    (base, token_requests)
+}
+
+#[tokio::test]
+async fn mcp_login_registers_client_and_stores_tokens() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (base, token_requests) = start_mock_authorization_server();
+    let server_url = format!("{base}/mcp");
+    let tmp = tempdir()?;
+    let codex_home = tmp.path().to_path_buf();
+
+    let mut opts = McpLoginOptions::new(codex_home.clone(), "docs".to_string(), server_url.clone());
+    opts.open_browser = false;
+    let server = run_mcp_login_server(opts).await?;
+
+    let auth_url = url::Url::parse(&server.auth_url)?;
+    assert_eq!(
+        format!(
+            "{}://{}{}",
+            auth_url.scheme(),
+            auth_url.authority(),
+            auth_url.path()
+        ),
+        format!("{base}/tenant/authorize")
+    );
+    let params: HashMap<String, String> = auth_url.query_pairs().into_owned().collect();
+    assert_eq!(params["client_id"], "client-abc");
+    assert_eq!(params["resource"], server_url);
+    assert_eq!(params["scope"], "files:read");
+    assert_eq!(params["code_challenge_method"], "S256");
+
+    // Simulate the browser being redirected back after authorization.
+    let callback = format!(
+        "{}?code=abc&state={}",
+        params["redirect_uri"],
+        urlencoding::encode(&params["state"])
+    );
+    let resp = reqwest::get(&callback).await?;
+    assert!(resp.status().is_success());
+    server.block_until_done().await?;
+
+    let token_request = token_requests.lock().unwrap().pop().unwrap_or_default();
+    let form: HashMap<String, String> = url::form_urlencoded::parse(token_request.as_bytes())
+        .into_owned()
+        .collect();
+    assert_eq!(form["grant_type"], "authorization_code");
+    assert_eq!(form["code"], "abc");
+    assert_eq!(form["resource"], server_url);
+    assert!(!form["code_verifier"].is_empty());
+
+    let tokens = load_oauth_tokens(&codex_home, "docs")?.unwrap();
+    assert_eq!(tokens.url, server_url);
+    assert_eq!(tokens.client_id, "client-abc");
+    assert_eq!(tokens.token_endpoint, format!("{base}/tenant/token"));
+    assert_eq!(tokens.access_token, "mcp-access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("mcp-refresh"));
+    assert!(tokens.expires_at.is_some());
+    Ok(())
+}
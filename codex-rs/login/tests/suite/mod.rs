@@ -1,2 +1,3 @@
 // Aggregates all former standalone integration tests as modules.
 mod login_server_e2e;
+mod mcp_login_e2e;
@@ -23,6 +23,9 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -31,11 +34,23 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourceTemplatesRequest;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourceTemplatesResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
@@ -346,6 +361,58 @@ impl McpClient {
         self.send_request::<CallToolRequest>(params, timeout).await
     }
 
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/templates/list`.
+    pub async fn list_resource_templates(
+        &self,
+        params: Option<ListResourceTemplatesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourceTemplatesResult> {
+        self.send_request::<ListResourceTemplatesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { arguments, name };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
     async fn dispatch_response(
         resp: JSONRPCResponse,
@@ -573,6 +573,7 @@ impl CodexMessageProcessor {
             env,
             with_escalated_permissions: None,
             justification: None,
+            resource_limits: self.config.resource_limits,
         };
 
         let effective_policy = params
@@ -1223,6 +1224,20 @@ async fn apply_bespoke_event_handling(
                 on_exec_approval_response(event_id, rx, conversation).await;
             });
         }
+        EventMsg::McpToolCallApprovalRequest(_) => {
+            // There is no client request for approving MCP tool calls yet, so
+            // deny the call rather than leave the turn waiting.
+            warn!("denying MCP tool call that requires approval");
+            if let Err(err) = conversation
+                .submit(Op::McpToolCallApproval {
+                    id: event_id,
+                    decision: ReviewDecision::Denied,
+                })
+                .await
+            {
+                error!("failed to submit McpToolCallApproval: {err}");
+            }
+        }
         // If this is a TurnAborted, reply to any pending interrupt requests.
         EventMsg::TurnAborted(turn_aborted_event) => {
             let pending = {
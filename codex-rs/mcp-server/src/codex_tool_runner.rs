@@ -20,6 +20,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_protocol::mcp_protocol::ConversationId;
@@ -192,6 +193,21 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
+                    EventMsg::McpToolCallApprovalRequest(_) => {
+                        // MCP clients have no way to approve a nested MCP
+                        // tool call yet, so deny it instead of waiting forever.
+                        tracing::warn!("denying MCP tool call that requires approval");
+                        if let Err(err) = codex
+                            .submit(Op::McpToolCallApproval {
+                                id: event.id.clone(),
+                                decision: ReviewDecision::Denied,
+                            })
+                            .await
+                        {
+                            tracing::error!("failed to submit MCP tool call denial: {err}");
+                        }
+                        continue;
+                    }
                     EventMsg::Error(err_event) => {
                         // Return a response to conclude the tool call when the Codex session reports an error (e.g., interruption).
                         let result = json!({
@@ -264,6 +280,13 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::McpListToolsResponse(_)
                     | EventMsg::ListCustomPromptsResponse(_)
+                    | EventMsg::McpListPromptsResponse(_)
+                    | EventMsg::McpListResourcesResponse(_)
+                    | EventMsg::CheckpointCreated(_)
+                    | EventMsg::DelegateTaskBegin(_)
+                    | EventMsg::DelegateTaskProgress(_)
+                    | EventMsg::DelegateTaskEnd(_)
+                    | EventMsg::NetworkRequest(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
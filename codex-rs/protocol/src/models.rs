@@ -234,6 +234,16 @@ impl From<Vec<InputItem>> for ResponseInputItem {
                             None
                         }
                     },
+                    InputItem::McpResource { server, uri } => {
+                        // The session replaces MCP items before building the
+                        // request; one left here could not be resolved.
+                        tracing::warn!("Skipping unresolved MCP resource {server}:{uri}");
+                        None
+                    }
+                    InputItem::McpPrompt { server, name, .. } => {
+                        tracing::warn!("Skipping unresolved MCP prompt {server}:{name}");
+                        None
+                    }
                 })
                 .collect::<Vec<ContentItem>>(),
         }
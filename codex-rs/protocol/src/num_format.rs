@@ -71,6 +71,16 @@ pub fn format_si_suffix(n: u64) -> String {
     format_si_suffix_with_formatter(n, formatter())
 }
 
+/// Format a USD amount, keeping four decimals for amounts under a dollar so
+/// that small per-session costs stay visible (e.g. "$0.0042", "$12.34").
+pub fn format_usd(amount: f64) -> String {
+    if amount.abs() < 1.0 {
+        format!("${amount:.4}")
+    } else {
+        format!("${amount:.2}")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,4 +105,11 @@ mod tests {
         // Above 1000G we keep whole‑G precision (no higher unit supported here).
         assert_eq!(fmt(1_234_000_000_000), "1,234G");
     }
+
+    #[test]
+    fn usd() {
+        assert_eq!(format_usd(0.0), "$0.0000");
+        assert_eq!(format_usd(0.00421), "$0.0042");
+        assert_eq!(format_usd(12.5), "$12.50");
+    }
 }
@@ -21,6 +21,9 @@ use crate::num_format::format_with_separators;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 use mcp_types::CallToolResult;
+use mcp_types::Prompt as McpPrompt;
+use mcp_types::Resource as McpResource;
+use mcp_types::ResourceTemplate as McpResourceTemplate;
 use mcp_types::Tool as McpTool;
 use serde::Deserialize;
 use serde::Serialize;
@@ -143,6 +146,14 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve an MCP tool call
+    McpToolCallApproval {
+        /// The id of the submission we are approving
+        id: String,
+        /// The user's decision in response to the request.
+        decision: ReviewDecision,
+    },
+
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
@@ -166,6 +177,14 @@ pub enum Op {
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
+    /// Request the prompts offered by the configured MCP servers.
+    /// Reply is delivered via `EventMsg::McpListPromptsResponse`.
+    ListMcpPrompts,
+
+    /// Request the resources and resource templates offered by the configured
+    /// MCP servers. Reply is delivered via `EventMsg::McpListResourcesResponse`.
+    ListMcpResources,
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
@@ -233,6 +252,19 @@ pub enum SandboxPolicy {
         #[serde(default)]
         network_access: bool,
 
+        /// Domains that commands may still reach through Codex's HTTP(S)
+        /// proxy when `network_access` is `false`. `example.com` matches that
+        /// host only; `*.example.com` matches its subdomains. Only enforced on
+        /// Linux.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        allowed_domains: Vec<String>,
+
+        /// Paths sandboxed commands may neither read nor write, e.g.
+        /// `~/.ssh`. Relative entries are resolved against the cwd and the
+        /// last component may contain `*` and `?` wildcards.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<String>,
+
         /// When set to `true`, will NOT include the per-user `TMPDIR`
         /// environment variable among the default writable roots. Defaults to
         /// `false`.
@@ -298,6 +330,8 @@ impl SandboxPolicy {
         SandboxPolicy::WorkspaceWrite {
             writable_roots: vec![],
             network_access: false,
+            allowed_domains: vec![],
+            deny_read: vec![],
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
         }
@@ -324,6 +358,28 @@ impl SandboxPolicy {
         }
     }
 
+    /// Domains reachable through the network proxy. Empty unless network
+    /// access is otherwise blocked.
+    pub fn allowed_domains(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite {
+                network_access: false,
+                allowed_domains,
+                ..
+            } => allowed_domains,
+            _ => &[],
+        }
+    }
+
+    /// Unresolved `deny_read` entries; see `codex_core::deny_read`. Only a
+    /// workspace-write policy has any, so nothing is denied under read-only.
+    pub fn deny_read(&self) -> &[String] {
+        match self {
+            SandboxPolicy::WorkspaceWrite { deny_read, .. } => deny_read,
+            _ => &[],
+        }
+    }
+
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
     /// each writable root.
@@ -336,6 +392,8 @@ impl SandboxPolicy {
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                allowed_domains: _,
+                deny_read: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -405,6 +463,22 @@ pub enum InputItem {
     LocalImage {
         path: std::path::PathBuf,
     },
+
+    /// Resource `uri` of MCP server `server`. Its contents are read when the
+    /// input is submitted and sent in place of this item.
+    McpResource {
+        server: String,
+        uri: String,
+    },
+
+    /// Prompt `name` of MCP server `server`. The messages it renders to are
+    /// sent in place of this item.
+    McpPrompt {
+        server: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<serde_json::Value>,
+    },
 }
 
 /// Event Queue Entry - events from agent
@@ -481,6 +555,8 @@ pub enum EventMsg {
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    McpToolCallApprovalRequest(McpToolCallApprovalRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
@@ -505,6 +581,12 @@ pub enum EventMsg {
     /// List of custom prompts available to the agent.
     ListCustomPromptsResponse(ListCustomPromptsResponseEvent),
 
+    /// List of prompts offered by MCP servers.
+    McpListPromptsResponse(McpListPromptsResponseEvent),
+
+    /// List of resources offered by MCP servers.
+    McpListResourcesResponse(McpListResourcesResponseEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -519,6 +601,21 @@ pub enum EventMsg {
 
     /// Exited review mode with an optional final result to apply.
     ExitedReviewMode(ExitedReviewModeEvent),
+
+    /// A checkpoint of the working tree was taken at the start of a turn.
+    CheckpointCreated(CheckpointItem),
+
+    /// A child conversation started by the `delegate_task` tool has begun.
+    DelegateTaskBegin(DelegateTaskBeginEvent),
+
+    /// An event from a running `delegate_task` child conversation.
+    DelegateTaskProgress(DelegateTaskProgressEvent),
+
+    /// A `delegate_task` child conversation has finished.
+    DelegateTaskEnd(DelegateTaskEndEvent),
+
+    /// A sandboxed command asked the network proxy to reach a host.
+    NetworkRequest(NetworkRequestEvent),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -557,6 +654,10 @@ pub struct TokenUsageInfo {
     pub total_token_usage: TokenUsage,
     pub last_token_usage: TokenUsage,
     pub model_context_window: Option<u64>,
+    /// Cumulative cost of the session in USD, for models with configured
+    /// pricing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
 }
 
 impl TokenUsageInfo {
@@ -575,6 +676,7 @@ impl TokenUsageInfo {
                 total_token_usage: TokenUsage::default(),
                 last_token_usage: TokenUsage::default(),
                 model_context_window,
+                total_cost_usd: None,
             },
         };
         if let Some(last) = last {
@@ -587,6 +689,10 @@ impl TokenUsageInfo {
         self.total_token_usage.add_assign(last);
         self.last_token_usage = last.clone();
     }
+
+    pub fn add_cost(&mut self, cost_usd: f64) {
+        self.total_cost_usd = Some(self.total_cost_usd.unwrap_or(0.0) + cost_usd);
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -899,6 +1005,9 @@ impl InitialHistory {
                     .iter()
                     .filter_map(|ri| match ri {
                         RolloutItem::EventMsg(ev) => Some(ev.clone()),
+                        RolloutItem::Checkpoint(checkpoint) => {
+                            Some(EventMsg::CheckpointCreated(checkpoint.clone()))
+                        }
                         _ => None,
                     })
                     .collect(),
@@ -908,6 +1017,9 @@ impl InitialHistory {
                     .iter()
                     .filter_map(|ri| match ri {
                         RolloutItem::EventMsg(ev) => Some(ev.clone()),
+                        RolloutItem::Checkpoint(checkpoint) => {
+                            Some(EventMsg::CheckpointCreated(checkpoint.clone()))
+                        }
                         _ => None,
                     })
                     .collect(),
@@ -941,6 +1053,7 @@ pub enum RolloutItem {
     ResponseItem(ResponseItem),
     Compacted(CompactedItem),
     TurnContext(TurnContextItem),
+    Checkpoint(CheckpointItem),
     EventMsg(EventMsg),
 }
 
@@ -967,11 +1080,28 @@ pub struct TurnContextItem {
     pub approval_policy: AskForApproval,
     pub sandbox_policy: SandboxPolicy,
     pub model: String,
+    /// Id of the model provider that served the turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_provider: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effort: Option<ReasoningEffortConfig>,
     pub summary: ReasoningSummaryConfig,
 }
 
+/// Ghost commit of the working tree taken at the start of a turn, before the
+/// agent acted on the user's message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+pub struct CheckpointItem {
+    /// Id of the ghost commit holding the snapshot.
+    pub commit_id: String,
+    /// Text of the user message that started the turn.
+    pub prompt: String,
+    /// Index of that user message in the conversation, as accepted by
+    /// `ConversationManager::fork_conversation` to drop the turn and
+    /// everything after it.
+    pub nth_user_message: usize,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RolloutLine {
     pub timestamp: String,
@@ -1107,6 +1237,18 @@ pub struct ExecApprovalRequestEvent {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpToolCallApprovalRequestEvent {
+    /// Identifier for the associated MCP tool call.
+    pub call_id: String,
+    /// The tool call awaiting approval.
+    pub invocation: McpInvocation,
+    /// Optional human-readable reason for the approval (e.g. the tool may
+    /// modify data).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct ApplyPatchApprovalRequestEvent {
     /// Responses API call id for the associated patch apply call, if available.
@@ -1152,6 +1294,50 @@ pub struct PatchApplyEndEvent {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct NetworkRequestEvent {
+    /// Identifier for the exec call whose command made the request.
+    pub call_id: String,
+    /// `CONNECT` for HTTPS tunnels, otherwise the HTTP method.
+    pub method: String,
+    pub host: String,
+    pub port: u16,
+    /// Whether the host is in `allowed_domains` and the request was let through.
+    pub allowed: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct DelegateTaskBeginEvent {
+    /// Identifier of the `delegate_task` call that started the child.
+    pub call_id: String,
+    /// Position of the task in the call's `tasks` list.
+    pub index: usize,
+    /// The task the child was asked to complete.
+    pub task: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct DelegateTaskProgressEvent {
+    /// Identifier of the `delegate_task` call that started the child.
+    pub call_id: String,
+    /// Position of the task in the call's `tasks` list.
+    pub index: usize,
+    /// The event emitted by the child conversation.
+    pub msg: Box<EventMsg>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct DelegateTaskEndEvent {
+    /// Identifier of the `delegate_task` call that started the child.
+    pub call_id: String,
+    /// Position of the task in the call's `tasks` list.
+    pub index: usize,
+    /// Whether the child finished with a final message.
+    pub success: bool,
+    /// The child's final message, or the reason it did not finish.
+    pub output: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct TurnDiffEvent {
     pub unified_diff: String,
@@ -1173,6 +1359,22 @@ pub struct McpListToolsResponseEvent {
     pub tools: std::collections::HashMap<String, McpTool>,
 }
 
+/// Response payload for `Op::ListMcpPrompts`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpListPromptsResponseEvent {
+    /// Server name -> prompts offered by that server.
+    pub prompts: std::collections::HashMap<String, Vec<McpPrompt>>,
+}
+
+/// Response payload for `Op::ListMcpResources`.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpListResourcesResponseEvent {
+    /// Server name -> resources offered by that server.
+    pub resources: std::collections::HashMap<String, Vec<McpResource>>,
+    /// Server name -> resource templates offered by that server.
+    pub resource_templates: std::collections::HashMap<String, Vec<McpResourceTemplate>>,
+}
+
 /// Response payload for `Op::ListCustomPrompts`.
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct ListCustomPromptsResponseEvent {
@@ -1,5 +1,15 @@
 mod logging_client_handler;
+mod oauth;
+mod oauth_http_client;
 mod rmcp_client;
 mod utils;
 
+pub use oauth::OAuthTokenResponse;
+pub use oauth::StoredOAuthTokens;
+pub use oauth::delete_oauth_tokens;
+pub use oauth::get_mcp_credentials_file;
+pub use oauth::load_fresh_oauth_tokens;
+pub use oauth::load_oauth_tokens;
+pub use oauth::refresh_oauth_tokens;
+pub use oauth::save_oauth_tokens;
 pub use rmcp_client::RmcpClient;
@@ -0,0 +1,363 @@
+//! OAuth tokens for streamable HTTP MCP servers.
+//!
+//! `codex mcp login <server>` runs the authorization flow and stores the
+//! resulting tokens in `CODEX_HOME/mcp_credentials.json`, keyed by server
+//! name. When Codex connects to the server, the stored access token is sent as
+//! a bearer token and refreshed first if it is about to expire. If the server
+//! rejects it later in the session, it is refreshed again (see
+//! `oauth_http_client`).
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde::Serialize;
+
+const CREDENTIALS_FILE: &str = "mcp_credentials.json";
+
+/// Advisory lock held while the credentials file is read, changed and
+/// written back. It is a separate file because writes replace the
+/// credentials file.
+const CREDENTIALS_LOCK_FILE: &str = "mcp_credentials.json.lock";
+
+/// Access tokens are refreshed when they expire within this margin.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Tokens issued to Codex for one MCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredOAuthTokens {
+    /// URL of the MCP server the tokens were issued for.
+    pub url: String,
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub token_endpoint: String,
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Unix time in seconds at which the access token expires, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+/// Successful token endpoint response (RFC 6749, section 5.1).
+#[derive(Debug, Clone, Deserialize)]
+pub struct OAuthTokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+impl StoredOAuthTokens {
+    pub fn from_token_response(
+        url: String,
+        client_id: String,
+        client_secret: Option<String>,
+        token_endpoint: String,
+        response: OAuthTokenResponse,
+    ) -> Self {
+        Self {
+            url,
+            client_id,
+            client_secret,
+            token_endpoint,
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at: response.expires_in.map(|secs| unix_now() + secs),
+        }
+    }
+
+    fn expires_within(&self, margin: Duration, now: u64) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now + margin.as_secs())
+    }
+}
+
+pub fn get_mcp_credentials_file(codex_home: &Path) -> PathBuf {
+    codex_home.join(CREDENTIALS_FILE)
+}
+
+/// Returns the stored tokens for `server_name`, if any.
+pub fn load_oauth_tokens(
+    codex_home: &Path,
+    server_name: &str,
+) -> io::Result<Option<StoredOAuthTokens>> {
+    Ok(read_credentials(codex_home)?.remove(server_name))
+}
+
+/// Stores `tokens` for `server_name`, replacing any previous entry.
+pub fn save_oauth_tokens(
+    codex_home: &Path,
+    server_name: &str,
+    tokens: &StoredOAuthTokens,
+) -> io::Result<()> {
+    update_credentials(codex_home, |credentials| {
+        credentials.insert(server_name.to_string(), tokens.clone());
+        true
+    })?;
+    Ok(())
+}
+
+/// Removes the stored tokens for `server_name`. Returns whether any existed.
+pub fn delete_oauth_tokens(codex_home: &Path, server_name: &str) -> io::Result<bool> {
+    update_credentials(codex_home, |credentials| {
+        credentials.remove(server_name).is_some()
+    })
+}
+
+/// Returns the tokens stored for `server_name`, refreshing and saving them
+/// first if the access token is about to expire. Tokens stored for a
+/// different URL are ignored.
+pub async fn load_fresh_oauth_tokens(
+    codex_home: &Path,
+    server_name: &str,
+    url: &str,
+) -> Result<Option<StoredOAuthTokens>> {
+    let Some(tokens) = load_oauth_tokens(codex_home, server_name)? else {
+        return Ok(None);
+    };
+    if tokens.url != url {
+        return Ok(None);
+    }
+    if !tokens.expires_within(REFRESH_MARGIN, unix_now()) {
+        return Ok(Some(tokens));
+    }
+
+    let refreshed = refresh_oauth_tokens(&reqwest::Client::new(), &tokens)
+        .await
+        .with_context(|| {
+            format!(
+                "OAuth token for MCP server `{server_name}` expired; run `codex mcp login {server_name}`"
+            )
+        })?;
+    save_oauth_tokens(codex_home, server_name, &refreshed)?;
+    Ok(Some(refreshed))
+}
+
+/// Exchanges the refresh token for a new access token. The refresh token is
+/// kept when the server does not rotate it.
+pub async fn refresh_oauth_tokens(
+    client: &reqwest::Client,
+    tokens: &StoredOAuthTokens,
+) -> Result<StoredOAuthTokens> {
+    let refresh_token = tokens
+        .refresh_token
+        .as_deref()
+        .ok_or_else(|| anyhow!("no refresh token was issued"))?;
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", tokens.client_id.as_str()),
+        ("resource", tokens.url.as_str()),
+    ];
+    if let Some(secret) = &tokens.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+
+    let response = client
+        .post(&tokens.token_endpoint)
+        .form(&form)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "token endpoint returned status {}",
+            response.status()
+        ));
+    }
+    let response: OAuthTokenResponse = response.json().await?;
+
+    let mut refreshed = StoredOAuthTokens::from_token_response(
+        tokens.url.clone(),
+        tokens.client_id.clone(),
+        tokens.client_secret.clone(),
+        tokens.token_endpoint.clone(),
+        response,
+    );
+    if refreshed.refresh_token.is_none() {
+        refreshed.refresh_token = tokens.refresh_token.clone();
+    }
+    Ok(refreshed)
+}
+
+fn read_credentials(codex_home: &Path) -> io::Result<BTreeMap<String, StoredOAuthTokens>> {
+    match std::fs::read_to_string(get_mcp_credentials_file(codex_home)) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(io::Error::other),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Applies `update` to the stored credentials and writes them back when it
+/// returns true, which is also what this returns. Sessions refreshing tokens
+/// for different servers at the same time would otherwise drop each other's
+/// entries, so the whole cycle runs under [`CREDENTIALS_LOCK_FILE`].
+fn update_credentials(
+    codex_home: &Path,
+    update: impl FnOnce(&mut BTreeMap<String, StoredOAuthTokens>) -> bool,
+) -> io::Result<bool> {
+    std::fs::create_dir_all(codex_home)?;
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(codex_home.join(CREDENTIALS_LOCK_FILE))?;
+    lock.lock()?;
+
+    let mut credentials = read_credentials(codex_home)?;
+    let changed = update(&mut credentials);
+    if changed {
+        write_credentials(codex_home, &credentials)?;
+    }
+    Ok(changed)
+}
+
+fn write_credentials(
+    codex_home: &Path,
+    credentials: &BTreeMap<String, StoredOAuthTokens>,
+) -> io::Result<()> {
+    std::fs::create_dir_all(codex_home)?;
+    let json_data = serde_json::to_string_pretty(credentials)?;
+    // Write to a temp file in the same directory and rename it over the old
+    // one, so a crash or a concurrent refresh never leaves a truncated file.
+    // The temp file is created with mode 0o600 on unix.
+    let mut file = tempfile::NamedTempFile::new_in(codex_home)?;
+    file.write_all(json_data.as_bytes())?;
+    file.as_file().sync_all()?;
+    file.persist(get_mcp_credentials_file(codex_home))
+        .map_err(|e| e.error)?;
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn tokens(url: &str, expires_at: Option<u64>) -> StoredOAuthTokens {
+        StoredOAuthTokens {
+            url: url.to_string(),
+            client_id: "client-1".to_string(),
+            client_secret: None,
+            token_endpoint: "https://auth.example.com/token".to_string(),
+            access_token: "access-1".to_string(),
+            refresh_token: Some("refresh-1".to_string()),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn tokens_round_trip_per_server() -> io::Result<()> {
+        let codex_home = tempfile::tempdir()?;
+        let docs = tokens("https://docs.example.com/mcp", Some(1_000));
+        let github = tokens("https://github.example.com/mcp", None);
+
+        save_oauth_tokens(codex_home.path(), "docs", &docs)?;
+        save_oauth_tokens(codex_home.path(), "github", &github)?;
+        assert_eq!(load_oauth_tokens(codex_home.path(), "docs")?, Some(docs));
+
+        assert!(delete_oauth_tokens(codex_home.path(), "docs")?);
+        assert!(!delete_oauth_tokens(codex_home.path(), "docs")?);
+        assert_eq!(load_oauth_tokens(codex_home.path(), "docs")?, None);
+        assert_eq!(
+            load_oauth_tokens(codex_home.path(), "github")?,
+            Some(github)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_saves_keep_every_server() -> io::Result<()> {
+        let codex_home = tempfile::tempdir()?;
+        let servers: Vec<String> = (0..16).map(|i| format!("server-{i}")).collect();
+
+        std::thread::scope(|scope| -> io::Result<()> {
+            let saves: Vec<_> = servers
+                .iter()
+                .map(|server| {
+                    let codex_home = codex_home.path();
+                    scope.spawn(move || {
+                        save_oauth_tokens(codex_home, server, &tokens("https://example.com", None))
+                    })
+                })
+                .collect();
+            for save in saves {
+                save.join()
+                    .map_err(|_| io::Error::other("save panicked"))??;
+            }
+            Ok(())
+        })?;
+
+        for server in &servers {
+            assert!(load_oauth_tokens(codex_home.path(), server)?.is_some());
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn credentials_file_is_private() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let codex_home = tempfile::tempdir()?;
+        save_oauth_tokens(codex_home.path(), "docs", &tokens("u", None))?;
+        let mode = std::fs::metadata(get_mcp_credentials_file(codex_home.path()))?
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unexpired_tokens_for_the_configured_url_are_used_as_is() -> Result<()> {
+        let codex_home = tempfile::tempdir()?;
+        let url = "https://docs.example.com/mcp";
+        save_oauth_tokens(codex_home.path(), "docs", &tokens(url, None))?;
+
+        assert_eq!(
+            load_fresh_oauth_tokens(codex_home.path(), "docs", url).await?,
+            Some(tokens(url, None))
+        );
+        assert_eq!(
+            load_fresh_oauth_tokens(
+                codex_home.path(),
+                "docs",
+                "https://elsewhere.example.com/mcp"
+            )
+            .await?,
+            None
+        );
+        assert_eq!(
+            load_fresh_oauth_tokens(codex_home.path(), "github", url).await?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expiry_uses_refresh_margin() {
+        let now = 10_000;
+        assert!(!tokens("u", None).expires_within(REFRESH_MARGIN, now));
+        assert!(!tokens("u", Some(now + 61)).expires_within(REFRESH_MARGIN, now));
+        assert!(tokens("u", Some(now + 60)).expires_within(REFRESH_MARGIN, now));
+        assert!(tokens("u", Some(now - 1)).expires_within(REFRESH_MARGIN, now));
+    }
+}
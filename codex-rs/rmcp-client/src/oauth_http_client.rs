@@ -0,0 +1,246 @@
+//! Streamable HTTP client that authenticates with stored OAuth tokens.
+//!
+//! The access token is read from shared state on every request instead of
+//! being fixed when the transport is created. When the server rejects it with
+//! `401 Unauthorized`, the token is refreshed, saved back to
+//! `CODEX_HOME/mcp_credentials.json` and the request is retried once, so a
+//! session keeps working after the token it started with expires.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::stream::BoxStream;
+use reqwest::StatusCode;
+use rmcp::model::ClientJsonRpcMessage;
+use rmcp::transport::streamable_http_client::SseError;
+use rmcp::transport::streamable_http_client::StreamableHttpClient;
+use rmcp::transport::streamable_http_client::StreamableHttpError;
+use rmcp::transport::streamable_http_client::StreamableHttpPostResponse;
+use sse_stream::Sse;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::oauth::StoredOAuthTokens;
+use crate::oauth::refresh_oauth_tokens;
+use crate::oauth::save_oauth_tokens;
+
+#[derive(Clone)]
+pub(crate) struct OAuthHttpClient {
+    http: reqwest::Client,
+    codex_home: Arc<PathBuf>,
+    server_name: Arc<str>,
+    tokens: Arc<Mutex<StoredOAuthTokens>>,
+}
+
+impl OAuthHttpClient {
+    pub(crate) fn new(codex_home: PathBuf, server_name: String, tokens: StoredOAuthTokens) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            codex_home: Arc::new(codex_home),
+            server_name: server_name.into(),
+            tokens: Arc::new(Mutex::new(tokens)),
+        }
+    }
+
+    async fn access_token(&self) -> String {
+        self.tokens.lock().await.access_token.clone()
+    }
+
+    /// Returns a token to retry with after the server rejected `rejected`, or
+    /// `None` if the token cannot be refreshed. Requests that fail
+    /// concurrently only trigger one refresh: later callers see that the
+    /// stored token already changed and retry with it.
+    async fn refresh_after_rejection(&self, rejected: &str) -> Option<String> {
+        let mut tokens = self.tokens.lock().await;
+        if tokens.access_token != rejected {
+            return Some(tokens.access_token.clone());
+        }
+
+        let refreshed = match refresh_oauth_tokens(&self.http, &tokens).await {
+            Ok(refreshed) => refreshed,
+            Err(e) => {
+                warn!(
+                    "failed to refresh OAuth token for MCP server `{}`: {e:#}; run `codex mcp login {}`",
+                    self.server_name, self.server_name
+                );
+                return None;
+            }
+        };
+        if let Err(e) = save_oauth_tokens(&self.codex_home, &self.server_name, &refreshed) {
+            warn!(
+                "failed to save refreshed OAuth token for MCP server `{}`: {e}",
+                self.server_name
+            );
+        }
+        *tokens = refreshed;
+        Some(tokens.access_token.clone())
+    }
+}
+
+fn is_unauthorized(err: &StreamableHttpError<reqwest::Error>) -> bool {
+    match err {
+        StreamableHttpError::AuthRequired(_) => true,
+        StreamableHttpError::Client(e) => e.status() == Some(StatusCode::UNAUTHORIZED),
+        _ => false,
+    }
+}
+
+// The `auth_header` passed in by the transport is ignored: the transport is
+// created without one and the current token is supplied here instead.
+impl StreamableHttpClient for OAuthHttpClient {
+    type Error = reqwest::Error;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        session_id: Option<Arc<str>>,
+        _auth_header: Option<String>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+        let token = self.access_token().await;
+        let result = self
+            .http
+            .post_message(
+                uri.clone(),
+                message.clone(),
+                session_id.clone(),
+                Some(token.clone()),
+            )
+            .await;
+        match result {
+            Err(err) if is_unauthorized(&err) => match self.refresh_after_rejection(&token).await {
+                Some(token) => {
+                    self.http
+                        .post_message(uri, message, session_id, Some(token))
+                        .await
+                }
+                None => Err(err),
+            },
+            result => result,
+        }
+    }
+
+    async fn delete_session(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        _auth_header: Option<String>,
+    ) -> Result<(), StreamableHttpError<Self::Error>> {
+        let token = self.access_token().await;
+        let result = self
+            .http
+            .delete_session(uri.clone(), session_id.clone(), Some(token.clone()))
+            .await;
+        match result {
+            Err(err) if is_unauthorized(&err) => match self.refresh_after_rejection(&token).await {
+                Some(token) => self.http.delete_session(uri, session_id, Some(token)).await,
+                None => Err(err),
+            },
+            result => result,
+        }
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        last_event_id: Option<String>,
+        _auth_header: Option<String>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+        let token = self.access_token().await;
+        let result = self
+            .http
+            .get_stream(
+                uri.clone(),
+                session_id.clone(),
+                last_event_id.clone(),
+                Some(token.clone()),
+            )
+            .await;
+        match result {
+            Err(err) if is_unauthorized(&err) => match self.refresh_after_rejection(&token).await {
+                Some(token) => {
+                    self.http
+                        .get_stream(uri, session_id, last_event_id, Some(token))
+                        .await
+                }
+                None => Err(err),
+            },
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oauth::load_oauth_tokens;
+    use axum::Router;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::http::header::CONTENT_TYPE;
+    use axum::response::IntoResponse;
+    use axum::routing::post;
+    use pretty_assertions::assert_eq;
+
+    async fn mcp(State(token): State<Arc<str>>, headers: HeaderMap) -> impl IntoResponse {
+        let expected = format!("Bearer {token}");
+        let authorized = headers
+            .get("authorization")
+            .is_some_and(|value| value.as_bytes() == expected.as_bytes());
+        if !authorized {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        StatusCode::ACCEPTED.into_response()
+    }
+
+    async fn token(body: String) -> impl IntoResponse {
+        assert!(body.contains("refresh_token=refresh-1"), "{body}");
+        (
+            [(CONTENT_TYPE, "application/json")],
+            r#"{"access_token":"access-2","expires_in":3600}"#,
+        )
+    }
+
+    #[tokio::test]
+    async fn rejected_token_is_refreshed_saved_and_retried() -> anyhow::Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let base = format!("http://{}", listener.local_addr()?);
+        let app = Router::new()
+            .route("/mcp", post(mcp))
+            .route("/token", post(token))
+            .with_state(Arc::<str>::from("access-2"));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let codex_home = tempfile::tempdir()?;
+        let url = format!("{base}/mcp");
+        let stale = StoredOAuthTokens {
+            url: url.clone(),
+            client_id: "client-1".to_string(),
+            client_secret: None,
+            token_endpoint: format!("{base}/token"),
+            access_token: "access-1".to_string(),
+            refresh_token: Some("refresh-1".to_string()),
+            expires_at: None,
+        };
+        let client =
+            OAuthHttpClient::new(codex_home.path().to_path_buf(), "docs".to_string(), stale);
+
+        let message: ClientJsonRpcMessage = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized",
+        }))?;
+        let response = client
+            .post_message(url.into(), message, None, None)
+            .await
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+
+        assert!(matches!(response, StreamableHttpPostResponse::Accepted));
+        assert_eq!(client.access_token().await, "access-2");
+        assert_eq!(
+            load_oauth_tokens(codex_home.path(), "docs")?.map(|tokens| tokens.access_token),
+            Some("access-2".to_string())
+        );
+        Ok(())
+    }
+}
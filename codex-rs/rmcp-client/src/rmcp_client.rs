@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::io;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::Duration;
@@ -10,13 +11,25 @@ use anyhow::anyhow;
 use futures::FutureExt;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializeResult;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourceTemplatesRequestParams;
+use mcp_types::ListResourceTemplatesResult;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use rmcp::model::CallToolRequestParam;
+use rmcp::model::GetPromptRequestParam;
 use rmcp::model::InitializeRequestParam;
 use rmcp::model::PaginatedRequestParam;
+use rmcp::model::ReadResourceRequestParam;
 use rmcp::service::RoleClient;
 use rmcp::service::RunningService;
 use rmcp::service::{self};
@@ -32,6 +45,8 @@ use tracing::info;
 use tracing::warn;
 
 use crate::logging_client_handler::LoggingClientHandler;
+use crate::oauth::StoredOAuthTokens;
+use crate::oauth_http_client::OAuthHttpClient;
 use crate::utils::convert_call_tool_result;
 use crate::utils::convert_to_mcp;
 use crate::utils::convert_to_rmcp;
@@ -41,6 +56,7 @@ use crate::utils::run_with_timeout;
 enum PendingTransport {
     ChildProcess(TokioChildProcess),
     StreamableHttp(StreamableHttpClientTransport<reqwest::Client>),
+    StreamableHttpOAuth(StreamableHttpClientTransport<OAuthHttpClient>),
 }
 
 enum ClientState {
@@ -118,6 +134,26 @@ impl RmcpClient {
         })
     }
 
+    /// Connects with tokens stored by `codex mcp login`. When the server
+    /// rejects the access token, it is refreshed, saved and the request is
+    /// retried.
+    pub fn new_streamable_http_oauth_client(
+        url: String,
+        codex_home: PathBuf,
+        server_name: String,
+        tokens: StoredOAuthTokens,
+    ) -> Result<Self> {
+        let client = OAuthHttpClient::new(codex_home, server_name, tokens);
+        let config = StreamableHttpClientTransportConfig::with_uri(url);
+        let transport = StreamableHttpClientTransport::with_client(client, config);
+
+        Ok(Self {
+            state: Mutex::new(ClientState::Connecting {
+                transport: Some(PendingTransport::StreamableHttpOAuth(transport)),
+            }),
+        })
+    }
+
     /// Perform the initialization handshake with the MCP server.
     /// https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle#initialization
     pub async fn initialize(
@@ -146,6 +182,9 @@ impl RmcpClient {
             PendingTransport::StreamableHttp(transport) => {
                 service::serve_client(client_handler, transport).boxed()
             }
+            PendingTransport::StreamableHttpOAuth(transport) => {
+                service::serve_client(client_handler, transport).boxed()
+            }
         };
 
         let service = match timeout {
@@ -203,6 +242,78 @@ impl RmcpClient {
         convert_call_tool_result(rmcp_result)
     }
 
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_resources(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "resources/list").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn list_resource_templates(
+        &self,
+        params: Option<ListResourceTemplatesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourceTemplatesResult> {
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_resource_templates(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "resources/templates/list").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let service = self.service().await?;
+        let rmcp_params: ReadResourceRequestParam =
+            convert_to_rmcp(ReadResourceRequestParams { uri })?;
+        let fut = service.read_resource(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "resources/read").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        let service = self.service().await?;
+        let rmcp_params = params
+            .map(convert_to_rmcp::<_, PaginatedRequestParam>)
+            .transpose()?;
+
+        let fut = service.list_prompts(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "prompts/list").await?;
+        convert_to_mcp(result)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let service = self.service().await?;
+        let params = GetPromptRequestParams { arguments, name };
+        let rmcp_params: GetPromptRequestParam = convert_to_rmcp(params)?;
+        let fut = service.get_prompt(rmcp_params);
+        let result = run_with_timeout(fut, timeout, "prompts/get").await?;
+        convert_to_mcp(result)
+    }
+
     async fn service(&self) -> Result<Arc<RunningService<RoleClient, LoggingClientHandler>>> {
         let guard = self.state.lock().await;
         match &*guard {
@@ -13,7 +13,6 @@ use codex_core::AuthManager;
 use codex_core::ConversationManager;
 use codex_core::config::Config;
 use codex_core::config::persist_model_selection;
-use codex_core::model_family::find_family_for_model;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::mcp_protocol::ConversationId;
@@ -312,7 +311,7 @@ impl App {
             AppEvent::UpdateModel(model) => {
                 self.chat_widget.set_model(&model);
                 self.config.model = model.clone();
-                if let Some(family) = find_family_for_model(&model) {
+                if let Some(family) = self.config.model_catalog.find_family(&model) {
                     self.config.model_family = family;
                 }
             }
@@ -363,6 +362,22 @@ impl App {
             AppEvent::OpenReviewCustomPrompt => {
                 self.chat_widget.show_review_custom_prompt();
             }
+            AppEvent::CheckpointChangesComputed(changes) => {
+                self.chat_widget.show_checkpoints_popup(changes);
+            }
+            AppEvent::OpenCheckpointRestoreOptions(index) => {
+                self.chat_widget.open_checkpoint_restore_options(index);
+            }
+            AppEvent::RestoreCheckpoint {
+                index,
+                rewind_conversation,
+            } => {
+                if let Some(checkpoint) = self.chat_widget.restore_checkpoint(index)
+                    && rewind_conversation
+                {
+                    self.backtrack_to_checkpoint(checkpoint);
+                }
+            }
         }
         Ok(true)
     }
@@ -8,6 +8,7 @@ use crate::history_cell::UserHistoryCell;
 use crate::pager_overlay::Overlay;
 use crate::tui;
 use crate::tui::TuiEvent;
+use codex_core::protocol::CheckpointItem;
 use codex_core::protocol::ConversationPathResponseEvent;
 use codex_protocol::mcp_protocol::ConversationId;
 use color_eyre::eyre::Result;
@@ -107,6 +108,14 @@ impl App {
         ));
     }
 
+    /// Fork the conversation just before the turn that `checkpoint` was taken
+    /// for, putting that turn's prompt back in the composer.
+    pub(crate) fn backtrack_to_checkpoint(&mut self, checkpoint: CheckpointItem) {
+        if let Some(base_id) = self.chat_widget.conversation_id() {
+            self.request_backtrack(checkpoint.prompt, base_id, checkpoint.nth_user_message);
+        }
+    }
+
     /// Open transcript overlay (enters alternate screen and shows full transcript).
     pub(crate) fn open_transcript_overlay(&mut self, tui: &mut tui::Tui) {
         let _ = tui.enter_alt_screen();
@@ -76,4 +76,18 @@ pub(crate) enum AppEvent {
 
     /// Open the custom prompt option from the review popup.
     OpenReviewCustomPrompt,
+
+    /// Changes made during each turn listed by `/checkpoints`, as
+    /// `(checkpoint commit id, summary)`, computed off the UI thread.
+    CheckpointChangesComputed(Vec<(String, String)>),
+
+    /// Open the restore options for the checkpoint at this index.
+    OpenCheckpointRestoreOptions(usize),
+
+    /// Restore the workspace to the checkpoint at `index`, optionally also
+    /// rewinding the conversation to before that turn.
+    RestoreCheckpoint {
+        index: usize,
+        rewind_conversation: bool,
+    },
 }
@@ -35,6 +35,13 @@ pub(crate) enum ApprovalRequest {
         reason: Option<String>,
         grant_root: Option<PathBuf>,
     },
+    McpToolCall {
+        id: String,
+        server: String,
+        tool: String,
+        arguments: Option<serde_json::Value>,
+        reason: Option<String>,
+    },
 }
 
 /// Modal overlay asking the user to approve or deny one or more requests.
@@ -96,6 +103,9 @@ impl ApprovalOverlay {
         let (options, title) = match &state.variant {
             ApprovalVariant::Exec { .. } => (exec_options(), "Allow command?".to_string()),
             ApprovalVariant::ApplyPatch { .. } => (patch_options(), "Apply changes?".to_string()),
+            ApprovalVariant::McpToolCall { .. } => {
+                (mcp_tool_call_options(), "Allow MCP tool call?".to_string())
+            }
         };
 
         let items = options
@@ -136,6 +146,9 @@ impl ApprovalOverlay {
                 (ApprovalVariant::ApplyPatch { id, .. }, decision) => {
                     self.handle_patch_decision(id, decision);
                 }
+                (ApprovalVariant::McpToolCall { id }, decision) => {
+                    self.handle_mcp_tool_call_decision(id, decision);
+                }
             }
         }
 
@@ -162,6 +175,14 @@ impl ApprovalOverlay {
         }));
     }
 
+    fn handle_mcp_tool_call_decision(&self, id: &str, decision: ReviewDecision) {
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::McpToolCallApproval {
+                id: id.to_string(),
+                decision,
+            }));
+    }
+
     fn advance_queue(&mut self) {
         if let Some(next) = self.queue.pop() {
             self.set_current(next);
@@ -224,6 +245,9 @@ impl BottomPaneView for ApprovalOverlay {
                 ApprovalVariant::ApplyPatch { id, .. } => {
                     self.handle_patch_decision(id, ReviewDecision::Abort);
                 }
+                ApprovalVariant::McpToolCall { id } => {
+                    self.handle_mcp_tool_call_decision(id, ReviewDecision::Abort);
+                }
             }
         }
         self.queue.clear();
@@ -322,6 +346,39 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     header,
                 }
             }
+            ApprovalRequest::McpToolCall {
+                id,
+                server,
+                tool,
+                arguments,
+                reason,
+            } => {
+                let mut header = Vec::new();
+                if let Some(reason) = reason
+                    && !reason.is_empty()
+                {
+                    header.push(HeaderLine::Text {
+                        text: reason,
+                        italic: true,
+                    });
+                    header.push(HeaderLine::Spacer);
+                }
+                header.push(HeaderLine::Text {
+                    text: format!("Tool: {server}.{tool}"),
+                    italic: false,
+                });
+                if let Some(arguments) = arguments {
+                    header.push(HeaderLine::Text {
+                        text: format!("Arguments: {}", truncate_text(&arguments.to_string(), 80)),
+                        italic: false,
+                    });
+                }
+                header.push(HeaderLine::Spacer);
+                Self {
+                    variant: ApprovalVariant::McpToolCall { id },
+                    header,
+                }
+            }
         }
     }
 }
@@ -329,6 +386,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
 enum ApprovalVariant {
     Exec { id: String, command: Vec<String> },
     ApplyPatch { id: String },
+    McpToolCall { id: String },
 }
 
 #[derive(Clone)]
@@ -380,6 +438,30 @@ fn patch_options() -> Vec<ApprovalOption> {
     ]
 }
 
+fn mcp_tool_call_options() -> Vec<ApprovalOption> {
+    vec![
+        ApprovalOption {
+            label: "Approve and call now".to_string(),
+            description: "(Y) Call this tool one time".to_string(),
+            decision: ReviewDecision::Approved,
+            shortcut: Some('y'),
+        },
+        ApprovalOption {
+            label: "Always approve this session".to_string(),
+            description: "(A) Automatically approve this tool for the rest of the session"
+                .to_string(),
+            decision: ReviewDecision::ApprovedForSession,
+            shortcut: Some('a'),
+        },
+        ApprovalOption {
+            label: "Cancel".to_string(),
+            description: "(N) Do not call the tool".to_string(),
+            decision: ReviewDecision::Abort,
+            shortcut: Some('n'),
+        },
+    ]
+}
+
 fn build_exec_history_lines(
     command: Vec<String>,
     decision: ReviewDecision,
@@ -534,6 +616,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mcp_tool_call_request_sends_mcp_approval() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let request = ApprovalRequest::McpToolCall {
+            id: "sub-1".into(),
+            server: "docs".into(),
+            tool: "update_page".into(),
+            arguments: Some(serde_json::json!({ "page": "home" })),
+            reason: None,
+        };
+        let mut view = ApprovalOverlay::new(request, tx);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, 8));
+        view.render(Rect::new(0, 0, 80, 8), &mut buf);
+        let rendered: Vec<String> = (0..buf.area.height)
+            .map(|row| {
+                (0..buf.area.width)
+                    .map(|col| buf[(col, row)].symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        assert!(
+            rendered
+                .iter()
+                .any(|line| line.contains("Tool: docs.update_page")),
+            "expected header to name the tool, got {rendered:?}"
+        );
+
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        assert!(view.is_complete());
+        let mut op = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(o) = ev {
+                op = Some(o);
+                break;
+            }
+        }
+        assert_eq!(
+            op,
+            Some(Op::McpToolCallApproval {
+                id: "sub-1".into(),
+                decision: ReviewDecision::ApprovedForSession,
+            })
+        );
+    }
+
     #[test]
     fn enter_sets_last_selected_index_without_dismissing() {
         let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
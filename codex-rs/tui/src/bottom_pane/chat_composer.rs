@@ -18,6 +18,7 @@ use ratatui::widgets::WidgetRef;
 use super::chat_composer_history::ChatComposerHistory;
 use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
+use super::command_popup::McpPromptItem;
 use super::file_search_popup::FileSearchPopup;
 use super::footer::FooterProps;
 use super::footer::render_footer;
@@ -27,7 +28,9 @@ use crate::bottom_pane::paste_burst::FlushResult;
 use crate::slash_command::SlashCommand;
 use crate::style::user_message_style;
 use crate::terminal_palette;
+use codex_common::fuzzy_match::fuzzy_match;
 use codex_protocol::custom_prompts::CustomPrompt;
+use mcp_types::Prompt;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
@@ -53,6 +56,14 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 pub enum InputResult {
     Submitted(String),
     Command(SlashCommand),
+    /// A command followed by arguments, e.g. `/undo 3`.
+    CommandWithArgs(SlashCommand, String),
+    /// An MCP prompt invoked as `/server:name`, with the arguments typed after it.
+    McpPrompt {
+        server: String,
+        name: String,
+        arguments: Option<serde_json::Value>,
+    },
     None,
 }
 
@@ -84,6 +95,9 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    mcp_prompts: Vec<McpPromptItem>,
+    // Resource URIs (and URI templates) by MCP server, for `@server:uri` mentions.
+    mcp_resources: HashMap<String, Vec<String>>,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -125,6 +139,8 @@ impl ChatComposer {
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            mcp_prompts: Vec::new(),
+            mcp_resources: HashMap::new(),
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
@@ -405,6 +421,16 @@ impl ChatComposer {
                                 }
                             }
                         }
+                        CommandItem::McpPrompt(idx) => {
+                            if let Some(item) = popup.mcp_prompt(idx) {
+                                let command = item.command();
+                                let starts_with_cmd =
+                                    first_line.trim_start().starts_with(&format!("/{command}"));
+                                if !starts_with_cmd {
+                                    self.textarea.set_text(&format!("/{command} "));
+                                }
+                            }
+                        }
                     }
                     // After completing the command, move cursor to the end.
                     if !self.textarea.text().is_empty() {
@@ -420,6 +446,13 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(sel) = popup.selected_item() {
+                    let first_line = self
+                        .textarea
+                        .text()
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .to_string();
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
                     // Capture any needed data from popup before clearing it.
@@ -429,12 +462,27 @@ impl ChatComposer {
                         }
                         _ => None,
                     };
+                    let mcp_prompt = match sel {
+                        CommandItem::McpPrompt(idx) => popup.mcp_prompt(idx).cloned(),
+                        _ => None,
+                    };
                     // Hide popup since an action has been dispatched.
                     self.active_popup = ActivePopup::None;
 
                     match sel {
                         CommandItem::Builtin(cmd) => {
-                            return (InputResult::Command(cmd), true);
+                            let args = first_line
+                                .trim_start()
+                                .strip_prefix(&format!("/{}", cmd.command()))
+                                .filter(|rest| rest.starts_with(char::is_whitespace))
+                                .map(str::trim)
+                                .filter(|args| !args.is_empty());
+                            return match args {
+                                Some(args) => {
+                                    (InputResult::CommandWithArgs(cmd, args.to_string()), true)
+                                }
+                                None => (InputResult::Command(cmd), true),
+                            };
                         }
                         CommandItem::UserPrompt(_) => {
                             if let Some(contents) = prompt_content {
@@ -442,6 +490,25 @@ impl ChatComposer {
                             }
                             return (InputResult::None, true);
                         }
+                        CommandItem::McpPrompt(_) => {
+                            let Some(item) = mcp_prompt else {
+                                return (InputResult::None, true);
+                            };
+                            let args = first_line
+                                .trim_start()
+                                .split_once(char::is_whitespace)
+                                .map(|(_, rest)| rest)
+                                .unwrap_or_default();
+                            let arguments = mcp_prompt_arguments(&item.prompt, args);
+                            return (
+                                InputResult::McpPrompt {
+                                    server: item.server,
+                                    name: item.prompt.name,
+                                    arguments,
+                                },
+                                true,
+                            );
+                        }
                     }
                 }
                 // Fallback to default newline handling if no command selected.
@@ -521,7 +588,8 @@ impl ChatComposer {
 
                 let sel_path = sel.to_string();
                 // If selected path looks like an image (png/jpeg), attach as image instead of inserting text.
-                let is_image = Self::is_image_path(&sel_path);
+                // MCP resource completions already carry their `@` and are inserted as-is.
+                let is_image = !sel_path.starts_with('@') && Self::is_image_path(&sel_path);
                 if is_image {
                     // Determine dimensions; if that fails fall back to normal path insertion.
                     let path_buf = PathBuf::from(&sel_path);
@@ -1162,6 +1230,7 @@ impl ChatComposer {
             _ => {
                 if input_starts_with_slash {
                     let mut command_popup = CommandPopup::new(self.custom_prompts.clone());
+                    command_popup.set_mcp_prompts(self.mcp_prompts.clone());
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1176,6 +1245,66 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptItem>) {
+        self.mcp_prompts = prompts.clone();
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(prompts);
+        }
+    }
+
+    pub(crate) fn set_mcp_resources(&mut self, resources: HashMap<String, Vec<String>>) {
+        self.mcp_resources = resources;
+    }
+
+    /// The `@server:uri` mentions in `text` that name a server offering MCP
+    /// resources, in order of appearance and without duplicates. Punctuation
+    /// ending a sentence right after a mention is not part of its URI.
+    pub(crate) fn mcp_resource_mentions(&self, text: &str) -> Vec<(String, String)> {
+        let mut mentions: Vec<(String, String)> = Vec::new();
+        for token in text.split_whitespace() {
+            let Some((server, uri)) = token
+                .strip_prefix('@')
+                .and_then(|mention| mention.split_once(':'))
+            else {
+                continue;
+            };
+            let uri = uri.trim_end_matches(['.', ',', ';', '!', '?', ')']);
+            if uri.is_empty() || !self.mcp_resources.contains_key(server) {
+                continue;
+            }
+            let mention = (server.to_string(), uri.to_string());
+            if !mentions.contains(&mention) {
+                mentions.push(mention);
+            }
+        }
+        mentions
+    }
+
+    /// Completions for an `@server:uri` query naming a server that offers MCP
+    /// resources. These are matched locally instead of searching files.
+    fn mcp_resource_matches(&self, query: &str) -> Option<Vec<FileMatch>> {
+        let (server, _) = query.split_once(':')?;
+        let uris = self.mcp_resources.get(server)?;
+        let mut scored: Vec<(i32, FileMatch)> = uris
+            .iter()
+            .filter_map(|uri| {
+                let candidate = format!("{server}:{uri}");
+                let (indices, score) = fuzzy_match(&candidate, query)?;
+                Some((
+                    score,
+                    FileMatch {
+                        score: 0,
+                        // Shift the highlights past the leading `@`.
+                        indices: Some(indices.into_iter().map(|i| i as u32 + 1).collect()),
+                        path: format!("@{candidate}"),
+                    },
+                ))
+            })
+            .collect();
+        scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.path.cmp(&b.1.path)));
+        Some(scored.into_iter().map(|(_, m)| m).collect())
+    }
+
     /// Synchronize `self.file_search_popup` with the current text in the textarea.
     /// Note this is only called when self.active_popup is NOT Command.
     fn sync_file_search_popup(&mut self) {
@@ -1194,6 +1323,19 @@ impl ChatComposer {
             return;
         }
 
+        if let Some(matches) = self.mcp_resource_matches(&query) {
+            if !matches!(self.active_popup, ActivePopup::File(_)) {
+                self.active_popup = ActivePopup::File(FileSearchPopup::new());
+            }
+            if let ActivePopup::File(popup) = &mut self.active_popup {
+                popup.set_query(&query);
+                popup.set_matches(&query, matches);
+            }
+            self.current_file_query = Some(query);
+            self.dismissed_file_popup_token = None;
+            return;
+        }
+
         if !query.is_empty() {
             self.app_event_tx
                 .send(AppEvent::StartFileSearch(query.clone()));
@@ -1289,6 +1431,50 @@ impl WidgetRef for ChatComposer {
     }
 }
 
+/// Turns the text typed after `/server:name` into MCP prompt arguments.
+/// `key=value` words set arguments by name and the remaining words fill the
+/// prompt's other arguments in order. A prompt with a single argument gets the
+/// whole text unless it is given as `key=value`.
+fn mcp_prompt_arguments(prompt: &Prompt, args: &str) -> Option<serde_json::Value> {
+    let args = args.trim();
+    if args.is_empty() {
+        return None;
+    }
+    let declared: Vec<&str> = prompt
+        .arguments
+        .iter()
+        .flatten()
+        .map(|argument| argument.name.as_str())
+        .collect();
+    let mut values = serde_json::Map::new();
+    if let [only] = declared.as_slice()
+        && !args.starts_with(&format!("{only}="))
+    {
+        values.insert((*only).to_string(), args.into());
+        return Some(serde_json::Value::Object(values));
+    }
+
+    let words =
+        shlex::split(args).unwrap_or_else(|| args.split_whitespace().map(str::to_string).collect());
+    let mut positional = Vec::new();
+    for word in words {
+        match word.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                values.insert(key.to_string(), value.into());
+            }
+            _ => positional.push(word),
+        }
+    }
+    let unset: Vec<&str> = declared
+        .into_iter()
+        .filter(|name| !values.contains_key(*name))
+        .collect();
+    for (name, word) in unset.into_iter().zip(positional) {
+        values.insert(name.to_string(), word.into());
+    }
+    Some(serde_json::Value::Object(values))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1735,7 +1921,7 @@ mod tests {
                 Some(CommandItem::Builtin(cmd)) => {
                     assert_eq!(cmd.command(), "model")
                 }
-                Some(CommandItem::UserPrompt(_)) => {
+                Some(CommandItem::UserPrompt(_) | CommandItem::McpPrompt(_)) => {
                     panic!("unexpected prompt selected for '/mo'")
                 }
                 None => panic!("no selected command for '/mo'"),
@@ -1788,6 +1974,15 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!(
+                    "expected no arguments for '/{}', got: {args}",
+                    cmd.command()
+                )
+            }
+            InputResult::McpPrompt { name, .. } => {
+                panic!("expected Command result for '/init', got MCP prompt {name}")
+            }
             InputResult::None => panic!("expected Command result for '/init'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -1809,7 +2004,7 @@ mod tests {
             false,
         );
 
-        type_chars_humanlike(&mut composer, &['/', 'c']);
+        type_chars_humanlike(&mut composer, &['/', 'c', 'o']);
 
         let (_result, _needs_redraw) =
             composer.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
@@ -1846,6 +2041,15 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgs(cmd, args) => {
+                panic!(
+                    "expected no arguments for '/{}', got: {args}",
+                    cmd.command()
+                )
+            }
+            InputResult::McpPrompt { name, .. } => {
+                panic!("expected Command result for '/mention', got MCP prompt {name}")
+            }
             InputResult::None => panic!("expected Command result for '/mention'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -1853,6 +2057,34 @@ mod tests {
         assert_eq!(composer.textarea.text(), "@");
     }
 
+    #[test]
+    fn slash_command_with_arguments_returns_them() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        type_chars_humanlike(&mut composer, &['/', 'u', 'n', 'd', 'o', ' ', '3']);
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert_eq!(
+            result,
+            InputResult::CommandWithArgs(SlashCommand::Undo, "3".to_string())
+        );
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn test_multiple_pastes_submission() {
         use crossterm::event::KeyCode;
@@ -2370,4 +2602,105 @@ mod tests {
         assert_eq!(composer.textarea.text(), "z".repeat(count));
         assert!(composer.pending_pastes.is_empty());
     }
+
+    #[test]
+    fn mcp_prompt_arguments_by_name_position_and_single_argument() {
+        use mcp_types::PromptArgument;
+        use serde_json::json;
+
+        let prompt = |names: &[&str]| Prompt {
+            arguments: Some(
+                names
+                    .iter()
+                    .map(|name| PromptArgument {
+                        description: None,
+                        name: name.to_string(),
+                        required: None,
+                        title: None,
+                    })
+                    .collect(),
+            ),
+            description: None,
+            name: "review".to_string(),
+            title: None,
+        };
+
+        let two = prompt(&["repo", "pr"]);
+        assert_eq!(mcp_prompt_arguments(&two, "  "), None);
+        assert_eq!(
+            mcp_prompt_arguments(&two, "pr=42 openai/codex"),
+            Some(json!({"pr": "42", "repo": "openai/codex"}))
+        );
+        assert_eq!(
+            mcp_prompt_arguments(&two, "\"my repo\" 7 extra"),
+            Some(json!({"repo": "my repo", "pr": "7"}))
+        );
+
+        // Unknown names are passed through; extra positional words are dropped.
+        assert_eq!(
+            mcp_prompt_arguments(&two, "repo=a pr=1 draft=true"),
+            Some(json!({"repo": "a", "pr": "1", "draft": "true"}))
+        );
+
+        let none = prompt(&[]);
+        assert_eq!(
+            mcp_prompt_arguments(&none, "key=value ignored"),
+            Some(json!({"key": "value"}))
+        );
+
+        let one = prompt(&["topic"]);
+        assert_eq!(
+            mcp_prompt_arguments(&one, "error handling in the=parser"),
+            Some(json!({"topic": "error handling in the=parser"}))
+        );
+        assert_eq!(
+            mcp_prompt_arguments(&one, "topic=\"release notes\""),
+            Some(json!({"topic": "release notes"}))
+        );
+    }
+
+    #[test]
+    fn mcp_resources_complete_and_are_extracted_from_mentions() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        composer.set_mcp_resources(HashMap::from([(
+            "docs".to_string(),
+            vec!["docs://guide".to_string(), "docs://api".to_string()],
+        )]));
+
+        type_chars_humanlike(&mut composer, &['@', 'd', 'o', 'c', 's', ':', 'g']);
+        let _ = composer.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(composer.textarea.text(), "@docs:docs://guide ");
+        while let Ok(event) = rx.try_recv() {
+            assert!(
+                !matches!(event, AppEvent::StartFileSearch(ref query) if query.starts_with("docs:")),
+                "resource mentions should not start a file search"
+            );
+        }
+
+        assert_eq!(
+            composer.mcp_resource_mentions(
+                "read @docs:docs://guide and @docs:docs://api, not @other:x, @docs: or @src/main.rs (see @docs:docs://guide)."
+            ),
+            vec![
+                ("docs".to_string(), "docs://guide".to_string()),
+                ("docs".to_string(), "docs://api".to_string()),
+            ]
+        );
+        assert_eq!(
+            composer.mcp_resource_mentions("no mentions here"),
+            Vec::<(String, String)>::new()
+        );
+    }
 }
@@ -334,6 +334,7 @@ mod tests {
             total_token_usage: usage,
             last_token_usage: last,
             model_context_window: Some(context_window),
+            total_cost_usd: None,
         }
     }
 
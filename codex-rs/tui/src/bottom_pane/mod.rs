@@ -1,4 +1,5 @@
 //! Bottom pane: shows the ChatComposer or a BottomPaneView, if one is active.
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
@@ -42,6 +43,7 @@ pub(crate) enum CancellationEvent {
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 use codex_protocol::custom_prompts::CustomPrompt;
+pub(crate) use command_popup::McpPromptItem;
 
 use crate::status_indicator_widget::StatusIndicatorWidget;
 pub(crate) use list_selection_view::SelectionAction;
@@ -362,6 +364,21 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Update the MCP prompts offered as `/server:name` in the slash popup.
+    pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<McpPromptItem>) {
+        self.composer.set_mcp_prompts(prompts);
+        self.request_redraw();
+    }
+
+    /// Update the MCP resource URIs offered as `@server:uri` completions.
+    pub(crate) fn set_mcp_resources(&mut self, resources: HashMap<String, Vec<String>>) {
+        self.composer.set_mcp_resources(resources);
+    }
+
+    pub(crate) fn mcp_resource_mentions(&self, text: &str) -> Vec<(String, String)> {
+        self.composer.mcp_resource_mentions(text)
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.composer.is_empty()
     }
@@ -7,6 +7,8 @@ use codex_core::config::Config;
 use codex_core::config_types::Notifications;
 use codex_core::git_info::current_branch_name;
 use codex_core::git_info::local_git_branches;
+use codex_core::memory::forget;
+use codex_core::memory::load_memory;
 use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
@@ -15,6 +17,10 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::CheckpointItem;
+use codex_core::protocol::DelegateTaskBeginEvent;
+use codex_core::protocol::DelegateTaskEndEvent;
+use codex_core::protocol::DelegateTaskProgressEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -25,9 +31,14 @@ use codex_core::protocol::ExitedReviewModeEvent;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
+use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpListPromptsResponseEvent;
+use codex_core::protocol::McpListResourcesResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpToolCallApprovalRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::NetworkRequestEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
@@ -64,6 +75,7 @@ use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::InputResult;
+use crate::bottom_pane::McpPromptItem;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
 use crate::bottom_pane::SelectionViewParams;
@@ -74,6 +86,7 @@ use crate::diff_render::display_path_for;
 use crate::exec_cell::CommandOutput;
 use crate::exec_cell::ExecCell;
 use crate::exec_cell::new_active_exec_command;
+use crate::exec_command::strip_bash_lc_and_escape;
 use crate::get_git_diff::get_git_diff;
 use crate::history_cell;
 use crate::history_cell::AgentMessageCell;
@@ -106,13 +119,13 @@ use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_file_search::FileMatch;
-use codex_git_tooling::CreateGhostCommitOptions;
-use codex_git_tooling::GhostCommit;
-use codex_git_tooling::GitToolingError;
-use codex_git_tooling::create_ghost_commit;
-use codex_git_tooling::restore_ghost_commit;
+use codex_git_tooling::DiffStat;
+use codex_git_tooling::diff_stat;
+use codex_git_tooling::restore_to_commit;
 
-const MAX_TRACKED_GHOST_COMMITS: usize = 20;
+/// Most recent checkpoints listed by `/checkpoints`; computing the diff stats
+/// of each one runs git.
+const MAX_LISTED_CHECKPOINTS: usize = 30;
 
 // Track information about an in-flight exec command.
 struct RunningCommand {
@@ -192,7 +205,9 @@ pub(crate) fn get_limits_duration(windows_minutes: u64) -> String {
     const MINUTES_PER_MONTH: u64 = 30 * MINUTES_PER_DAY;
     const ROUNDING_BIAS_MINUTES: u64 = 3;
 
-    if windows_minutes <= MINUTES_PER_DAY.saturating_add(ROUNDING_BIAS_MINUTES) {
+    if windows_minutes < MINUTES_PER_HOUR {
+        format!("{windows_minutes}m")
+    } else if windows_minutes <= MINUTES_PER_DAY.saturating_add(ROUNDING_BIAS_MINUTES) {
         let adjusted = windows_minutes.saturating_add(ROUNDING_BIAS_MINUTES);
         let hours = std::cmp::max(1, adjusted / MINUTES_PER_HOUR);
         format!("{hours}h")
@@ -251,9 +266,8 @@ pub(crate) struct ChatWidget {
     pending_notification: Option<Notification>,
     // Simple review mode flag; used to adjust layout and banners.
     is_review_mode: bool,
-    // List of ghost commits corresponding to each turn.
-    ghost_snapshots: Vec<GhostCommit>,
-    ghost_snapshots_disabled: bool,
+    // Checkpoints recorded by the agent at the start of each turn, oldest first.
+    checkpoints: Vec<CheckpointItem>,
     // Whether to add a final message separator after the last message
     needs_final_message_separator: bool,
 }
@@ -261,6 +275,25 @@ pub(crate) struct ChatWidget {
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
+    /// MCP resources and prompts, resolved by codex-core before the turn.
+    mcp_items: Vec<InputItem>,
+}
+
+impl UserMessage {
+    /// Messages that only invoke an MCP prompt are shown as the command.
+    fn display_text(&self) -> String {
+        if !self.text.is_empty() {
+            return self.text.clone();
+        }
+        self.mcp_items
+            .iter()
+            .filter_map(|item| match item {
+                InputItem::McpPrompt { server, name, .. } => Some(format!("/{server}:{name}")),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 }
 
 impl From<String> for UserMessage {
@@ -268,6 +301,7 @@ impl From<String> for UserMessage {
         Self {
             text,
             image_paths: Vec::new(),
+            mcp_items: Vec::new(),
         }
     }
 }
@@ -276,7 +310,11 @@ fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Optio
     if text.is_empty() && image_paths.is_empty() {
         None
     } else {
-        Some(UserMessage { text, image_paths })
+        Some(UserMessage {
+            text,
+            image_paths,
+            mcp_items: Vec::new(),
+        })
     }
 }
 
@@ -307,6 +345,9 @@ impl ChatWidget {
         }
         // Ask codex-core to enumerate custom prompts for this session.
         self.submit_op(Op::ListCustomPrompts);
+        // And the prompts and resources offered by MCP servers.
+        self.submit_op(Op::ListMcpPrompts);
+        self.submit_op(Op::ListMcpResources);
         if let Some(user_message) = self.initial_user_message.take() {
             self.submit_user_message(user_message);
         }
@@ -510,6 +551,19 @@ impl ChatWidget {
         );
     }
 
+    fn on_mcp_tool_call_approval_request(
+        &mut self,
+        id: String,
+        ev: McpToolCallApprovalRequestEvent,
+    ) {
+        let id2 = id.clone();
+        let ev2 = ev.clone();
+        self.defer_or_handle(
+            |q| q.push_mcp_tool_call_approval(id, ev),
+            |s| s.handle_mcp_tool_call_approval_now(id2, ev2),
+        );
+    }
+
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
         let ev2 = ev.clone();
@@ -568,6 +622,37 @@ impl ChatWidget {
         )));
     }
 
+    fn on_delegate_task_begin(&mut self, ev: DelegateTaskBeginEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_delegate_task_begin(ev.index, ev.task));
+        self.request_redraw();
+    }
+
+    /// Workers' own output is summarized as one line per step so several
+    /// tasks running at once stay readable.
+    fn on_delegate_task_progress(&mut self, ev: DelegateTaskProgressEvent) {
+        let activity = match *ev.msg {
+            EventMsg::ExecCommandBegin(begin) => {
+                format!("Ran {}", strip_bash_lc_and_escape(&begin.command))
+            }
+            EventMsg::PatchApplyEnd(end) if end.success => "Applied patch".to_string(),
+            EventMsg::PatchApplyEnd(_) => "Failed to apply patch".to_string(),
+            EventMsg::WebSearchEnd(search) => format!("Searched: {}", search.query),
+            EventMsg::Error(ErrorEvent { message })
+            | EventMsg::StreamError(StreamErrorEvent { message }) => message,
+            _ => return,
+        };
+        self.add_to_history(history_cell::new_delegate_task_activity(ev.index, activity));
+        self.request_redraw();
+    }
+
+    fn on_delegate_task_end(&mut self, ev: DelegateTaskEndEvent) {
+        self.add_to_history(history_cell::new_delegate_task_end(
+            ev.index, ev.success, ev.output,
+        ));
+        self.request_redraw();
+    }
+
     fn on_get_history_entry_response(
         &mut self,
         event: codex_core::protocol::GetHistoryEntryResponseEvent,
@@ -593,6 +678,25 @@ impl ChatWidget {
         debug!("BackgroundEvent: {message}");
     }
 
+    /// Requests that went through are routine; only blocked ones are shown.
+    fn on_network_request(&mut self, ev: NetworkRequestEvent) {
+        let NetworkRequestEvent {
+            host,
+            port,
+            allowed,
+            ..
+        } = ev;
+        if allowed {
+            debug!("NetworkRequest: {host}:{port} allowed");
+            return;
+        }
+        self.add_to_history(history_cell::new_info_event(
+            format!("Blocked network request to {host}:{port}"),
+            Some("(not in sandbox_workspace_write.allowed_domains)".to_string()),
+        ));
+        self.request_redraw();
+    }
+
     fn on_stream_error(&mut self, message: String) {
         // Show stream errors in the transcript so users see retry/backoff info.
         self.add_to_history(history_cell::new_stream_error_event(message));
@@ -764,6 +868,32 @@ impl ChatWidget {
         });
     }
 
+    pub(crate) fn handle_mcp_tool_call_approval_now(
+        &mut self,
+        id: String,
+        ev: McpToolCallApprovalRequestEvent,
+    ) {
+        self.flush_answer_stream_with_separator();
+        let McpInvocation {
+            server,
+            tool,
+            arguments,
+        } = ev.invocation;
+        self.notify(Notification::ExecApprovalRequested {
+            command: format!("{server}.{tool}"),
+        });
+
+        let request = ApprovalRequest::McpToolCall {
+            id,
+            server,
+            tool,
+            arguments,
+            reason: ev.reason,
+        };
+        self.bottom_pane.push_approval_request(request);
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_exec_begin_now(&mut self, ev: ExecCommandBeginEvent) {
         // Ensure the status indicator is visible while the command runs.
         self.running_commands.insert(
@@ -910,8 +1040,7 @@ impl ChatWidget {
             suppress_session_configured_redraw: false,
             pending_notification: None,
             is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
+            checkpoints: Vec::new(),
             needs_final_message_separator: false,
         }
     }
@@ -972,8 +1101,7 @@ impl ChatWidget {
             suppress_session_configured_redraw: true,
             pending_notification: None,
             is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
+            checkpoints: Vec::new(),
             needs_final_message_separator: false,
         }
     }
@@ -1028,27 +1156,45 @@ impl ChatWidget {
                     self.request_redraw();
                 }
             }
-            _ => {
-                match self.bottom_pane.handle_key_event(key_event) {
-                    InputResult::Submitted(text) => {
-                        // If a task is running, queue the user input to be sent after the turn completes.
-                        let user_message = UserMessage {
-                            text,
-                            image_paths: self.bottom_pane.take_recent_submission_images(),
-                        };
-                        if self.bottom_pane.is_task_running() {
-                            self.queued_user_messages.push_back(user_message);
-                            self.refresh_queued_user_messages();
-                        } else {
-                            self.submit_user_message(user_message);
-                        }
-                    }
-                    InputResult::Command(cmd) => {
-                        self.dispatch_command(cmd);
-                    }
-                    InputResult::None => {}
+            _ => match self.bottom_pane.handle_key_event(key_event) {
+                InputResult::Submitted(text) => {
+                    let mcp_items = self
+                        .bottom_pane
+                        .mcp_resource_mentions(&text)
+                        .into_iter()
+                        .map(|(server, uri)| InputItem::McpResource { server, uri })
+                        .collect();
+                    let user_message = UserMessage {
+                        text,
+                        image_paths: self.bottom_pane.take_recent_submission_images(),
+                        mcp_items,
+                    };
+                    self.submit_or_queue_user_message(user_message);
                 }
-            }
+                InputResult::McpPrompt {
+                    server,
+                    name,
+                    arguments,
+                } => {
+                    let user_message = UserMessage {
+                        text: String::new(),
+                        image_paths: Vec::new(),
+                        mcp_items: vec![InputItem::McpPrompt {
+                            server,
+                            name,
+                            arguments,
+                        }],
+                    };
+                    self.submit_or_queue_user_message(user_message);
+                }
+                InputResult::Command(cmd) => {
+                    self.dispatch_command(cmd);
+                }
+                InputResult::CommandWithArgs(cmd, args) => {
+                    self.dispatch_command_with_args(cmd, &args);
+                }
+                InputResult::None => {}
+            },
         }
     }
 
@@ -1067,6 +1213,38 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    /// If a task is running, queue the user input to be sent after the turn completes.
+    fn submit_or_queue_user_message(&mut self, user_message: UserMessage) {
+        if self.bottom_pane.is_task_running() {
+            self.queued_user_messages.push_back(user_message);
+            self.refresh_queued_user_messages();
+        } else {
+            self.submit_user_message(user_message);
+        }
+    }
+
+    fn dispatch_command_with_args(&mut self, cmd: SlashCommand, args: &str) {
+        if cmd != SlashCommand::Undo {
+            self.dispatch_command(cmd);
+            return;
+        }
+        if self.bottom_pane.is_task_running() {
+            let message = format!(
+                "'/{}' is disabled while a task is in progress.",
+                cmd.command()
+            );
+            self.add_to_history(history_cell::new_error_event(message));
+            self.request_redraw();
+            return;
+        }
+        match args.trim().parse::<usize>() {
+            Ok(turns) if turns > 0 => self.undo_turns(turns),
+            _ => {
+                self.add_error_message(format!("Invalid number of turns: {args}. Usage: /undo [N]"))
+            }
+        }
+    }
+
     fn dispatch_command(&mut self, cmd: SlashCommand) {
         if !cmd.available_during_task() && self.bottom_pane.is_task_running() {
             let message = format!(
@@ -1108,7 +1286,10 @@ impl ChatWidget {
                 self.app_event_tx.send(AppEvent::ExitRequest);
             }
             SlashCommand::Undo => {
-                self.undo_last_snapshot();
+                self.undo_turns(1);
+            }
+            SlashCommand::Checkpoints => {
+                self.open_checkpoints_popup();
             }
             SlashCommand::Diff => {
                 self.add_diff_in_progress();
@@ -1136,6 +1317,9 @@ impl ChatWidget {
             SlashCommand::Mcp => {
                 self.add_mcp_output();
             }
+            SlashCommand::Memory => {
+                self.open_memory_popup();
+            }
             #[cfg(debug_assertions)]
             SlashCommand::TestApproval => {
                 use codex_core::protocol::EventMsg;
@@ -1220,13 +1404,16 @@ impl ChatWidget {
     }
 
     fn submit_user_message(&mut self, user_message: UserMessage) {
-        let UserMessage { text, image_paths } = user_message;
-        if text.is_empty() && image_paths.is_empty() {
+        let display_text = user_message.display_text();
+        let UserMessage {
+            text,
+            image_paths,
+            mcp_items,
+        } = user_message;
+        if text.is_empty() && image_paths.is_empty() && mcp_items.is_empty() {
             return;
         }
 
-        self.capture_ghost_snapshot();
-
         let mut items: Vec<InputItem> = Vec::new();
 
         if !text.is_empty() {
@@ -1237,6 +1424,8 @@ impl ChatWidget {
             items.push(InputItem::LocalImage { path });
         }
 
+        items.extend(mcp_items);
+
         self.codex_op_tx
             .send(Op::UserInput { items })
             .unwrap_or_else(|e| {
@@ -1246,68 +1435,203 @@ impl ChatWidget {
         // Persist the text to cross-session message history.
         if !text.is_empty() {
             self.codex_op_tx
-                .send(Op::AddToHistory { text: text.clone() })
+                .send(Op::AddToHistory { text })
                 .unwrap_or_else(|e| {
                     tracing::error!("failed to send AddHistory op: {e}");
                 });
         }
 
         // Only show the text portion in conversation history.
-        if !text.is_empty() {
-            self.add_to_history(history_cell::new_user_prompt(text));
+        if !display_text.is_empty() {
+            self.add_to_history(history_cell::new_user_prompt(display_text));
         }
         self.needs_final_message_separator = false;
     }
 
-    fn capture_ghost_snapshot(&mut self) {
-        if self.ghost_snapshots_disabled {
+    /// Restores the workspace to the checkpoint taken before the `turns`-th
+    /// most recent turn and drops the checkpoints after it, so repeated
+    /// `/undo` keeps stepping back.
+    fn undo_turns(&mut self, turns: usize) {
+        let Some(index) = self.checkpoints.len().checked_sub(turns) else {
+            let message = if self.checkpoints.is_empty() {
+                "No checkpoint available to undo.".to_string()
+            } else {
+                format!(
+                    "Not enough checkpoints to undo {turns} turns (available: {}).",
+                    self.checkpoints.len()
+                )
+            };
+            self.add_info_message(message, None);
             return;
+        };
+
+        if self.restore_checkpoint(index).is_some() {
+            self.checkpoints.truncate(index);
         }
+    }
 
-        let options = CreateGhostCommitOptions::new(&self.config.cwd);
-        match create_ghost_commit(&options) {
-            Ok(commit) => {
-                self.ghost_snapshots.push(commit);
-                if self.ghost_snapshots.len() > MAX_TRACKED_GHOST_COMMITS {
-                    self.ghost_snapshots.remove(0);
-                }
-            }
-            Err(err) => {
-                self.ghost_snapshots_disabled = true;
-                let (message, hint) = match &err {
-                    GitToolingError::NotAGitRepository { .. } => (
-                        "Snapshots disabled: current directory is not a Git repository."
-                            .to_string(),
-                        None,
-                    ),
-                    _ => (
-                        format!("Snapshots disabled after error: {err}"),
-                        Some(
-                            "Restart Codex after resolving the issue to re-enable snapshots."
-                                .to_string(),
-                        ),
-                    ),
-                };
-                self.add_info_message(message, hint);
-                tracing::warn!("failed to create ghost snapshot: {err}");
-            }
+    /// Restores the workspace files to the checkpoint at `index`, returning it
+    /// on success. The conversation is left untouched.
+    pub(crate) fn restore_checkpoint(&mut self, index: usize) -> Option<CheckpointItem> {
+        let checkpoint = self.checkpoints.get(index)?.clone();
+        if let Err(err) = restore_to_commit(&self.config.cwd, &checkpoint.commit_id) {
+            self.add_error_message(format!("Failed to restore checkpoint: {err}"));
+            return None;
         }
+
+        let short_id: String = checkpoint.commit_id.chars().take(8).collect();
+        self.add_info_message(
+            format!("Restored workspace to checkpoint {short_id}"),
+            Some(format!(
+                "Taken before: {}",
+                prompt_summary(&checkpoint.prompt)
+            )),
+        );
+        Some(checkpoint)
+    }
+
+    /// Computes the changes made during each listed turn in the background;
+    /// the list is shown by [`Self::show_checkpoints_popup`] once they are
+    /// known.
+    pub(crate) fn open_checkpoints_popup(&mut self) {
+        if self.checkpoints.is_empty() {
+            self.add_info_message(
+                "No checkpoints recorded in this session yet.".to_string(),
+                Some(
+                    "Set `checkpoints = true` in config.toml to record one at the start of every turn inside a Git repository."
+                        .to_string(),
+                ),
+            );
+            return;
+        }
+
+        let first_listed = self
+            .checkpoints
+            .len()
+            .saturating_sub(MAX_LISTED_CHECKPOINTS);
+        let commit_ids: Vec<String> = self.checkpoints[first_listed..]
+            .iter()
+            .map(|checkpoint| checkpoint.commit_id.clone())
+            .collect();
+        let cwd = self.config.cwd.clone();
+        let tx = self.app_event_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let changes = commit_ids
+                .iter()
+                .enumerate()
+                .map(|(i, commit_id)| {
+                    // The last turn is still reflected in the working tree.
+                    let next = commit_ids.get(i + 1).map(String::as_str);
+                    let changes = match diff_stat(&cwd, commit_id, next) {
+                        Ok(stat) => format_diff_stat(stat),
+                        Err(err) => {
+                            tracing::warn!("failed to compute checkpoint diff stats: {err}");
+                            "changes unknown".to_string()
+                        }
+                    };
+                    (commit_id.clone(), changes)
+                })
+                .collect();
+            tx.send(AppEvent::CheckpointChangesComputed(changes));
+        });
     }
 
-    fn undo_last_snapshot(&mut self) {
-        let Some(commit) = self.ghost_snapshots.pop() else {
-            self.add_info_message("No snapshot available to undo.".to_string(), None);
+    /// Lists the checkpoints of this session, newest first, with the changes
+    /// made during each turn, keyed by the checkpoint's commit id.
+    pub(crate) fn show_checkpoints_popup(&mut self, changes: Vec<(String, String)>) {
+        let changes: HashMap<String, String> = changes.into_iter().collect();
+        let first_listed = self
+            .checkpoints
+            .len()
+            .saturating_sub(MAX_LISTED_CHECKPOINTS);
+        let mut items: Vec<SelectionItem> = Vec::new();
+        for (index, checkpoint) in self.checkpoints.iter().enumerate().skip(first_listed).rev() {
+            // Checkpoints recorded while the stats were computed are not listed.
+            let Some(changes) = changes.get(&checkpoint.commit_id) else {
+                continue;
+            };
+            let short_id: String = checkpoint.commit_id.chars().take(8).collect();
+            items.push(SelectionItem {
+                name: format!("{}. {}", index + 1, prompt_summary(&checkpoint.prompt)),
+                description: Some(format!("{short_id} · {changes}")),
+                is_current: false,
+                actions: vec![Box::new(move |tx| {
+                    tx.send(AppEvent::OpenCheckpointRestoreOptions(index));
+                })],
+                dismiss_on_select: false,
+                search_value: None,
+            });
+        }
+        if items.is_empty() {
             return;
+        }
+
+        let subtitle = if first_listed > 0 {
+            format!(
+                "Newest first; showing the last {MAX_LISTED_CHECKPOINTS} of {} turns",
+                self.checkpoints.len()
+            )
+        } else {
+            "Newest first; select a turn to restore the state before it".to_string()
         };
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: "Checkpoints".to_string(),
+            subtitle: Some(subtitle),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
+    }
 
-        if let Err(err) = restore_ghost_commit(&self.config.cwd, &commit) {
-            self.add_error_message(format!("Failed to restore snapshot: {err}"));
-            self.ghost_snapshots.push(commit);
+    /// Asks whether to restore only the files of checkpoint `index` or the
+    /// conversation as well.
+    pub(crate) fn open_checkpoint_restore_options(&mut self, index: usize) {
+        let Some(checkpoint) = self.checkpoints.get(index) else {
             return;
-        }
+        };
+        let short_id: String = checkpoint.commit_id.chars().take(8).collect();
+        let items = vec![
+            SelectionItem {
+                name: "Restore files".to_string(),
+                description: Some(
+                    "Reset the workspace to how it was before this turn; keep the conversation."
+                        .to_string(),
+                ),
+                is_current: false,
+                actions: vec![Box::new(move |tx| {
+                    tx.send(AppEvent::RestoreCheckpoint {
+                        index,
+                        rewind_conversation: false,
+                    });
+                })],
+                dismiss_on_select: true,
+                search_value: None,
+            },
+            SelectionItem {
+                name: "Restore files and conversation".to_string(),
+                description: Some(
+                    "Also rewind the conversation to before this turn and put its prompt back in the composer."
+                        .to_string(),
+                ),
+                is_current: false,
+                actions: vec![Box::new(move |tx| {
+                    tx.send(AppEvent::RestoreCheckpoint {
+                        index,
+                        rewind_conversation: true,
+                    });
+                })],
+                dismiss_on_select: true,
+                search_value: None,
+            },
+        ];
 
-        let short_id: String = commit.id().chars().take(8).collect();
-        self.add_info_message(format!("Restored workspace to snapshot {short_id}"), None);
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: format!("Restore checkpoint {short_id}"),
+            subtitle: Some(prompt_summary(&checkpoint.prompt)),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
     }
 
     /// Replay a subset of initial events into the UI to seed the transcript when
@@ -1389,6 +1713,9 @@ impl ChatWidget {
             EventMsg::ApplyPatchApprovalRequest(ev) => {
                 self.on_apply_patch_approval_request(id.unwrap_or_default(), ev)
             }
+            EventMsg::McpToolCallApprovalRequest(ev) => {
+                self.on_mcp_tool_call_approval_request(id.unwrap_or_default(), ev)
+            }
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
@@ -1401,6 +1728,8 @@ impl ChatWidget {
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
+            EventMsg::McpListPromptsResponse(ev) => self.on_list_mcp_prompts(ev),
+            EventMsg::McpListResourcesResponse(ev) => self.on_list_mcp_resources(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
@@ -1420,6 +1749,11 @@ impl ChatWidget {
                 self.on_entered_review_mode(review_request)
             }
             EventMsg::ExitedReviewMode(review) => self.on_exited_review_mode(review),
+            EventMsg::CheckpointCreated(checkpoint) => self.checkpoints.push(checkpoint),
+            EventMsg::DelegateTaskBegin(ev) => self.on_delegate_task_begin(ev),
+            EventMsg::DelegateTaskProgress(ev) => self.on_delegate_task_progress(ev),
+            EventMsg::DelegateTaskEnd(ev) => self.on_delegate_task_end(ev),
+            EventMsg::NetworkRequest(ev) => self.on_network_request(ev),
         }
     }
 
@@ -1535,7 +1869,7 @@ impl ChatWidget {
         let messages: Vec<String> = self
             .queued_user_messages
             .iter()
-            .map(|m| m.text.clone())
+            .map(UserMessage::display_text)
             .collect();
         self.bottom_pane.set_queued_user_messages(messages);
     }
@@ -1559,6 +1893,7 @@ impl ChatWidget {
         self.add_to_history(crate::status::new_status_output(
             &self.config,
             usage_ref,
+            self.token_info.as_ref().and_then(|ti| ti.total_cost_usd),
             &self.conversation_id,
             self.rate_limit_snapshot.as_ref(),
         ));
@@ -1670,6 +2005,71 @@ impl ChatWidget {
         });
     }
 
+    /// Lists the saved memory entries; selecting one removes it.
+    pub(crate) fn open_memory_popup(&mut self) {
+        let codex_home = self.config.codex_home.clone();
+        let cwd = self.config.cwd.clone();
+        let entries = match load_memory(&codex_home, &cwd) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.add_error_message(format!("Failed to read memory: {e}"));
+                return;
+            }
+        };
+        if entries.is_empty() {
+            self.add_info_message(
+                "No memory entries saved yet.".to_string(),
+                Some("Codex saves notes here when the `remember` tool is enabled.".to_string()),
+            );
+            return;
+        }
+
+        let items = entries
+            .into_iter()
+            .map(|entry| {
+                let codex_home = codex_home.clone();
+                let cwd = cwd.clone();
+                let id = entry.id.clone();
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    let cell = match forget(&codex_home, &cwd, &id) {
+                        Ok(Some(entry)) => history_cell::new_info_event(
+                            format!("Forgot memory entry [{}]: {}", entry.id, entry.text),
+                            None,
+                        ),
+                        Ok(None) => history_cell::new_error_event(format!(
+                            "Memory entry [{id}] no longer exists."
+                        )),
+                        Err(e) => history_cell::new_error_event(format!(
+                            "Failed to remove memory entry [{id}]: {e}"
+                        )),
+                    };
+                    tx.send(AppEvent::InsertHistoryCell(Box::new(cell)));
+                })];
+                SelectionItem {
+                    description: Some(format!(
+                        "{} · {} · [{}]",
+                        entry.scope.label(),
+                        entry.date,
+                        entry.id
+                    )),
+                    name: entry.text,
+                    is_current: false,
+                    actions,
+                    dismiss_on_select: true,
+                    search_value: None,
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: "Memory".to_string(),
+            subtitle: Some("Select an entry to forget it".to_string()),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
+    }
+
     /// Set the approval policy in the widget's config copy.
     pub(crate) fn set_approval_policy(&mut self, policy: AskForApproval) {
         self.config.approval_policy = policy;
@@ -1776,6 +2176,41 @@ impl ChatWidget {
         self.bottom_pane.set_custom_prompts(ev.custom_prompts);
     }
 
+    fn on_list_mcp_prompts(&mut self, ev: McpListPromptsResponseEvent) {
+        let prompts: Vec<McpPromptItem> = ev
+            .prompts
+            .into_iter()
+            .flat_map(|(server, prompts)| {
+                prompts.into_iter().map(move |prompt| McpPromptItem {
+                    server: server.clone(),
+                    prompt,
+                })
+            })
+            .collect();
+        debug!("received {} MCP prompts", prompts.len());
+        self.bottom_pane.set_mcp_prompts(prompts);
+    }
+
+    fn on_list_mcp_resources(&mut self, ev: McpListResourcesResponseEvent) {
+        let mut resources: HashMap<String, Vec<String>> = ev
+            .resources
+            .into_iter()
+            .map(|(server, resources)| {
+                (
+                    server,
+                    resources.into_iter().map(|resource| resource.uri).collect(),
+                )
+            })
+            .collect();
+        for (server, templates) in ev.resource_templates {
+            resources
+                .entry(server)
+                .or_default()
+                .extend(templates.into_iter().map(|template| template.uri_template));
+        }
+        self.bottom_pane.set_mcp_resources(resources);
+    }
+
     pub(crate) fn open_review_popup(&mut self) {
         let mut items: Vec<SelectionItem> = Vec::new();
 
@@ -2076,6 +2511,37 @@ const EXAMPLE_PROMPTS: [&str; 6] = [
     "Improve documentation in @filename",
 ];
 
+/// First line of a turn prompt, shortened for checkpoint listings.
+fn prompt_summary(prompt: &str) -> String {
+    const MAX_CHARS: usize = 60;
+    let first_line = prompt.lines().find(|line| !line.trim().is_empty());
+    let Some(first_line) = first_line else {
+        return "(no text)".to_string();
+    };
+    let first_line = first_line.trim();
+    if first_line.chars().count() > MAX_CHARS {
+        let truncated: String = first_line.chars().take(MAX_CHARS - 1).collect();
+        format!("{truncated}…")
+    } else {
+        first_line.to_string()
+    }
+}
+
+fn format_diff_stat(stat: DiffStat) -> String {
+    if stat.files_changed == 0 {
+        return "no changes".to_string();
+    }
+    let noun = if stat.files_changed == 1 {
+        "file"
+    } else {
+        "files"
+    };
+    format!(
+        "+{} -{} in {} {noun}",
+        stat.insertions, stat.deletions, stat.files_changed
+    )
+}
+
 // Extract the first bold (Markdown) element in the form **...** from `s`.
 // Returns the inner text if found; otherwise `None`.
 fn extract_first_bold(s: &str) -> Option<String> {
@@ -15,6 +15,10 @@ use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::CheckpointItem;
+use codex_core::protocol::DelegateTaskBeginEvent;
+use codex_core::protocol::DelegateTaskEndEvent;
+use codex_core::protocol::DelegateTaskProgressEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
@@ -338,8 +342,7 @@ fn make_chatwidget_manual() -> (
         suppress_session_configured_redraw: false,
         pending_notification: None,
         is_review_mode: false,
-        ghost_snapshots: Vec::new(),
-        ghost_snapshots_disabled: false,
+        checkpoints: Vec::new(),
         needs_final_message_separator: false,
     };
     (widget, rx, op_rx)
@@ -601,6 +604,94 @@ fn open_fixture(name: &str) -> File {
     File::open(name).expect("open fixture file")
 }
 
+#[test]
+fn undo_reports_missing_checkpoints() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::CheckpointCreated(CheckpointItem {
+            commit_id: "0123456789abcdef".to_string(),
+            prompt: "fix the build".to_string(),
+            nth_user_message: 0,
+        }),
+    });
+    assert_eq!(chat.checkpoints.len(), 1);
+
+    chat.dispatch_command_with_args(SlashCommand::Undo, "3");
+    let cells = drain_insert_history(&mut rx);
+    let rendered = lines_to_single_string(cells.last().expect("info message"));
+    assert!(
+        rendered.contains("Not enough checkpoints to undo 3 turns (available: 1)."),
+        "unexpected message: {rendered}"
+    );
+    assert_eq!(chat.checkpoints.len(), 1);
+
+    chat.dispatch_command_with_args(SlashCommand::Undo, "zero");
+    let cells = drain_insert_history(&mut rx);
+    let rendered = lines_to_single_string(cells.last().expect("error message"));
+    assert!(
+        rendered.contains("Usage: /undo [N]"),
+        "unexpected message: {rendered}"
+    );
+}
+
+#[test]
+fn delegated_task_activity_is_nested_under_the_task() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::DelegateTaskBegin(DelegateTaskBeginEvent {
+            call_id: "call-1".into(),
+            index: 1,
+            task: "rename the config module".into(),
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::DelegateTaskProgress(DelegateTaskProgressEvent {
+            call_id: "call-1".into(),
+            index: 1,
+            msg: Box::new(EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+                call_id: "child-call".into(),
+                command: vec!["bash".into(), "-lc".into(), "cargo check".into()],
+                cwd: PathBuf::from("."),
+                parsed_cmd: Vec::new(),
+            })),
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::DelegateTaskEnd(DelegateTaskEndEvent {
+            call_id: "call-1".into(),
+            index: 1,
+            success: true,
+            output: "Renamed config to settings.\nAll checks pass.".into(),
+        }),
+    });
+
+    let rendered: Vec<String> = drain_insert_history(&mut rx)
+        .iter()
+        .map(|lines| lines_to_single_string(lines))
+        .collect();
+    let rendered = rendered.join("");
+    assert!(
+        rendered.contains("Delegated task 2: rename the config module"),
+        "missing begin line: {rendered}"
+    );
+    assert!(
+        rendered.contains("[2] Ran cargo check"),
+        "missing activity: {rendered}"
+    );
+    assert!(
+        rendered.contains("Task 2 finished"),
+        "missing end line: {rendered}"
+    );
+    assert!(rendered.contains("Renamed config to settings."));
+    assert!(!rendered.contains("All checks pass."));
+}
+
 #[test]
 fn empty_enter_during_task_does_not_queue() {
     let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
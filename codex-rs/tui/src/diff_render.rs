@@ -11,6 +11,10 @@ use std::path::PathBuf;
 
 use crate::exec_command::relativize_to_home;
 use crate::history_cell::PatchEventType;
+use crate::render::syntax::Highlighter;
+use crate::render::syntax::Language;
+use crate::render::syntax::LineState;
+use crate::render::syntax::language_for_path;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::FileChange;
 
@@ -46,7 +50,6 @@ pub(crate) fn create_diff_summary(
 // Shared row for per-file presentation
 #[derive(Clone)]
 struct Row {
-    #[allow(dead_code)]
     path: PathBuf,
     move_path: Option<PathBuf>,
     added: usize,
@@ -190,6 +193,7 @@ fn render_changes_block(
             out.push(RtLine::from(header));
         }
 
+        let mut syntax = DiffSyntax::new(r.move_path.as_ref().unwrap_or(&r.path));
         match r.change {
             FileChange::Add { content } => {
                 for (i, raw) in content.lines().enumerate() {
@@ -198,6 +202,7 @@ fn render_changes_block(
                         DiffLineType::Insert,
                         raw,
                         term_cols,
+                        &mut syntax,
                     ));
                 }
             }
@@ -208,6 +213,7 @@ fn render_changes_block(
                         DiffLineType::Delete,
                         raw,
                         term_cols,
+                        &mut syntax,
                     ));
                 }
             }
@@ -219,6 +225,7 @@ fn render_changes_block(
                             out.push(RtLine::from(vec!["    ".into(), "⋮".dim()]));
                         }
                         is_first_hunk = false;
+                        syntax.start_hunk();
 
                         let mut old_ln = h.old_range().start();
                         let mut new_ln = h.new_range().start();
@@ -231,6 +238,7 @@ fn render_changes_block(
                                         DiffLineType::Insert,
                                         s,
                                         term_cols,
+                                        &mut syntax,
                                     ));
                                     new_ln += 1;
                                 }
@@ -241,6 +249,7 @@ fn render_changes_block(
                                         DiffLineType::Delete,
                                         s,
                                         term_cols,
+                                        &mut syntax,
                                     ));
                                     old_ln += 1;
                                 }
@@ -251,6 +260,7 @@ fn render_changes_block(
                                         DiffLineType::Context,
                                         s,
                                         term_cols,
+                                        &mut syntax,
                                     ));
                                     old_ln += 1;
                                     new_ln += 1;
@@ -296,15 +306,97 @@ fn calculate_add_remove_from_diff(diff: &str) -> (usize, usize) {
     }
 }
 
+/// Highlights the contents of one file's diff lines by the file's language.
+/// Deleted lines continue the old version of the file and inserted lines the
+/// new one; context lines belong to both.
+struct DiffSyntax {
+    language: Option<&'static Language>,
+    highlighter: Highlighter,
+    old_state: LineState,
+    new_state: LineState,
+}
+
+impl DiffSyntax {
+    fn new(path: &Path) -> Self {
+        Self {
+            language: language_for_path(path),
+            highlighter: Highlighter::default(),
+            old_state: LineState::default(),
+            new_state: LineState::default(),
+        }
+    }
+
+    /// Hunks start at arbitrary lines, so state from the previous hunk does not
+    /// carry over.
+    fn start_hunk(&mut self) {
+        self.old_state = LineState::default();
+        self.new_state = LineState::default();
+    }
+
+    fn highlight(&mut self, kind: &DiffLineType, text: &str, style: Style) -> Vec<RtSpan<'static>> {
+        let Some(language) = self.language else {
+            return vec![RtSpan::styled(text.to_string(), style)];
+        };
+        let state = match kind {
+            DiffLineType::Delete => self.old_state,
+            DiffLineType::Insert | DiffLineType::Context => self.new_state,
+        };
+        let (spans, next_state) = self
+            .highlighter
+            .highlight_line(language, text, state, style);
+        match kind {
+            DiffLineType::Delete => self.old_state = next_state,
+            DiffLineType::Insert => self.new_state = next_state,
+            DiffLineType::Context => {
+                self.old_state = next_state;
+                self.new_state = next_state;
+            }
+        }
+        spans
+    }
+}
+
+/// Splits `spans` after their first `cols` characters.
+fn split_spans_at(
+    spans: Vec<RtSpan<'static>>,
+    cols: usize,
+) -> (Vec<RtSpan<'static>>, Vec<RtSpan<'static>>) {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let mut remaining = cols;
+    for span in spans {
+        if remaining == 0 {
+            tail.push(span);
+            continue;
+        }
+        let len = span.content.chars().count();
+        if len <= remaining {
+            remaining -= len;
+            head.push(span);
+            continue;
+        }
+        let split_at_byte_index = span
+            .content
+            .char_indices()
+            .nth(remaining)
+            .map_or(span.content.len(), |(i, _)| i);
+        let (first, rest) = span.content.split_at(split_at_byte_index);
+        head.push(RtSpan::styled(first.to_string(), span.style));
+        tail.push(RtSpan::styled(rest.to_string(), span.style));
+        remaining = 0;
+    }
+    (head, tail)
+}
+
 fn push_wrapped_diff_line(
     line_number: usize,
     kind: DiffLineType,
     text: &str,
     term_cols: usize,
+    syntax: &mut DiffSyntax,
 ) -> Vec<RtLine<'static>> {
     let indent = "    ";
     let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
 
     // Reserve a fixed number of spaces after the line number so that content starts
     // at a consistent column. Content includes a 1-character diff sign prefix
@@ -320,39 +412,38 @@ fn push_wrapped_diff_line(
         DiffLineType::Context => (' ', style_context()),
     };
     let mut lines: Vec<RtLine<'static>> = Vec::new();
+    let mut remaining_spans = syntax.highlight(&kind, text, line_style);
 
     loop {
         // Fit the content for the current terminal row:
         // compute how many columns are available after the prefix, then split
         // at a UTF-8 character boundary so this row's chunk fits exactly.
         let available_content_cols = term_cols.saturating_sub(prefix_cols + 1).max(1);
-        let split_at_byte_index = remaining_text
-            .char_indices()
-            .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
+        let (mut chunk, rest) = split_spans_at(remaining_spans, available_content_cols);
+        remaining_spans = rest;
 
         if first {
             // Build gutter (indent + line number + spacing) as a dimmed span
             let gutter = format!("{indent}{ln_str}{}", " ".repeat(gap_after_ln));
             // Content with a sign ('+'/'-'/' ') styled per diff kind
-            let content = format!("{sign_char}{chunk}");
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(content, line_style),
-            ]));
+            let mut spans = vec![RtSpan::styled(gutter, style_gutter())];
+            match chunk.first_mut() {
+                Some(first_span) if first_span.style == line_style => {
+                    first_span.content = format!("{sign_char}{}", first_span.content).into();
+                }
+                _ => spans.push(RtSpan::styled(sign_char.to_string(), line_style)),
+            }
+            spans.append(&mut chunk);
+            lines.push(RtLine::from(spans));
             first = false;
         } else {
             // Continuation lines keep a space for the sign column so content aligns
             let gutter = format!("{indent}{} ", " ".repeat(ln_str.len() + gap_after_ln));
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(chunk.to_string(), line_style),
-            ]));
+            let mut spans = vec![RtSpan::styled(gutter, style_gutter())];
+            spans.append(&mut chunk);
+            lines.push(RtLine::from(spans));
         }
-        if remaining_text.is_empty() {
+        if remaining_spans.is_empty() {
             break;
         }
     }
@@ -463,7 +554,13 @@ mod tests {
         let long_line = "this is a very long line that should wrap across multiple terminal columns and continue";
 
         // Call the wrapping function directly so we can precisely control the width
-        let lines = push_wrapped_diff_line(1, DiffLineType::Insert, long_line, 80);
+        let lines = push_wrapped_diff_line(
+            1,
+            DiffLineType::Insert,
+            long_line,
+            80,
+            &mut DiffSyntax::new(Path::new("notes.txt")),
+        );
 
         // Render into a small terminal to capture the visual layout
         snapshot_lines("wrap_behavior_insert", lines, 90, 8);
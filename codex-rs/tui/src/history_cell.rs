@@ -779,6 +779,46 @@ pub(crate) fn new_web_search_call(query: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_delegate_task_begin(index: usize, task: String) -> PlainHistoryCell {
+    let number = index + 1;
+    let lines: Vec<Line<'static>> = vec![
+        vec![
+            "• ".into(),
+            format!("Delegated task {number}: ").bold(),
+            task.into(),
+        ]
+        .into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+/// One step taken by a delegated task's worker, nested under the task.
+pub(crate) fn new_delegate_task_activity(index: usize, activity: String) -> PlainHistoryCell {
+    let number = index + 1;
+    let lines: Vec<Line<'static>> =
+        vec![vec!["  └ ".dim(), format!("[{number}] ").dim(), activity.dim()].into()];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_delegate_task_end(
+    index: usize,
+    success: bool,
+    output: String,
+) -> PlainHistoryCell {
+    let number = index + 1;
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    if success {
+        lines.push(vec!["✓ ".green(), format!("Task {number} finished").bold()].into());
+        // The full report goes to the model; the transcript shows its opening.
+        if let Some(first) = output.lines().find(|line| !line.trim().is_empty()) {
+            lines.push(vec!["  └ ".dim(), first.to_string().dim()].into());
+        }
+    } else {
+        lines.push(vec![format!("✘ Task {number} failed: {output}").red()].into());
+    }
+    PlainHistoryCell { lines }
+}
+
 /// If the first content is an image, return a new cell with the image.
 /// TODO(rgwood-dd): Handle images properly even if they're not the first result.
 fn try_new_completed_mcp_tool_call_with_image_output(
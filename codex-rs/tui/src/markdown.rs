@@ -1,3 +1,4 @@
+use crate::render::syntax::Highlighter;
 use codex_core::config::Config;
 use codex_core::config_types::UriBasedFileOpener;
 use ratatui::text::Line;
@@ -11,17 +12,51 @@ pub(crate) fn append_markdown(
     append_markdown_with_opener_and_cwd(markdown_source, lines, config.file_opener, &config.cwd);
 }
 
+/// Like [`append_markdown`], but reuses `highlighter` for code blocks so that
+/// rendering a growing source repeatedly only highlights its new lines.
+pub(crate) fn append_markdown_with_highlighter(
+    markdown_source: &str,
+    lines: &mut Vec<Line<'static>>,
+    config: &Config,
+    highlighter: &mut Highlighter,
+) {
+    append_rendered_markdown(
+        markdown_source,
+        lines,
+        config.file_opener,
+        &config.cwd,
+        highlighter,
+    );
+}
+
 fn append_markdown_with_opener_and_cwd(
     markdown_source: &str,
     lines: &mut Vec<Line<'static>>,
     file_opener: UriBasedFileOpener,
     cwd: &Path,
+) {
+    append_rendered_markdown(
+        markdown_source,
+        lines,
+        file_opener,
+        cwd,
+        &mut Highlighter::default(),
+    );
+}
+
+fn append_rendered_markdown(
+    markdown_source: &str,
+    lines: &mut Vec<Line<'static>>,
+    file_opener: UriBasedFileOpener,
+    cwd: &Path,
+    highlighter: &mut Highlighter,
 ) {
     // Render via pulldown-cmark and rewrite citations during traversal (outside code blocks).
     let rendered = crate::markdown_render::render_markdown_text_with_citations(
         markdown_source,
         file_opener.get_scheme(),
         cwd,
+        highlighter,
     );
     crate::render::line_utils::push_owned_lines(&rendered.lines, lines);
 }
@@ -1,4 +1,8 @@
 use crate::citation_regex::CITATION_REGEX;
+use crate::render::syntax::Highlighter;
+use crate::render::syntax::Language;
+use crate::render::syntax::LineState;
+use crate::render::syntax::language_for_fence;
 use pulldown_cmark::CodeBlockKind;
 use pulldown_cmark::CowStr;
 use pulldown_cmark::Event;
@@ -37,21 +41,30 @@ pub(crate) fn render_markdown_text(input: &str) -> Text<'static> {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     let parser = Parser::new_ext(input, options);
-    let mut w = Writer::new(parser, None, None);
+    let mut w = Writer::new(parser, None, None, Highlighter::default());
     w.run();
     w.text
 }
 
+/// Renders `input`, highlighting code blocks with `highlighter`, which keeps
+/// what it highlighted for the next render of the same (e.g. streamed) text.
 pub(crate) fn render_markdown_text_with_citations(
     input: &str,
     scheme: Option<&str>,
     cwd: &Path,
+    highlighter: &mut Highlighter,
 ) -> Text<'static> {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     let parser = Parser::new_ext(input, options);
-    let mut w = Writer::new(parser, scheme.map(str::to_string), Some(cwd.to_path_buf()));
+    let mut w = Writer::new(
+        parser,
+        scheme.map(str::to_string),
+        Some(cwd.to_path_buf()),
+        std::mem::take(highlighter),
+    );
     w.run();
+    *highlighter = w.highlighter;
     w.text
 }
 
@@ -71,13 +84,21 @@ where
     scheme: Option<String>,
     cwd: Option<std::path::PathBuf>,
     in_code_block: bool,
+    code_language: Option<&'static Language>,
+    code_state: LineState,
+    highlighter: Highlighter,
 }
 
 impl<'a, I> Writer<'a, I>
 where
     I: Iterator<Item = Event<'a>>,
 {
-    fn new(iter: I, scheme: Option<String>, cwd: Option<std::path::PathBuf>) -> Self {
+    fn new(
+        iter: I,
+        scheme: Option<String>,
+        cwd: Option<std::path::PathBuf>,
+        highlighter: Highlighter,
+    ) -> Self {
         Self {
             iter,
             text: Text::default(),
@@ -91,6 +112,9 @@ where
             scheme,
             cwd,
             in_code_block: false,
+            code_language: None,
+            code_state: LineState::default(),
+            highlighter,
         }
     }
 
@@ -265,11 +289,18 @@ where
                     content = s;
                 }
             }
-            let span = Span::styled(
-                content,
-                self.inline_styles.last().copied().unwrap_or_default(),
-            );
-            self.push_span(span);
+            let style = self.inline_styles.last().copied().unwrap_or_default();
+            if let Some(language) = self.code_language {
+                let (spans, state) =
+                    self.highlighter
+                        .highlight_line(language, &content, self.code_state, style);
+                self.code_state = state;
+                for span in spans {
+                    self.push_span(span);
+                }
+            } else {
+                self.push_span(Span::styled(content, style));
+            }
         }
         self.needs_newline = false;
     }
@@ -350,11 +381,13 @@ where
         self.needs_newline = false;
     }
 
-    fn start_codeblock(&mut self, _lang: Option<String>, indent: Option<Span<'static>>) {
+    fn start_codeblock(&mut self, lang: Option<String>, indent: Option<Span<'static>>) {
         if !self.text.lines.is_empty() {
             self.push_blank_line();
         }
         self.in_code_block = true;
+        self.code_language = lang.as_deref().and_then(language_for_fence);
+        self.code_state = LineState::default();
         self.indent_stack.push(IndentContext::new(
             vec![indent.unwrap_or_default()],
             None,
@@ -372,6 +405,7 @@ where
         // self.push_line("```".into());
         self.needs_newline = true;
         self.in_code_block = false;
+        self.code_language = None;
         self.indent_stack.pop();
     }
 
@@ -546,7 +580,8 @@ mod tests {
         let unchanged = rewrite_file_citations_with_scheme(markdown, Some("vscode"), cwd);
         // The helper itself always rewrites – this test validates behaviour of
         // append_markdown when `file_opener` is None.
-        let rendered = render_markdown_text_with_citations(markdown, None, cwd);
+        let rendered =
+            render_markdown_text_with_citations(markdown, None, cwd, &mut Highlighter::default());
         // Convert lines back to string for comparison.
         let rendered: String = rendered
             .lines
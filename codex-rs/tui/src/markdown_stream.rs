@@ -2,12 +2,15 @@ use codex_core::config::Config;
 use ratatui::text::Line;
 
 use crate::markdown;
+use crate::render::syntax::Highlighter;
 
 /// Newline-gated accumulator that renders markdown and commits only fully
-/// completed logical lines.
+/// completed logical lines. Code lines highlighted by an earlier commit are
+/// not highlighted again when the buffer is re-rendered.
 pub(crate) struct MarkdownStreamCollector {
     buffer: String,
     committed_line_count: usize,
+    highlighter: Highlighter,
 }
 
 impl MarkdownStreamCollector {
@@ -15,12 +18,14 @@ impl MarkdownStreamCollector {
         Self {
             buffer: String::new(),
             committed_line_count: 0,
+            highlighter: Highlighter::default(),
         }
     }
 
     pub fn clear(&mut self) {
         self.buffer.clear();
         self.committed_line_count = 0;
+        self.highlighter = Highlighter::default();
     }
 
     pub fn push_delta(&mut self, delta: &str) {
@@ -40,7 +45,12 @@ impl MarkdownStreamCollector {
             return Vec::new();
         };
         let mut rendered: Vec<Line<'static>> = Vec::new();
-        markdown::append_markdown(&source, &mut rendered, config);
+        markdown::append_markdown_with_highlighter(
+            &source,
+            &mut rendered,
+            config,
+            &mut self.highlighter,
+        );
         let mut complete_line_count = rendered.len();
         if complete_line_count > 0
             && crate::render::line_utils::is_blank_line_spaces_only(
@@ -81,7 +91,12 @@ impl MarkdownStreamCollector {
         tracing::trace!("markdown finalize (raw source):\n---\n{source}\n---");
 
         let mut rendered: Vec<Line<'static>> = Vec::new();
-        markdown::append_markdown(&source, &mut rendered, config);
+        markdown::append_markdown_with_highlighter(
+            &source,
+            &mut rendered,
+            config,
+            &mut self.highlighter,
+        );
 
         let out = if self.committed_line_count >= rendered.len() {
             Vec::new()
@@ -420,6 +435,16 @@ mod tests {
         assert_streamed_equals_full(&["```", "\nco", "de 1\ncode 2\n", "```\n"]);
     }
 
+    #[test]
+    fn highlighted_fences_stream_like_full_render() {
+        assert_streamed_equals_full(&[
+            "```rust\nfn a() {} /* multi",
+            "\nline */ let s = \"",
+            "x\";\n",
+            "```\n",
+        ]);
+    }
+
     #[test]
     fn utf8_boundary_safety_and_wide_chars() {
         let cfg = test_config();
@@ -0,0 +1,960 @@
+//! Language-aware syntax highlighting for fenced code blocks and diffs.
+//!
+//! Highlighting is line-oriented: each line is tokenized on its own and only a
+//! small [`LineState`] (inside a block comment or a multi-line string) carries
+//! over to the next line. This lets streamed markdown be highlighted one
+//! completed line at a time, and lets [`Highlighter`] reuse the spans of lines
+//! it has already highlighted when the same text is rendered again.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Span;
+
+use crate::color::is_light;
+use crate::color::perceptual_distance;
+use crate::terminal_palette::default_bg;
+use crate::terminal_palette::terminal_palette;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Keyword,
+    Type,
+    Function,
+    String,
+    Number,
+    Comment,
+    Inserted,
+    Deleted,
+}
+
+/// Where a line starts: in ordinary code, inside a block comment, or inside a
+/// string opened by the language's quote at the given index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) enum LineState {
+    #[default]
+    Code,
+    BlockComment,
+    String(usize),
+}
+
+struct Quote {
+    delim: &'static str,
+    multiline: bool,
+    escapes: bool,
+}
+
+const DOUBLE: Quote = Quote {
+    delim: "\"",
+    multiline: false,
+    escapes: true,
+};
+const SINGLE: Quote = Quote {
+    delim: "'",
+    multiline: false,
+    escapes: true,
+};
+const RAW_SINGLE: Quote = Quote {
+    delim: "'",
+    multiline: false,
+    escapes: false,
+};
+const MULTILINE_DOUBLE: Quote = Quote {
+    delim: "\"",
+    multiline: true,
+    escapes: true,
+};
+const MULTILINE_RAW_SINGLE: Quote = Quote {
+    delim: "'",
+    multiline: true,
+    escapes: false,
+};
+const TRIPLE_DOUBLE: Quote = Quote {
+    delim: "\"\"\"",
+    multiline: true,
+    escapes: true,
+};
+const TRIPLE_SINGLE: Quote = Quote {
+    delim: "'''",
+    multiline: true,
+    escapes: true,
+};
+const TEMPLATE: Quote = Quote {
+    delim: "`",
+    multiline: true,
+    escapes: true,
+};
+const RAW_BACKTICK: Quote = Quote {
+    delim: "`",
+    multiline: true,
+    escapes: false,
+};
+
+pub(crate) struct Language {
+    name: &'static str,
+    /// Space-separated.
+    keywords: &'static str,
+    /// Space-separated names of built-in types.
+    types: &'static str,
+    /// Identifiers starting with an uppercase letter name types.
+    capitalized_types: bool,
+    case_insensitive: bool,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Longer delimiters come first, so `"""` is tried before `"`.
+    quotes: &'static [Quote],
+    /// `'a` is a lifetime rather than the start of a character literal.
+    lifetimes: bool,
+    /// `name!` invokes a macro.
+    macros: bool,
+    /// Lines starting with `#` are preprocessor directives.
+    preprocessor: bool,
+    /// Lines are colored by their leading `+`/`-` rather than tokenized.
+    diff: bool,
+}
+
+const PLAIN: Language = Language {
+    name: "",
+    keywords: "",
+    types: "",
+    capitalized_types: false,
+    case_insensitive: false,
+    line_comments: &[],
+    block_comment: None,
+    quotes: &[],
+    lifetimes: false,
+    macros: false,
+    preprocessor: false,
+    diff: false,
+};
+
+const RUST: Language = Language {
+    name: "rust",
+    keywords: "as async await break const continue crate dyn else enum extern false fn for if \
+              impl in let loop match mod move mut pub ref return self static struct super trait \
+              true type unsafe use where while",
+    types: "bool char str u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64",
+    capitalized_types: true,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[MULTILINE_DOUBLE, SINGLE],
+    lifetimes: true,
+    macros: true,
+    ..PLAIN
+};
+
+const PYTHON: Language = Language {
+    name: "python",
+    keywords: "False None True and as assert async await break class continue def del elif else \
+              except finally for from global if import in is lambda nonlocal not or pass raise \
+              return self try while with yield",
+    types: "bool bytes dict float int list object set str tuple",
+    capitalized_types: true,
+    line_comments: &["#"],
+    quotes: &[TRIPLE_DOUBLE, TRIPLE_SINGLE, DOUBLE, SINGLE],
+    ..PLAIN
+};
+
+const JAVASCRIPT: Language = Language {
+    name: "javascript",
+    keywords: "async await break case catch class const continue debugger default delete do else \
+              export extends false finally for from function if import in instanceof let new \
+              null of return static super switch this throw true try typeof undefined var void \
+              while yield",
+    capitalized_types: true,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[DOUBLE, SINGLE, TEMPLATE],
+    ..PLAIN
+};
+
+const TYPESCRIPT: Language = Language {
+    name: "typescript",
+    keywords: "abstract as async await break case catch class const continue declare default \
+              delete do else enum export extends false finally for from function if implements \
+              import in instanceof interface keyof let namespace new null of private protected \
+              public readonly return static super switch this throw true try type typeof \
+              undefined var void while yield",
+    types: "any bigint boolean never number object string symbol unknown",
+    ..JAVASCRIPT
+};
+
+const GO: Language = Language {
+    name: "go",
+    keywords: "break case chan const continue default defer else fallthrough false for func go \
+              goto if import interface iota map nil package range return select struct switch \
+              true type var",
+    types: "any bool byte complex64 complex128 error float32 float64 int int8 int16 int32 int64 \
+           rune string uint uint8 uint16 uint32 uint64 uintptr",
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[DOUBLE, SINGLE, RAW_BACKTICK],
+    ..PLAIN
+};
+
+const C: Language = Language {
+    name: "c",
+    keywords: "NULL auto break case const continue default do else enum extern false for goto if \
+              inline register restrict return sizeof static struct switch true typedef union \
+              volatile while",
+    types: "bool char double float int int8_t int16_t int32_t int64_t long short signed size_t \
+           ssize_t uint8_t uint16_t uint32_t uint64_t unsigned void",
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[DOUBLE, SINGLE],
+    preprocessor: true,
+    ..PLAIN
+};
+
+const CPP: Language = Language {
+    name: "cpp",
+    keywords: "auto break case catch class const constexpr continue default delete do else enum \
+              explicit extern false for friend goto if inline mutable namespace new noexcept \
+              nullptr operator override private protected public return sizeof static struct \
+              switch template this throw true try typedef typename union using virtual volatile \
+              while",
+    capitalized_types: true,
+    ..C
+};
+
+const JAVA: Language = Language {
+    name: "java",
+    keywords: "abstract assert break case catch class continue default do else enum extends false \
+              final finally for if implements import instanceof interface new null package \
+              private protected public record return static super switch synchronized this throw \
+              throws true try var volatile while",
+    types: "boolean byte char double float int long short void",
+    capitalized_types: true,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[TRIPLE_DOUBLE, DOUBLE, SINGLE],
+    ..PLAIN
+};
+
+const RUBY: Language = Language {
+    name: "ruby",
+    keywords: "and begin class def do else elsif end ensure false for if in module next nil not \
+              or redo require rescue retry return self super then true undef unless until when \
+              while yield",
+    capitalized_types: true,
+    line_comments: &["#"],
+    quotes: &[DOUBLE, SINGLE],
+    ..PLAIN
+};
+
+const SHELL: Language = Language {
+    name: "shell",
+    keywords: "break case continue declare do done elif else esac exit export fi for function if \
+              in local readonly return then unset until while",
+    line_comments: &["#"],
+    quotes: &[MULTILINE_DOUBLE, MULTILINE_RAW_SINGLE],
+    ..PLAIN
+};
+
+const SQL: Language = Language {
+    name: "sql",
+    keywords: "add all alter and as asc begin between by case commit create default delete desc \
+              distinct drop else end exists foreign from group having in index inner insert into \
+              is join key left like limit not null offset on or order outer primary references \
+              right select set table then union update values when where with",
+    types: "bigint boolean char date decimal float int integer real text timestamp varchar",
+    case_insensitive: true,
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[RAW_SINGLE, DOUBLE],
+    ..PLAIN
+};
+
+const JSON: Language = Language {
+    name: "json",
+    keywords: "false null true",
+    line_comments: &["//"],
+    quotes: &[DOUBLE],
+    ..PLAIN
+};
+
+const TOML: Language = Language {
+    name: "toml",
+    keywords: "false true",
+    line_comments: &["#"],
+    quotes: &[TRIPLE_DOUBLE, MULTILINE_RAW_SINGLE, DOUBLE],
+    ..PLAIN
+};
+
+const YAML: Language = Language {
+    name: "yaml",
+    keywords: "false no null true yes",
+    line_comments: &["#"],
+    quotes: &[DOUBLE, RAW_SINGLE],
+    ..PLAIN
+};
+
+const DIFF: Language = Language {
+    name: "diff",
+    diff: true,
+    ..PLAIN
+};
+
+/// The language named by a fenced code block's info string, e.g. `rust` in
+/// "```rust,ignore".
+pub(crate) fn language_for_fence(info: &str) -> Option<&'static Language> {
+    let name = info
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .unwrap_or_default();
+    language_for_name(&name.to_ascii_lowercase())
+}
+
+/// The language of the file at `path`, from its extension.
+pub(crate) fn language_for_path(path: &Path) -> Option<&'static Language> {
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        return language_for_name(&extension.to_ascii_lowercase());
+    }
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some(".bashrc" | ".bash_profile" | ".profile" | ".zshrc") => Some(&SHELL),
+        _ => None,
+    }
+}
+
+fn language_for_name(name: &str) -> Option<&'static Language> {
+    let language = match name {
+        "rust" | "rs" => &RUST,
+        "python" | "python3" | "py" | "pyi" => &PYTHON,
+        "javascript" | "js" | "jsx" | "mjs" | "cjs" | "node" => &JAVASCRIPT,
+        "typescript" | "ts" | "tsx" | "mts" | "cts" => &TYPESCRIPT,
+        "go" | "golang" => &GO,
+        "c" | "h" => &C,
+        "cpp" | "c++" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => &CPP,
+        "java" => &JAVA,
+        "ruby" | "rb" => &RUBY,
+        "sh" | "bash" | "zsh" | "shell" | "console" => &SHELL,
+        "sql" => &SQL,
+        "json" | "jsonc" | "json5" => &JSON,
+        "toml" => &TOML,
+        "yaml" | "yml" => &YAML,
+        "diff" | "patch" => &DIFF,
+        _ => return None,
+    };
+    Some(language)
+}
+
+impl Language {
+    fn is_keyword(&self, word: &str) -> bool {
+        let mut keywords = self.keywords.split_ascii_whitespace();
+        if self.case_insensitive {
+            keywords.any(|keyword| keyword.eq_ignore_ascii_case(word))
+        } else {
+            keywords.any(|keyword| keyword == word)
+        }
+    }
+
+    fn is_type(&self, word: &str) -> bool {
+        self.types.split_ascii_whitespace().any(|name| name == word)
+            || (self.capitalized_types && word.starts_with(|c: char| c.is_uppercase()))
+    }
+}
+
+/// Accumulates the tokens of one line; text between tokens is kept unstyled.
+struct Tokens<'a> {
+    line: &'a str,
+    tokens: Vec<(Option<TokenKind>, &'a str)>,
+    plain_start: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn push(&mut self, kind: TokenKind, start: usize, end: usize) {
+        if self.plain_start < start {
+            self.tokens
+                .push((None, &self.line[self.plain_start..start]));
+        }
+        self.tokens.push((Some(kind), &self.line[start..end]));
+        self.plain_start = end;
+    }
+
+    fn finish(mut self) -> Vec<(Option<TokenKind>, &'a str)> {
+        if self.plain_start < self.line.len() {
+            self.tokens.push((None, &self.line[self.plain_start..]));
+        }
+        self.tokens
+    }
+}
+
+/// Splits `line` into tokens, continuing from `state`, and returns them with
+/// the state the next line starts in.
+pub(crate) fn tokenize<'a>(
+    language: &Language,
+    line: &'a str,
+    state: LineState,
+) -> (Vec<(Option<TokenKind>, &'a str)>, LineState) {
+    let mut tokens = Tokens {
+        line,
+        tokens: Vec::new(),
+        plain_start: 0,
+    };
+    if language.diff {
+        let kind = if ["diff ", "index ", "+++", "---"]
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+        {
+            Some(TokenKind::Keyword)
+        } else if line.starts_with("@@") {
+            Some(TokenKind::Type)
+        } else if line.starts_with('+') {
+            Some(TokenKind::Inserted)
+        } else if line.starts_with('-') {
+            Some(TokenKind::Deleted)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            tokens.push(kind, 0, line.len());
+        }
+        return (tokens.finish(), LineState::Code);
+    }
+
+    let mut i = 0;
+    match state {
+        LineState::Code => {}
+        LineState::BlockComment => {
+            let close = language.block_comment.map_or("*/", |(_, close)| close);
+            match line.find(close) {
+                Some(p) => {
+                    i = p + close.len();
+                    tokens.push(TokenKind::Comment, 0, i);
+                }
+                None => {
+                    tokens.push(TokenKind::Comment, 0, line.len());
+                    return (tokens.finish(), state);
+                }
+            }
+        }
+        LineState::String(index) => {
+            let Some(quote) = language.quotes.get(index) else {
+                return (tokens.finish(), LineState::Code);
+            };
+            match string_end(line, 0, quote) {
+                Some(end) => {
+                    i = end;
+                    tokens.push(TokenKind::String, 0, i);
+                }
+                None => {
+                    tokens.push(TokenKind::String, 0, line.len());
+                    return (tokens.finish(), state);
+                }
+            }
+        }
+    }
+
+    while let Some(c) = line[i..].chars().next() {
+        let rest = &line[i..];
+        // `#` only starts a comment at the start of a word, so `$#` in a
+        // shell script does not.
+        if language
+            .line_comments
+            .iter()
+            .any(|comment| rest.starts_with(comment))
+            && (c != '#'
+                || line[..i]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace))
+        {
+            tokens.push(TokenKind::Comment, i, line.len());
+            break;
+        }
+        if let Some((open, close)) = language.block_comment
+            && rest.starts_with(open)
+        {
+            match rest[open.len()..].find(close) {
+                Some(p) => {
+                    let end = i + open.len() + p + close.len();
+                    tokens.push(TokenKind::Comment, i, end);
+                    i = end;
+                    continue;
+                }
+                None => {
+                    tokens.push(TokenKind::Comment, i, line.len());
+                    return (tokens.finish(), LineState::BlockComment);
+                }
+            }
+        }
+        if let Some((index, quote)) = language
+            .quotes
+            .iter()
+            .enumerate()
+            .find(|(_, quote)| rest.starts_with(quote.delim))
+            && (!language.lifetimes || quote.delim != "'" || !is_lifetime(&rest[1..]))
+        {
+            match string_end(line, i + quote.delim.len(), quote) {
+                Some(end) => {
+                    tokens.push(TokenKind::String, i, end);
+                    i = end;
+                    continue;
+                }
+                None => {
+                    tokens.push(TokenKind::String, i, line.len());
+                    let state = if quote.multiline {
+                        LineState::String(index)
+                    } else {
+                        LineState::Code
+                    };
+                    return (tokens.finish(), state);
+                }
+            }
+        }
+        if c.is_ascii_digit() {
+            let end = number_end(line, i);
+            tokens.push(TokenKind::Number, i, end);
+            i = end;
+            continue;
+        }
+        if c == '#' && language.preprocessor && line[..i].trim().is_empty() {
+            let end = word_end(line, i + 1);
+            tokens.push(TokenKind::Keyword, i, end);
+            i = end;
+            continue;
+        }
+        if c.is_alphabetic() || c == '_' {
+            let end = word_end(line, i);
+            let word = &line[i..end];
+            let after = &line[end..];
+            let kind = if language.is_keyword(word) {
+                Some(TokenKind::Keyword)
+            } else if language.is_type(word) {
+                Some(TokenKind::Type)
+            } else if after.starts_with('(')
+                || (language.macros && after.starts_with('!') && !after.starts_with("!="))
+            {
+                Some(TokenKind::Function)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                tokens.push(kind, i, end);
+            }
+            i = end;
+            continue;
+        }
+        i += c.len_utf8();
+    }
+    (tokens.finish(), LineState::Code)
+}
+
+/// The end of the string whose contents start at `from`, just past its closing
+/// delimiter.
+fn string_end(line: &str, from: usize, quote: &Quote) -> Option<usize> {
+    let mut chars = line[from..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        if quote.escapes && c == '\\' {
+            chars.next();
+            continue;
+        }
+        if line[from + offset..].starts_with(quote.delim) {
+            return Some(from + offset + quote.delim.len());
+        }
+    }
+    None
+}
+
+/// Whether the text after a `'` is a lifetime or loop label, such as `'a`,
+/// rather than a character literal, such as `'a'`.
+fn is_lifetime(after_quote: &str) -> bool {
+    let mut chars = after_quote.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.next() != Some('\'')
+}
+
+fn word_end(line: &str, start: usize) -> usize {
+    line[start..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(line.len(), |p| start + p)
+}
+
+/// Numbers include suffixes and hex digits (`0xffu8`) and a fractional part,
+/// but not a range's `..`.
+fn number_end(line: &str, start: usize) -> usize {
+    let bytes = line.as_bytes();
+    let mut end = start;
+    while end < bytes.len() {
+        let b = bytes[end];
+        let is_fraction = b == b'.' && bytes.get(end + 1).is_some_and(u8::is_ascii_digit);
+        if !(b.is_ascii_alphanumeric() || b == b'_' || is_fraction) {
+            break;
+        }
+        end += 1;
+    }
+    end
+}
+
+/// How many colors the terminal can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorDepth {
+    None,
+    Ansi,
+    Indexed,
+    TrueColor,
+}
+
+/// Colors for a light or dark terminal background.
+struct Palette {
+    keyword: (u8, u8, u8),
+    type_name: (u8, u8, u8),
+    function: (u8, u8, u8),
+    string: (u8, u8, u8),
+    number: (u8, u8, u8),
+    comment: (u8, u8, u8),
+}
+
+const DARK_PALETTE: Palette = Palette {
+    keyword: (198, 120, 221),
+    type_name: (229, 192, 123),
+    function: (97, 175, 239),
+    string: (152, 195, 121),
+    number: (209, 154, 102),
+    comment: (127, 132, 142),
+};
+
+const LIGHT_PALETTE: Palette = Palette {
+    keyword: (166, 38, 164),
+    type_name: (152, 104, 1),
+    function: (64, 120, 242),
+    string: (80, 161, 79),
+    number: (183, 107, 1),
+    comment: (140, 141, 147),
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SyntaxTheme {
+    keyword: Style,
+    type_name: Style,
+    function: Style,
+    string: Style,
+    number: Style,
+    comment: Style,
+    inserted: Style,
+    deleted: Style,
+}
+
+impl SyntaxTheme {
+    /// The theme for the terminal Codex is running in, chosen once from its
+    /// color support and background color.
+    pub(crate) fn current() -> Self {
+        static THEME: OnceLock<SyntaxTheme> = OnceLock::new();
+        *THEME.get_or_init(|| {
+            let depth = match supports_color::on_cached(supports_color::Stream::Stdout) {
+                Some(level) if level.has_16m => ColorDepth::TrueColor,
+                Some(level) if level.has_256 => ColorDepth::Indexed,
+                Some(_) => ColorDepth::Ansi,
+                None => ColorDepth::None,
+            };
+            let palette = match depth {
+                ColorDepth::Indexed => terminal_palette(),
+                _ => None,
+            };
+            Self::detect(depth, default_bg(), palette)
+        })
+    }
+
+    /// Exact colors need to know whether the background is light or dark; when
+    /// it is unknown, or the terminal only has 16 colors, the ANSI colors are
+    /// used, which the terminal's own theme already adapts to its background.
+    #[allow(clippy::disallowed_methods)]
+    fn detect(
+        depth: ColorDepth,
+        terminal_bg: Option<(u8, u8, u8)>,
+        terminal_palette: Option<[(u8, u8, u8); 256]>,
+    ) -> Self {
+        let colors = match terminal_bg {
+            Some(bg) if is_light(bg) => &LIGHT_PALETTE,
+            Some(_) => &DARK_PALETTE,
+            None if depth == ColorDepth::None => return Self::plain(),
+            None => return Self::ansi(),
+        };
+        match (depth, terminal_palette) {
+            (ColorDepth::None, _) => Self::plain(),
+            (ColorDepth::TrueColor, _) => {
+                Self::from_palette(colors, |(r, g, b)| Color::Rgb(r, g, b))
+            }
+            (ColorDepth::Indexed, Some(terminal_palette)) => Self::from_palette(colors, |target| {
+                terminal_palette
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| {
+                        perceptual_distance(**a, target)
+                            .partial_cmp(&perceptual_distance(**b, target))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .map_or(Color::default(), |(i, _)| Color::Indexed(i as u8))
+            }),
+            (ColorDepth::Ansi | ColorDepth::Indexed, _) => Self::ansi(),
+        }
+    }
+
+    /// No highlighting, for terminals without color.
+    pub(crate) fn plain() -> Self {
+        Self {
+            keyword: Style::default(),
+            type_name: Style::default(),
+            function: Style::default(),
+            string: Style::default(),
+            number: Style::default(),
+            comment: Style::default(),
+            inserted: Style::default(),
+            deleted: Style::default(),
+        }
+    }
+
+    /// The basic ANSI colors, following `tui/styles.md`.
+    pub(crate) fn ansi() -> Self {
+        Self {
+            keyword: Style::default().fg(Color::Magenta),
+            type_name: Style::default().fg(Color::Cyan),
+            function: Style::default(),
+            string: Style::default().fg(Color::Green),
+            number: Style::default().fg(Color::Cyan),
+            comment: Style::default().add_modifier(Modifier::DIM),
+            inserted: Style::default().fg(Color::Green),
+            deleted: Style::default().fg(Color::Red),
+        }
+    }
+
+    fn from_palette(colors: &Palette, to_color: impl Fn((u8, u8, u8)) -> Color) -> Self {
+        Self {
+            keyword: Style::default().fg(to_color(colors.keyword)),
+            type_name: Style::default().fg(to_color(colors.type_name)),
+            function: Style::default().fg(to_color(colors.function)),
+            string: Style::default().fg(to_color(colors.string)),
+            number: Style::default().fg(to_color(colors.number)),
+            comment: Style::default().fg(to_color(colors.comment)),
+            inserted: Style::default().fg(Color::Green),
+            deleted: Style::default().fg(Color::Red),
+        }
+    }
+
+    fn style(&self, kind: TokenKind) -> Style {
+        match kind {
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Type => self.type_name,
+            TokenKind::Function => self.function,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.comment,
+            TokenKind::Inserted => self.inserted,
+            TokenKind::Deleted => self.deleted,
+        }
+    }
+}
+
+/// A highlighted line and the state the next line starts in.
+type HighlightedLine = (Vec<(Style, String)>, LineState);
+
+/// Highlights lines with a theme, remembering each line it has highlighted so
+/// that rendering the same text again, as streamed markdown is re-rendered
+/// whenever a line completes, does not highlight it again.
+pub(crate) struct Highlighter {
+    theme: SyntaxTheme,
+    cache: HashMap<(&'static str, LineState, String), HighlightedLine>,
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Self::with_theme(SyntaxTheme::current())
+    }
+}
+
+impl Highlighter {
+    pub(crate) fn with_theme(theme: SyntaxTheme) -> Self {
+        Self {
+            theme,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Highlights `line`, which starts in `state`, over the `base` style.
+    /// Adjacent tokens with the same style share a span, so a line without
+    /// highlighting is a single span.
+    pub(crate) fn highlight_line(
+        &mut self,
+        language: &'static Language,
+        line: &str,
+        state: LineState,
+        base: Style,
+    ) -> (Vec<Span<'static>>, LineState) {
+        let key = (language.name, state, line.to_string());
+        let theme = self.theme;
+        let (styled, next_state) = self.cache.entry(key).or_insert_with(|| {
+            let (tokens, next_state) = tokenize(language, line, state);
+            let mut styled: Vec<(Style, String)> = Vec::new();
+            for (kind, text) in tokens {
+                let style = kind.map_or_else(Style::default, |kind| theme.style(kind));
+                match styled.last_mut() {
+                    Some((last_style, last_text)) if *last_style == style => {
+                        last_text.push_str(text);
+                    }
+                    _ => styled.push((style, text.to_string())),
+                }
+            }
+            (styled, next_state)
+        });
+        let spans = if styled.is_empty() {
+            vec![Span::styled(String::new(), base)]
+        } else {
+            styled
+                .iter()
+                .map(|(style, text)| Span::styled(text.clone(), base.patch(*style)))
+                .collect()
+        };
+        (spans, *next_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn kinds(language: &Language, line: &str) -> Vec<(Option<TokenKind>, String)> {
+        let (tokens, _) = tokenize(language, line, LineState::Code);
+        tokens
+            .into_iter()
+            .map(|(kind, text)| (kind, text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_languages_by_fence_and_extension() {
+        assert_eq!(
+            language_for_fence("rust,ignore").map(|l| l.name),
+            Some("rust")
+        );
+        assert_eq!(
+            language_for_fence("Python title=\"x\"").map(|l| l.name),
+            Some("python")
+        );
+        assert_eq!(language_for_fence("ts").map(|l| l.name), Some("typescript"));
+        assert_eq!(language_for_fence("").map(|l| l.name), None);
+        assert_eq!(
+            language_for_path(Path::new("src/main.RS")).map(|l| l.name),
+            Some("rust")
+        );
+        assert_eq!(
+            language_for_path(Path::new("/home/u/.zshrc")).map(|l| l.name),
+            Some("shell")
+        );
+        assert_eq!(language_for_path(Path::new("README")).map(|l| l.name), None);
+    }
+
+    #[test]
+    fn tokenizes_rust() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(
+                &RUST,
+                "fn parse<'a>(s: &'a str) -> Vec<u8> { println!(\"{s}\", '\\n'); 0x1f..2 } // done"
+            ),
+            vec![
+                (Some(Keyword), "fn".to_string()),
+                (None, " parse<'a>(s: &'a ".to_string()),
+                (Some(Type), "str".to_string()),
+                (None, ") -> ".to_string()),
+                (Some(Type), "Vec".to_string()),
+                (None, "<".to_string()),
+                (Some(Type), "u8".to_string()),
+                (None, "> { ".to_string()),
+                (Some(Function), "println".to_string()),
+                (None, "!(".to_string()),
+                (Some(String), "\"{s}\"".to_string()),
+                (None, ", ".to_string()),
+                (Some(String), "'\\n'".to_string()),
+                (None, "); ".to_string()),
+                (Some(Number), "0x1f".to_string()),
+                (None, "..".to_string()),
+                (Some(Number), "2".to_string()),
+                (None, " } ".to_string()),
+                (Some(Comment), "// done".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn carries_block_comments_and_strings_across_lines() {
+        let (_, state) = tokenize(&RUST, "let x = 1; /* starts", LineState::Code);
+        assert_eq!(state, LineState::BlockComment);
+        let (tokens, state) = tokenize(&RUST, "ends */ x", state);
+        assert_eq!(
+            tokens,
+            vec![(Some(TokenKind::Comment), "ends */"), (None, " x")]
+        );
+        assert_eq!(state, LineState::Code);
+
+        let (_, state) = tokenize(&PYTHON, "doc = \"\"\"first", LineState::Code);
+        assert_eq!(state, LineState::String(0));
+        let (tokens, state) = tokenize(&PYTHON, "last\"\"\" # note", state);
+        assert_eq!(
+            tokens,
+            vec![
+                (Some(TokenKind::String), "last\"\"\""),
+                (None, " "),
+                (Some(TokenKind::Comment), "# note"),
+            ]
+        );
+        assert_eq!(state, LineState::Code);
+    }
+
+    #[test]
+    fn shell_hash_starts_a_comment_only_at_a_word() {
+        assert_eq!(
+            kinds(&SHELL, "echo $# # count"),
+            vec![
+                (None, "echo $# ".to_string()),
+                (Some(TokenKind::Comment), "# count".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn themes_degrade_with_color_support() {
+        let dark = Some((0, 0, 0));
+        assert_eq!(
+            SyntaxTheme::detect(ColorDepth::None, dark, None),
+            SyntaxTheme::plain()
+        );
+        assert_eq!(
+            SyntaxTheme::detect(ColorDepth::Ansi, dark, None),
+            SyntaxTheme::ansi()
+        );
+        assert_eq!(
+            SyntaxTheme::detect(ColorDepth::TrueColor, None, None),
+            SyntaxTheme::ansi()
+        );
+        assert_eq!(
+            SyntaxTheme::detect(ColorDepth::Indexed, dark, None),
+            SyntaxTheme::ansi()
+        );
+        let light = SyntaxTheme::detect(ColorDepth::TrueColor, Some((255, 255, 255)), None);
+        let dark = SyntaxTheme::detect(ColorDepth::TrueColor, dark, None);
+        assert_ne!(light.keyword, dark.keyword);
+    }
+
+    #[test]
+    fn highlighter_merges_spans_over_the_base_style() {
+        let mut highlighter = Highlighter::with_theme(SyntaxTheme::plain());
+        let (spans, _) =
+            highlighter.highlight_line(&RUST, "let x = 1;", LineState::Code, Style::default());
+        assert_eq!(spans, vec![Span::from("let x = 1;")]);
+
+        let mut highlighter = Highlighter::with_theme(SyntaxTheme::ansi());
+        let base = Style::default().fg(Color::Red);
+        let (spans, _) = highlighter.highlight_line(&RUST, "let x", LineState::Code, base);
+        assert_eq!(
+            spans,
+            vec![
+                Span::styled("let", Style::default().fg(Color::Magenta)),
+                Span::styled(" x", base),
+            ]
+        );
+    }
+}
@@ -19,10 +19,12 @@ pub enum SlashCommand {
     Init,
     Compact,
     Undo,
+    Checkpoints,
     Diff,
     Mention,
     Status,
     Mcp,
+    Memory,
     Logout,
     Quit,
     #[cfg(debug_assertions)]
@@ -37,7 +39,8 @@ impl SlashCommand {
             SlashCommand::Init => "create an AGENTS.md file with instructions for Codex",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
             SlashCommand::Review => "review my current changes and find issues",
-            SlashCommand::Undo => "restore the workspace to the last Codex snapshot",
+            SlashCommand::Undo => "undo the file changes of the last turn (/undo N for more)",
+            SlashCommand::Checkpoints => "browse turn checkpoints and restore files or chat",
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
@@ -45,6 +48,7 @@ impl SlashCommand {
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what Codex can do without approval",
             SlashCommand::Mcp => "list configured MCP tools",
+            SlashCommand::Memory => "review and delete saved memory entries",
             SlashCommand::Logout => "log out of Codex",
             #[cfg(debug_assertions)]
             SlashCommand::TestApproval => "test approval request",
@@ -64,6 +68,7 @@ impl SlashCommand {
             | SlashCommand::Init
             | SlashCommand::Compact
             | SlashCommand::Undo
+            | SlashCommand::Checkpoints
             | SlashCommand::Model
             | SlashCommand::Approvals
             | SlashCommand::Review
@@ -72,6 +77,7 @@ impl SlashCommand {
             | SlashCommand::Mention
             | SlashCommand::Status
             | SlashCommand::Mcp
+            | SlashCommand::Memory
             | SlashCommand::Quit => true,
 
             #[cfg(debug_assertions)]
@@ -82,20 +88,5 @@ impl SlashCommand {
 
 /// Return all built-in commands in a Vec paired with their command string.
 pub fn built_in_slash_commands() -> Vec<(&'static str, SlashCommand)> {
-    let show_beta_features = beta_features_enabled();
-
-    SlashCommand::iter()
-        .filter(|cmd| {
-            if *cmd == SlashCommand::Undo {
-                show_beta_features
-            } else {
-                true
-            }
-        })
-        .map(|c| (c.command(), c))
-        .collect()
-}
-
-fn beta_features_enabled() -> bool {
-    std::env::var_os("BETA_FEATURE").is_some()
+    SlashCommand::iter().map(|c| (c.command(), c)).collect()
 }
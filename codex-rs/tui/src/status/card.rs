@@ -8,6 +8,7 @@ use codex_core::config::Config;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TokenUsage;
 use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::num_format::format_usd;
 use ratatui::prelude::*;
 use ratatui::style::Stylize;
 use std::collections::BTreeSet;
@@ -47,17 +48,19 @@ struct StatusHistoryCell {
     account: Option<StatusAccountDisplay>,
     session_id: Option<String>,
     token_usage: StatusTokenUsageData,
+    total_cost_usd: Option<f64>,
     rate_limits: StatusRateLimitData,
 }
 
 pub(crate) fn new_status_output(
     config: &Config,
     usage: &TokenUsage,
+    total_cost_usd: Option<f64>,
     session_id: &Option<ConversationId>,
     rate_limits: Option<&RateLimitSnapshotDisplay>,
 ) -> CompositeHistoryCell {
     let command = PlainHistoryCell::new(vec!["/status".magenta().into()]);
-    let card = StatusHistoryCell::new(config, usage, session_id, rate_limits);
+    let card = StatusHistoryCell::new(config, usage, total_cost_usd, session_id, rate_limits);
 
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(card)])
 }
@@ -66,6 +69,7 @@ impl StatusHistoryCell {
     fn new(
         config: &Config,
         usage: &TokenUsage,
+        total_cost_usd: Option<f64>,
         session_id: &Option<ConversationId>,
         rate_limits: Option<&RateLimitSnapshotDisplay>,
     ) -> Self {
@@ -101,6 +105,7 @@ impl StatusHistoryCell {
             account,
             session_id,
             token_usage,
+            total_cost_usd,
             rate_limits,
         }
     }
@@ -235,6 +240,9 @@ impl HistoryCell for StatusHistoryCell {
             push_label(&mut labels, &mut seen, "Session");
         }
         push_label(&mut labels, &mut seen, "Token usage");
+        if self.total_cost_usd.is_some() {
+            push_label(&mut labels, &mut seen, "Cost");
+        }
         self.collect_rate_limit_labels(&mut seen, &mut labels);
 
         let formatter = FieldFormatter::from_labels(labels.iter().map(String::as_str));
@@ -265,6 +273,9 @@ impl HistoryCell for StatusHistoryCell {
 
         lines.push(Line::from(Vec::<Span<'static>>::new()));
         lines.push(formatter.line("Token usage", self.token_usage_spans()));
+        if let Some(cost) = self.total_cost_usd {
+            lines.push(formatter.line("Cost", vec![Span::from(format_usd(cost))]));
+        }
 
         lines.extend(self.rate_limit_lines(available_inner_width, &formatter));
 
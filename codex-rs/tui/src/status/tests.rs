@@ -71,6 +71,8 @@ fn status_snapshot_includes_reasoning_details() {
     config.sandbox_policy = SandboxPolicy::WorkspaceWrite {
         writable_roots: Vec::new(),
         network_access: false,
+        allowed_domains: vec![],
+        deny_read: vec![],
         exclude_tmpdir_env_var: false,
         exclude_slash_tmp: false,
     };
@@ -103,7 +105,7 @@ fn status_snapshot_includes_reasoning_details() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -144,7 +146,7 @@ fn status_snapshot_includes_monthly_limit() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -170,7 +172,7 @@ fn status_card_token_usage_excludes_cached_tokens() {
         total_tokens: 2_100,
     };
 
-    let composite = new_status_output(&config, &usage, &None, None);
+    let composite = new_status_output(&config, &usage, None, &None, None);
     let rendered = render_lines(&composite.display_lines(120));
 
     assert!(
@@ -179,6 +181,38 @@ fn status_card_token_usage_excludes_cached_tokens() {
     );
 }
 
+#[test]
+fn status_card_shows_session_cost_when_priced() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home);
+    config.model = "grok-code-fast-1".to_string();
+    config.cwd = PathBuf::from("/workspace/tests");
+
+    let usage = TokenUsage {
+        input_tokens: 1_200,
+        cached_input_tokens: 200,
+        output_tokens: 900,
+        reasoning_output_tokens: 0,
+        total_tokens: 2_100,
+    };
+
+    let composite = new_status_output(&config, &usage, Some(0.0042), &None, None);
+    let rendered = render_lines(&composite.display_lines(120));
+    assert!(
+        rendered
+            .iter()
+            .any(|line| line.contains("Cost:") && line.contains("$0.0042")),
+        "expected a cost line, got: {rendered:?}"
+    );
+
+    let composite = new_status_output(&config, &usage, None, &None, None);
+    let rendered = render_lines(&composite.display_lines(120));
+    assert!(
+        rendered.iter().all(|line| !line.contains("Cost:")),
+        "cost should be hidden without pricing, got: {rendered:?}"
+    );
+}
+
 #[test]
 fn status_snapshot_truncates_in_narrow_terminal() {
     let temp_home = TempDir::new().expect("temp home");
@@ -211,7 +245,7 @@ fn status_snapshot_truncates_in_narrow_terminal() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(46));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -238,7 +272,7 @@ fn status_snapshot_shows_missing_limits_message() {
         total_tokens: 750,
     };
 
-    let composite = new_status_output(&config, &usage, &None, None);
+    let composite = new_status_output(&config, &usage, None, &None, None);
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -274,7 +308,7 @@ fn status_snapshot_shows_empty_limits_message() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {